@@ -58,6 +58,8 @@ However, it's important to note that this doesn't automatically make everything
 
 So, these lines make the modules themselves accessible, but the accessibility of the code within them depends on whether those internal items are marked pub.
 */
+#[path = "../common/account_header.rs"]
+pub mod account_header;
 pub mod entrypoint;
 pub mod error;
 pub mod instruction;
@@ -71,6 +73,39 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
+/// Layout version written into the header of every account this program packs.
+/// Bump it whenever a layout changes and teach `MigrateConfig` to upgrade the old one.
+pub const CURRENT_VERSION: u8 = 1;
+
+pub use crate::account_header::HEADER_LEN;
+
+/// Discriminator stored in the second header byte, so a config can never be
+/// unpacked as NFT state or vice versa.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountType {
+    Uninitialized = 0,
+    Config,
+    NftEvolution,
+}
+
+fn pack_with_header<T: BorshSerialize>(value: &T, account_type: AccountType, dst: &mut [u8]) {
+    crate::account_header::pack_with_header(value, CURRENT_VERSION, account_type as u8, dst)
+}
+
+fn unpack_with_header<T: BorshDeserialize>(
+    src: &[u8],
+    account_type: AccountType,
+) -> Result<T, solana_program::program_error::ProgramError> {
+    crate::account_header::unpack_with_header(src, CURRENT_VERSION, account_type as u8)
+}
+
+/// Length of the unversioned config layout (no header). `MigrateConfig` upgrades such accounts.
+pub const CONFIG_V0_LEN: usize = 1 + 32; // bool + Pubkey
+
+/// Length of the unversioned NFT state layout (no header). `MigrateNftState` upgrades such accounts.
+pub const NFT_STATE_V0_LEN: usize = 1 + 32 + 8 + 8 + 8; // bool + Pubkey + u64 + i64 + u64
+
 /// Configuration account holding trusted oracle information.
 /// Initialized once by the program admin.
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
@@ -86,16 +121,13 @@ impl IsInitialized for ConfigAccount {
     fn is_initialized(&self) -> bool { self.is_initialized }
 }
 impl Pack for ConfigAccount {
-    const LEN: usize = 1 + 32; // bool + Pubkey
-    // Pack/Unpack implementations using Borsh (similar to stablecoin example)
+    const LEN: usize = HEADER_LEN + CONFIG_V0_LEN; // header + bool + Pubkey
+    // Pack/Unpack implementations using Borsh behind the header (similar to stablecoin example)
      fn pack_into_slice(&self, dst: &mut [u8]) {
-        let mut writer = std::io::Cursor::new(dst);
-        self.serialize(&mut writer).unwrap();
+        pack_with_header(self, AccountType::Config, dst)
     }
     fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
-        let mut reader = std::io::Cursor::new(src);
-        ConfigAccount::deserialize(&mut reader)
-            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)
+        unpack_with_header(src, AccountType::Config)
     }
 }
 
@@ -114,7 +146,7 @@ pub struct OracleDataAccount {
 }
 // Note: This account is typically *not* marked as initialized or packed using Solana's Pack
 // trait if it's only ever written to/read from directly by external services and this program.
-// However, defining LEN is useful.
+// However, defining LEN is useful. It has no header since the oracle owns its layout.
 impl OracleDataAccount {
     pub const LEN: usize = 8 + 8 + 64; // u64 + i64 + signature bytes
 }
@@ -142,16 +174,13 @@ impl IsInitialized for NftEvolutionAccount {
 }
 impl Pack for NftEvolutionAccount {
     // Adjust LEN based on actual fields
-    const LEN: usize = 1 + 32 + 8 + 8 + 8; // bool + Pubkey + u64 + i64 + u64
-    // Pack/Unpack implementations using Borsh
+    const LEN: usize = HEADER_LEN + NFT_STATE_V0_LEN; // header + bool + Pubkey + u64 + i64 + u64
+    // Pack/Unpack implementations using Borsh behind the header
      fn pack_into_slice(&self, dst: &mut [u8]) {
-        let mut writer = std::io::Cursor::new(dst);
-        self.serialize(&mut writer).unwrap();
+        pack_with_header(self, AccountType::NftEvolution, dst)
     }
     fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
-        let mut reader = std::io::Cursor::new(src);
-        NftEvolutionAccount::deserialize(&mut reader)
-            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)
+        unpack_with_header(src, AccountType::NftEvolution)
     }
 }
 
@@ -188,6 +217,23 @@ pub enum AiNftInstruction {
     /// 2. `[]` OracleDataAccount containing latest AI score and signature.
     /// 3. `[]` ConfigAccount containing the trusted oracle pubkey.
     UpdateNftState,
+
+    /// Upgrades a config account written in an older layout to the current one in place
+    /// (realloc, rent top-up from the payer, rewrite with the current header).
+    /// Does nothing if the config is already current.
+    /// Accounts:
+    /// 0. `[writable, signer]` Payer for the extra rent.
+    /// 1. `[writable]` Config account.
+    /// 2. `[]` System program.
+    MigrateConfig,
+
+    /// Upgrades an NFT state account written before account headers existed to the current
+    /// layout in place, like `MigrateConfig`. Does nothing if the account is already current.
+    /// Accounts:
+    /// 0. `[writable, signer]` Payer for the extra rent.
+    /// 1. `[writable]` NftEvolutionAccount to migrate.
+    /// 2. `[]` System program.
+    MigrateNftState,
 }
 
 
@@ -225,6 +271,7 @@ impl From<AiNftError> for ProgramError {
 
 
 // === src/processor.rs ===
+use borsh::BorshDeserialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
@@ -239,9 +286,13 @@ use ed25519_dalek::{Signature, Verifier, VerifyingKey}; // Using 2.x version syn
 use sha3::{Digest, Keccak256}; // Example using Keccak256, adjust if needed
 
 use crate::{
+    account_header::grow_account,
     error::AiNftError,
     instruction::AiNftInstruction,
-    state::{ConfigAccount, OracleDataAccount, NftEvolutionAccount},
+    state::{
+        ConfigAccount, OracleDataAccount, NftEvolutionAccount, CONFIG_V0_LEN, CURRENT_VERSION,
+        NFT_STATE_V0_LEN,
+    },
 };
 
 
@@ -268,6 +319,14 @@ impl Processor {
                  msg!("Instruction: UpdateNftState");
                  Self::process_update_nft_state(accounts, program_id)
             }
+            AiNftInstruction::MigrateConfig => {
+                 msg!("Instruction: MigrateConfig");
+                 Self::process_migrate_config(accounts, program_id)
+            }
+            AiNftInstruction::MigrateNftState => {
+                 msg!("Instruction: MigrateNftState");
+                 Self::process_migrate_nft_state(accounts, program_id)
+            }
         }
     }
 
@@ -304,6 +363,89 @@ impl Processor {
         Ok(())
     }
 
+    // --- Migrate Config Implementation ---
+    fn process_migrate_config(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_account = next_account_info(account_info_iter)?; // Signer, Writable
+        let config_account = next_account_info(account_info_iter)?; // Writable
+        let system_program = next_account_info(account_info_iter)?; // System
+
+        if !payer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if config_account.owner != program_id {
+            return Err(AiNftError::InvalidConfigAccountOwner.into());
+        }
+
+        // Each layout has a distinct length, so the length tells us which one we have
+        let config_data = {
+            let data = config_account.data.borrow();
+            match data.len() {
+                ConfigAccount::LEN => {
+                    msg!("Config already at layout version {}", data[0]);
+                    return Ok(());
+                }
+                // Unversioned layout: same fields, no header
+                CONFIG_V0_LEN => ConfigAccount::deserialize(&mut &data[..])
+                    .map_err(|_| ProgramError::InvalidAccountData)?,
+                _ => return Err(ProgramError::InvalidAccountData),
+            }
+        };
+        if !config_data.is_initialized() {
+            return Err(AiNftError::NotInitialized.into());
+        }
+
+        // Top up rent for the larger account, then grow it
+        grow_account(payer_account, config_account, system_program, ConfigAccount::LEN)?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+        msg!("Config migrated to layout version {}", CURRENT_VERSION);
+        Ok(())
+    }
+
+    // --- Migrate NFT State Implementation ---
+    fn process_migrate_nft_state(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_account = next_account_info(account_info_iter)?; // Signer, Writable
+        let nft_state_account = next_account_info(account_info_iter)?; // Writable
+        let system_program = next_account_info(account_info_iter)?; // System
+
+        if !payer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if nft_state_account.owner != program_id {
+            return Err(AiNftError::InvalidNftStateAccountOwner.into());
+        }
+
+        // Headerless NFT states have a length no config layout has
+        let nft_state_data = {
+            let data = nft_state_account.data.borrow();
+            match data.len() {
+                NftEvolutionAccount::LEN => {
+                    msg!("NFT state already at layout version {}", data[0]);
+                    return Ok(());
+                }
+                NFT_STATE_V0_LEN => NftEvolutionAccount::deserialize(&mut &data[..])
+                    .map_err(|_| ProgramError::InvalidAccountData)?,
+                _ => return Err(ProgramError::InvalidAccountData),
+            }
+        };
+        if !nft_state_data.is_initialized() {
+            return Err(AiNftError::NotInitialized.into());
+        }
+
+        // Top up rent for the larger account, then grow it
+        grow_account(payer_account, nft_state_account, system_program, NftEvolutionAccount::LEN)?;
+        NftEvolutionAccount::pack(nft_state_data, &mut nft_state_account.data.borrow_mut())?;
+        msg!("NFT state migrated to layout version {}", CURRENT_VERSION);
+        Ok(())
+    }
+
      // --- Initialize NFT State Implementation ---
     fn process_initialize_nft_state(
         accounts: &[AccountInfo],
@@ -0,0 +1,79 @@
+// Versioned account header shared by the loyalty, stablecoin and AI NFT programs.
+//
+// Every account a program packs starts with `[version, account_type]`. Each program keeps
+// its own `CURRENT_VERSION` and `AccountType` discriminators and passes them in, so the
+// same bytes never unpack as another program's (or another type's) account by accident.
+// Layouts only ever grow by appending fields, which is what lets `upgrade_body` read an
+// older body by zero-extending it.
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program::invoke,
+    program_error::ProgramError,
+    system_instruction,
+    sysvar::{rent::Rent, Sysvar},
+};
+
+/// Length of the `[version, account_type]` header at the front of every account.
+pub const HEADER_LEN: usize = 2;
+
+/// Writes the header followed by the Borsh body of `value` into `dst`.
+pub fn pack_with_header<T: BorshSerialize>(value: &T, version: u8, account_type: u8, dst: &mut [u8]) {
+    dst[0] = version;
+    dst[1] = account_type;
+    let mut writer = std::io::Cursor::new(&mut dst[HEADER_LEN..]);
+    value.serialize(&mut writer).unwrap();
+}
+
+/// Reads a body written by `pack_with_header` with any version up to `current_version`.
+/// Older versions stay readable for types whose layout didn't change; a changed layout
+/// has a different `LEN`, which `Pack` rejects until the account is migrated.
+pub fn unpack_with_header<T: BorshDeserialize>(
+    src: &[u8],
+    current_version: u8,
+    account_type: u8,
+) -> Result<T, ProgramError> {
+    if !is_zeroed_header(src) && !has_header(src, current_version, account_type) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    T::deserialize(&mut &src[HEADER_LEN..]).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+/// Whether `src` starts with a header of `account_type` at a version up to `current_version`.
+pub fn has_header(src: &[u8], current_version: u8, account_type: u8) -> bool {
+    src.len() >= HEADER_LEN && (1..=current_version).contains(&src[0]) && src[1] == account_type
+}
+
+/// Zeroed account that hasn't been written yet; `Pack::unpack` rejects it as uninitialized.
+fn is_zeroed_header(src: &[u8]) -> bool {
+    src.len() >= HEADER_LEN && src[0] == 0 && src[1] == 0
+}
+
+/// Decodes a body written in an older layout, with the fields it predates set to zero.
+/// `body_len` is the length of the current body (without the header).
+pub fn upgrade_body<T: BorshDeserialize>(body: &[u8], body_len: usize) -> Result<T, ProgramError> {
+    let mut body = body.to_vec();
+    if body.len() < body_len {
+        body.resize(body_len, 0);
+    }
+    T::deserialize(&mut &body[..]).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+/// Grows `account` to `new_len` in place, topping up rent exemption from `payer` first.
+pub fn grow_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    new_len: usize,
+) -> ProgramResult {
+    let required_lamports = Rent::get()?.minimum_balance(new_len);
+    let top_up = required_lamports.saturating_sub(account.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, top_up),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    account.realloc(new_len, false)
+}
@@ -22,6 +22,54 @@ pub enum LoyaltyError {
     NumericalOverflow,
     #[error("Owner mismatch for token account")]
     OwnerMismatch, // For redeem check
+    #[error("Program authority PDA mismatch")]
+    AuthorityMismatch,
+    #[error("Invalid token program")]
+    InvalidTokenProgram,
+    #[error("Invalid account owner")]
+    InvalidAccountOwner,
+    #[error("Account belongs to a different config")]
+    ConfigMismatch,
+    #[error("Invalid merkle proof")]
+    InvalidMerkleProof,
+    #[error("Distribution leaf already claimed")]
+    AlreadyClaimed,
+    #[error("Distribution has expired")]
+    DistributionExpired,
+    #[error("Distribution index out of range")]
+    InvalidDistributionIndex,
+    #[error("Claim exceeds distribution total")]
+    DistributionExhausted,
+    #[error("Mint authority is not the program authority PDA")]
+    InvalidMintAuthority,
+    #[error("Swap agreement not approved by both brands")]
+    SwapNotApproved,
+    #[error("Swap exceeds the agreement's daily limit")]
+    SwapDailyLimitExceeded,
+    #[error("Invalid swap rate")]
+    InvalidSwapRate,
+    #[error("Member account does not match")]
+    MemberMismatch,
+    #[error("Voucher signature missing or invalid")]
+    InvalidVoucherSignature,
+    #[error("Voucher has expired")]
+    VoucherExpired,
+    #[error("Voucher nonce already used")]
+    VoucherNonceUsed,
+    #[error("Invalid staking parameters")]
+    InvalidStakeParams,
+    #[error("Invalid lock duration")]
+    InvalidLockDuration,
+    #[error("No points staked")]
+    NothingStaked,
+    #[error("Merchant account does not match")]
+    MerchantMismatch,
+    #[error("Nothing to settle")]
+    NothingToSettle,
+    #[error("Settlement amount exceeds pending settlement")]
+    SettlementExceedsPending,
+    #[error("Member already checked in today")]
+    AlreadyCheckedIn,
 }
 
 impl From<LoyaltyError> for ProgramError {
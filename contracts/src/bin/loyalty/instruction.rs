@@ -1,35 +1,46 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
+use crate::state::{AccountType, StakeTier, StreakBonus, STAKE_TIER_COUNT, STREAK_BONUS_COUNT};
 
 /// Defines the instructions for the loyalty program.
 /// ETH Dev Analogy: Public functions in a Solidity contract.
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub enum LoyaltyInstruction {
-    /// Initializes the loyalty program configuration. Must be called once.
+    /// Initializes the configuration for one brand. Must be called once per brand.
+    /// The program creates the config PDA (`[b"config", brand_id]`).
     ///
     /// Accounts expected:
-    /// 0. `[signer]` Payer/Admin initializing the program.
-    /// 1. `[writable]` Config account (needs to be created via SystemProgram first).
-    /// 2. `[]` Loyalty Point SPL Token Mint address.
+    /// 0. `[writable, signer]` Payer/Admin initializing the brand (pays rent for the config PDA).
+    /// 1. `[writable]` Config PDA for `brand_id` (must not exist yet).
+    /// 2. `[]` Loyalty Point SPL Token Mint address. Its mint authority must already be
+    ///    the program authority PDA (`[b"authority", config]`).
     /// 3. `[]` Rent sysvar.
     /// 4. `[]` System program.
+    /// 5. `[writable]` Stats PDA for the config (`[b"stats", config]`, must not exist yet).
     Initialize {
         /// The initial admin address.
         admin: Pubkey,
+        /// Identifier of the brand this config belongs to.
+        brand_id: u64,
     },
 
     /// Awards loyalty points (mints tokens) to a user's token account.
     /// Only callable by the current admin.
     ///
     /// Accounts expected:
-    /// 0. `[signer]` Current Admin account (must match `config_account.admin`).
+    /// 0. `[writable, signer]` Current Admin account (must match `config_account.admin`).
     /// 1. `[]` Config account (holds admin and mint info).
     /// 2. `[writable]` Loyalty Point SPL Token Mint account (the mint address stored in config).
     /// 3. `[writable]` Destination User SPL Token Account (ATA of the recipient). Must exist.
     /// 4. `[]` SPL Token Program ID.
-    /// 5. `[]` This Program's ID (as Mint Authority) - or PDA if using PDA authority.
+    /// 5. `[]` Program authority PDA (`[b"authority", config]`), the mint authority.
+    /// 6. `[writable]` Stats PDA (`[b"stats", config]`).
+    /// 7. `[writable]` Issuer stats PDA (`[b"issuer_stats", config, admin]`), created on first award.
+    /// 8. `[]` System program.
+    /// 9. `[]` Recipient's stake position PDA (`[b"stake", config, recipient]`); may not exist.
+    ///    An active stake multiplies the awarded amount by the member's tier multiplier.
     AwardPoints {
-        /// Amount of loyalty points (smallest unit) to award.
+        /// Amount of loyalty points (smallest unit) to award, before any stake multiplier.
         amount: u64,
     },
 
@@ -38,16 +49,20 @@ pub enum LoyaltyInstruction {
     ///
     /// Accounts expected:
     /// 0. `[signer]` User redeeming points (owner of the source token account).
-    /// 1. `[writable]` User's Source SPL Token Account (ATA holding the points).
-    /// 2. `[writable]` Loyalty Point SPL Token Mint account.
-    /// 3. `[]` SPL Token Program ID.
+    /// 1. `[]` Config account of the brand the points belong to.
+    /// 2. `[writable]` User's Source SPL Token Account (ATA holding the points).
+    /// 3. `[writable]` Loyalty Point SPL Token Mint account.
+    /// 4. `[]` SPL Token Program ID.
+    /// 5. `[writable]` Stats PDA (`[b"stats", config]`).
     RedeemPoints {
         /// Amount of loyalty points (smallest unit) to redeem.
         amount: u64,
     },
 
     /// Sets a new admin for the loyalty program.
-    /// Only callable by the current admin.
+    /// Only callable by the current admin. Set the stablecoin program's multisig signer PDA
+    /// to put the brand under a multisig; its admin instructions then go through `Propose`,
+    /// `Approve` and `Execute` there.
     ///
     /// Accounts expected:
     /// 0. `[signer]` Current Admin account (must match `config_account.admin`).
@@ -56,4 +71,317 @@ pub enum LoyaltyInstruction {
         /// The public key of the new admin.
         new_admin: Pubkey,
     },
+
+    /// Claws back (burns) fraudulently earned points from a member's token account.
+    /// Only callable by the current admin. The loyalty mint must be a Token-2022 mint
+    /// whose permanent delegate is this program's authority PDA, so no member signature is needed.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account (must match `config_account.admin`).
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Loyalty Point Token-2022 Mint account.
+    /// 3. `[writable]` Member's SPL Token Account to burn from.
+    /// 4. `[writable]` Clawback record account (created via SystemProgram first, owned by this program).
+    /// 5. `[]` Program authority PDA (`[b"authority", config]`), the mint's permanent delegate.
+    /// 6. `[]` SPL Token-2022 Program ID.
+    /// 7. `[writable]` Stats PDA (`[b"stats", config]`).
+    ClawbackPoints {
+        /// Amount of loyalty points (smallest unit) to claw back.
+        amount: u64,
+        /// Compliance reason code stored in the clawback record.
+        reason_code: u16,
+    },
+
+    /// Creates a merkle distribution of claimable points (e.g. for legacy migrations).
+    /// Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account (must match `config_account.admin`).
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Distribution account (created via SystemProgram first, owned by this program,
+    ///    with at least `Distribution::account_len(num_leaves)` bytes for the claimed bitmap).
+    CreateDistribution {
+        /// Root of the merkle tree built by the `build-distribution` tool.
+        merkle_root: [u8; 32],
+        /// Sum of all leaf amounts; claims can never mint more than this.
+        total_amount: u64,
+        /// Number of leaves in the tree (size of the claimed bitmap).
+        num_leaves: u64,
+        /// Unix timestamp after which claims are rejected.
+        expiry: i64,
+    },
+
+    /// Claims points from a merkle distribution into the claimant's token account.
+    /// Callable by anyone; the points always go to the claimant named in the leaf.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Fee payer submitting the claim.
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Distribution account.
+    /// 3. `[writable]` Loyalty Point SPL Token Mint account.
+    /// 4. `[writable]` Claimant's SPL Token Account (owner must match the leaf claimant).
+    /// 5. `[]` Program authority PDA (`[b"authority", config]`), the mint authority.
+    /// 6. `[]` SPL Token Program ID.
+    /// 7. `[writable]` Stats PDA (`[b"stats", config]`).
+    ClaimPoints {
+        /// Leaf index in the distribution.
+        index: u64,
+        /// Amount of points in the leaf.
+        amount: u64,
+        /// Sibling hashes from the leaf up to the root.
+        proof: Vec<[u8; 32]>,
+    },
+
+    /// Proposes a coalition swap agreement converting source brand points into target brand points.
+    /// Callable by the admin of either brand; the proposer's side is approved automatically.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Admin of the source or target brand (pays rent for the agreement PDA).
+    /// 1. `[]` Source brand config account.
+    /// 2. `[]` Target brand config account.
+    /// 3. `[writable]` Swap agreement PDA (`[b"swap", source_config, target_config]`), must not exist yet.
+    /// 4. `[]` System program.
+    ProposeSwapAgreement {
+        /// Target points minted per source point is `rate_numerator / rate_denominator`.
+        rate_numerator: u64,
+        rate_denominator: u64,
+        /// Maximum source points that can be swapped per day.
+        daily_limit: u64,
+    },
+
+    /// Grants or revokes one brand's approval of a swap agreement.
+    /// Callable by the admin of either brand.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Admin of the source or target brand.
+    /// 1. `[]` Source brand config account.
+    /// 2. `[]` Target brand config account.
+    /// 3. `[writable]` Swap agreement PDA.
+    ApproveSwapAgreement {
+        /// `true` to approve, `false` to revoke.
+        approved: bool,
+    },
+
+    /// Swaps points of the source brand into points of the target brand at the agreed rate.
+    /// Callable by the user who owns the source points.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` User swapping points (owner of the source token account).
+    /// 1. `[]` Source brand config account.
+    /// 2. `[]` Target brand config account.
+    /// 3. `[writable]` Swap agreement PDA.
+    /// 4. `[writable]` Source brand's Loyalty Point Mint account.
+    /// 5. `[writable]` User's source brand SPL Token Account.
+    /// 6. `[writable]` Target brand's Loyalty Point Mint account.
+    /// 7. `[writable]` Destination target brand SPL Token Account.
+    /// 8. `[]` Target brand's program authority PDA (`[b"authority", target_config]`).
+    /// 9. `[]` SPL Token Program ID.
+    /// 10. `[writable]` Source brand's stats PDA.
+    /// 11. `[writable]` Target brand's stats PDA.
+    SwapPoints {
+        /// Amount of source brand points (smallest unit) to swap.
+        amount: u64,
+    },
+
+    /// Signs a wallet up as a member of a brand. Creates the member PDA and approves the
+    /// program authority PDA as delegate on the member's token account, so the program
+    /// can later burn points for signed vouchers without the member being online.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Member wallet (owner of the token account, pays rent for the member PDA).
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Member PDA (`[b"member", config, owner]`), must not exist yet.
+    /// 3. `[writable]` Member's Loyalty Point SPL Token Account.
+    /// 4. `[]` Program authority PDA (`[b"authority", config]`).
+    /// 5. `[]` SPL Token Program ID.
+    /// 6. `[]` System program.
+    RegisterMember {
+        /// Maximum amount the program may burn for vouchers (the delegate allowance).
+        voucher_allowance: u64,
+    },
+
+    /// Redeems (burns) points for a voucher the member signed offline.
+    /// Callable by anyone (typically the store cashier, who pays the fees). The instruction
+    /// right before this one must be an Ed25519 program instruction verifying the member's
+    /// signature over `voucher::voucher_message(config, amount, nonce, expiry)`.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Cashier / fee payer.
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Member PDA.
+    /// 3. `[writable]` Member's Loyalty Point SPL Token Account (the one approved at signup).
+    /// 4. `[writable]` Loyalty Point SPL Token Mint account.
+    /// 5. `[]` Program authority PDA (`[b"authority", config]`), the approved delegate.
+    /// 6. `[]` SPL Token Program ID.
+    /// 7. `[writable]` Stats PDA (`[b"stats", config]`).
+    /// 8. `[]` Instructions sysvar.
+    RedeemWithVoucher {
+        /// Amount of loyalty points (smallest unit) to redeem.
+        amount: u64,
+        /// Voucher nonce; each nonce redeems once, in any order, while it is within
+        /// `VOUCHER_NONCE_WINDOW` of the highest nonce the member has redeemed.
+        nonce: u64,
+        /// Unix timestamp after which the voucher is rejected.
+        expiry: i64,
+    },
+
+    /// Sets the brand's staking parameters.
+    /// Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account (must match `config_account.admin`).
+    /// 1. `[writable]` Config account.
+    SetStakeParams {
+        /// Share of the stake burned on early unlock, in basis points.
+        early_unlock_penalty_bps: u16,
+        /// Stake tiers, ordered from lowest to highest.
+        stake_tiers: [StakeTier; STAKE_TIER_COUNT],
+    },
+
+    /// Locks points in the brand's stake vault. Adds to an existing position, and the
+    /// lock can only be lengthened, never shortened.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Member wallet (owner of the source token account, pays rent for new PDAs).
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Stake position PDA (`[b"stake", config, owner]`), created on first stake.
+    /// 3. `[writable]` Member's Loyalty Point SPL Token Account.
+    /// 4. `[writable]` Stake vault PDA (`[b"vault", config]`), created on the brand's first stake.
+    /// 5. `[]` Loyalty Point SPL Token Mint account.
+    /// 6. `[]` Program authority PDA (`[b"authority", config]`), the vault owner.
+    /// 7. `[]` SPL Token Program ID.
+    /// 8. `[]` System program.
+    Stake {
+        /// Amount of loyalty points (smallest unit) to lock.
+        amount: u64,
+        /// Lock duration in seconds, counted from now.
+        lock_secs: i64,
+    },
+
+    /// Withdraws all staked points. Before the lock ends, the configured penalty is burned
+    /// from the vault and only the remainder is returned.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Member wallet.
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Stake position PDA.
+    /// 3. `[writable]` Member's Loyalty Point SPL Token Account.
+    /// 4. `[writable]` Stake vault PDA.
+    /// 5. `[writable]` Loyalty Point SPL Token Mint account.
+    /// 6. `[]` Program authority PDA (`[b"authority", config]`).
+    /// 7. `[]` SPL Token Program ID.
+    /// 8. `[writable]` Stats PDA (`[b"stats", config]`).
+    Unstake,
+
+    /// Extends the lock of an existing stake position.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Member wallet.
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Stake position PDA.
+    ExtendLock {
+        /// Seconds added to the current unlock time (or to now, if already unlocked).
+        additional_secs: i64,
+    },
+
+    /// Registers a merchant (store) that can accept redemptions for the brand.
+    /// Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Current Admin account (pays rent for the merchant PDA).
+    /// 1. `[]` Config account.
+    /// 2. `[]` Merchant wallet.
+    /// 3. `[writable]` Merchant PDA (`[b"merchant", config, merchant]`), must not exist yet.
+    /// 4. `[]` System program.
+    RegisterMerchant,
+
+    /// Redeems (burns) points at a physical store. Both the member and the merchant sign,
+    /// and the amount is added to the merchant's settlement ledger.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Member (owner of the source token account).
+    /// 1. `[signer]` Merchant wallet confirming the goods were handed over.
+    /// 2. `[]` Config account.
+    /// 3. `[writable]` Merchant PDA.
+    /// 4. `[writable]` Member's Loyalty Point SPL Token Account.
+    /// 5. `[writable]` Loyalty Point SPL Token Mint account.
+    /// 6. `[]` SPL Token Program ID.
+    /// 7. `[writable]` Stats PDA (`[b"stats", config]`).
+    RedeemAtMerchant {
+        /// Amount of loyalty points (smallest unit) to redeem.
+        amount: u64,
+    },
+
+    /// Records that the brand paid out `amount` of a merchant's pending settlement.
+    /// Redemptions recorded after the payout was computed stay pending for the next one.
+    /// Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account.
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Merchant PDA.
+    Settle {
+        /// Points covered by the payout; at most the merchant's pending settlement.
+        amount: u64,
+        /// Off-chain payment reference (e.g. hash of the bank transfer ID).
+        payment_reference: [u8; 32],
+    },
+
+    /// Sets the check-in streak bonuses. Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account (must match `config_account.admin`).
+    /// 1. `[writable]` Config account.
+    SetStreakBonuses {
+        /// Bonuses paid when a streak reaches the configured length.
+        streak_bonuses: [StreakBonus; STREAK_BONUS_COUNT],
+    },
+
+    /// Records a member's daily visit. Co-signed by the issuing store (a registered
+    /// merchant), at most once per day. Pays the configured bonus when the streak
+    /// reaches a bonus length.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Member wallet.
+    /// 1. `[writable, signer]` Issuer (merchant wallet, pays rent for its issuer stats PDA).
+    /// 2. `[]` Config account.
+    /// 3. `[]` Merchant PDA of the issuer.
+    /// 4. `[writable]` Member PDA.
+    /// 5. `[writable]` Member's registered Loyalty Point SPL Token Account (bonus destination).
+    /// 6. `[writable]` Loyalty Point SPL Token Mint account.
+    /// 7. `[]` Program authority PDA (`[b"authority", config]`), the mint authority.
+    /// 8. `[]` SPL Token Program ID.
+    /// 9. `[writable]` Stats PDA (`[b"stats", config]`).
+    /// 10. `[writable]` Issuer stats PDA (`[b"issuer_stats", config, issuer]`).
+    /// 11. `[]` System program.
+    CheckIn,
+
+    /// Upgrades a config account written in an older layout to the current one in place:
+    /// reallocates it, tops up rent from the payer and rewrites it with the current header.
+    /// A baseline config keeps its keypair address and gets the stats PDA it predates, with
+    /// the mint's current supply counted as awarded. Does nothing if the config is already current.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Payer for the extra rent and the stats account.
+    /// 1. `[writable]` Config account.
+    /// 2. `[]` System program.
+    /// 3. `[writable]` Stats PDA (`[b"stats", config]`), created here.
+    /// 4. `[]` Loyalty Mint account.
+    MigrateConfig,
+
+    /// Upgrades any other account written in an older header version to the current one in
+    /// place: reallocates it, tops up rent from the payer and rewrites it with the current
+    /// header. Only the admin of the brand it belongs to may migrate it.
+    /// Does nothing if the account is already current.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Payer for the extra rent.
+    /// 1. `[signer]` Current Admin account.
+    /// 2. `[]` Config account the migrated account belongs to.
+    /// 3. `[writable]` Account to migrate.
+    /// 4. `[]` System program.
+    MigrateAccount {
+        /// Type of the account being migrated. Configs are migrated with `MigrateConfig`.
+        account_type: AccountType,
+    },
 }
@@ -1,6 +1,11 @@
+#[path = "../common/account_header.rs"]
+pub mod account_header;
+#[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
 pub mod error;
 pub mod instruction;
+pub mod merkle;
 pub mod processor;
 pub mod state;
+pub mod voucher;
 
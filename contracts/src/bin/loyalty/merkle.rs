@@ -0,0 +1,68 @@
+use solana_program::{hash::hashv, pubkey::Pubkey};
+
+// Merkle tree helpers shared by the on-chain `ClaimPoints` verification and the
+// off-chain `build-distribution` tool, so both sides always hash the same way.
+//
+// Leaves and inner nodes use different prefixes so a leaf can never be passed off
+// as an inner node. Inner nodes hash their children in sorted order, which means a
+// proof is just the list of sibling hashes (no left/right flags needed).
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Hashes a distribution leaf: `(index, claimant, amount)`.
+pub fn leaf_hash(index: u64, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[
+        LEAF_PREFIX,
+        &index.to_le_bytes(),
+        claimant.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Hashes two sibling nodes into their parent.
+pub fn node_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Verifies that `leaf` is part of the tree with the given `root`.
+pub fn verify(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf, |node, sibling| node_hash(&node, sibling));
+    computed == *root
+}
+
+/// Builds every level of the tree, from the leaves (level 0) up to the root.
+/// An odd node at the end of a level is carried up unchanged.
+pub fn build_levels(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaves];
+    while levels.last().is_some_and(|level| level.len() > 1) {
+        let level = levels.last().unwrap();
+        let next = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => node_hash(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Returns the proof for the leaf at `index`, given the levels from `build_levels`.
+pub fn proof(levels: &[Vec<[u8; 32]>], mut index: usize) -> Vec<[u8; 32]> {
+    let mut proof = Vec::new();
+    for level in &levels[..levels.len().saturating_sub(1)] {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            proof.push(level[sibling]);
+        }
+        index /= 2;
+    }
+    proof
+}
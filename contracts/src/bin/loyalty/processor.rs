@@ -1,21 +1,44 @@
+use borsh::BorshDeserialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     msg,
     program::{invoke, invoke_signed}, // For CPI
     program_error::ProgramError,
+    program_option::COption,
     program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
-    sysvar::{rent::Rent, Sysvar},
+    system_instruction,
+    sysvar::{
+        clock::Clock,
+        instructions::{load_current_index_checked, load_instruction_at_checked},
+        rent::Rent,
+        Sysvar,
+    },
 };
-use spl_token::{
+// Token-2022 instruction builders accept both the classic SPL Token and Token-2022 program IDs.
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
     instruction as token_instruction,
-    state::Account as TokenAccount, // To check token account owner
+    state::{Account as TokenAccount, Mint}, // To check token account owner and mint authority
 };
 use crate::{
+    account_header::{grow_account, has_header, upgrade_body, HEADER_LEN},
     error::LoyaltyError,
     instruction::LoyaltyInstruction,
-    state::ConfigAccount,
+    merkle,
+    state::{
+        find_authority_address, find_config_address, find_issuer_stats_address,
+        find_member_address, find_merchant_address, find_stake_position_address,
+        find_stats_address, find_swap_agreement_address, find_vault_address, streak_bonus,
+        AccountType, ClawbackRecord,
+        ConfigAccount, Distribution, IssuerStatsAccount, MemberAccount, MerchantAccount,
+        StakePosition, StakeTier, StatsAccount, StreakBonus, SwapAgreement, AUTHORITY_SEED,
+        BPS_DENOMINATOR, CONFIG_SEED, CONFIG_V0_LEN, CURRENT_VERSION, ISSUER_STATS_SEED, MEMBER_SEED, MERCHANT_SEED,
+        SECONDS_PER_DAY, STAKE_SEED, STAKE_TIER_COUNT, STATS_SEED, STREAK_BONUS_COUNT, SWAP_SEED,
+        VAULT_SEED,
+    },
+    voucher,
 };
 
 pub struct Processor;
@@ -29,9 +52,9 @@ impl Processor {
             .map_err(|_| ProgramError::InvalidInstructionData)?;
 
         match instruction {
-            LoyaltyInstruction::Initialize { admin } => {
+            LoyaltyInstruction::Initialize { admin, brand_id } => {
                 msg!("Instruction: Initialize");
-                Self::process_initialize(accounts, admin, program_id)
+                Self::process_initialize(accounts, admin, brand_id, program_id)
             }
             LoyaltyInstruction::AwardPoints { amount } => {
                 msg!("Instruction: AwardPoints");
@@ -45,50 +68,319 @@ impl Processor {
                 msg!("Instruction: SetAdmin");
                 Self::process_set_admin(accounts, new_admin, program_id)
             }
+            LoyaltyInstruction::ClawbackPoints { amount, reason_code } => {
+                msg!("Instruction: ClawbackPoints");
+                Self::process_clawback_points(accounts, amount, reason_code, program_id)
+            }
+            LoyaltyInstruction::CreateDistribution { merkle_root, total_amount, num_leaves, expiry } => {
+                msg!("Instruction: CreateDistribution");
+                Self::process_create_distribution(accounts, merkle_root, total_amount, num_leaves, expiry, program_id)
+            }
+            LoyaltyInstruction::ClaimPoints { index, amount, proof } => {
+                msg!("Instruction: ClaimPoints");
+                Self::process_claim_points(accounts, index, amount, proof, program_id)
+            }
+            LoyaltyInstruction::ProposeSwapAgreement { rate_numerator, rate_denominator, daily_limit } => {
+                msg!("Instruction: ProposeSwapAgreement");
+                Self::process_propose_swap_agreement(accounts, rate_numerator, rate_denominator, daily_limit, program_id)
+            }
+            LoyaltyInstruction::ApproveSwapAgreement { approved } => {
+                msg!("Instruction: ApproveSwapAgreement");
+                Self::process_approve_swap_agreement(accounts, approved, program_id)
+            }
+            LoyaltyInstruction::SwapPoints { amount } => {
+                msg!("Instruction: SwapPoints");
+                Self::process_swap_points(accounts, amount, program_id)
+            }
+            LoyaltyInstruction::RegisterMember { voucher_allowance } => {
+                msg!("Instruction: RegisterMember");
+                Self::process_register_member(accounts, voucher_allowance, program_id)
+            }
+            LoyaltyInstruction::RedeemWithVoucher { amount, nonce, expiry } => {
+                msg!("Instruction: RedeemWithVoucher");
+                Self::process_redeem_with_voucher(accounts, amount, nonce, expiry, program_id)
+            }
+            LoyaltyInstruction::SetStakeParams { early_unlock_penalty_bps, stake_tiers } => {
+                msg!("Instruction: SetStakeParams");
+                Self::process_set_stake_params(accounts, early_unlock_penalty_bps, stake_tiers, program_id)
+            }
+            LoyaltyInstruction::Stake { amount, lock_secs } => {
+                msg!("Instruction: Stake");
+                Self::process_stake(accounts, amount, lock_secs, program_id)
+            }
+            LoyaltyInstruction::Unstake => {
+                msg!("Instruction: Unstake");
+                Self::process_unstake(accounts, program_id)
+            }
+            LoyaltyInstruction::ExtendLock { additional_secs } => {
+                msg!("Instruction: ExtendLock");
+                Self::process_extend_lock(accounts, additional_secs, program_id)
+            }
+            LoyaltyInstruction::RegisterMerchant => {
+                msg!("Instruction: RegisterMerchant");
+                Self::process_register_merchant(accounts, program_id)
+            }
+            LoyaltyInstruction::RedeemAtMerchant { amount } => {
+                msg!("Instruction: RedeemAtMerchant");
+                Self::process_redeem_at_merchant(accounts, amount, program_id)
+            }
+            LoyaltyInstruction::Settle { amount, payment_reference } => {
+                msg!("Instruction: Settle");
+                Self::process_settle(accounts, amount, payment_reference, program_id)
+            }
+            LoyaltyInstruction::SetStreakBonuses { streak_bonuses } => {
+                msg!("Instruction: SetStreakBonuses");
+                Self::process_set_streak_bonuses(accounts, streak_bonuses, program_id)
+            }
+            LoyaltyInstruction::CheckIn => {
+                msg!("Instruction: CheckIn");
+                Self::process_check_in(accounts, program_id)
+            }
+            LoyaltyInstruction::MigrateConfig => {
+                msg!("Instruction: MigrateConfig");
+                Self::process_migrate_config(accounts, program_id)
+            }
+            LoyaltyInstruction::MigrateAccount { account_type } => {
+                msg!("Instruction: MigrateAccount");
+                Self::process_migrate_account(accounts, account_type, program_id)
+            }
+        }
+    }
+
+    /// Checks the token program is SPL Token or Token-2022, so CPIs signed by the
+    /// authority PDA can't be redirected to a fake program.
+    fn check_token_program(token_program_account: &AccountInfo) -> ProgramResult {
+        if *token_program_account.key != spl_token::id() && *token_program_account.key != spl_token_2022::id() {
+            msg!("Error: Token program is not SPL Token or Token-2022");
+            return Err(LoyaltyError::InvalidTokenProgram.into());
+        }
+        Ok(())
+    }
+
+    /// Loads a brand's config account and checks that it is the canonical
+    /// `[b"config", brand_id]` PDA of this program (or the migrated legacy config it
+    /// records), so every instruction is scoped to exactly one brand.
+    fn load_config(
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<ConfigAccount, ProgramError> {
+        if config_account.owner != program_id {
+            msg!("Error: Config account not owned by program");
+            return Err(LoyaltyError::InvalidConfigAccountOwner.into());
+        }
+
+        let config_data = ConfigAccount::unpack_unchecked(&config_account.data.borrow())?;
+        if !config_data.is_initialized() {
+            msg!("Error: Config account not initialized");
+            return Err(LoyaltyError::NotInitialized.into());
+        }
+
+        // Legacy configs predate brands and live at a keypair address
+        let expected_key = if config_data.legacy_address != Pubkey::default() {
+            config_data.legacy_address
+        } else {
+            Pubkey::create_program_address(
+                &[CONFIG_SEED, &config_data.brand_id.to_le_bytes(), &[config_data.bump]],
+                program_id,
+            )?
+        };
+        if expected_key != *config_account.key {
+            msg!("Error: Config account is not the PDA for brand {}", config_data.brand_id);
+            return Err(LoyaltyError::ConfigMismatch.into());
+        }
+        Ok(config_data)
+    }
+
+    /// Loads a brand's stats account and checks it belongs to the given config.
+    fn load_stats(
+        stats_account: &AccountInfo,
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<StatsAccount, ProgramError> {
+        if stats_account.owner != program_id {
+            msg!("Error: Stats account not owned by program");
+            return Err(LoyaltyError::InvalidAccountOwner.into());
+        }
+        let stats_data = StatsAccount::unpack(&stats_account.data.borrow())?;
+        if stats_data.config != *config_account.key {
+            msg!("Error: Stats account belongs to a different config");
+            return Err(LoyaltyError::ConfigMismatch.into());
         }
+        Ok(stats_data)
+    }
+
+    /// Creates a PDA account of `space` bytes owned by `owner` (usually this program), paid for by `payer`.
+    fn create_pda_account<'a>(
+        payer: &AccountInfo<'a>,
+        pda_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        rent: &Rent,
+        space: usize,
+        signer_seeds: &[&[u8]],
+        owner: &Pubkey,
+    ) -> ProgramResult {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                pda_account.key,
+                rent.minimum_balance(space),
+                space as u64,
+                owner,
+            ),
+            &[payer.clone(), pda_account.clone(), system_program.clone()],
+            &[signer_seeds],
+        )
     }
 
     /// Processes Initialize instruction.
     fn process_initialize(
         accounts: &[AccountInfo],
         admin: Pubkey,
+        brand_id: u64,
         program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
-        let initializer_account = next_account_info(account_info_iter)?; // Signer
-        let config_account = next_account_info(account_info_iter)?;      // Writable
+        let initializer_account = next_account_info(account_info_iter)?; // Signer, Writable (payer)
+        let config_account = next_account_info(account_info_iter)?;      // Writable (config PDA)
         let loyalty_mint_account = next_account_info(account_info_iter)?; // Readonly
         let rent_sysvar_account = next_account_info(account_info_iter)?; // Rent
-        let _system_program = next_account_info(account_info_iter)?;     // System
+        let system_program = next_account_info(account_info_iter)?;      // System
+        let stats_account = next_account_info(account_info_iter)?;       // Writable (stats PDA)
 
         if !initializer_account.is_signer {
              msg!("Initializer signature missing");
              return Err(ProgramError::MissingRequiredSignature);
         }
 
-        // Check ownership, rent-exemption, initialization status
-        if config_account.owner != program_id {
-             msg!("Error: Config account not owned by program");
-             return Err(LoyaltyError::InvalidConfigAccountOwner.into());
+        // Each brand gets its own config at `[b"config", brand_id]`
+        let brand_id_bytes = brand_id.to_le_bytes();
+        let (config_key, config_bump) = find_config_address(brand_id, program_id);
+        if config_key != *config_account.key {
+            msg!("Error: Config account is not the PDA for brand {}", brand_id);
+            return Err(LoyaltyError::ConfigMismatch.into());
         }
-        let rent = Rent::from_account_info(rent_sysvar_account)?;
-        if !rent.is_exempt(config_account.lamports(), config_account.data_len()) {
-             msg!("Error: Config account not rent exempt");
-             return Err(LoyaltyError::NotRentExempt.into());
+        if !config_account.data_is_empty() {
+            msg!("Error: Config account already initialized");
+            return Err(LoyaltyError::AlreadyInitialized.into());
         }
-        let mut config_data = ConfigAccount::unpack_unchecked(&config_account.data.borrow())?;
-        if config_data.is_initialized() {
-             msg!("Error: Config account already initialized");
-             return Err(LoyaltyError::AlreadyInitialized.into());
+
+        // The brand's mint must already have this config's authority PDA as mint authority,
+        // which also guarantees that no two brands can share a mint.
+        let (authority_key, _) = find_authority_address(config_account.key, program_id);
+        let mint_data = StateWithExtensions::<Mint>::unpack(&loyalty_mint_account.data.borrow())?.base;
+        if mint_data.mint_authority != COption::Some(authority_key) {
+            msg!("Error: Mint authority must be the program authority PDA {}", authority_key);
+            return Err(LoyaltyError::InvalidMintAuthority.into());
+        }
+
+        let (stats_key, stats_bump) = find_stats_address(config_account.key, program_id);
+        if stats_key != *stats_account.key {
+            msg!("Error: Stats account is not the PDA for this config");
+            return Err(LoyaltyError::ConfigMismatch.into());
         }
 
+        // Create the config and stats PDAs, paid for by the initializer
+        let rent = Rent::from_account_info(rent_sysvar_account)?;
+        Self::create_pda_account(
+            initializer_account,
+            config_account,
+            system_program,
+            &rent,
+            ConfigAccount::LEN,
+            &[CONFIG_SEED, &brand_id_bytes, &[config_bump]],
+            program_id,
+        )?;
+        Self::create_pda_account(
+            initializer_account,
+            stats_account,
+            system_program,
+            &rent,
+            StatsAccount::LEN,
+            &[STATS_SEED, config_account.key.as_ref(), &[stats_bump]],
+            program_id,
+        )?;
+
         // Initialize state
-        config_data.is_initialized = true;
-        config_data.admin = admin;
-        config_data.loyalty_mint = *loyalty_mint_account.key;
+        let config_data = ConfigAccount {
+            is_initialized: true,
+            admin,
+            loyalty_mint: *loyalty_mint_account.key,
+            brand_id,
+            bump: config_bump,
+            ..ConfigAccount::default()
+        };
 
         ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
-        msg!("Loyalty Config initialized. Admin: {}, Mint: {}", admin, loyalty_mint_account.key);
+
+        // Points already in circulation count as awarded so the stats reconcile with the supply
+        let stats_data = StatsAccount {
+            is_initialized: true,
+            config: *config_account.key,
+            total_awarded: mint_data.supply,
+            bump: stats_bump,
+            ..StatsAccount::default()
+        };
+        StatsAccount::pack(stats_data, &mut stats_account.data.borrow_mut())?;
+
+        msg!(
+            "Loyalty Config initialized for brand {}. Admin: {}, Mint: {}",
+            brand_id,
+            admin,
+            loyalty_mint_account.key
+        );
+        Ok(())
+    }
+
+    /// Adds an award to the issuer's stats PDA, creating it (paid by the issuer) on first use.
+    fn record_issuer_award<'a>(
+        issuer_account: &AccountInfo<'a>,
+        issuer_stats_account: &AccountInfo<'a>,
+        config_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let (issuer_stats_key, issuer_stats_bump) =
+            find_issuer_stats_address(config_account.key, issuer_account.key, program_id);
+        if issuer_stats_key != *issuer_stats_account.key {
+            msg!("Error: Issuer stats account is not the PDA for this issuer");
+            return Err(LoyaltyError::ConfigMismatch.into());
+        }
+        let mut issuer_stats_data = if issuer_stats_account.data_is_empty() {
+            // First award by this issuer: create its stats account
+            Self::create_pda_account(
+                issuer_account,
+                issuer_stats_account,
+                system_program,
+                &Rent::get()?,
+                IssuerStatsAccount::LEN,
+                &[
+                    ISSUER_STATS_SEED,
+                    config_account.key.as_ref(),
+                    issuer_account.key.as_ref(),
+                    &[issuer_stats_bump],
+                ],
+                program_id,
+            )?;
+            IssuerStatsAccount {
+                is_initialized: true,
+                config: *config_account.key,
+                issuer: *issuer_account.key,
+                bump: issuer_stats_bump,
+                ..IssuerStatsAccount::default()
+            }
+        } else {
+            IssuerStatsAccount::unpack(&issuer_stats_account.data.borrow())?
+        };
+        issuer_stats_data.award_count = issuer_stats_data
+            .award_count
+            .checked_add(1)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        issuer_stats_data.total_awarded = issuer_stats_data
+            .total_awarded
+            .checked_add(amount)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        IssuerStatsAccount::pack(issuer_stats_data, &mut issuer_stats_account.data.borrow_mut())?;
         Ok(())
     }
 
@@ -104,23 +396,19 @@ impl Processor {
         let loyalty_mint_account = next_account_info(account_info_iter)?;  // Writable (SPL Token requires)
         let destination_token_account = next_account_info(account_info_iter)?; // Writable
         let token_program_account = next_account_info(account_info_iter)?; // Readonly (SPL Token Program ID)
-        // Account 5: Mint Authority (this program_id or its PDA) is implicitly derived or passed if needed for invoke_signed
+        let authority_account = next_account_info(account_info_iter)?;     // Readonly (Mint Authority PDA)
+        let stats_account = next_account_info(account_info_iter)?;         // Writable (stats PDA)
+        let issuer_stats_account = next_account_info(account_info_iter)?;  // Writable (issuer stats PDA)
+        let system_program = next_account_info(account_info_iter)?;        // System
+        let stake_position_account = next_account_info(account_info_iter)?; // Readonly (may be empty)
 
         // --- Validation ---
         if !admin_account.is_signer {
             msg!("Error: Admin signature missing");
             return Err(ProgramError::MissingRequiredSignature);
         }
-        if config_account.owner != program_id {
-             msg!("Error: Config account not owned by program");
-             return Err(LoyaltyError::InvalidConfigAccountOwner.into());
-        }
 
-        let config_data = ConfigAccount::unpack(&config_account.data.borrow())?;
-        if !config_data.is_initialized() {
-             msg!("Error: Config account not initialized");
-             return Err(LoyaltyError::NotInitialized.into());
-        }
+        let config_data = Self::load_config(config_account, program_id)?;
 
         // Check if signer is the admin
         if config_data.admin != *admin_account.key {
@@ -134,44 +422,79 @@ impl Processor {
             return Err(LoyaltyError::MintAccountMismatch.into());
         }
 
+        let (authority_key, authority_bump) = find_authority_address(config_account.key, program_id);
+        if authority_key != *authority_account.key {
+            msg!("Error: Authority account is not the program authority PDA");
+            return Err(LoyaltyError::AuthorityMismatch.into());
+        }
+
+        Self::check_token_program(token_program_account)?;
+
+        let destination_data =
+            StateWithExtensions::<TokenAccount>::unpack(&destination_token_account.data.borrow())?.base;
+        if destination_data.mint != *loyalty_mint_account.key {
+            msg!("Error: Destination token account is for the wrong mint");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+
+        // --- Apply the recipient's stake multiplier ---
+        let (stake_position_key, _) =
+            find_stake_position_address(config_account.key, &destination_data.owner, program_id);
+        if stake_position_key != *stake_position_account.key {
+            msg!("Error: Stake position account is not the PDA for the recipient");
+            return Err(LoyaltyError::MemberMismatch.into());
+        }
+        let (tier, multiplier_bps) = if stake_position_account.data_is_empty() {
+            (0, BPS_DENOMINATOR)
+        } else {
+            let stake_position = Self::load_stake_position(stake_position_account, config_account, program_id)?;
+            stake_position.effective_tier(&config_data.stake_tiers, Clock::get()?.unix_timestamp)
+        };
+        let amount = Self::apply_bps(amount, multiplier_bps)?;
+        if tier > 0 {
+            msg!("Recipient is stake tier {} ({} bps multiplier)", tier, multiplier_bps);
+        }
+
+        // --- Update brand and issuer statistics ---
+        let mut stats_data = Self::load_stats(stats_account, config_account, program_id)?;
+        stats_data.record_mint(amount, destination_data.amount)?;
+        StatsAccount::pack(stats_data, &mut stats_account.data.borrow_mut())?;
+
+        Self::record_issuer_award(
+            admin_account,
+            issuer_stats_account,
+            config_account,
+            system_program,
+            amount,
+            program_id,
+        )?;
+
         // --- CPI to SPL Token Program ---
         msg!("Awarding {} loyalty points to {}", amount, destination_token_account.key);
 
-        // ** IMPORTANT: Mint Authority Assumption **
-        // This assumes this program's address (`program_id`) was set as the
-        // mint authority when the `loyalty_mint_account` was created.
-        // If a PDA is the authority, use `invoke_signed` with PDA seeds.
+        // The mint authority of `loyalty_mint_account` must be set to the program
+        // authority PDA (`[b"authority", config]`) when the mint is created, so the
+        // program signs the CPI with the PDA seeds.
         let mint_cpi_instruction = token_instruction::mint_to(
             token_program_account.key,    // SPL Token program ID
             loyalty_mint_account.key,     // The Mint to mint from
             destination_token_account.key,// Destination user ATA
-            program_id,                   // Mint Authority (this program's ID)
-            &[program_id],                // Signer seeds (empty if program_id is authority)
+            authority_account.key,        // Mint Authority (program authority PDA)
+            &[],                          // No multisig signers
             amount,
         )?;
 
-        invoke(
+        invoke_signed(
             &mint_cpi_instruction,
             &[
                 loyalty_mint_account.clone(),       // Mint account
                 destination_token_account.clone(),  // Destination ATA
+                authority_account.clone(),          // Mint Authority (PDA)
                 token_program_account.clone(),      // SPL Token program ID
-                // Authority account info - If program_id is authority, it doesn't need to be passed
-                // explicitly here as it's derived by invoke/invoke_signed.
-                // If using PDA, pass the PDA account info here.
-                // If the *admin* was authority (less secure), pass admin_account.clone().
-                // Let's assume program_id is authority for simplicity:
-                // We need an AccountInfo for program_id if invoke requires it as authority
-                // However, typically the authority is implicitly handled when it's the calling program.
-                // Let's refine this - the authority needs to be provided.
-                // We need an AccountInfo representing this program itself as the authority.
-                // This is tricky. Let's assume the *admin* is the authority for this simpler example.
-                // ** REVISED ASSUMPTION: Admin account is the mint authority **
-                admin_account.clone(),             // Mint Authority (admin - Revised Assumption)
             ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
         )?;
 
-
         msg!("Points awarded successfully.");
         Ok(())
     }
@@ -180,13 +503,15 @@ impl Processor {
     fn process_redeem_points(
         accounts: &[AccountInfo],
         amount: u64,
-        program_id: &Pubkey, // program_id not strictly needed here but good practice
+        program_id: &Pubkey,
     ) -> ProgramResult {
          let account_info_iter = &mut accounts.iter();
          let user_account = next_account_info(account_info_iter)?;           // Signer (owner of source_token_account)
+         let config_account = next_account_info(account_info_iter)?;         // Readonly
          let source_token_account = next_account_info(account_info_iter)?;   // Writable (User's ATA)
          let loyalty_mint_account = next_account_info(account_info_iter)?;   // Writable (SPL Token requires)
          let token_program_account = next_account_info(account_info_iter)?; // Readonly (SPL Token Program ID)
+         let stats_account = next_account_info(account_info_iter)?;         // Writable (stats PDA)
 
          // --- Validation ---
          if !user_account.is_signer {
@@ -194,8 +519,16 @@ impl Processor {
              return Err(ProgramError::MissingRequiredSignature);
          }
 
+         // Redemptions are scoped to one brand: the mint must be that brand's mint
+         let config_data = Self::load_config(config_account, program_id)?;
+         if config_data.loyalty_mint != *loyalty_mint_account.key {
+             msg!("Error: Mint account does not match configured mint");
+             return Err(LoyaltyError::MintAccountMismatch.into());
+         }
+
          // Check that the user_account (signer) is the owner of the source_token_account
-         let token_account_data = TokenAccount::unpack(&source_token_account.data.borrow())?;
+         let token_account_data =
+             StateWithExtensions::<TokenAccount>::unpack(&source_token_account.data.borrow())?.base;
          if token_account_data.owner != *user_account.key {
              msg!("Error: Signer is not the owner of the source token account");
              return Err(LoyaltyError::OwnerMismatch.into());
@@ -207,6 +540,11 @@ impl Processor {
               return Err(LoyaltyError::MintAccountMismatch.into()); // Re-use error or add specific one
          }
 
+         Self::check_token_program(token_program_account)?;
+
+         let mut stats_data = Self::load_stats(stats_account, config_account, program_id)?;
+         stats_data.record_redeem(amount, token_account_data.amount)?;
+         StatsAccount::pack(stats_data, &mut stats_account.data.borrow_mut())?;
 
          // --- CPI to SPL Token Program to Burn ---
          msg!("Redeeming (burning) {} loyalty points from {}", amount, source_token_account.key);
@@ -248,16 +586,8 @@ impl Processor {
             msg!("Error: Current admin signature missing");
             return Err(ProgramError::MissingRequiredSignature);
         }
-         if config_account.owner != program_id {
-             msg!("Error: Config account not owned by program");
-             return Err(LoyaltyError::InvalidConfigAccountOwner.into());
-        }
 
-        let mut config_data = ConfigAccount::unpack(&config_account.data.borrow())?;
-        if !config_data.is_initialized() {
-             msg!("Error: Config account not initialized");
-             return Err(LoyaltyError::NotInitialized.into());
-        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
 
         // Verify signer is the current admin
         if config_data.admin != *current_admin_account.key {
@@ -272,4 +602,3410 @@ impl Processor {
         msg!("Loyalty program admin updated successfully to: {}", new_admin);
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Processes ClawbackPoints instruction.
+    fn process_clawback_points(
+        accounts: &[AccountInfo],
+        amount: u64,
+        reason_code: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;          // Signer
+        let config_account = next_account_info(account_info_iter)?;         // Readonly
+        let loyalty_mint_account = next_account_info(account_info_iter)?;   // Writable
+        let member_token_account = next_account_info(account_info_iter)?;   // Writable
+        let clawback_record_account = next_account_info(account_info_iter)?; // Writable
+        let authority_account = next_account_info(account_info_iter)?;      // Readonly (PDA)
+        let token_program_account = next_account_info(account_info_iter)?;  // Readonly (Token-2022)
+        let stats_account = next_account_info(account_info_iter)?;          // Writable (stats PDA)
+
+        // --- Validation ---
+        if !admin_account.is_signer {
+            msg!("Error: Admin signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        if config_data.admin != *admin_account.key {
+            msg!("Error: Signer is not the configured admin");
+            return Err(LoyaltyError::AdminSignatureMismatch.into());
+        }
+        if config_data.loyalty_mint != *loyalty_mint_account.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+
+        // Permanent delegates only exist on Token-2022 mints.
+        Self::check_token_program(token_program_account)?;
+        if *token_program_account.key != spl_token_2022::id() {
+            msg!("Error: Clawbacks require the Token-2022 program");
+            return Err(LoyaltyError::InvalidTokenProgram.into());
+        }
+
+        let (authority_key, authority_bump) = find_authority_address(config_account.key, program_id);
+        if authority_key != *authority_account.key {
+            msg!("Error: Authority account is not the program authority PDA");
+            return Err(LoyaltyError::AuthorityMismatch.into());
+        }
+
+        let member_token_data =
+            StateWithExtensions::<TokenAccount>::unpack(&member_token_account.data.borrow())?.base;
+        if member_token_data.mint != *loyalty_mint_account.key {
+            msg!("Error: Member token account is for the wrong mint");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+
+        // The record account is created client-side, like the config account.
+        if clawback_record_account.owner != program_id {
+            msg!("Error: Clawback record account not owned by program");
+            return Err(LoyaltyError::InvalidAccountOwner.into());
+        }
+        let rent = Rent::get()?;
+        if !rent.is_exempt(clawback_record_account.lamports(), clawback_record_account.data_len()) {
+            msg!("Error: Clawback record account not rent exempt");
+            return Err(LoyaltyError::NotRentExempt.into());
+        }
+        let mut record_data = ClawbackRecord::unpack_unchecked(&clawback_record_account.data.borrow())?;
+        if record_data.is_initialized() {
+            msg!("Error: Clawback record account already initialized");
+            return Err(LoyaltyError::AlreadyInitialized.into());
+        }
+
+        let mut stats_data = Self::load_stats(stats_account, config_account, program_id)?;
+        stats_data.record_clawback(amount, member_token_data.amount)?;
+        StatsAccount::pack(stats_data, &mut stats_account.data.borrow_mut())?;
+
+        // --- CPI to Token-2022 to Burn as permanent delegate ---
+        msg!(
+            "Clawing back {} loyalty points from {} (reason code {})",
+            amount,
+            member_token_account.key,
+            reason_code
+        );
+
+        let burn_cpi_instruction = token_instruction::burn(
+            token_program_account.key,    // Token-2022 program ID
+            member_token_account.key,     // Account to burn from
+            loyalty_mint_account.key,     // Mint of the token
+            authority_account.key,        // Permanent delegate (program authority PDA)
+            &[],                          // No multisig signers
+            amount,
+        )?;
+
+        invoke_signed(
+            &burn_cpi_instruction,
+            &[
+                member_token_account.clone(),
+                loyalty_mint_account.clone(),
+                authority_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+
+        // --- Record the clawback for auditors ---
+        record_data.is_initialized = true;
+        record_data.config = *config_account.key;
+        record_data.member = member_token_data.owner;
+        record_data.token_account = *member_token_account.key;
+        record_data.admin = *admin_account.key;
+        record_data.amount = amount;
+        record_data.reason_code = reason_code;
+        record_data.timestamp = Clock::get()?.unix_timestamp;
+        ClawbackRecord::pack(record_data, &mut clawback_record_account.data.borrow_mut())?;
+
+        msg!("Points clawed back successfully. Record: {}", clawback_record_account.key);
+        Ok(())
+    }
+
+    /// Processes CreateDistribution instruction.
+    fn process_create_distribution(
+        accounts: &[AccountInfo],
+        merkle_root: [u8; 32],
+        total_amount: u64,
+        num_leaves: u64,
+        expiry: i64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;        // Signer
+        let config_account = next_account_info(account_info_iter)?;       // Readonly
+        let distribution_account = next_account_info(account_info_iter)?; // Writable
+
+        // --- Validation ---
+        if !admin_account.is_signer {
+            msg!("Error: Admin signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        if config_data.admin != *admin_account.key {
+            msg!("Error: Signer is not the configured admin");
+            return Err(LoyaltyError::AdminSignatureMismatch.into());
+        }
+
+        if distribution_account.owner != program_id {
+            msg!("Error: Distribution account not owned by program");
+            return Err(LoyaltyError::InvalidAccountOwner.into());
+        }
+        let rent = Rent::get()?;
+        if !rent.is_exempt(distribution_account.lamports(), distribution_account.data_len()) {
+            msg!("Error: Distribution account not rent exempt");
+            return Err(LoyaltyError::NotRentExempt.into());
+        }
+        if distribution_account.data_len() < Distribution::account_len(num_leaves) {
+            msg!("Error: Distribution account too small for {} leaves", num_leaves);
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+
+        let mut distribution_data = distribution_account.data.borrow_mut();
+        let (header, bitmap) = distribution_data.split_at_mut(Distribution::LEN);
+        let mut distribution = Distribution::unpack_unchecked(header)?;
+        if distribution.is_initialized() {
+            msg!("Error: Distribution account already initialized");
+            return Err(LoyaltyError::AlreadyInitialized.into());
+        }
+
+        // Initialize state with an empty claimed bitmap
+        bitmap.fill(0);
+        distribution.is_initialized = true;
+        distribution.config = *config_account.key;
+        distribution.merkle_root = merkle_root;
+        distribution.total_amount = total_amount;
+        distribution.claimed_amount = 0;
+        distribution.num_leaves = num_leaves;
+        distribution.expiry = expiry;
+        Distribution::pack(distribution, header)?;
+
+        msg!(
+            "Distribution {} created: {} points across {} leaves, expires at {}",
+            distribution_account.key,
+            total_amount,
+            num_leaves,
+            expiry
+        );
+        Ok(())
+    }
+
+    /// Processes ClaimPoints instruction.
+    fn process_claim_points(
+        accounts: &[AccountInfo],
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_account = next_account_info(account_info_iter)?;             // Signer
+        let config_account = next_account_info(account_info_iter)?;            // Readonly
+        let distribution_account = next_account_info(account_info_iter)?;      // Writable
+        let loyalty_mint_account = next_account_info(account_info_iter)?;      // Writable
+        let destination_token_account = next_account_info(account_info_iter)?; // Writable
+        let authority_account = next_account_info(account_info_iter)?;         // Readonly (PDA)
+        let token_program_account = next_account_info(account_info_iter)?;     // Readonly
+        let stats_account = next_account_info(account_info_iter)?;             // Writable (stats PDA)
+
+        // --- Validation ---
+        if !payer_account.is_signer {
+            msg!("Error: Payer signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if distribution_account.owner != program_id {
+            msg!("Error: Distribution account not owned by program");
+            return Err(LoyaltyError::InvalidAccountOwner.into());
+        }
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        if config_data.loyalty_mint != *loyalty_mint_account.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+
+        let (authority_key, authority_bump) = find_authority_address(config_account.key, program_id);
+        if authority_key != *authority_account.key {
+            msg!("Error: Authority account is not the program authority PDA");
+            return Err(LoyaltyError::AuthorityMismatch.into());
+        }
+
+        Self::check_token_program(token_program_account)?;
+
+        let mut distribution_data = distribution_account.data.borrow_mut();
+        if distribution_data.len() < Distribution::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (header, bitmap) = distribution_data.split_at_mut(Distribution::LEN);
+        let mut distribution = Distribution::unpack(header)?;
+        if distribution.config != *config_account.key {
+            msg!("Error: Distribution belongs to a different config");
+            return Err(LoyaltyError::ConfigMismatch.into());
+        }
+        if Clock::get()?.unix_timestamp > distribution.expiry {
+            msg!("Error: Distribution expired at {}", distribution.expiry);
+            return Err(LoyaltyError::DistributionExpired.into());
+        }
+        if index >= distribution.num_leaves {
+            msg!("Error: Index {} out of range ({} leaves)", index, distribution.num_leaves);
+            return Err(LoyaltyError::InvalidDistributionIndex.into());
+        }
+        if Distribution::is_claimed(bitmap, index) {
+            msg!("Error: Leaf {} already claimed", index);
+            return Err(LoyaltyError::AlreadyClaimed.into());
+        }
+
+        // The leaf commits to the claimant's wallet, so the points can only land in their account.
+        let destination_data =
+            StateWithExtensions::<TokenAccount>::unpack(&destination_token_account.data.borrow())?.base;
+        if destination_data.mint != *loyalty_mint_account.key {
+            msg!("Error: Destination token account is for the wrong mint");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+        let leaf = merkle::leaf_hash(index, &destination_data.owner, amount);
+        if !merkle::verify(&proof, &distribution.merkle_root, leaf) {
+            msg!("Error: Merkle proof does not match distribution root");
+            return Err(LoyaltyError::InvalidMerkleProof.into());
+        }
+
+        let claimed_amount = distribution
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        if claimed_amount > distribution.total_amount {
+            msg!("Error: Claim would exceed distribution total");
+            return Err(LoyaltyError::DistributionExhausted.into());
+        }
+
+        // --- Mark claimed before the CPI ---
+        Distribution::set_claimed(bitmap, index);
+        distribution.claimed_amount = claimed_amount;
+        Distribution::pack(distribution, header)?;
+
+        let mut stats_data = Self::load_stats(stats_account, config_account, program_id)?;
+        stats_data.record_mint(amount, destination_data.amount)?;
+        StatsAccount::pack(stats_data, &mut stats_account.data.borrow_mut())?;
+
+        // --- CPI to SPL Token Program ---
+        msg!("Claiming {} loyalty points (leaf {}) to {}", amount, index, destination_token_account.key);
+        let mint_cpi_instruction = token_instruction::mint_to(
+            token_program_account.key,
+            loyalty_mint_account.key,
+            destination_token_account.key,
+            authority_account.key,
+            &[],
+            amount,
+        )?;
+
+        invoke_signed(
+            &mint_cpi_instruction,
+            &[
+                loyalty_mint_account.clone(),
+                destination_token_account.clone(),
+                authority_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+
+        msg!("Points claimed successfully.");
+        Ok(())
+    }
+
+    /// Loads a swap agreement and checks it links the given source and target configs.
+    fn load_swap_agreement(
+        agreement_account: &AccountInfo,
+        source_config_account: &AccountInfo,
+        target_config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<SwapAgreement, ProgramError> {
+        if agreement_account.owner != program_id {
+            msg!("Error: Swap agreement account not owned by program");
+            return Err(LoyaltyError::InvalidAccountOwner.into());
+        }
+        let agreement = SwapAgreement::unpack(&agreement_account.data.borrow())?;
+        if agreement.source_config != *source_config_account.key
+            || agreement.target_config != *target_config_account.key
+        {
+            msg!("Error: Swap agreement does not link the given configs");
+            return Err(LoyaltyError::ConfigMismatch.into());
+        }
+        Ok(agreement)
+    }
+
+    /// Processes ProposeSwapAgreement instruction.
+    fn process_propose_swap_agreement(
+        accounts: &[AccountInfo],
+        rate_numerator: u64,
+        rate_denominator: u64,
+        daily_limit: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;         // Signer, Writable (payer)
+        let source_config_account = next_account_info(account_info_iter)?; // Readonly
+        let target_config_account = next_account_info(account_info_iter)?; // Readonly
+        let agreement_account = next_account_info(account_info_iter)?;     // Writable (PDA)
+        let system_program = next_account_info(account_info_iter)?;        // System
+
+        // --- Validation ---
+        if !admin_account.is_signer {
+            msg!("Error: Admin signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if source_config_account.key == target_config_account.key {
+            msg!("Error: Source and target brands must differ");
+            return Err(LoyaltyError::ConfigMismatch.into());
+        }
+        if rate_numerator == 0 || rate_denominator == 0 {
+            msg!("Error: Swap rate must be non-zero");
+            return Err(LoyaltyError::InvalidSwapRate.into());
+        }
+
+        let source_config = Self::load_config(source_config_account, program_id)?;
+        let target_config = Self::load_config(target_config_account, program_id)?;
+        let is_source_admin = source_config.admin == *admin_account.key;
+        let is_target_admin = target_config.admin == *admin_account.key;
+        if !is_source_admin && !is_target_admin {
+            msg!("Error: Signer is not the admin of either brand");
+            return Err(LoyaltyError::AdminSignatureMismatch.into());
+        }
+
+        let (agreement_key, agreement_bump) = find_swap_agreement_address(
+            source_config_account.key,
+            target_config_account.key,
+            program_id,
+        );
+        if agreement_key != *agreement_account.key {
+            msg!("Error: Swap agreement account is not the expected PDA");
+            return Err(LoyaltyError::ConfigMismatch.into());
+        }
+        if !agreement_account.data_is_empty() {
+            msg!("Error: Swap agreement already exists");
+            return Err(LoyaltyError::AlreadyInitialized.into());
+        }
+
+        // --- Create the agreement PDA ---
+        Self::create_pda_account(
+            admin_account,
+            agreement_account,
+            system_program,
+            &Rent::get()?,
+            SwapAgreement::LEN,
+            &[
+                SWAP_SEED,
+                source_config_account.key.as_ref(),
+                target_config_account.key.as_ref(),
+                &[agreement_bump],
+            ],
+            program_id,
+        )?;
+
+        let agreement = SwapAgreement {
+            is_initialized: true,
+            source_config: *source_config_account.key,
+            target_config: *target_config_account.key,
+            rate_numerator,
+            rate_denominator,
+            daily_limit,
+            current_day: 0,
+            swapped_today: 0,
+            source_approved: is_source_admin,
+            target_approved: is_target_admin,
+            bump: agreement_bump,
+        };
+        SwapAgreement::pack(agreement, &mut agreement_account.data.borrow_mut())?;
+
+        msg!(
+            "Swap agreement {} proposed: brand {} -> brand {} at {}/{}, daily limit {}",
+            agreement_account.key,
+            source_config.brand_id,
+            target_config.brand_id,
+            rate_numerator,
+            rate_denominator,
+            daily_limit
+        );
+        Ok(())
+    }
+
+    /// Processes ApproveSwapAgreement instruction.
+    fn process_approve_swap_agreement(
+        accounts: &[AccountInfo],
+        approved: bool,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;         // Signer
+        let source_config_account = next_account_info(account_info_iter)?; // Readonly
+        let target_config_account = next_account_info(account_info_iter)?; // Readonly
+        let agreement_account = next_account_info(account_info_iter)?;     // Writable
+
+        if !admin_account.is_signer {
+            msg!("Error: Admin signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let source_config = Self::load_config(source_config_account, program_id)?;
+        let target_config = Self::load_config(target_config_account, program_id)?;
+        let mut agreement = Self::load_swap_agreement(
+            agreement_account,
+            source_config_account,
+            target_config_account,
+            program_id,
+        )?;
+
+        // An admin of both brands approves (or revokes) both sides at once
+        let mut is_admin = false;
+        if source_config.admin == *admin_account.key {
+            agreement.source_approved = approved;
+            is_admin = true;
+        }
+        if target_config.admin == *admin_account.key {
+            agreement.target_approved = approved;
+            is_admin = true;
+        }
+        if !is_admin {
+            msg!("Error: Signer is not the admin of either brand");
+            return Err(LoyaltyError::AdminSignatureMismatch.into());
+        }
+
+        SwapAgreement::pack(agreement.clone(), &mut agreement_account.data.borrow_mut())?;
+        msg!(
+            "Swap agreement {} approvals: source={}, target={}",
+            agreement_account.key,
+            agreement.source_approved,
+            agreement.target_approved
+        );
+        Ok(())
+    }
+
+    /// Processes SwapPoints instruction.
+    fn process_swap_points(
+        accounts: &[AccountInfo],
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_account = next_account_info(account_info_iter)?;              // Signer
+        let source_config_account = next_account_info(account_info_iter)?;     // Readonly
+        let target_config_account = next_account_info(account_info_iter)?;     // Readonly
+        let agreement_account = next_account_info(account_info_iter)?;         // Writable
+        let source_mint_account = next_account_info(account_info_iter)?;       // Writable
+        let source_token_account = next_account_info(account_info_iter)?;      // Writable
+        let target_mint_account = next_account_info(account_info_iter)?;       // Writable
+        let destination_token_account = next_account_info(account_info_iter)?; // Writable
+        let target_authority_account = next_account_info(account_info_iter)?;  // Readonly (PDA)
+        let token_program_account = next_account_info(account_info_iter)?;     // Readonly
+        let source_stats_account = next_account_info(account_info_iter)?;      // Writable (source stats PDA)
+        let target_stats_account = next_account_info(account_info_iter)?;      // Writable (target stats PDA)
+
+        // --- Validation ---
+        if !user_account.is_signer {
+            msg!("Error: User signature missing for swap");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let source_config = Self::load_config(source_config_account, program_id)?;
+        let target_config = Self::load_config(target_config_account, program_id)?;
+        if source_config.loyalty_mint != *source_mint_account.key
+            || target_config.loyalty_mint != *target_mint_account.key
+        {
+            msg!("Error: Mint accounts do not match the brands' configured mints");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+
+        let mut agreement = Self::load_swap_agreement(
+            agreement_account,
+            source_config_account,
+            target_config_account,
+            program_id,
+        )?;
+        if !agreement.source_approved || !agreement.target_approved {
+            msg!("Error: Swap agreement is not approved by both brands");
+            return Err(LoyaltyError::SwapNotApproved.into());
+        }
+
+        let (target_authority_key, target_authority_bump) =
+            find_authority_address(target_config_account.key, program_id);
+        if target_authority_key != *target_authority_account.key {
+            msg!("Error: Authority account is not the target brand's authority PDA");
+            return Err(LoyaltyError::AuthorityMismatch.into());
+        }
+        Self::check_token_program(token_program_account)?;
+
+        let source_token_data =
+            StateWithExtensions::<TokenAccount>::unpack(&source_token_account.data.borrow())?.base;
+        if source_token_data.owner != *user_account.key {
+            msg!("Error: Signer is not the owner of the source token account");
+            return Err(LoyaltyError::OwnerMismatch.into());
+        }
+
+        // --- Daily limit (per agreement, bucketed by day) ---
+        let today = Clock::get()?.unix_timestamp / SECONDS_PER_DAY;
+        if agreement.current_day != today {
+            agreement.current_day = today;
+            agreement.swapped_today = 0;
+        }
+        let swapped_today = agreement
+            .swapped_today
+            .checked_add(amount)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        if swapped_today > agreement.daily_limit {
+            msg!(
+                "Error: Swap of {} exceeds daily limit ({} of {} used)",
+                amount,
+                agreement.swapped_today,
+                agreement.daily_limit
+            );
+            return Err(LoyaltyError::SwapDailyLimitExceeded.into());
+        }
+        agreement.swapped_today = swapped_today;
+
+        // --- Convert at the agreed rate (rounded down) ---
+        let target_amount = (amount as u128)
+            .checked_mul(agreement.rate_numerator as u128)
+            .and_then(|product| product.checked_div(agreement.rate_denominator as u128))
+            .and_then(|quotient| u64::try_from(quotient).ok())
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        if target_amount == 0 {
+            msg!("Error: Swap amount too small to convert at the agreed rate");
+            return Err(LoyaltyError::InvalidSwapRate.into());
+        }
+
+        SwapAgreement::pack(agreement, &mut agreement_account.data.borrow_mut())?;
+
+        // Outgoing points count as redeemed for the source brand, awarded for the target brand
+        let destination_data =
+            StateWithExtensions::<TokenAccount>::unpack(&destination_token_account.data.borrow())?.base;
+        let mut source_stats = Self::load_stats(source_stats_account, source_config_account, program_id)?;
+        source_stats.record_redeem(amount, source_token_data.amount)?;
+        StatsAccount::pack(source_stats, &mut source_stats_account.data.borrow_mut())?;
+        let mut target_stats = Self::load_stats(target_stats_account, target_config_account, program_id)?;
+        target_stats.record_mint(target_amount, destination_data.amount)?;
+        StatsAccount::pack(target_stats, &mut target_stats_account.data.borrow_mut())?;
+
+        // --- Burn source points (user signs as owner) ---
+        msg!(
+            "Swapping {} points of brand {} into {} points of brand {}",
+            amount,
+            source_config.brand_id,
+            target_amount,
+            target_config.brand_id
+        );
+        let burn_cpi_instruction = token_instruction::burn(
+            token_program_account.key,
+            source_token_account.key,
+            source_mint_account.key,
+            user_account.key,
+            &[],
+            amount,
+        )?;
+        invoke(
+            &burn_cpi_instruction,
+            &[
+                source_token_account.clone(),
+                source_mint_account.clone(),
+                user_account.clone(),
+                token_program_account.clone(),
+            ],
+        )?;
+
+        // --- Mint target points (target brand's authority PDA signs) ---
+        let mint_cpi_instruction = token_instruction::mint_to(
+            token_program_account.key,
+            target_mint_account.key,
+            destination_token_account.key,
+            target_authority_account.key,
+            &[],
+            target_amount,
+        )?;
+        invoke_signed(
+            &mint_cpi_instruction,
+            &[
+                target_mint_account.clone(),
+                destination_token_account.clone(),
+                target_authority_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[&[AUTHORITY_SEED, target_config_account.key.as_ref(), &[target_authority_bump]]],
+        )?;
+
+        msg!("Points swapped successfully.");
+        Ok(())
+    }
+
+    /// Loads a member account and checks it belongs to the given config.
+    fn load_member(
+        member_account: &AccountInfo,
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<MemberAccount, ProgramError> {
+        if member_account.owner != program_id {
+            msg!("Error: Member account not owned by program");
+            return Err(LoyaltyError::InvalidAccountOwner.into());
+        }
+        let member_data = MemberAccount::unpack(&member_account.data.borrow())?;
+        if member_data.config != *config_account.key {
+            msg!("Error: Member account belongs to a different config");
+            return Err(LoyaltyError::ConfigMismatch.into());
+        }
+        Ok(member_data)
+    }
+
+    /// Processes RegisterMember instruction.
+    fn process_register_member(
+        accounts: &[AccountInfo],
+        voucher_allowance: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_account = next_account_info(account_info_iter)?;         // Signer, Writable (payer)
+        let config_account = next_account_info(account_info_iter)?;        // Readonly
+        let member_account = next_account_info(account_info_iter)?;        // Writable (member PDA)
+        let member_token_account = next_account_info(account_info_iter)?;  // Writable
+        let authority_account = next_account_info(account_info_iter)?;     // Readonly (PDA)
+        let token_program_account = next_account_info(account_info_iter)?; // Readonly
+        let system_program = next_account_info(account_info_iter)?;        // System
+
+        // --- Validation ---
+        if !owner_account.is_signer {
+            msg!("Error: Member signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let config_data = Self::load_config(config_account, program_id)?;
+
+        let (authority_key, _) = find_authority_address(config_account.key, program_id);
+        if authority_key != *authority_account.key {
+            msg!("Error: Authority account is not the program authority PDA");
+            return Err(LoyaltyError::AuthorityMismatch.into());
+        }
+        Self::check_token_program(token_program_account)?;
+
+        let member_token_data =
+            StateWithExtensions::<TokenAccount>::unpack(&member_token_account.data.borrow())?.base;
+        if member_token_data.owner != *owner_account.key {
+            msg!("Error: Signer is not the owner of the member token account");
+            return Err(LoyaltyError::OwnerMismatch.into());
+        }
+        if member_token_data.mint != config_data.loyalty_mint {
+            msg!("Error: Member token account is for the wrong mint");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+
+        let (member_key, member_bump) =
+            find_member_address(config_account.key, owner_account.key, program_id);
+        if member_key != *member_account.key {
+            msg!("Error: Member account is not the PDA for this wallet");
+            return Err(LoyaltyError::MemberMismatch.into());
+        }
+        if !member_account.data_is_empty() {
+            msg!("Error: Member already registered");
+            return Err(LoyaltyError::AlreadyInitialized.into());
+        }
+
+        // --- Create the member PDA ---
+        Self::create_pda_account(
+            owner_account,
+            member_account,
+            system_program,
+            &Rent::get()?,
+            MemberAccount::LEN,
+            &[
+                MEMBER_SEED,
+                config_account.key.as_ref(),
+                owner_account.key.as_ref(),
+                &[member_bump],
+            ],
+            program_id,
+        )?;
+        let member_data = MemberAccount {
+            is_initialized: true,
+            config: *config_account.key,
+            owner: *owner_account.key,
+            token_account: *member_token_account.key,
+            last_voucher_nonce: 0,
+            bump: member_bump,
+            ..MemberAccount::default()
+        };
+        MemberAccount::pack(member_data, &mut member_account.data.borrow_mut())?;
+
+        // --- Approve the program authority PDA as delegate for voucher redemptions ---
+        let approve_cpi_instruction = token_instruction::approve(
+            token_program_account.key,
+            member_token_account.key,
+            authority_account.key,
+            owner_account.key,
+            &[],
+            voucher_allowance,
+        )?;
+        invoke(
+            &approve_cpi_instruction,
+            &[
+                member_token_account.clone(),
+                authority_account.clone(),
+                owner_account.clone(),
+                token_program_account.clone(),
+            ],
+        )?;
+
+        msg!(
+            "Member {} registered for brand {} with voucher allowance {}",
+            owner_account.key,
+            config_data.brand_id,
+            voucher_allowance
+        );
+        Ok(())
+    }
+
+    /// Processes RedeemWithVoucher instruction.
+    fn process_redeem_with_voucher(
+        accounts: &[AccountInfo],
+        amount: u64,
+        nonce: u64,
+        expiry: i64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let cashier_account = next_account_info(account_info_iter)?;       // Signer
+        let config_account = next_account_info(account_info_iter)?;        // Readonly
+        let member_account = next_account_info(account_info_iter)?;        // Writable
+        let member_token_account = next_account_info(account_info_iter)?;  // Writable
+        let loyalty_mint_account = next_account_info(account_info_iter)?;  // Writable
+        let authority_account = next_account_info(account_info_iter)?;     // Readonly (PDA)
+        let token_program_account = next_account_info(account_info_iter)?; // Readonly
+        let stats_account = next_account_info(account_info_iter)?;         // Writable (stats PDA)
+        let instructions_sysvar = next_account_info(account_info_iter)?;   // Instructions sysvar
+
+        // --- Validation ---
+        if !cashier_account.is_signer {
+            msg!("Error: Cashier signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        if config_data.loyalty_mint != *loyalty_mint_account.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+
+        let (authority_key, authority_bump) = find_authority_address(config_account.key, program_id);
+        if authority_key != *authority_account.key {
+            msg!("Error: Authority account is not the program authority PDA");
+            return Err(LoyaltyError::AuthorityMismatch.into());
+        }
+        Self::check_token_program(token_program_account)?;
+
+        let mut member_data = Self::load_member(member_account, config_account, program_id)?;
+        if member_data.token_account != *member_token_account.key {
+            msg!("Error: Token account is not the one registered for this member");
+            return Err(LoyaltyError::MemberMismatch.into());
+        }
+
+        if Clock::get()?.unix_timestamp > expiry {
+            msg!("Error: Voucher expired at {}", expiry);
+            return Err(LoyaltyError::VoucherExpired.into());
+        }
+
+        // --- Verify the member's signature via the preceding Ed25519 instruction ---
+        let current_index = load_current_index_checked(instructions_sysvar)?;
+        if current_index == 0 {
+            msg!("Error: Voucher redemption must follow an Ed25519 signature instruction");
+            return Err(LoyaltyError::InvalidVoucherSignature.into());
+        }
+        let ed25519_instruction =
+            load_instruction_at_checked(current_index as usize - 1, instructions_sysvar)?;
+        let (signer, message) = voucher::verified_signature(&ed25519_instruction).map_err(|_| {
+            msg!("Error: Preceding instruction is not a single Ed25519 signature check");
+            LoyaltyError::InvalidVoucherSignature
+        })?;
+        if signer != member_data.owner
+            || message != voucher::voucher_message(config_account.key, amount, nonce, expiry)
+        {
+            msg!("Error: Voucher was not signed by the member for these terms");
+            return Err(LoyaltyError::InvalidVoucherSignature.into());
+        }
+
+        // --- Record the nonce before burning to block replays ---
+        if member_data.use_voucher_nonce(nonce).is_err() {
+            msg!("Error: Voucher nonce {} already used or too old (last {})", nonce, member_data.last_voucher_nonce);
+            return Err(LoyaltyError::VoucherNonceUsed.into());
+        }
+        MemberAccount::pack(member_data, &mut member_account.data.borrow_mut())?;
+
+        let member_token_data =
+            StateWithExtensions::<TokenAccount>::unpack(&member_token_account.data.borrow())?.base;
+        let mut stats_data = Self::load_stats(stats_account, config_account, program_id)?;
+        stats_data.record_redeem(amount, member_token_data.amount)?;
+        StatsAccount::pack(stats_data, &mut stats_account.data.borrow_mut())?;
+
+        // --- Burn as the delegate approved at signup ---
+        msg!("Redeeming {} loyalty points from {} with voucher nonce {}", amount, member_token_account.key, nonce);
+        let burn_cpi_instruction = token_instruction::burn(
+            token_program_account.key,
+            member_token_account.key,
+            loyalty_mint_account.key,
+            authority_account.key,
+            &[],
+            amount,
+        )?;
+        invoke_signed(
+            &burn_cpi_instruction,
+            &[
+                member_token_account.clone(),
+                loyalty_mint_account.clone(),
+                authority_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+
+        msg!("Voucher redeemed successfully.");
+        Ok(())
+    }
+
+    /// Returns `amount * bps / BPS_DENOMINATOR`, rounded down.
+    fn apply_bps(amount: u64, bps: u64) -> Result<u64, ProgramError> {
+        (amount as u128)
+            .checked_mul(bps as u128)
+            .map(|product| product / BPS_DENOMINATOR as u128)
+            .and_then(|result| u64::try_from(result).ok())
+            .ok_or_else(|| LoyaltyError::NumericalOverflow.into())
+    }
+
+    /// Loads a stake position and checks it belongs to the given config.
+    fn load_stake_position(
+        stake_position_account: &AccountInfo,
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<StakePosition, ProgramError> {
+        if stake_position_account.owner != program_id {
+            msg!("Error: Stake position account not owned by program");
+            return Err(LoyaltyError::InvalidAccountOwner.into());
+        }
+        let stake_position = StakePosition::unpack(&stake_position_account.data.borrow())?;
+        if stake_position.config != *config_account.key {
+            msg!("Error: Stake position belongs to a different config");
+            return Err(LoyaltyError::ConfigMismatch.into());
+        }
+        Ok(stake_position)
+    }
+
+    /// Processes SetStakeParams instruction.
+    fn process_set_stake_params(
+        accounts: &[AccountInfo],
+        early_unlock_penalty_bps: u16,
+        stake_tiers: [StakeTier; STAKE_TIER_COUNT],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;  // Signer
+        let config_account = next_account_info(account_info_iter)?; // Writable
+
+        if !admin_account.is_signer {
+            msg!("Error: Admin signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        if config_data.admin != *admin_account.key {
+            msg!("Error: Signer is not the configured admin");
+            return Err(LoyaltyError::AdminSignatureMismatch.into());
+        }
+
+        if early_unlock_penalty_bps as u64 > BPS_DENOMINATOR {
+            msg!("Error: Early unlock penalty cannot exceed 100%");
+            return Err(LoyaltyError::InvalidStakeParams.into());
+        }
+        for tier in stake_tiers.iter() {
+            if tier.multiplier_bps != 0 && (tier.multiplier_bps as u64) < BPS_DENOMINATOR {
+                msg!("Error: Tier multipliers must be at least 1x");
+                return Err(LoyaltyError::InvalidStakeParams.into());
+            }
+            if tier.min_lock_secs < 0 {
+                msg!("Error: Tier lock durations cannot be negative");
+                return Err(LoyaltyError::InvalidStakeParams.into());
+            }
+        }
+
+        config_data.early_unlock_penalty_bps = early_unlock_penalty_bps;
+        config_data.stake_tiers = stake_tiers;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Stake params updated. Early unlock penalty: {} bps", early_unlock_penalty_bps);
+        Ok(())
+    }
+
+    /// Processes Stake instruction.
+    fn process_stake(
+        accounts: &[AccountInfo],
+        amount: u64,
+        lock_secs: i64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_account = next_account_info(account_info_iter)?;          // Signer, Writable (payer)
+        let config_account = next_account_info(account_info_iter)?;         // Readonly
+        let stake_position_account = next_account_info(account_info_iter)?; // Writable (PDA)
+        let source_token_account = next_account_info(account_info_iter)?;   // Writable
+        let vault_account = next_account_info(account_info_iter)?;          // Writable (vault PDA)
+        let loyalty_mint_account = next_account_info(account_info_iter)?;   // Readonly
+        let authority_account = next_account_info(account_info_iter)?;      // Readonly (PDA)
+        let token_program_account = next_account_info(account_info_iter)?;  // Readonly
+        let system_program = next_account_info(account_info_iter)?;         // System
+
+        // --- Validation ---
+        if !owner_account.is_signer {
+            msg!("Error: Member signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if amount == 0 {
+            msg!("Error: Stake amount must be non-zero");
+            return Err(LoyaltyError::InvalidStakeParams.into());
+        }
+        if lock_secs < 0 {
+            msg!("Error: Lock duration cannot be negative");
+            return Err(LoyaltyError::InvalidLockDuration.into());
+        }
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        if config_data.loyalty_mint != *loyalty_mint_account.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+
+        let (authority_key, _) = find_authority_address(config_account.key, program_id);
+        if authority_key != *authority_account.key {
+            msg!("Error: Authority account is not the program authority PDA");
+            return Err(LoyaltyError::AuthorityMismatch.into());
+        }
+        // Checked before anything is created: the vault is assigned to the token program
+        Self::check_token_program(token_program_account)?;
+        let (vault_key, vault_bump) = find_vault_address(config_account.key, program_id);
+        if vault_key != *vault_account.key {
+            msg!("Error: Vault account is not the stake vault PDA");
+            return Err(LoyaltyError::AuthorityMismatch.into());
+        }
+        let (stake_position_key, stake_position_bump) =
+            find_stake_position_address(config_account.key, owner_account.key, program_id);
+        if stake_position_key != *stake_position_account.key {
+            msg!("Error: Stake position account is not the PDA for this wallet");
+            return Err(LoyaltyError::MemberMismatch.into());
+        }
+
+        let rent = Rent::get()?;
+        // The vault needs room for any account extensions the (Token-2022) mint requires
+        let (decimals, vault_len) = {
+            let mint_data = loyalty_mint_account.data.borrow();
+            let mint_state = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+            let account_extensions =
+                ExtensionType::get_required_init_account_extensions(&mint_state.get_extension_types()?);
+            (
+                mint_state.base.decimals,
+                ExtensionType::try_calculate_account_len::<TokenAccount>(&account_extensions)?,
+            )
+        };
+
+        // --- Create the brand's stake vault on first use ---
+        if vault_account.data_is_empty() {
+            Self::create_pda_account(
+                owner_account,
+                vault_account,
+                system_program,
+                &rent,
+                vault_len,
+                &[VAULT_SEED, config_account.key.as_ref(), &[vault_bump]],
+                token_program_account.key,
+            )?;
+            invoke(
+                &token_instruction::initialize_account3(
+                    token_program_account.key,
+                    vault_account.key,
+                    loyalty_mint_account.key,
+                    authority_account.key,
+                )?,
+                &[vault_account.clone(), loyalty_mint_account.clone()],
+            )?;
+            msg!("Stake vault {} created", vault_account.key);
+        }
+
+        // --- Load or create the member's stake position ---
+        let mut stake_position = if stake_position_account.data_is_empty() {
+            Self::create_pda_account(
+                owner_account,
+                stake_position_account,
+                system_program,
+                &rent,
+                StakePosition::LEN,
+                &[
+                    STAKE_SEED,
+                    config_account.key.as_ref(),
+                    owner_account.key.as_ref(),
+                    &[stake_position_bump],
+                ],
+                program_id,
+            )?;
+            StakePosition {
+                is_initialized: true,
+                config: *config_account.key,
+                owner: *owner_account.key,
+                bump: stake_position_bump,
+                ..StakePosition::default()
+            }
+        } else {
+            Self::load_stake_position(stake_position_account, config_account, program_id)?
+        };
+
+        let now = Clock::get()?.unix_timestamp;
+        let unlock_at = now.checked_add(lock_secs).ok_or(LoyaltyError::NumericalOverflow)?;
+        if stake_position.amount == 0 || now >= stake_position.unlock_at {
+            // No active lock: start a fresh one
+            stake_position.lock_start = now;
+            stake_position.unlock_at = unlock_at;
+        } else {
+            // Adding to an active lock can only lengthen it
+            stake_position.unlock_at = stake_position.unlock_at.max(unlock_at);
+        }
+        stake_position.amount = stake_position
+            .amount
+            .checked_add(amount)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        let (tier, multiplier_bps) = stake_position.effective_tier(&config_data.stake_tiers, now);
+        let unlock_at = stake_position.unlock_at;
+        let staked = stake_position.amount;
+        StakePosition::pack(stake_position, &mut stake_position_account.data.borrow_mut())?;
+
+        // --- Move the points into the vault (member signs as owner) ---
+        let transfer_cpi_instruction = token_instruction::transfer_checked(
+            token_program_account.key,
+            source_token_account.key,
+            loyalty_mint_account.key,
+            vault_account.key,
+            owner_account.key,
+            &[],
+            amount,
+            decimals,
+        )?;
+        invoke(
+            &transfer_cpi_instruction,
+            &[
+                source_token_account.clone(),
+                loyalty_mint_account.clone(),
+                vault_account.clone(),
+                owner_account.clone(),
+                token_program_account.clone(),
+            ],
+        )?;
+
+        msg!(
+            "Staked {} points ({} total) until {}. Tier {}, {} bps multiplier",
+            amount,
+            staked,
+            unlock_at,
+            tier,
+            multiplier_bps
+        );
+        Ok(())
+    }
+
+    /// Processes Unstake instruction.
+    fn process_unstake(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_account = next_account_info(account_info_iter)?;          // Signer
+        let config_account = next_account_info(account_info_iter)?;         // Readonly
+        let stake_position_account = next_account_info(account_info_iter)?; // Writable
+        let destination_token_account = next_account_info(account_info_iter)?; // Writable
+        let vault_account = next_account_info(account_info_iter)?;          // Writable
+        let loyalty_mint_account = next_account_info(account_info_iter)?;   // Writable (penalty burn)
+        let authority_account = next_account_info(account_info_iter)?;      // Readonly (PDA)
+        let token_program_account = next_account_info(account_info_iter)?;  // Readonly
+        let stats_account = next_account_info(account_info_iter)?;          // Writable (stats PDA)
+
+        // --- Validation ---
+        if !owner_account.is_signer {
+            msg!("Error: Member signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        if config_data.loyalty_mint != *loyalty_mint_account.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+
+        let (authority_key, authority_bump) = find_authority_address(config_account.key, program_id);
+        if authority_key != *authority_account.key {
+            msg!("Error: Authority account is not the program authority PDA");
+            return Err(LoyaltyError::AuthorityMismatch.into());
+        }
+        Self::check_token_program(token_program_account)?;
+        let (vault_key, _) = find_vault_address(config_account.key, program_id);
+        if vault_key != *vault_account.key {
+            msg!("Error: Vault account is not the stake vault PDA");
+            return Err(LoyaltyError::AuthorityMismatch.into());
+        }
+
+        let mut stake_position = Self::load_stake_position(stake_position_account, config_account, program_id)?;
+        if stake_position.owner != *owner_account.key {
+            msg!("Error: Signer does not own this stake position");
+            return Err(LoyaltyError::OwnerMismatch.into());
+        }
+        if stake_position.amount == 0 {
+            msg!("Error: No points staked");
+            return Err(LoyaltyError::NothingStaked.into());
+        }
+
+        // --- Early unlock penalty ---
+        let staked = stake_position.amount;
+        let penalty = if Clock::get()?.unix_timestamp < stake_position.unlock_at {
+            Self::apply_bps(staked, config_data.early_unlock_penalty_bps as u64)?
+        } else {
+            0
+        };
+        let returned = staked - penalty;
+
+        stake_position.amount = 0;
+        stake_position.lock_start = 0;
+        stake_position.unlock_at = 0;
+        StakePosition::pack(stake_position, &mut stake_position_account.data.borrow_mut())?;
+
+        let authority_bump_seed = [authority_bump];
+        let authority_seeds: &[&[u8]] = &[AUTHORITY_SEED, config_account.key.as_ref(), &authority_bump_seed];
+
+        if penalty > 0 {
+            let mut stats_data = Self::load_stats(stats_account, config_account, program_id)?;
+            stats_data.record_penalty(penalty)?;
+            StatsAccount::pack(stats_data, &mut stats_account.data.borrow_mut())?;
+
+            msg!("Burning early unlock penalty of {} points", penalty);
+            let burn_cpi_instruction = token_instruction::burn(
+                token_program_account.key,
+                vault_account.key,
+                loyalty_mint_account.key,
+                authority_account.key,
+                &[],
+                penalty,
+            )?;
+            invoke_signed(
+                &burn_cpi_instruction,
+                &[
+                    vault_account.clone(),
+                    loyalty_mint_account.clone(),
+                    authority_account.clone(),
+                    token_program_account.clone(),
+                ],
+                &[authority_seeds],
+            )?;
+        }
+
+        if returned > 0 {
+            let decimals = StateWithExtensions::<Mint>::unpack(&loyalty_mint_account.data.borrow())?
+                .base
+                .decimals;
+            let transfer_cpi_instruction = token_instruction::transfer_checked(
+                token_program_account.key,
+                vault_account.key,
+                loyalty_mint_account.key,
+                destination_token_account.key,
+                authority_account.key,
+                &[],
+                returned,
+                decimals,
+            )?;
+            invoke_signed(
+                &transfer_cpi_instruction,
+                &[
+                    vault_account.clone(),
+                    loyalty_mint_account.clone(),
+                    destination_token_account.clone(),
+                    authority_account.clone(),
+                    token_program_account.clone(),
+                ],
+                &[authority_seeds],
+            )?;
+        }
+
+        msg!("Unstaked {} points ({} returned, {} penalty)", staked, returned, penalty);
+        Ok(())
+    }
+
+    /// Processes ExtendLock instruction.
+    fn process_extend_lock(
+        accounts: &[AccountInfo],
+        additional_secs: i64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_account = next_account_info(account_info_iter)?;          // Signer
+        let config_account = next_account_info(account_info_iter)?;         // Readonly
+        let stake_position_account = next_account_info(account_info_iter)?; // Writable
+
+        if !owner_account.is_signer {
+            msg!("Error: Member signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if additional_secs <= 0 {
+            msg!("Error: Lock extension must be positive");
+            return Err(LoyaltyError::InvalidLockDuration.into());
+        }
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        let mut stake_position = Self::load_stake_position(stake_position_account, config_account, program_id)?;
+        if stake_position.owner != *owner_account.key {
+            msg!("Error: Signer does not own this stake position");
+            return Err(LoyaltyError::OwnerMismatch.into());
+        }
+        if stake_position.amount == 0 {
+            msg!("Error: No points staked");
+            return Err(LoyaltyError::NothingStaked.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        if now >= stake_position.unlock_at {
+            // The previous lock already ended: relock from now
+            stake_position.lock_start = now;
+        }
+        stake_position.unlock_at = stake_position
+            .unlock_at
+            .max(now)
+            .checked_add(additional_secs)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        let (tier, multiplier_bps) = stake_position.effective_tier(&config_data.stake_tiers, now);
+        let unlock_at = stake_position.unlock_at;
+        StakePosition::pack(stake_position, &mut stake_position_account.data.borrow_mut())?;
+
+        msg!("Lock extended until {}. Tier {}, {} bps multiplier", unlock_at, tier, multiplier_bps);
+        Ok(())
+    }
+
+    /// Loads a merchant account and checks it belongs to the given config.
+    fn load_merchant(
+        merchant_account: &AccountInfo,
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<MerchantAccount, ProgramError> {
+        if merchant_account.owner != program_id {
+            msg!("Error: Merchant account not owned by program");
+            return Err(LoyaltyError::InvalidAccountOwner.into());
+        }
+        let merchant_data = MerchantAccount::unpack(&merchant_account.data.borrow())?;
+        if merchant_data.config != *config_account.key {
+            msg!("Error: Merchant account belongs to a different config");
+            return Err(LoyaltyError::ConfigMismatch.into());
+        }
+        Ok(merchant_data)
+    }
+
+    /// Processes RegisterMerchant instruction.
+    fn process_register_merchant(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;    // Signer, Writable (payer)
+        let config_account = next_account_info(account_info_iter)?;   // Readonly
+        let merchant_wallet = next_account_info(account_info_iter)?;  // Readonly
+        let merchant_account = next_account_info(account_info_iter)?; // Writable (merchant PDA)
+        let system_program = next_account_info(account_info_iter)?;   // System
+
+        if !admin_account.is_signer {
+            msg!("Error: Admin signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        if config_data.admin != *admin_account.key {
+            msg!("Error: Signer is not the configured admin");
+            return Err(LoyaltyError::AdminSignatureMismatch.into());
+        }
+
+        let (merchant_key, merchant_bump) =
+            find_merchant_address(config_account.key, merchant_wallet.key, program_id);
+        if merchant_key != *merchant_account.key {
+            msg!("Error: Merchant account is not the PDA for this merchant");
+            return Err(LoyaltyError::MerchantMismatch.into());
+        }
+        if !merchant_account.data_is_empty() {
+            msg!("Error: Merchant already registered");
+            return Err(LoyaltyError::AlreadyInitialized.into());
+        }
+
+        Self::create_pda_account(
+            admin_account,
+            merchant_account,
+            system_program,
+            &Rent::get()?,
+            MerchantAccount::LEN,
+            &[
+                MERCHANT_SEED,
+                config_account.key.as_ref(),
+                merchant_wallet.key.as_ref(),
+                &[merchant_bump],
+            ],
+            program_id,
+        )?;
+        let merchant_data = MerchantAccount {
+            is_initialized: true,
+            config: *config_account.key,
+            merchant: *merchant_wallet.key,
+            bump: merchant_bump,
+            ..MerchantAccount::default()
+        };
+        MerchantAccount::pack(merchant_data, &mut merchant_account.data.borrow_mut())?;
+
+        msg!("Merchant {} registered for brand {}", merchant_wallet.key, config_data.brand_id);
+        Ok(())
+    }
+
+    /// Processes RedeemAtMerchant instruction.
+    fn process_redeem_at_merchant(
+        accounts: &[AccountInfo],
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_account = next_account_info(account_info_iter)?;          // Signer (member)
+        let merchant_wallet = next_account_info(account_info_iter)?;       // Signer (merchant)
+        let config_account = next_account_info(account_info_iter)?;        // Readonly
+        let merchant_account = next_account_info(account_info_iter)?;      // Writable
+        let source_token_account = next_account_info(account_info_iter)?;  // Writable
+        let loyalty_mint_account = next_account_info(account_info_iter)?;  // Writable
+        let token_program_account = next_account_info(account_info_iter)?; // Readonly
+        let stats_account = next_account_info(account_info_iter)?;         // Writable (stats PDA)
+
+        // --- Validation ---
+        if !user_account.is_signer {
+            msg!("Error: User signature missing for redemption");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if !merchant_wallet.is_signer {
+            msg!("Error: Merchant signature missing for redemption");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        if config_data.loyalty_mint != *loyalty_mint_account.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+        Self::check_token_program(token_program_account)?;
+
+        let mut merchant_data = Self::load_merchant(merchant_account, config_account, program_id)?;
+        if merchant_data.merchant != *merchant_wallet.key {
+            msg!("Error: Signer is not the registered merchant");
+            return Err(LoyaltyError::MerchantMismatch.into());
+        }
+
+        let token_account_data =
+            StateWithExtensions::<TokenAccount>::unpack(&source_token_account.data.borrow())?.base;
+        if token_account_data.owner != *user_account.key {
+            msg!("Error: Signer is not the owner of the source token account");
+            return Err(LoyaltyError::OwnerMismatch.into());
+        }
+
+        // --- Update the merchant's settlement ledger and brand stats ---
+        merchant_data.pending_settlement = merchant_data
+            .pending_settlement
+            .checked_add(amount)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        merchant_data.total_redeemed = merchant_data
+            .total_redeemed
+            .checked_add(amount)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        merchant_data.redemption_count = merchant_data
+            .redemption_count
+            .checked_add(1)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        let pending_settlement = merchant_data.pending_settlement;
+        MerchantAccount::pack(merchant_data, &mut merchant_account.data.borrow_mut())?;
+
+        let mut stats_data = Self::load_stats(stats_account, config_account, program_id)?;
+        stats_data.record_redeem(amount, token_account_data.amount)?;
+        StatsAccount::pack(stats_data, &mut stats_account.data.borrow_mut())?;
+
+        // --- CPI to SPL Token Program to Burn (member signs as owner) ---
+        msg!("Redeeming {} loyalty points at merchant {}", amount, merchant_wallet.key);
+        let burn_cpi_instruction = token_instruction::burn(
+            token_program_account.key,
+            source_token_account.key,
+            loyalty_mint_account.key,
+            user_account.key,
+            &[],
+            amount,
+        )?;
+        invoke(
+            &burn_cpi_instruction,
+            &[
+                source_token_account.clone(),
+                loyalty_mint_account.clone(),
+                user_account.clone(),
+                token_program_account.clone(),
+            ],
+        )?;
+
+        msg!("Points redeemed at merchant. Pending settlement: {}", pending_settlement);
+        Ok(())
+    }
+
+    /// Processes Settle instruction.
+    fn process_settle(
+        accounts: &[AccountInfo],
+        amount: u64,
+        payment_reference: [u8; 32],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;    // Signer
+        let config_account = next_account_info(account_info_iter)?;   // Readonly
+        let merchant_account = next_account_info(account_info_iter)?; // Writable
+
+        if !admin_account.is_signer {
+            msg!("Error: Admin signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        if config_data.admin != *admin_account.key {
+            msg!("Error: Signer is not the configured admin");
+            return Err(LoyaltyError::AdminSignatureMismatch.into());
+        }
+
+        let mut merchant_data = Self::load_merchant(merchant_account, config_account, program_id)?;
+        if amount == 0 {
+            msg!("Error: Settlement amount must be greater than zero");
+            return Err(LoyaltyError::NothingToSettle.into());
+        }
+        merchant_data.pending_settlement = merchant_data
+            .pending_settlement
+            .checked_sub(amount)
+            .ok_or_else(|| {
+                msg!("Error: Settlement of {} exceeds pending {}", amount, merchant_data.pending_settlement);
+                LoyaltyError::SettlementExceedsPending
+            })?;
+        merchant_data.total_settled = merchant_data
+            .total_settled
+            .checked_add(amount)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        merchant_data.last_settled_amount = amount;
+        merchant_data.last_settled_at = Clock::get()?.unix_timestamp;
+        merchant_data.last_payment_reference = payment_reference;
+        let merchant = merchant_data.merchant;
+        MerchantAccount::pack(merchant_data, &mut merchant_account.data.borrow_mut())?;
+
+        msg!("Settled {} points for merchant {}", amount, merchant);
+        Ok(())
+    }
+
+    /// Processes SetStreakBonuses instruction.
+    fn process_set_streak_bonuses(
+        accounts: &[AccountInfo],
+        streak_bonuses: [StreakBonus; STREAK_BONUS_COUNT],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;  // Signer
+        let config_account = next_account_info(account_info_iter)?; // Writable
+
+        if !admin_account.is_signer {
+            msg!("Error: Admin signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        if config_data.admin != *admin_account.key {
+            msg!("Error: Signer is not the configured admin");
+            return Err(LoyaltyError::AdminSignatureMismatch.into());
+        }
+
+        config_data.streak_bonuses = streak_bonuses;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Streak bonuses updated.");
+        Ok(())
+    }
+
+    /// Processes CheckIn instruction.
+    fn process_check_in(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_account = next_account_info(account_info_iter)?;         // Signer (member)
+        let issuer_account = next_account_info(account_info_iter)?;        // Signer, Writable (merchant, payer)
+        let config_account = next_account_info(account_info_iter)?;        // Readonly
+        let merchant_account = next_account_info(account_info_iter)?;      // Readonly (issuer's merchant PDA)
+        let member_account = next_account_info(account_info_iter)?;        // Writable (member PDA)
+        let member_token_account = next_account_info(account_info_iter)?;  // Writable
+        let loyalty_mint_account = next_account_info(account_info_iter)?;  // Writable
+        let authority_account = next_account_info(account_info_iter)?;     // Readonly (Mint Authority PDA)
+        let token_program_account = next_account_info(account_info_iter)?; // Readonly
+        let stats_account = next_account_info(account_info_iter)?;         // Writable (stats PDA)
+        let issuer_stats_account = next_account_info(account_info_iter)?;  // Writable (issuer stats PDA)
+        let system_program = next_account_info(account_info_iter)?;        // System
+
+        // --- Validation ---
+        if !owner_account.is_signer {
+            msg!("Error: Member signature missing for check-in");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if !issuer_account.is_signer {
+            msg!("Error: Issuer signature missing for check-in");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        if config_data.loyalty_mint != *loyalty_mint_account.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+
+        // Only the brand's registered stores can vouch for a visit
+        let merchant_data = Self::load_merchant(merchant_account, config_account, program_id)?;
+        if merchant_data.merchant != *issuer_account.key {
+            msg!("Error: Issuer is not a registered merchant");
+            return Err(LoyaltyError::MerchantMismatch.into());
+        }
+
+        let mut member_data = Self::load_member(member_account, config_account, program_id)?;
+        if member_data.owner != *owner_account.key {
+            msg!("Error: Signer is not the member");
+            return Err(LoyaltyError::MemberMismatch.into());
+        }
+        if member_data.token_account != *member_token_account.key {
+            msg!("Error: Token account is not the member's registered token account");
+            return Err(LoyaltyError::MemberMismatch.into());
+        }
+
+        let (authority_key, authority_bump) = find_authority_address(config_account.key, program_id);
+        if authority_key != *authority_account.key {
+            msg!("Error: Authority account is not the program authority PDA");
+            return Err(LoyaltyError::AuthorityMismatch.into());
+        }
+        Self::check_token_program(token_program_account)?;
+
+        // --- Update the streak ---
+        let today = Clock::get()?.unix_timestamp / SECONDS_PER_DAY;
+        let streak = member_data.check_in(today)?;
+        MemberAccount::pack(member_data, &mut member_account.data.borrow_mut())?;
+        msg!("Member {} checked in. Streak: {} days", owner_account.key, streak);
+
+        let bonus = streak_bonus(&config_data.streak_bonuses, streak)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        if bonus == 0 {
+            return Ok(());
+        }
+
+        // --- Pay the streak bonus ---
+        let member_token_data =
+            StateWithExtensions::<TokenAccount>::unpack(&member_token_account.data.borrow())?.base;
+        let mut stats_data = Self::load_stats(stats_account, config_account, program_id)?;
+        stats_data.record_mint(bonus, member_token_data.amount)?;
+        StatsAccount::pack(stats_data, &mut stats_account.data.borrow_mut())?;
+
+        Self::record_issuer_award(
+            issuer_account,
+            issuer_stats_account,
+            config_account,
+            system_program,
+            bonus,
+            program_id,
+        )?;
+
+        msg!("Awarding {} point streak bonus to {}", bonus, member_token_account.key);
+        let mint_cpi_instruction = token_instruction::mint_to(
+            token_program_account.key,
+            loyalty_mint_account.key,
+            member_token_account.key,
+            authority_account.key,
+            &[],
+            bonus,
+        )?;
+        invoke_signed(
+            &mint_cpi_instruction,
+            &[
+                loyalty_mint_account.clone(),
+                member_token_account.clone(),
+                authority_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+
+        Ok(())
+    }
+
+    /// Processes MigrateConfig instruction.
+    fn process_migrate_config(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_account = next_account_info(account_info_iter)?;  // Signer, Writable
+        let config_account = next_account_info(account_info_iter)?; // Writable
+        let system_program = next_account_info(account_info_iter)?; // System
+        let stats_account = next_account_info(account_info_iter)?;  // Writable (stats PDA)
+        let loyalty_mint_account = next_account_info(account_info_iter)?; // Readonly
+
+        if !payer_account.is_signer {
+            msg!("Error: Payer signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if config_account.owner != program_id {
+            msg!("Error: Config account not owned by program");
+            return Err(LoyaltyError::InvalidConfigAccountOwner.into());
+        }
+
+        // Each layout has a distinct length, so the length tells us which one we have
+        let mut config_data = {
+            let data = config_account.data.borrow();
+            match data.len() {
+                ConfigAccount::LEN => {
+                    msg!("Config already at layout version {}", data[0]);
+                    return Ok(());
+                }
+                // Baseline layout: no header and only the admin and mint fields
+                CONFIG_V0_LEN => ConfigAccount::unpack_v0(&data)?,
+                _ => {
+                    msg!("Error: Unknown config layout ({} bytes)", data.len());
+                    return Err(ProgramError::InvalidAccountData);
+                }
+            }
+        };
+        if !config_data.is_initialized() {
+            msg!("Error: Config account not initialized");
+            return Err(LoyaltyError::NotInitialized.into());
+        }
+        if config_data.loyalty_mint != *loyalty_mint_account.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(LoyaltyError::MintAccountMismatch.into());
+        }
+
+        // Baseline configs were plain keypair accounts, so they keep their address instead of
+        // being checked against a brand PDA
+        config_data.legacy_address = *config_account.key;
+
+        // They also predate the stats account, which every mint and burn now updates
+        let (stats_key, stats_bump) = find_stats_address(config_account.key, program_id);
+        if stats_key != *stats_account.key {
+            msg!("Error: Stats account is not the PDA for this config");
+            return Err(LoyaltyError::ConfigMismatch.into());
+        }
+        Self::create_pda_account(
+            payer_account,
+            stats_account,
+            system_program,
+            &Rent::get()?,
+            StatsAccount::LEN,
+            &[STATS_SEED, config_account.key.as_ref(), &[stats_bump]],
+            program_id,
+        )?;
+        // Points already in circulation count as awarded so the stats reconcile with the supply
+        let mint_data = StateWithExtensions::<Mint>::unpack(&loyalty_mint_account.data.borrow())?.base;
+        let stats_data = StatsAccount {
+            is_initialized: true,
+            config: *config_account.key,
+            total_awarded: mint_data.supply,
+            bump: stats_bump,
+            ..StatsAccount::default()
+        };
+        StatsAccount::pack(stats_data, &mut stats_account.data.borrow_mut())?;
+
+        // --- Top up rent for the larger account, then grow it ---
+        grow_account(payer_account, config_account, system_program, ConfigAccount::LEN)?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Config migrated to layout version {}", CURRENT_VERSION);
+        Ok(())
+    }
+
+    /// Processes MigrateAccount instruction.
+    fn process_migrate_account(
+        accounts: &[AccountInfo],
+        account_type: AccountType,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_account = next_account_info(account_info_iter)?;  // Signer, Writable
+        let admin_account = next_account_info(account_info_iter)?;  // Signer
+        let config_account = next_account_info(account_info_iter)?; // Readonly
+        let target_account = next_account_info(account_info_iter)?; // Writable
+        let system_program = next_account_info(account_info_iter)?; // System
+
+        if !payer_account.is_signer || !admin_account.is_signer {
+            msg!("Error: Payer or admin signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let config_data = Self::load_config(config_account, program_id)?;
+        if config_data.admin != *admin_account.key {
+            msg!("Error: Signer is not the configured admin");
+            return Err(LoyaltyError::AdminSignatureMismatch.into());
+        }
+        if target_account.owner != program_id {
+            msg!("Error: Account not owned by program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let migrate_accounts = [
+            payer_account.clone(),
+            config_account.clone(),
+            target_account.clone(),
+            system_program.clone(),
+        ];
+        match account_type {
+            AccountType::ClawbackRecord => {
+                Self::migrate_account::<ClawbackRecord>(&migrate_accounts, account_type, |_| 0)
+            }
+            AccountType::Distribution => Self::migrate_account::<Distribution>(
+                &migrate_accounts,
+                account_type,
+                |distribution| Distribution::bitmap_len(distribution.num_leaves),
+            ),
+            AccountType::SwapAgreement => {
+                Self::migrate_account::<SwapAgreement>(&migrate_accounts, account_type, |_| 0)
+            }
+            AccountType::Stats => Self::migrate_account::<StatsAccount>(&migrate_accounts, account_type, |_| 0),
+            AccountType::IssuerStats => {
+                Self::migrate_account::<IssuerStatsAccount>(&migrate_accounts, account_type, |_| 0)
+            }
+            AccountType::Member => Self::migrate_account::<MemberAccount>(&migrate_accounts, account_type, |_| 0),
+            AccountType::StakePosition => {
+                Self::migrate_account::<StakePosition>(&migrate_accounts, account_type, |_| 0)
+            }
+            AccountType::Merchant => {
+                Self::migrate_account::<MerchantAccount>(&migrate_accounts, account_type, |_| 0)
+            }
+            AccountType::Uninitialized | AccountType::Config => {
+                msg!("Error: Configs are migrated with MigrateConfig");
+                Err(ProgramError::InvalidArgument)
+            }
+        }
+    }
+
+    /// Rewrites the account to migrate as a current `T` with header, keeping the trailing
+    /// `tail_len` bytes (a distribution's claimed bitmap) after the body.
+    /// Takes the payer, config, account to migrate and system program, in that order.
+    fn migrate_account<T: Pack + BorshDeserialize>(
+        accounts: &[AccountInfo],
+        account_type: AccountType,
+        tail_len: fn(&T) -> usize,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let target_account = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+        let body_len = T::LEN - HEADER_LEN;
+
+        let (value, tail) = {
+            let data = target_account.data.borrow();
+            if !has_header(&data, CURRENT_VERSION, account_type as u8) {
+                msg!("Error: Unknown {:?} layout ({} bytes)", account_type, data.len());
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let value = upgrade_body::<T>(&data[HEADER_LEN..], body_len)?;
+            if data[0] == CURRENT_VERSION && data.len() == T::LEN + tail_len(&value) {
+                msg!("Account already at layout version {}", CURRENT_VERSION);
+                return Ok(());
+            }
+            // Every account type starts with `is_initialized` followed by its config
+            let body = &data[HEADER_LEN..];
+            if body[0] != 1 || body[1..33] != config_account.key.to_bytes() {
+                msg!("Error: Account is not an initialized account of this config");
+                return Err(LoyaltyError::ConfigMismatch.into());
+            }
+            let tail = data[data.len() - tail_len(&value)..].to_vec();
+            (value, tail)
+        };
+
+        // --- Top up rent for the larger account, then grow it ---
+        grow_account(payer_account, target_account, system_program, T::LEN + tail.len())?;
+        let mut data = target_account.data.borrow_mut();
+        let (body, rest) = data.split_at_mut(T::LEN);
+        T::pack(value, body)?;
+        rest.copy_from_slice(&tail);
+
+        msg!("{:?} account migrated to layout version {}", account_type, CURRENT_VERSION);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::{
+        entrypoint::{MAX_PERMITTED_DATA_INCREASE, SUCCESS},
+        instruction::Instruction,
+        program_option::COption,
+        program_stubs,
+        program_utils::limited_deserialize,
+        system_instruction::SystemInstruction,
+        system_program,
+        sysvar::instructions::{construct_instructions_data, store_current_index, BorrowedInstruction},
+    };
+    use spl_token_2022::{
+        extension::{ExtensionType, StateWithExtensionsMut},
+        state::{AccountState, Mint},
+    };
+    use std::{
+        cell::Cell,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    use crate::state::{DAYS_PER_GRACE_PERIOD, VOUCHER_NONCE_WINDOW};
+
+    const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+    thread_local! {
+        static NOW: Cell<i64> = const { Cell::new(1_700_000_000) };
+    }
+
+    /// Runs token program CPIs in-process, signing for the PDAs named by the signer seeds.
+    struct TokenStubs;
+
+    impl program_stubs::SyscallStubs for TokenStubs {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock { unix_timestamp: NOW.with(Cell::get), ..Clock::default() };
+            unsafe { *(var_addr as *mut Clock) = clock };
+            SUCCESS
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            let signers: Vec<Pubkey> = signers_seeds
+                .iter()
+                .map(|seeds| Pubkey::create_program_address(seeds, &PROGRAM_ID).unwrap())
+                .collect();
+            let accounts: Vec<AccountInfo> = instruction
+                .accounts
+                .iter()
+                .map(|meta| {
+                    let info = account_infos.iter().find(|info| *info.key == meta.pubkey).unwrap().clone();
+                    AccountInfo { is_signer: info.is_signer || signers.contains(info.key), ..info }
+                })
+                .collect();
+            if instruction.program_id == spl_token_2022::id() {
+                spl_token_2022::processor::Processor::process(&instruction.program_id, &accounts, &instruction.data)
+            } else if instruction.program_id == system_program::id() {
+                process_system_instruction(&accounts, &instruction.data)
+            } else {
+                Err(ProgramError::IncorrectProgramId)
+            }
+        }
+    }
+
+    /// The System program instructions the loyalty program uses: creating PDAs.
+    fn process_system_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+        let SystemInstruction::CreateAccount { lamports, space, owner } = limited_deserialize(data, 1024).unwrap() else {
+            unimplemented!("system instruction");
+        };
+        let (from, to) = (&accounts[0], &accounts[1]);
+        if !from.is_signer || !to.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        **from.lamports.borrow_mut() -= lamports;
+        **to.lamports.borrow_mut() += lamports;
+        *to.data.borrow_mut() = Box::leak(vec![0; space as usize].into_boxed_slice());
+        to.assign(&owner);
+        Ok(())
+    }
+
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
+
+    impl TestAccount {
+        fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+            Self { key, owner, lamports: 1_000_000_000, data }
+        }
+
+        fn packed<T: Pack>(key: Pubkey, owner: Pubkey, value: T) -> Self {
+            let mut data = vec![0; T::LEN];
+            T::pack(value, &mut data).unwrap();
+            Self::new(key, owner, data)
+        }
+
+        fn wallet() -> Self {
+            Self::new(Pubkey::new_unique(), Pubkey::default(), vec![])
+        }
+
+        /// A Token-2022 mint whose permanent delegate is `delegate`.
+        fn mint(key: Pubkey, mint_authority: Pubkey, delegate: Pubkey) -> Self {
+            let len = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::PermanentDelegate]).unwrap();
+            let mut mint = Self::new(key, spl_token_2022::id(), vec![0; len]);
+            let token_program = spl_token_2022::id();
+            let instructions = [
+                token_instruction::initialize_permanent_delegate(&token_program, &key, &delegate).unwrap(),
+                token_instruction::initialize_mint2(&token_program, &key, &mint_authority, None, 0).unwrap(),
+            ];
+            for instruction in instructions {
+                let info = mint.info(false);
+                spl_token_2022::processor::Processor::process(&token_program, &[info], &instruction.data).unwrap();
+            }
+            mint
+        }
+
+        fn token(key: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> Self {
+            let account = TokenAccount { mint, owner, amount, state: AccountState::Initialized, ..TokenAccount::default() };
+            Self::packed(key, spl_token_2022::id(), account)
+        }
+
+        fn info(&mut self, is_signer: bool) -> AccountInfo<'_> {
+            AccountInfo::new(&self.key, is_signer, true, &mut self.lamports, &mut self.data, &self.owner, false, 0)
+        }
+    }
+
+    fn run(accounts: &[&AccountInfo], instruction: LoyaltyInstruction) -> ProgramResult {
+        let accounts: Vec<AccountInfo> = accounts.iter().map(|&info| info.clone()).collect();
+        Processor::process(&PROGRAM_ID, &accounts, &borsh::to_vec(&instruction).unwrap())
+    }
+
+    fn token_amount(info: &AccountInfo) -> u64 {
+        StateWithExtensions::<TokenAccount>::unpack(&info.data.borrow()).unwrap().base.amount
+    }
+
+    fn supply(info: &AccountInfo) -> u64 {
+        StateWithExtensions::<Mint>::unpack(&info.data.borrow()).unwrap().base.supply
+    }
+
+    fn stats_of(info: &AccountInfo) -> StatsAccount {
+        StatsAccount::unpack(&info.data.borrow()).unwrap()
+    }
+
+    /// Checks the brand totals account for every point in the mint's supply.
+    fn assert_reconciles(stats: &AccountInfo, mint: &AccountInfo) {
+        let stats = stats_of(stats);
+        assert_eq!(
+            supply(mint),
+            stats.total_awarded - stats.total_redeemed - stats.total_clawed_back - stats.total_penalties
+        );
+    }
+
+    /// A brand with an initialized config, its Token-2022 mint and a member holding points.
+    struct Brand {
+        admin: TestAccount,
+        config: TestAccount,
+        mint: TestAccount,
+        authority: TestAccount,
+        token_program: TestAccount,
+        member: TestAccount,
+        member_tokens: TestAccount,
+        stats: TestAccount,
+        issuer_stats: TestAccount,
+        system: TestAccount,
+        stake_position: TestAccount,
+    }
+
+    impl Brand {
+        fn new(member_points: u64) -> Self {
+            program_stubs::set_syscall_stubs(Box::new(TokenStubs));
+            static NEXT_BRAND_ID: AtomicU64 = AtomicU64::new(1);
+            let brand_id = NEXT_BRAND_ID.fetch_add(1, Ordering::Relaxed);
+            let admin = TestAccount::wallet();
+            let (config_key, bump) = find_config_address(brand_id, &PROGRAM_ID);
+            let mint_key = Pubkey::new_unique();
+            let (authority_key, _) = find_authority_address(&config_key, &PROGRAM_ID);
+            let config = TestAccount::packed(
+                config_key,
+                PROGRAM_ID,
+                ConfigAccount {
+                    is_initialized: true,
+                    admin: admin.key,
+                    loyalty_mint: mint_key,
+                    brand_id,
+                    bump,
+                    ..ConfigAccount::default()
+                },
+            );
+            let mut mint = TestAccount::mint(mint_key, authority_key, authority_key);
+            let mut mint_state = StateWithExtensionsMut::<Mint>::unpack(&mut mint.data).unwrap();
+            mint_state.base.supply = member_points;
+            mint_state.pack_base();
+            let member = TestAccount::wallet();
+            let member_tokens = TestAccount::token(Pubkey::new_unique(), mint_key, member.key, member_points);
+            let (stats_key, stats_bump) = find_stats_address(&config_key, &PROGRAM_ID);
+            let stats = TestAccount::packed(
+                stats_key,
+                PROGRAM_ID,
+                StatsAccount {
+                    is_initialized: true,
+                    config: config_key,
+                    total_awarded: member_points,
+                    active_members: u64::from(member_points > 0),
+                    bump: stats_bump,
+                    ..StatsAccount::default()
+                },
+            );
+            let (issuer_stats_key, _) = find_issuer_stats_address(&config_key, &admin.key, &PROGRAM_ID);
+            let (stake_position_key, _) = find_stake_position_address(&config_key, &member.key, &PROGRAM_ID);
+            Self {
+                admin,
+                config,
+                mint,
+                authority: TestAccount::new(authority_key, Pubkey::default(), vec![]),
+                token_program: TestAccount::new(spl_token_2022::id(), Pubkey::default(), vec![]),
+                member,
+                member_tokens,
+                stats,
+                issuer_stats: TestAccount::new(issuer_stats_key, system_program::id(), vec![]),
+                system: TestAccount::new(system_program::id(), Pubkey::default(), vec![]),
+                stake_position: TestAccount::new(stake_position_key, system_program::id(), vec![]),
+            }
+        }
+    }
+
+    fn clawback_record() -> TestAccount {
+        TestAccount::new(Pubkey::new_unique(), PROGRAM_ID, vec![0; ClawbackRecord::LEN])
+    }
+
+    #[test]
+    fn clawback_burns_as_permanent_delegate_and_records_it() {
+        let mut brand = Brand::new(500);
+        let mut record = clawback_record();
+        let member_key = brand.member.key;
+        let member_tokens = brand.member_tokens.info(false);
+        let mint = brand.mint.info(false);
+        let record = record.info(false);
+        let stats = brand.stats.info(false);
+
+        run(
+            &[
+                &brand.admin.info(true),
+                &brand.config.info(false),
+                &mint,
+                &member_tokens,
+                &record,
+                &brand.authority.info(false),
+                &brand.token_program.info(false),
+                &stats,
+            ],
+            LoyaltyInstruction::ClawbackPoints { amount: 200, reason_code: 7 },
+        )
+        .unwrap();
+
+        assert_eq!(token_amount(&member_tokens), 300);
+        assert_eq!(supply(&mint), 300);
+        assert_eq!(stats_of(&stats).total_clawed_back, 200);
+        assert_eq!(stats_of(&stats).active_members, 1);
+        assert_reconciles(&stats, &mint);
+        let record_data = ClawbackRecord::unpack(&record.data.borrow()).unwrap();
+        assert_eq!(record_data.member, member_key);
+        assert_eq!(record_data.token_account, *member_tokens.key);
+        assert_eq!(record_data.admin, brand.admin.key);
+        assert_eq!(record_data.amount, 200);
+        assert_eq!(record_data.reason_code, 7);
+        assert_eq!(record_data.timestamp, NOW.with(Cell::get));
+    }
+
+    #[test]
+    fn clawback_rejects_non_admin_and_unsigned_calls() {
+        let mut brand = Brand::new(500);
+        let mut record = clawback_record();
+        let mut outsider = TestAccount::wallet();
+        let config = brand.config.info(false);
+        let mint = brand.mint.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let record = record.info(false);
+        let authority = brand.authority.info(false);
+        let token_program = brand.token_program.info(false);
+        let stats = brand.stats.info(false);
+        let clawback = LoyaltyInstruction::ClawbackPoints { amount: 200, reason_code: 7 };
+
+        let result = run(
+            &[&outsider.info(true), &config, &mint, &member_tokens, &record, &authority, &token_program, &stats],
+            clawback.clone(),
+        );
+        assert_eq!(result, Err(LoyaltyError::AdminSignatureMismatch.into()));
+        let result = run(
+            &[&brand.admin.info(false), &config, &mint, &member_tokens, &record, &authority, &token_program, &stats],
+            clawback,
+        );
+        assert_eq!(result, Err(ProgramError::MissingRequiredSignature));
+        assert_eq!(token_amount(&member_tokens), 500);
+    }
+
+    #[test]
+    fn clawback_rejects_other_token_programs_and_authorities() {
+        let mut brand = Brand::new(500);
+        let mut record = clawback_record();
+        let mut classic_token_program = TestAccount::new(spl_token::id(), Pubkey::default(), vec![]);
+        let mut wrong_authority = TestAccount::wallet();
+        let admin = brand.admin.info(true);
+        let config = brand.config.info(false);
+        let mint = brand.mint.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let record = record.info(false);
+        let authority = brand.authority.info(false);
+        let token_program = brand.token_program.info(false);
+        let stats = brand.stats.info(false);
+        let clawback = LoyaltyInstruction::ClawbackPoints { amount: 200, reason_code: 7 };
+
+        let result = run(
+            &[&admin, &config, &mint, &member_tokens, &record, &authority, &classic_token_program.info(false), &stats],
+            clawback.clone(),
+        );
+        assert_eq!(result, Err(LoyaltyError::InvalidTokenProgram.into()));
+        let result = run(
+            &[&admin, &config, &mint, &member_tokens, &record, &wrong_authority.info(false), &token_program, &stats],
+            clawback,
+        );
+        assert_eq!(result, Err(LoyaltyError::AuthorityMismatch.into()));
+        assert_eq!(token_amount(&member_tokens), 500);
+    }
+
+    #[test]
+    fn award_mints_through_the_authority_pda_and_counts_it() {
+        let mut brand = Brand::new(0);
+        let admin = brand.admin.info(true);
+        let config = brand.config.info(false);
+        let mint = brand.mint.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let token_program = brand.token_program.info(false);
+        let authority = brand.authority.info(false);
+        let stats = brand.stats.info(false);
+        let issuer_stats = brand.issuer_stats.info(false);
+        let system = brand.system.info(false);
+        let stake_position = brand.stake_position.info(false);
+        let award = |amount| {
+            run(
+                &[&admin, &config, &mint, &member_tokens, &token_program, &authority, &stats, &issuer_stats, &system, &stake_position],
+                LoyaltyInstruction::AwardPoints { amount },
+            )
+        };
+
+        award(250).unwrap();
+        assert_eq!(token_amount(&member_tokens), 250);
+        assert_eq!(supply(&mint), 250);
+        // The first award creates the issuer's stats PDA
+        assert_eq!(*issuer_stats.owner, PROGRAM_ID);
+
+        award(50).unwrap();
+        let brand_stats = stats_of(&stats);
+        assert_eq!((brand_stats.total_awarded, brand_stats.active_members), (300, 1));
+        let issuer_data = IssuerStatsAccount::unpack(&issuer_stats.data.borrow()).unwrap();
+        assert_eq!((issuer_data.issuer, issuer_data.config), (*admin.key, *config.key));
+        assert_eq!((issuer_data.award_count, issuer_data.total_awarded), (2, 300));
+        assert_reconciles(&stats, &mint);
+    }
+
+    #[test]
+    fn award_rejects_another_brands_stats() {
+        let mut brand = Brand::new(0);
+        let mut other = Brand::new(0);
+        let member_tokens = brand.member_tokens.info(false);
+
+        let result = run(
+            &[
+                &brand.admin.info(true),
+                &brand.config.info(false),
+                &brand.mint.info(false),
+                &member_tokens,
+                &brand.token_program.info(false),
+                &brand.authority.info(false),
+                &other.stats.info(false),
+                &brand.issuer_stats.info(false),
+                &brand.system.info(false),
+                &brand.stake_position.info(false),
+            ],
+            LoyaltyInstruction::AwardPoints { amount: 250 },
+        );
+
+        assert_eq!(result, Err(LoyaltyError::ConfigMismatch.into()));
+        assert_eq!(token_amount(&member_tokens), 0);
+    }
+
+    #[test]
+    fn award_rejects_a_fake_token_program() {
+        let mut brand = Brand::new(0);
+        let mut fake_token_program = TestAccount::wallet();
+        let member_tokens = brand.member_tokens.info(false);
+
+        let result = run(
+            &[
+                &brand.admin.info(true),
+                &brand.config.info(false),
+                &brand.mint.info(false),
+                &member_tokens,
+                &fake_token_program.info(false),
+                &brand.authority.info(false),
+                &brand.stats.info(false),
+                &brand.issuer_stats.info(false),
+                &brand.system.info(false),
+                &brand.stake_position.info(false),
+            ],
+            LoyaltyInstruction::AwardPoints { amount: 250 },
+        );
+
+        assert_eq!(result, Err(LoyaltyError::InvalidTokenProgram.into()));
+        assert_eq!(token_amount(&member_tokens), 0);
+    }
+
+    /// A distribution of `leaves` created by the brand admin, with the proofs to claim them.
+    fn create_distribution(brand: &mut Brand, leaves: &[(Pubkey, u64)], expiry: i64) -> (TestAccount, Vec<Vec<[u8; 32]>>) {
+        let hashes = leaves
+            .iter()
+            .enumerate()
+            .map(|(index, (claimant, amount))| merkle::leaf_hash(index as u64, claimant, *amount))
+            .collect();
+        let levels = merkle::build_levels(hashes);
+        let num_leaves = leaves.len() as u64;
+        let mut distribution =
+            TestAccount::new(Pubkey::new_unique(), PROGRAM_ID, vec![0; Distribution::account_len(num_leaves)]);
+        run(
+            &[&brand.admin.info(true), &brand.config.info(false), &distribution.info(false)],
+            LoyaltyInstruction::CreateDistribution {
+                merkle_root: levels.last().unwrap()[0],
+                total_amount: leaves.iter().map(|(_, amount)| amount).sum(),
+                num_leaves,
+                expiry,
+            },
+        )
+        .unwrap();
+        let proofs = (0..leaves.len()).map(|index| merkle::proof(&levels, index)).collect();
+        (distribution, proofs)
+    }
+
+    #[test]
+    fn create_distribution_is_admin_only_and_sized_for_its_bitmap() {
+        let mut brand = Brand::new(0);
+        let mut outsider = TestAccount::wallet();
+        let mut small = TestAccount::new(Pubkey::new_unique(), PROGRAM_ID, vec![0; Distribution::account_len(8)]);
+        let admin = brand.admin.info(true);
+        let config = brand.config.info(false);
+        let small = small.info(false);
+        let create = |num_leaves| LoyaltyInstruction::CreateDistribution {
+            merkle_root: [1; 32],
+            total_amount: 100,
+            num_leaves,
+            expiry: i64::MAX,
+        };
+
+        let result = run(&[&outsider.info(true), &config, &small], create(8));
+        assert_eq!(result, Err(LoyaltyError::AdminSignatureMismatch.into()));
+        let result = run(&[&admin, &config, &small], create(9));
+        assert_eq!(result, Err(ProgramError::AccountDataTooSmall));
+        run(&[&admin, &config, &small], create(8)).unwrap();
+        let result = run(&[&admin, &config, &small], create(8));
+        assert_eq!(result, Err(LoyaltyError::AlreadyInitialized.into()));
+    }
+
+    #[test]
+    fn claims_mint_each_leaf_once_to_its_claimant() {
+        let mut brand = Brand::new(0);
+        let other = Pubkey::new_unique();
+        let leaves = [(brand.member.key, 300), (other, 200), (Pubkey::new_unique(), 100)];
+        let (mut distribution, proofs) = create_distribution(&mut brand, &leaves, i64::MAX);
+        let mut other_tokens = TestAccount::token(Pubkey::new_unique(), brand.mint.key, other, 0);
+        let mut payer = TestAccount::wallet();
+        let payer = payer.info(true);
+        let config = brand.config.info(false);
+        let distribution = distribution.info(false);
+        let mint = brand.mint.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let other_tokens = other_tokens.info(false);
+        let authority = brand.authority.info(false);
+        let token_program = brand.token_program.info(false);
+        let stats = brand.stats.info(false);
+        let claim = |index: usize, amount| LoyaltyInstruction::ClaimPoints {
+            index: index as u64,
+            amount,
+            proof: proofs[index].clone(),
+        };
+
+        run(&[&payer, &config, &distribution, &mint, &member_tokens, &authority, &token_program, &stats], claim(0, 300)).unwrap();
+        assert_eq!(token_amount(&member_tokens), 300);
+
+        // Replays, inflated amounts and someone else's token account are all refused
+        let result = run(&[&payer, &config, &distribution, &mint, &member_tokens, &authority, &token_program, &stats], claim(0, 300));
+        assert_eq!(result, Err(LoyaltyError::AlreadyClaimed.into()));
+        let result = run(&[&payer, &config, &distribution, &mint, &other_tokens, &authority, &token_program, &stats], claim(1, 201));
+        assert_eq!(result, Err(LoyaltyError::InvalidMerkleProof.into()));
+        let result = run(&[&payer, &config, &distribution, &mint, &member_tokens, &authority, &token_program, &stats], claim(1, 200));
+        assert_eq!(result, Err(LoyaltyError::InvalidMerkleProof.into()));
+
+        run(&[&payer, &config, &distribution, &mint, &other_tokens, &authority, &token_program, &stats], claim(1, 200)).unwrap();
+        assert_eq!(token_amount(&other_tokens), 200);
+        assert_eq!(supply(&mint), 500);
+        let data = distribution.data.borrow();
+        assert_eq!(Distribution::unpack(&data[..Distribution::LEN]).unwrap().claimed_amount, 500);
+        assert_eq!((stats_of(&stats).total_awarded, stats_of(&stats).active_members), (500, 2));
+        assert_reconciles(&stats, &mint);
+    }
+
+    #[test]
+    fn claims_reject_expired_distributions_and_fake_token_programs() {
+        let mut brand = Brand::new(0);
+        let leaves = [(brand.member.key, 300), (Pubkey::new_unique(), 200)];
+        let now = NOW.with(Cell::get);
+        let (mut expired, expired_proofs) = create_distribution(&mut brand, &leaves, now - 1);
+        let (mut live, live_proofs) = create_distribution(&mut brand, &leaves, now);
+        let mut fake_token_program = TestAccount::wallet();
+        let mut payer = TestAccount::wallet();
+        let payer = payer.info(true);
+        let config = brand.config.info(false);
+        let mint = brand.mint.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let authority = brand.authority.info(false);
+        let stats = brand.stats.info(false);
+
+        let result = run(
+            &[&payer, &config, &expired.info(false), &mint, &member_tokens, &authority, &brand.token_program.info(false), &stats],
+            LoyaltyInstruction::ClaimPoints { index: 0, amount: 300, proof: expired_proofs[0].clone() },
+        );
+        assert_eq!(result, Err(LoyaltyError::DistributionExpired.into()));
+        let result = run(
+            &[&payer, &config, &live.info(false), &mint, &member_tokens, &authority, &fake_token_program.info(false), &stats],
+            LoyaltyInstruction::ClaimPoints { index: 0, amount: 300, proof: live_proofs[0].clone() },
+        );
+        assert_eq!(result, Err(LoyaltyError::InvalidTokenProgram.into()));
+        assert_eq!(token_amount(&member_tokens), 0);
+    }
+
+    fn rent_sysvar() -> TestAccount {
+        let mut rent = TestAccount::new(solana_program::sysvar::rent::id(), solana_program::sysvar::id(), vec![0; Rent::size_of()]);
+        Rent::default().to_account_info(&mut rent.info(false)).unwrap();
+        rent
+    }
+
+    #[test]
+    fn initialize_creates_the_brand_config_pda() {
+        program_stubs::set_syscall_stubs(Box::new(TokenStubs));
+        let brand_id = 9_001;
+        let (config_key, bump) = find_config_address(brand_id, &PROGRAM_ID);
+        let (authority_key, _) = find_authority_address(&config_key, &PROGRAM_ID);
+        let (other_config_key, _) = find_config_address(brand_id + 1, &PROGRAM_ID);
+        let (other_authority_key, _) = find_authority_address(&other_config_key, &PROGRAM_ID);
+        let admin = Pubkey::new_unique();
+        let mut payer = TestAccount::wallet();
+        let mut config = TestAccount::new(config_key, system_program::id(), vec![]);
+        let mut other_config = TestAccount::new(other_config_key, system_program::id(), vec![]);
+        let mut mint = TestAccount::mint(Pubkey::new_unique(), authority_key, authority_key);
+        let mut mint_state = StateWithExtensionsMut::<Mint>::unpack(&mut mint.data).unwrap();
+        mint_state.base.supply = 1_000;
+        mint_state.pack_base();
+        let mut stats = TestAccount::new(find_stats_address(&config_key, &PROGRAM_ID).0, system_program::id(), vec![]);
+        let mut foreign_mint = TestAccount::mint(Pubkey::new_unique(), other_authority_key, other_authority_key);
+        let mut rent = rent_sysvar();
+        let mut system = TestAccount::new(system_program::id(), Pubkey::default(), vec![]);
+        let payer = payer.info(true);
+        let config = config.info(false);
+        let mint = mint.info(false);
+        let rent = rent.info(false);
+        let system = system.info(false);
+        let stats = stats.info(false);
+        let initialize = LoyaltyInstruction::Initialize { admin, brand_id };
+
+        // Another brand's PDA, or a mint whose authority is another brand's PDA, is refused
+        let result = run(&[&payer, &other_config.info(false), &mint, &rent, &system, &stats], initialize.clone());
+        assert_eq!(result, Err(LoyaltyError::ConfigMismatch.into()));
+        let result = run(&[&payer, &config, &foreign_mint.info(false), &rent, &system, &stats], initialize.clone());
+        assert_eq!(result, Err(LoyaltyError::InvalidMintAuthority.into()));
+
+        run(&[&payer, &config, &mint, &rent, &system, &stats], initialize.clone()).unwrap();
+        assert_eq!(*config.owner, PROGRAM_ID);
+        let config_data = ConfigAccount::unpack(&config.data.borrow()).unwrap();
+        assert_eq!(config_data.admin, admin);
+        assert_eq!(config_data.loyalty_mint, *mint.key);
+        assert_eq!((config_data.brand_id, config_data.bump), (brand_id, bump));
+        // Points minted before the brand existed are counted as awarded
+        assert_eq!(*stats.owner, PROGRAM_ID);
+        assert_eq!(stats_of(&stats).config, config_key);
+        assert_eq!(stats_of(&stats).total_awarded, 1_000);
+        assert_reconciles(&stats, &mint);
+
+        let result = run(&[&payer, &config, &mint, &rent, &system, &stats], initialize);
+        assert_eq!(result, Err(LoyaltyError::AlreadyInitialized.into()));
+    }
+
+    #[test]
+    fn instructions_reject_accounts_from_another_brand() {
+        let mut brand = Brand::new(100);
+        let mut other = Brand::new(100);
+        let mut copy = TestAccount::new(Pubkey::new_unique(), PROGRAM_ID, brand.config.data.clone());
+        let admin = brand.admin.info(true);
+        let config = brand.config.info(false);
+        let mint = brand.mint.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let token_program = brand.token_program.info(false);
+        let authority = brand.authority.info(false);
+        let other_mint = other.mint.info(false);
+        let other_member_tokens = other.member_tokens.info(false);
+        let stats = brand.stats.info(false);
+        let issuer_stats = brand.issuer_stats.info(false);
+        let system = brand.system.info(false);
+        let stake_position = brand.stake_position.info(false);
+        let award = LoyaltyInstruction::AwardPoints { amount: 50 };
+
+        let result = run(
+            &[&admin, &config, &other_mint, &other_member_tokens, &token_program, &authority, &stats, &issuer_stats, &system, &stake_position],
+            award.clone(),
+        );
+        assert_eq!(result, Err(LoyaltyError::MintAccountMismatch.into()));
+        let result = run(
+            &[&admin, &config, &mint, &member_tokens, &token_program, &other.authority.info(false), &stats, &issuer_stats, &system, &stake_position],
+            award.clone(),
+        );
+        assert_eq!(result, Err(LoyaltyError::AuthorityMismatch.into()));
+        let result = run(
+            &[&other.member.info(true), &config, &other_member_tokens, &other_mint, &token_program, &stats],
+            LoyaltyInstruction::RedeemPoints { amount: 50 },
+        );
+        assert_eq!(result, Err(LoyaltyError::MintAccountMismatch.into()));
+
+        // A copy of a brand's config at any address other than its PDA is not a config
+        let result = run(
+            &[&admin, &copy.info(false), &mint, &member_tokens, &token_program, &authority, &stats, &issuer_stats, &system, &stake_position],
+            award,
+        );
+        assert_eq!(result, Err(LoyaltyError::ConfigMismatch.into()));
+
+        assert_eq!(token_amount(&member_tokens), 100);
+        assert_eq!(token_amount(&other_member_tokens), 100);
+    }
+
+    #[test]
+    fn redeem_burns_the_signers_own_points() {
+        let mut brand = Brand::new(100);
+        let mut thief = TestAccount::wallet();
+        let member = brand.member.info(true);
+        let config = brand.config.info(false);
+        let mint = brand.mint.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let token_program = brand.token_program.info(false);
+        let stats = brand.stats.info(false);
+
+        let result = run(
+            &[&thief.info(true), &config, &member_tokens, &mint, &token_program, &stats],
+            LoyaltyInstruction::RedeemPoints { amount: 40 },
+        );
+        assert_eq!(result, Err(LoyaltyError::OwnerMismatch.into()));
+        run(&[&member, &config, &member_tokens, &mint, &token_program, &stats], LoyaltyInstruction::RedeemPoints { amount: 40 })
+            .unwrap();
+
+        assert_eq!(token_amount(&member_tokens), 60);
+        assert_eq!(supply(&mint), 60);
+        assert_eq!((stats_of(&stats).total_redeemed, stats_of(&stats).active_members), (40, 1));
+
+        // Emptying the account drops the member from the active count
+        run(&[&member, &config, &member_tokens, &mint, &token_program, &stats], LoyaltyInstruction::RedeemPoints { amount: 60 })
+            .unwrap();
+        assert_eq!((stats_of(&stats).total_redeemed, stats_of(&stats).active_members), (100, 0));
+        assert_reconciles(&stats, &mint);
+    }
+
+    #[test]
+    fn redeem_rejects_a_fake_token_program() {
+        let mut brand = Brand::new(100);
+        let mut fake_token_program = TestAccount::wallet();
+        let member_tokens = brand.member_tokens.info(false);
+        let stats = brand.stats.info(false);
+
+        let result = run(
+            &[
+                &brand.member.info(true),
+                &brand.config.info(false),
+                &member_tokens,
+                &brand.mint.info(false),
+                &fake_token_program.info(false),
+                &stats,
+            ],
+            LoyaltyInstruction::RedeemPoints { amount: 40 },
+        );
+        assert_eq!(result, Err(LoyaltyError::InvalidTokenProgram.into()));
+        assert_eq!(token_amount(&member_tokens), 100);
+        assert_eq!(stats_of(&stats).total_redeemed, 0);
+    }
+
+    #[test]
+    fn swaps_need_both_approvals_and_respect_the_daily_limit() {
+        let mut source = Brand::new(1_000);
+        let mut target = Brand::new(0);
+        let mut outsider = TestAccount::wallet();
+        let (agreement_key, _) = find_swap_agreement_address(&source.config.key, &target.config.key, &PROGRAM_ID);
+        let mut agreement = TestAccount::new(agreement_key, system_program::id(), vec![]);
+        let mut system = TestAccount::new(system_program::id(), Pubkey::default(), vec![]);
+        let mut destination = TestAccount::token(Pubkey::new_unique(), target.mint.key, source.member.key, 0);
+        let source_admin = source.admin.info(true);
+        let target_admin = target.admin.info(true);
+        let outsider = outsider.info(true);
+        let system = system.info(false);
+        let member = source.member.info(true);
+        let source_config = source.config.info(false);
+        let target_config = target.config.info(false);
+        let agreement = agreement.info(false);
+        let source_mint = source.mint.info(false);
+        let source_tokens = source.member_tokens.info(false);
+        let target_mint = target.mint.info(false);
+        let destination = destination.info(false);
+        let target_authority = target.authority.info(false);
+        let token_program = source.token_program.info(false);
+        let source_stats = source.stats.info(false);
+        let target_stats = target.stats.info(false);
+        let swap = |amount| {
+            run(
+                &[
+                    &member,
+                    &source_config,
+                    &target_config,
+                    &agreement,
+                    &source_mint,
+                    &source_tokens,
+                    &target_mint,
+                    &destination,
+                    &target_authority,
+                    &token_program,
+                    &source_stats,
+                    &target_stats,
+                ],
+                LoyaltyInstruction::SwapPoints { amount },
+            )
+        };
+        let approve = |admin: usize, approved| {
+            let admin = [&source_admin, &target_admin, &outsider][admin];
+            run(&[admin, &source_config, &target_config, &agreement], LoyaltyInstruction::ApproveSwapAgreement { approved })
+        };
+
+        let propose = LoyaltyInstruction::ProposeSwapAgreement { rate_numerator: 3, rate_denominator: 2, daily_limit: 500 };
+        let result = run(&[&outsider, &source_config, &target_config, &agreement, &system], propose.clone());
+        assert_eq!(result, Err(LoyaltyError::AdminSignatureMismatch.into()));
+        run(&[&source_admin, &source_config, &target_config, &agreement, &system], propose).unwrap();
+
+        // Only the source brand has approved so far
+        assert_eq!(swap(100), Err(LoyaltyError::SwapNotApproved.into()));
+        assert_eq!(approve(2, true), Err(LoyaltyError::AdminSignatureMismatch.into()));
+        approve(1, true).unwrap();
+
+        swap(300).unwrap();
+        assert_eq!(token_amount(&source_tokens), 700);
+        assert_eq!(token_amount(&destination), 450);
+        assert_eq!(swap(201), Err(LoyaltyError::SwapDailyLimitExceeded.into()));
+        swap(200).unwrap();
+        assert_eq!(token_amount(&destination), 750);
+
+        // The limit resets the next day, and either brand can pull out
+        NOW.with(|now| now.set(now.get() + SECONDS_PER_DAY));
+        swap(100).unwrap();
+        approve(1, false).unwrap();
+        assert_eq!(swap(100), Err(LoyaltyError::SwapNotApproved.into()));
+        assert_eq!(token_amount(&source_tokens), 400);
+        assert_eq!(supply(&target_mint), 900);
+        assert_eq!(stats_of(&source_stats).total_redeemed, 600);
+        assert_eq!(stats_of(&target_stats).total_awarded, 900);
+        assert_reconciles(&source_stats, &source_mint);
+        assert_reconciles(&target_stats, &target_mint);
+    }
+
+    #[test]
+    fn swaps_reject_a_fake_token_program() {
+        let mut source = Brand::new(1_000);
+        let mut target = Brand::new(0);
+        let (agreement_key, bump) = find_swap_agreement_address(&source.config.key, &target.config.key, &PROGRAM_ID);
+        let mut agreement = TestAccount::packed(
+            agreement_key,
+            PROGRAM_ID,
+            SwapAgreement {
+                is_initialized: true,
+                source_config: source.config.key,
+                target_config: target.config.key,
+                rate_numerator: 1,
+                rate_denominator: 1,
+                daily_limit: u64::MAX,
+                source_approved: true,
+                target_approved: true,
+                bump,
+                ..SwapAgreement::default()
+            },
+        );
+        let mut destination = TestAccount::token(Pubkey::new_unique(), target.mint.key, source.member.key, 0);
+        let mut fake_token_program = TestAccount::wallet();
+        let source_tokens = source.member_tokens.info(false);
+
+        let result = run(
+            &[
+                &source.member.info(true),
+                &source.config.info(false),
+                &target.config.info(false),
+                &agreement.info(false),
+                &source.mint.info(false),
+                &source_tokens,
+                &target.mint.info(false),
+                &destination.info(false),
+                &target.authority.info(false),
+                &fake_token_program.info(false),
+                &source.stats.info(false),
+                &target.stats.info(false),
+            ],
+            LoyaltyInstruction::SwapPoints { amount: 100 },
+        );
+        assert_eq!(result, Err(LoyaltyError::InvalidTokenProgram.into()));
+        assert_eq!(token_amount(&source_tokens), 1_000);
+    }
+
+    #[test]
+    fn register_member_approves_the_authority_for_vouchers() {
+        let mut brand = Brand::new(500);
+        let (member_key, member_bump) = find_member_address(&brand.config.key, &brand.member.key, &PROGRAM_ID);
+        let mut member_pda = TestAccount::new(member_key, system_program::id(), vec![]);
+        let mut fake_token_program = TestAccount::wallet();
+        let mut outsider = TestAccount::wallet();
+        let owner = brand.member.info(true);
+        let config = brand.config.info(false);
+        let member_pda = member_pda.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let authority = brand.authority.info(false);
+        let token_program = brand.token_program.info(false);
+        let system = brand.system.info(false);
+        let register = LoyaltyInstruction::RegisterMember { voucher_allowance: 300 };
+
+        let result = run(
+            &[&outsider.info(true), &config, &member_pda, &member_tokens, &authority, &token_program, &system],
+            register.clone(),
+        );
+        assert_eq!(result, Err(LoyaltyError::OwnerMismatch.into()));
+        let result = run(
+            &[&owner, &config, &member_pda, &member_tokens, &authority, &fake_token_program.info(false), &system],
+            register.clone(),
+        );
+        assert_eq!(result, Err(LoyaltyError::InvalidTokenProgram.into()));
+
+        run(&[&owner, &config, &member_pda, &member_tokens, &authority, &token_program, &system], register.clone()).unwrap();
+        assert_eq!(*member_pda.owner, PROGRAM_ID);
+        let member_data = MemberAccount::unpack(&member_pda.data.borrow()).unwrap();
+        assert_eq!((member_data.owner, member_data.token_account), (*owner.key, *member_tokens.key));
+        assert_eq!((member_data.last_voucher_nonce, member_data.bump), (0, member_bump));
+        let token_data = StateWithExtensions::<TokenAccount>::unpack(&member_tokens.data.borrow()).unwrap().base;
+        assert_eq!(token_data.delegate, COption::Some(*authority.key));
+        assert_eq!(token_data.delegated_amount, 300);
+
+        let result = run(&[&owner, &config, &member_pda, &member_tokens, &authority, &token_program, &system], register);
+        assert_eq!(result, Err(LoyaltyError::AlreadyInitialized.into()));
+    }
+
+    /// A registered member PDA for the brand's member, with the authority approved for `allowance`.
+    fn registered_member(brand: &mut Brand, allowance: u64) -> TestAccount {
+        let (member_key, bump) = find_member_address(&brand.config.key, &brand.member.key, &PROGRAM_ID);
+        let mut token_data = TokenAccount::unpack(&brand.member_tokens.data).unwrap();
+        token_data.delegate = COption::Some(brand.authority.key);
+        token_data.delegated_amount = allowance;
+        TokenAccount::pack(token_data, &mut brand.member_tokens.data).unwrap();
+        TestAccount::packed(
+            member_key,
+            PROGRAM_ID,
+            MemberAccount {
+                is_initialized: true,
+                config: brand.config.key,
+                owner: brand.member.key,
+                token_account: brand.member_tokens.key,
+                bump,
+                ..MemberAccount::default()
+            },
+        )
+    }
+
+    /// An instructions sysvar for a transaction of an Ed25519 check of `signer` over `message`
+    /// followed by the instruction being processed.
+    fn ed25519_checked(signer: &Pubkey, message: &[u8]) -> TestAccount {
+        const DATA_START: u16 = 16;
+        let public_key_offset = DATA_START;
+        let signature_offset = public_key_offset + 32;
+        let message_offset = signature_offset + 64;
+        let mut data = vec![1, 0];
+        for field in [signature_offset, u16::MAX, public_key_offset, u16::MAX, message_offset, message.len() as u16, u16::MAX] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(message);
+        let instructions = [
+            BorrowedInstruction { program_id: &solana_program::ed25519_program::id(), accounts: vec![], data: &data },
+            BorrowedInstruction { program_id: &PROGRAM_ID, accounts: vec![], data: &[] },
+        ];
+        let mut sysvar_data = construct_instructions_data(&instructions);
+        store_current_index(&mut sysvar_data, 1);
+        TestAccount::new(solana_program::sysvar::instructions::id(), solana_program::sysvar::id(), sysvar_data)
+    }
+
+    #[test]
+    fn vouchers_redeem_each_nonce_once_within_the_window() {
+        let mut brand = Brand::new(500);
+        let mut member_pda = registered_member(&mut brand, 300);
+        let expiry = NOW.with(Cell::get) + 60;
+        let cashier = brand.admin.info(true);
+        let config = brand.config.info(false);
+        let member_pda = member_pda.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let mint = brand.mint.info(false);
+        let authority = brand.authority.info(false);
+        let token_program = brand.token_program.info(false);
+        let stats = brand.stats.info(false);
+        let member_key = brand.member.key;
+        let mut sysvar = ed25519_checked(&member_key, &[]);
+        let sysvar = sysvar.info(false);
+        // Each redemption follows an Ed25519 check of the member's voucher for that nonce
+        let redeem = |nonce| {
+            let checked = ed25519_checked(&member_key, &voucher::voucher_message(config.key, 10, nonce, expiry));
+            *sysvar.data.borrow_mut() = Box::leak(checked.data.into_boxed_slice());
+            run(
+                &[
+                    &cashier,
+                    &config,
+                    &member_pda,
+                    &member_tokens,
+                    &mint,
+                    &authority,
+                    &token_program,
+                    &stats,
+                    &sysvar,
+                ],
+                LoyaltyInstruction::RedeemWithVoucher { amount: 10, nonce, expiry },
+            )
+        };
+
+        redeem(5).unwrap();
+        assert_eq!(redeem(5), Err(LoyaltyError::VoucherNonceUsed.into()));
+        assert_eq!(redeem(0), Err(LoyaltyError::VoucherNonceUsed.into()));
+        // Earlier nonces can still redeem out of order, once each
+        redeem(3).unwrap();
+        assert_eq!(redeem(3), Err(LoyaltyError::VoucherNonceUsed.into()));
+        // Nonces that fall out of the window can no longer be redeemed
+        redeem(5 + VOUCHER_NONCE_WINDOW).unwrap();
+        redeem(6).unwrap();
+        assert_eq!(redeem(5), Err(LoyaltyError::VoucherNonceUsed.into()));
+
+        assert_eq!(token_amount(&member_tokens), 460);
+        assert_eq!(supply(&mint), 460);
+        assert_eq!(stats_of(&stats).total_redeemed, 40);
+    }
+
+    #[test]
+    fn vouchers_need_the_members_signature_over_their_terms() {
+        let mut brand = Brand::new(500);
+        let mut member_pda = registered_member(&mut brand, 300);
+        let mut fake_token_program = TestAccount::wallet();
+        let now = NOW.with(Cell::get);
+        let cashier = brand.admin.info(true);
+        let config = brand.config.info(false);
+        let member_pda = member_pda.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let mint = brand.mint.info(false);
+        let authority = brand.authority.info(false);
+        let token_programs = [brand.token_program.info(false), fake_token_program.info(false)];
+        let stats = brand.stats.info(false);
+        let mut sysvar = ed25519_checked(&brand.member.key, &[]);
+        let sysvar = sysvar.info(false);
+        let redeem = |signer: &Pubkey, signed_amount, expiry, token_program: usize| {
+            let checked = ed25519_checked(signer, &voucher::voucher_message(config.key, signed_amount, 1, expiry));
+            *sysvar.data.borrow_mut() = Box::leak(checked.data.into_boxed_slice());
+            run(
+                &[
+                    &cashier,
+                    &config,
+                    &member_pda,
+                    &member_tokens,
+                    &mint,
+                    &authority,
+                    &token_programs[token_program],
+                    &stats,
+                    &sysvar,
+                ],
+                LoyaltyInstruction::RedeemWithVoucher { amount: 100, nonce: 1, expiry },
+            )
+        };
+
+        let result = redeem(&Pubkey::new_unique(), 100, now, 0);
+        assert_eq!(result, Err(LoyaltyError::InvalidVoucherSignature.into()));
+        let result = redeem(&brand.member.key, 10, now, 0);
+        assert_eq!(result, Err(LoyaltyError::InvalidVoucherSignature.into()));
+        let result = redeem(&brand.member.key, 100, now - 1, 0);
+        assert_eq!(result, Err(LoyaltyError::VoucherExpired.into()));
+        let result = redeem(&brand.member.key, 100, now, 1);
+        assert_eq!(result, Err(LoyaltyError::InvalidTokenProgram.into()));
+
+        // Without a preceding Ed25519 instruction there is nothing to check
+        store_current_index(&mut sysvar.data.borrow_mut(), 0);
+        let result = run(
+            &[&cashier, &config, &member_pda, &member_tokens, &mint, &authority, &token_programs[0], &stats, &sysvar],
+            LoyaltyInstruction::RedeemWithVoucher { amount: 100, nonce: 1, expiry: now },
+        );
+        assert_eq!(result, Err(LoyaltyError::InvalidVoucherSignature.into()));
+        assert_eq!(token_amount(&member_tokens), 500);
+    }
+
+    /// The brand's stake vault PDA, before the first stake creates it.
+    fn stake_vault(brand: &Brand) -> TestAccount {
+        TestAccount::new(find_vault_address(&brand.config.key, &PROGRAM_ID).0, system_program::id(), vec![])
+    }
+
+    #[test]
+    fn staking_multiplies_awards_and_early_unstake_burns_the_penalty() {
+        let mut brand = Brand::new(1_000);
+        let mut vault = stake_vault(&brand);
+        let mut outsider = TestAccount::wallet();
+        let admin = brand.admin.info(true);
+        let member = brand.member.info(true);
+        let config = brand.config.info(false);
+        let stake_position = brand.stake_position.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let vault = vault.info(false);
+        let mint = brand.mint.info(false);
+        let authority = brand.authority.info(false);
+        let token_program = brand.token_program.info(false);
+        let system = brand.system.info(false);
+        let stats = brand.stats.info(false);
+        let issuer_stats = brand.issuer_stats.info(false);
+        let tier = StakeTier { min_amount: 500, min_lock_secs: 100, multiplier_bps: 15_000 };
+        let set_params = LoyaltyInstruction::SetStakeParams {
+            early_unlock_penalty_bps: 1_000,
+            stake_tiers: [tier, StakeTier::default(), StakeTier::default()],
+        };
+
+        let result = run(&[&outsider.info(true), &config], set_params.clone());
+        assert_eq!(result, Err(LoyaltyError::AdminSignatureMismatch.into()));
+        run(&[&admin, &config], set_params).unwrap();
+
+        run(
+            &[&member, &config, &stake_position, &member_tokens, &vault, &mint, &authority, &token_program, &system],
+            LoyaltyInstruction::Stake { amount: 600, lock_secs: 200 },
+        )
+        .unwrap();
+        assert_eq!(*vault.owner, spl_token_2022::id());
+        assert_eq!(token_amount(&vault), 600);
+        assert_eq!(token_amount(&member_tokens), 400);
+
+        // Tier 1 earns 1.5x while the lock is active
+        run(
+            &[&admin, &config, &mint, &member_tokens, &token_program, &authority, &stats, &issuer_stats, &system, &stake_position],
+            LoyaltyInstruction::AwardPoints { amount: 100 },
+        )
+        .unwrap();
+        assert_eq!(token_amount(&member_tokens), 550);
+
+        let unstake = [&member, &config, &stake_position, &member_tokens, &vault, &mint, &authority, &token_program, &stats];
+        run(&unstake, LoyaltyInstruction::Unstake).unwrap();
+        assert_eq!(token_amount(&member_tokens), 1_090);
+        assert_eq!(token_amount(&vault), 0);
+        assert_eq!(stats_of(&stats).total_penalties, 60);
+        assert_eq!(supply(&mint), 1_090);
+        assert_eq!(run(&unstake, LoyaltyInstruction::Unstake), Err(LoyaltyError::NothingStaked.into()));
+    }
+
+    #[test]
+    fn unstake_after_the_lock_is_free_and_owner_only() {
+        let mut brand = Brand::new(1_000);
+        let mut vault = stake_vault(&brand);
+        let mut outsider = TestAccount::wallet();
+        let admin = brand.admin.info(true);
+        let member = brand.member.info(true);
+        let config = brand.config.info(false);
+        let stake_position = brand.stake_position.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let vault = vault.info(false);
+        let mint = brand.mint.info(false);
+        let authority = brand.authority.info(false);
+        let token_program = brand.token_program.info(false);
+        let stats = brand.stats.info(false);
+        run(
+            &[&admin, &config],
+            LoyaltyInstruction::SetStakeParams { early_unlock_penalty_bps: 1_000, stake_tiers: Default::default() },
+        )
+        .unwrap();
+        run(
+            &[&member, &config, &stake_position, &member_tokens, &vault, &mint, &authority, &token_program, &brand.system.info(false)],
+            LoyaltyInstruction::Stake { amount: 600, lock_secs: 200 },
+        )
+        .unwrap();
+
+        let result = run(
+            &[&outsider.info(true), &config, &stake_position, &member_tokens, &vault, &mint, &authority, &token_program, &stats],
+            LoyaltyInstruction::Unstake,
+        );
+        assert_eq!(result, Err(LoyaltyError::OwnerMismatch.into()));
+
+        NOW.with(|now| now.set(now.get() + 200));
+        run(
+            &[&member, &config, &stake_position, &member_tokens, &vault, &mint, &authority, &token_program, &stats],
+            LoyaltyInstruction::Unstake,
+        )
+        .unwrap();
+        assert_eq!(token_amount(&member_tokens), 1_000);
+        assert_eq!(stats_of(&stats).total_penalties, 0);
+    }
+
+    #[test]
+    fn staking_rejects_a_fake_token_program_before_creating_the_vault() {
+        let mut brand = Brand::new(1_000);
+        let mut vault = stake_vault(&brand);
+        let mut fake_token_program = TestAccount::wallet();
+        let vault = vault.info(false);
+        let stake_position = brand.stake_position.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+
+        let result = run(
+            &[
+                &brand.member.info(true),
+                &brand.config.info(false),
+                &stake_position,
+                &member_tokens,
+                &vault,
+                &brand.mint.info(false),
+                &brand.authority.info(false),
+                &fake_token_program.info(false),
+                &brand.system.info(false),
+            ],
+            LoyaltyInstruction::Stake { amount: 600, lock_secs: 200 },
+        );
+        assert_eq!(result, Err(LoyaltyError::InvalidTokenProgram.into()));
+        assert!(vault.data_is_empty() && stake_position.data_is_empty());
+        assert_eq!(*vault.owner, system_program::id());
+        assert_eq!(token_amount(&member_tokens), 1_000);
+    }
+
+    #[test]
+    fn unstake_rejects_a_fake_token_program() {
+        let mut brand = Brand::new(1_000);
+        let (stake_position_key, bump) = find_stake_position_address(&brand.config.key, &brand.member.key, &PROGRAM_ID);
+        let now = NOW.with(Cell::get);
+        let mut stake_position = TestAccount::packed(
+            stake_position_key,
+            PROGRAM_ID,
+            StakePosition {
+                is_initialized: true,
+                config: brand.config.key,
+                owner: brand.member.key,
+                amount: 600,
+                lock_start: now,
+                unlock_at: now + 200,
+                bump,
+            },
+        );
+        let vault_key = find_vault_address(&brand.config.key, &PROGRAM_ID).0;
+        let mut vault = TestAccount::token(vault_key, brand.mint.key, brand.authority.key, 600);
+        let mut fake_token_program = TestAccount::wallet();
+        let vault = vault.info(false);
+        let stake_position = stake_position.info(false);
+
+        let result = run(
+            &[
+                &brand.member.info(true),
+                &brand.config.info(false),
+                &stake_position,
+                &brand.member_tokens.info(false),
+                &vault,
+                &brand.mint.info(false),
+                &brand.authority.info(false),
+                &fake_token_program.info(false),
+                &brand.stats.info(false),
+            ],
+            LoyaltyInstruction::Unstake,
+        );
+        assert_eq!(result, Err(LoyaltyError::InvalidTokenProgram.into()));
+        assert_eq!(StakePosition::unpack(&stake_position.data.borrow()).unwrap().amount, 600);
+        assert_eq!(token_amount(&vault), 600);
+    }
+
+    #[test]
+    fn register_merchant_is_admin_only() {
+        let mut brand = Brand::new(0);
+        let mut merchant_wallet = TestAccount::wallet();
+        let (merchant_key, merchant_bump) = find_merchant_address(&brand.config.key, &merchant_wallet.key, &PROGRAM_ID);
+        let mut merchant = TestAccount::new(merchant_key, system_program::id(), vec![]);
+        let mut outsider = TestAccount::wallet();
+        let config = brand.config.info(false);
+        let merchant_wallet = merchant_wallet.info(false);
+        let merchant = merchant.info(false);
+        let system = brand.system.info(false);
+
+        let result = run(&[&outsider.info(true), &config, &merchant_wallet, &merchant, &system], LoyaltyInstruction::RegisterMerchant);
+        assert_eq!(result, Err(LoyaltyError::AdminSignatureMismatch.into()));
+        let admin = brand.admin.info(true);
+        run(&[&admin, &config, &merchant_wallet, &merchant, &system], LoyaltyInstruction::RegisterMerchant).unwrap();
+        assert_eq!(*merchant.owner, PROGRAM_ID);
+        let merchant_data = MerchantAccount::unpack(&merchant.data.borrow()).unwrap();
+        assert_eq!((merchant_data.merchant, merchant_data.bump), (*merchant_wallet.key, merchant_bump));
+
+        let result = run(&[&admin, &config, &merchant_wallet, &merchant, &system], LoyaltyInstruction::RegisterMerchant);
+        assert_eq!(result, Err(LoyaltyError::AlreadyInitialized.into()));
+    }
+
+    /// A registered merchant of the brand, with its wallet.
+    fn registered_merchant(brand: &Brand) -> (TestAccount, TestAccount) {
+        let wallet = TestAccount::wallet();
+        let (key, bump) = find_merchant_address(&brand.config.key, &wallet.key, &PROGRAM_ID);
+        let merchant = TestAccount::packed(
+            key,
+            PROGRAM_ID,
+            MerchantAccount { is_initialized: true, config: brand.config.key, merchant: wallet.key, bump, ..MerchantAccount::default() },
+        );
+        (wallet, merchant)
+    }
+
+    #[test]
+    fn merchant_redemptions_need_both_signatures_and_fill_the_ledger() {
+        let mut brand = Brand::new(500);
+        let (mut merchant_wallet, mut merchant) = registered_merchant(&brand);
+        let mut impostor = TestAccount::wallet();
+        let mut fake_token_program = TestAccount::wallet();
+        let member = brand.member.info(true);
+        let merchant_wallet = merchant_wallet.info(true);
+        let config = brand.config.info(false);
+        let merchant = merchant.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let mint = brand.mint.info(false);
+        let token_program = brand.token_program.info(false);
+        let stats = brand.stats.info(false);
+        let unsigned_wallet = AccountInfo { is_signer: false, ..merchant_wallet.clone() };
+        let redeem = LoyaltyInstruction::RedeemAtMerchant { amount: 120 };
+
+        let result = run(
+            &[&member, &unsigned_wallet, &config, &merchant, &member_tokens, &mint, &token_program, &stats],
+            redeem.clone(),
+        );
+        assert_eq!(result, Err(ProgramError::MissingRequiredSignature));
+        let result = run(
+            &[&member, &impostor.info(true), &config, &merchant, &member_tokens, &mint, &token_program, &stats],
+            redeem.clone(),
+        );
+        assert_eq!(result, Err(LoyaltyError::MerchantMismatch.into()));
+        let result = run(
+            &[&member, &merchant_wallet, &config, &merchant, &member_tokens, &mint, &fake_token_program.info(false), &stats],
+            redeem.clone(),
+        );
+        assert_eq!(result, Err(LoyaltyError::InvalidTokenProgram.into()));
+        assert_eq!(MerchantAccount::unpack(&merchant.data.borrow()).unwrap().pending_settlement, 0);
+
+        run(&[&member, &merchant_wallet, &config, &merchant, &member_tokens, &mint, &token_program, &stats], redeem.clone())
+            .unwrap();
+        run(&[&member, &merchant_wallet, &config, &merchant, &member_tokens, &mint, &token_program, &stats], redeem).unwrap();
+
+        assert_eq!(token_amount(&member_tokens), 260);
+        assert_eq!(supply(&mint), 260);
+        let merchant_data = MerchantAccount::unpack(&merchant.data.borrow()).unwrap();
+        assert_eq!(
+            (merchant_data.pending_settlement, merchant_data.total_redeemed, merchant_data.redemption_count),
+            (240, 240, 2)
+        );
+        assert_eq!(stats_of(&stats).total_redeemed, 240);
+    }
+
+    #[test]
+    fn settlements_are_admin_only_and_capped_at_the_pending_amount() {
+        let mut brand = Brand::new(0);
+        let (_, mut merchant) = registered_merchant(&brand);
+        let mut merchant_data = MerchantAccount::unpack(&merchant.data).unwrap();
+        merchant_data.pending_settlement = 300;
+        MerchantAccount::pack(merchant_data, &mut merchant.data).unwrap();
+        let mut outsider = TestAccount::wallet();
+        let admin = brand.admin.info(true);
+        let config = brand.config.info(false);
+        let merchant = merchant.info(false);
+        let settle = |amount| LoyaltyInstruction::Settle { amount, payment_reference: [9; 32] };
+
+        let result = run(&[&outsider.info(true), &config, &merchant], settle(100));
+        assert_eq!(result, Err(LoyaltyError::AdminSignatureMismatch.into()));
+        assert_eq!(run(&[&admin, &config, &merchant], settle(0)), Err(LoyaltyError::NothingToSettle.into()));
+        assert_eq!(run(&[&admin, &config, &merchant], settle(301)), Err(LoyaltyError::SettlementExceedsPending.into()));
+
+        // Redemptions not covered by the payout stay pending
+        run(&[&admin, &config, &merchant], settle(200)).unwrap();
+        let merchant_data = MerchantAccount::unpack(&merchant.data.borrow()).unwrap();
+        assert_eq!((merchant_data.pending_settlement, merchant_data.total_settled), (100, 200));
+        assert_eq!((merchant_data.last_settled_amount, merchant_data.last_payment_reference), (200, [9; 32]));
+        assert_eq!(merchant_data.last_settled_at, NOW.with(Cell::get));
+    }
+
+    /// Accounts for a check-in by the brand's member at a registered merchant.
+    struct CheckInAccounts {
+        merchant_wallet: TestAccount,
+        merchant: TestAccount,
+        member_pda: TestAccount,
+        issuer_stats: TestAccount,
+    }
+
+    impl CheckInAccounts {
+        fn new(brand: &mut Brand) -> Self {
+            let (merchant_wallet, merchant) = registered_merchant(brand);
+            let (issuer_stats_key, _) = find_issuer_stats_address(&brand.config.key, &merchant_wallet.key, &PROGRAM_ID);
+            Self {
+                member_pda: registered_member(brand, 0),
+                issuer_stats: TestAccount::new(issuer_stats_key, system_program::id(), vec![]),
+                merchant_wallet,
+                merchant,
+            }
+        }
+    }
+
+    fn set_day(day: i64) {
+        NOW.with(|now| now.set(day * SECONDS_PER_DAY + 1_000));
+    }
+
+    #[test]
+    fn check_ins_build_streaks_and_pay_bonuses() {
+        let mut brand = Brand::new(0);
+        let mut check_in = CheckInAccounts::new(&mut brand);
+        let mut outsider = TestAccount::wallet();
+        let member = brand.member.info(true);
+        let merchant_wallet = check_in.merchant_wallet.info(true);
+        let config = brand.config.info(false);
+        let merchant = check_in.merchant.info(false);
+        let member_pda = check_in.member_pda.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let mint = brand.mint.info(false);
+        let authority = brand.authority.info(false);
+        let token_program = brand.token_program.info(false);
+        let stats = brand.stats.info(false);
+        let issuer_stats = check_in.issuer_stats.info(false);
+        let system = brand.system.info(false);
+        let check_in = || {
+            run(
+                &[
+                    &member,
+                    &merchant_wallet,
+                    &config,
+                    &merchant,
+                    &member_pda,
+                    &member_tokens,
+                    &mint,
+                    &authority,
+                    &token_program,
+                    &stats,
+                    &issuer_stats,
+                    &system,
+                ],
+                LoyaltyInstruction::CheckIn,
+            )
+        };
+        let mut streak_bonuses = [StreakBonus::default(); STREAK_BONUS_COUNT];
+        streak_bonuses[0] = StreakBonus { streak_days: 3, bonus: 50 };
+        let set_bonuses = LoyaltyInstruction::SetStreakBonuses { streak_bonuses };
+
+        let result = run(&[&outsider.info(true), &config], set_bonuses.clone());
+        assert_eq!(result, Err(LoyaltyError::AdminSignatureMismatch.into()));
+        run(&[&brand.admin.info(true), &config], set_bonuses).unwrap();
+
+        set_day(20_000);
+        check_in().unwrap();
+        assert_eq!(check_in(), Err(LoyaltyError::AlreadyCheckedIn.into()));
+        set_day(20_001);
+        check_in().unwrap();
+        assert_eq!(token_amount(&member_tokens), 0);
+
+        // The third consecutive day pays the bonus, credited to the merchant as issuer
+        set_day(20_002);
+        check_in().unwrap();
+        assert_eq!(token_amount(&member_tokens), 50);
+        let member_data = MemberAccount::unpack(&member_pda.data.borrow()).unwrap();
+        assert_eq!((member_data.current_streak, member_data.longest_streak), (3, 3));
+        assert_eq!(stats_of(&stats).total_awarded, 50);
+        let issuer_data = IssuerStatsAccount::unpack(&issuer_stats.data.borrow()).unwrap();
+        assert_eq!((issuer_data.issuer, issuer_data.total_awarded), (*merchant_wallet.key, 50));
+    }
+
+    #[test]
+    fn check_ins_forgive_one_missed_day_per_grace_period() {
+        let mut brand = Brand::new(0);
+        let mut check_in = CheckInAccounts::new(&mut brand);
+        let member = brand.member.info(true);
+        let merchant_wallet = check_in.merchant_wallet.info(true);
+        let config = brand.config.info(false);
+        let merchant = check_in.merchant.info(false);
+        let member_pda = check_in.member_pda.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let mint = brand.mint.info(false);
+        let authority = brand.authority.info(false);
+        let token_program = brand.token_program.info(false);
+        let stats = brand.stats.info(false);
+        let issuer_stats = check_in.issuer_stats.info(false);
+        let system = brand.system.info(false);
+        let check_in_on = |day| {
+            set_day(day);
+            run(
+                &[
+                    &member,
+                    &merchant_wallet,
+                    &config,
+                    &merchant,
+                    &member_pda,
+                    &member_tokens,
+                    &mint,
+                    &authority,
+                    &token_program,
+                    &stats,
+                    &issuer_stats,
+                    &system,
+                ],
+                LoyaltyInstruction::CheckIn,
+            )
+            .unwrap();
+            MemberAccount::unpack(&member_pda.data.borrow()).unwrap().current_streak
+        };
+        // Two days before the end of a grace period
+        let start = 701 * DAYS_PER_GRACE_PERIOD - 3;
+
+        assert_eq!(check_in_on(start), 1);
+        assert_eq!(check_in_on(start + 2), 2);
+        // The next period brings a new grace day, which is then used up
+        assert_eq!(check_in_on(start + 4), 3);
+        assert_eq!(check_in_on(start + 6), 1);
+        assert_eq!(check_in_on(start + 7), 2);
+        assert_eq!(MemberAccount::unpack(&member_pda.data.borrow()).unwrap().longest_streak, 3);
+    }
+
+    #[test]
+    fn check_ins_need_a_registered_merchant_and_the_real_token_program() {
+        let mut brand = Brand::new(0);
+        let mut check_in = CheckInAccounts::new(&mut brand);
+        let mut impostor = TestAccount::wallet();
+        let mut fake_token_program = TestAccount::wallet();
+        let member = brand.member.info(true);
+        let config = brand.config.info(false);
+        let merchant = check_in.merchant.info(false);
+        let member_pda = check_in.member_pda.info(false);
+        let member_tokens = brand.member_tokens.info(false);
+        let mint = brand.mint.info(false);
+        let authority = brand.authority.info(false);
+        let stats = brand.stats.info(false);
+        let issuer_stats = check_in.issuer_stats.info(false);
+        let system = brand.system.info(false);
+        let issuers = [check_in.merchant_wallet.info(true), impostor.info(true)];
+        let token_programs = [brand.token_program.info(false), fake_token_program.info(false)];
+        let check_in = |issuer: usize, token_program: usize| {
+            run(
+                &[
+                    &member,
+                    &issuers[issuer],
+                    &config,
+                    &merchant,
+                    &member_pda,
+                    &member_tokens,
+                    &mint,
+                    &authority,
+                    &token_programs[token_program],
+                    &stats,
+                    &issuer_stats,
+                    &system,
+                ],
+                LoyaltyInstruction::CheckIn,
+            )
+        };
+
+        assert_eq!(check_in(1, 0), Err(LoyaltyError::MerchantMismatch.into()));
+        assert_eq!(check_in(0, 1), Err(LoyaltyError::InvalidTokenProgram.into()));
+        assert_eq!(MemberAccount::unpack(&member_pda.data.borrow()).unwrap().current_streak, 0);
+    }
+
+    /// Account data laid out like the runtime's input buffer, with its length in the 8 bytes
+    /// before it and room to grow, so `AccountInfo::realloc` can extend it in place.
+    fn reallocatable(data: &[u8]) -> &'static mut [u8] {
+        let words = (8 + data.len() + MAX_PERMITTED_DATA_INCREASE).div_ceil(8);
+        let buffer = Box::leak(vec![0u64; words].into_boxed_slice());
+        let bytes = unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, words * 8) };
+        bytes[..8].copy_from_slice(&(data.len() as u64).to_le_bytes());
+        bytes[8..8 + data.len()].copy_from_slice(data);
+        &mut bytes[8..8 + data.len()]
+    }
+
+    #[test]
+    fn migrated_baseline_config_keeps_its_address_and_can_award() {
+        let mut brand = Brand::new(100);
+        // Baseline configs were keypair accounts holding is_initialized, admin, loyalty_mint
+        let mut legacy_config = TestAccount::new(Pubkey::new_unique(), PROGRAM_ID, vec![]);
+        let (authority_key, _) = find_authority_address(&legacy_config.key, &PROGRAM_ID);
+        let mut mint = TestAccount::mint(brand.mint.key, authority_key, authority_key);
+        let mut mint_state = StateWithExtensionsMut::<Mint>::unpack(&mut mint.data).unwrap();
+        mint_state.base.supply = 100;
+        mint_state.pack_base();
+        let (stats_key, _) = find_stats_address(&legacy_config.key, &PROGRAM_ID);
+        let mut stats = TestAccount::new(stats_key, system_program::id(), vec![]);
+        let (issuer_stats_key, _) = find_issuer_stats_address(&legacy_config.key, &brand.admin.key, &PROGRAM_ID);
+        let mut issuer_stats = TestAccount::new(issuer_stats_key, system_program::id(), vec![]);
+        let (stake_position_key, _) = find_stake_position_address(&legacy_config.key, &brand.member.key, &PROGRAM_ID);
+        let mut stake_position = TestAccount::new(stake_position_key, system_program::id(), vec![]);
+        let mut authority = TestAccount::new(authority_key, Pubkey::default(), vec![]);
+
+        let mut v0 = vec![1u8];
+        v0.extend_from_slice(brand.admin.key.as_ref());
+        v0.extend_from_slice(brand.mint.key.as_ref());
+        let config = legacy_config.info(false);
+        *config.data.borrow_mut() = reallocatable(&v0);
+        let mint = mint.info(false);
+        let stats = stats.info(false);
+        let payer = brand.admin.info(true);
+        let system = brand.system.info(false);
+        let migrate = || run(&[&payer, &config, &system, &stats, &mint], LoyaltyInstruction::MigrateConfig);
+
+        // Unmigrated, the config cannot even be loaded
+        let member_tokens = brand.member_tokens.info(false);
+        let token_program = brand.token_program.info(false);
+        let authority = authority.info(false);
+        let issuer_stats = issuer_stats.info(false);
+        let stake_position = stake_position.info(false);
+        let award = |amount| {
+            run(
+                &[&payer, &config, &mint, &member_tokens, &token_program, &authority, &stats, &issuer_stats, &system, &stake_position],
+                LoyaltyInstruction::AwardPoints { amount },
+            )
+        };
+        assert!(award(50).is_err());
+
+        migrate().unwrap();
+        let config_data = ConfigAccount::unpack(&config.data.borrow()).unwrap();
+        assert_eq!(config_data.legacy_address, *config.key);
+        assert_eq!((config_data.admin, config_data.loyalty_mint), (*payer.key, *mint.key));
+        // The supply in circulation before the migration counts as awarded
+        assert_eq!(*stats.owner, PROGRAM_ID);
+        assert_eq!(stats_of(&stats).total_awarded, 100);
+        // Migrating a current config is a no-op
+        migrate().unwrap();
+
+        award(50).unwrap();
+        assert_eq!(token_amount(&member_tokens), 150);
+        assert_eq!(stats_of(&stats).total_awarded, 150);
+        assert_reconciles(&stats, &mint);
+    }
+
+    #[test]
+    fn migrated_baseline_config_rejects_a_stats_account_that_is_not_its_pda() {
+        let mut brand = Brand::new(0);
+        let mut legacy_config = TestAccount::new(Pubkey::new_unique(), PROGRAM_ID, vec![]);
+        let mut v0 = vec![1u8];
+        v0.extend_from_slice(brand.admin.key.as_ref());
+        v0.extend_from_slice(brand.mint.key.as_ref());
+        let config = legacy_config.info(false);
+        *config.data.borrow_mut() = reallocatable(&v0);
+
+        // The brand's own stats PDA belongs to a different config
+        let result = run(
+            &[&brand.admin.info(true), &config, &brand.system.info(false), &brand.stats.info(false), &brand.mint.info(false)],
+            LoyaltyInstruction::MigrateConfig,
+        );
+        assert_eq!(result, Err(LoyaltyError::ConfigMismatch.into()));
+        assert_eq!(config.data_len(), CONFIG_V0_LEN);
+    }
+
+    #[test]
+    fn migrate_account_rejects_accounts_without_a_header() {
+        let mut brand = Brand::new(0);
+        let record = ClawbackRecord { is_initialized: true, config: brand.config.key, ..ClawbackRecord::default() };
+        let mut headerless = TestAccount::new(Pubkey::new_unique(), PROGRAM_ID, borsh::to_vec(&record).unwrap());
+        let headerless = headerless.info(false);
+        let admin = brand.admin.info(true);
+
+        let result = run(
+            &[
+                &admin,
+                &admin,
+                &brand.config.info(false),
+                &headerless,
+                &brand.system.info(false),
+            ],
+            LoyaltyInstruction::MigrateAccount { account_type: AccountType::ClawbackRecord },
+        );
+        assert_eq!(result, Err(ProgramError::InvalidAccountData));
+    }
+}
@@ -3,6 +3,171 @@ use solana_program::{
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::Pubkey,
 };
+use crate::{account_header::upgrade_body, error::LoyaltyError};
+
+/// Layout version written into the header of every account this program creates.
+/// Bump it whenever a layout changes and teach `MigrateConfig` to upgrade the old one.
+pub const CURRENT_VERSION: u8 = 1;
+
+pub use crate::account_header::HEADER_LEN;
+
+/// Discriminator stored in the second header byte, so one account type can never be
+/// unpacked as another.
+#[repr(u8)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+#[borsh(use_discriminant = true)]
+pub enum AccountType {
+    Uninitialized = 0,
+    Config,
+    ClawbackRecord,
+    Distribution,
+    SwapAgreement,
+    Stats,
+    IssuerStats,
+    Member,
+    StakePosition,
+    Merchant,
+}
+
+fn pack_with_header<T: BorshSerialize>(value: &T, account_type: AccountType, dst: &mut [u8]) {
+    crate::account_header::pack_with_header(value, CURRENT_VERSION, account_type as u8, dst)
+}
+
+fn unpack_with_header<T: BorshDeserialize>(
+    src: &[u8],
+    account_type: AccountType,
+) -> Result<T, solana_program::program_error::ProgramError> {
+    crate::account_header::unpack_with_header(src, CURRENT_VERSION, account_type as u8)
+}
+
+/// Seed for brand config PDAs (`[b"config", brand_id]`).
+/// One deployment serves many brands, each with its own config, mint and admin.
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Derives the config PDA for a brand.
+pub fn find_config_address(brand_id: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED, &brand_id.to_le_bytes()], program_id)
+}
+
+/// Seed for the program authority PDA (`[b"authority", config]`).
+/// The PDA is the mint authority of the loyalty mint and its permanent delegate for clawbacks.
+pub const AUTHORITY_SEED: &[u8] = b"authority";
+
+/// Derives the program authority PDA for a given config account.
+pub fn find_authority_address(config: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY_SEED, config.as_ref()], program_id)
+}
+
+/// Seed for coalition swap agreement PDAs (`[b"swap", source_config, target_config]`).
+pub const SWAP_SEED: &[u8] = b"swap";
+
+/// Derives the swap agreement PDA for converting points from `source_config` into `target_config`.
+pub fn find_swap_agreement_address(
+    source_config: &Pubkey,
+    target_config: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SWAP_SEED, source_config.as_ref(), target_config.as_ref()],
+        program_id,
+    )
+}
+
+/// Seed for the per-brand statistics PDA (`[b"stats", config]`).
+pub const STATS_SEED: &[u8] = b"stats";
+
+/// Derives the statistics PDA for a brand config.
+pub fn find_stats_address(config: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATS_SEED, config.as_ref()], program_id)
+}
+
+/// Seed for per-issuer statistics PDAs (`[b"issuer_stats", config, issuer]`).
+pub const ISSUER_STATS_SEED: &[u8] = b"issuer_stats";
+
+/// Derives the statistics PDA of an issuer within a brand config.
+pub fn find_issuer_stats_address(config: &Pubkey, issuer: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ISSUER_STATS_SEED, config.as_ref(), issuer.as_ref()], program_id)
+}
+
+/// Seed for member PDAs (`[b"member", config, owner]`).
+pub const MEMBER_SEED: &[u8] = b"member";
+
+/// Derives the member PDA of a wallet within a brand config.
+pub fn find_member_address(config: &Pubkey, owner: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MEMBER_SEED, config.as_ref(), owner.as_ref()], program_id)
+}
+
+/// Seed for stake position PDAs (`[b"stake", config, owner]`).
+pub const STAKE_SEED: &[u8] = b"stake";
+
+/// Derives the stake position PDA of a wallet within a brand config.
+pub fn find_stake_position_address(config: &Pubkey, owner: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_SEED, config.as_ref(), owner.as_ref()], program_id)
+}
+
+/// Seed for the stake vault PDA (`[b"vault", config]`), a token account owned by the authority PDA.
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Derives the stake vault PDA for a brand config.
+pub fn find_vault_address(config: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, config.as_ref()], program_id)
+}
+
+/// Seed for merchant PDAs (`[b"merchant", config, merchant]`).
+pub const MERCHANT_SEED: &[u8] = b"merchant";
+
+/// Derives the merchant PDA of a merchant wallet within a brand config.
+pub fn find_merchant_address(config: &Pubkey, merchant: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MERCHANT_SEED, config.as_ref(), merchant.as_ref()], program_id)
+}
+
+/// Length of the daily bucket used for swap limits and check-in streaks, in seconds.
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Number of voucher nonces below the highest redeemed one that can still be redeemed.
+pub const VOUCHER_NONCE_WINDOW: u64 = 64;
+
+/// Length of the monthly bucket for streak grace days, in days.
+pub const DAYS_PER_GRACE_PERIOD: i64 = 30;
+
+/// Denominator for basis point values (10_000 bps = 100%).
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Number of stake tiers a brand can configure.
+pub const STAKE_TIER_COUNT: usize = 3;
+
+/// A stake tier: members who lock at least `min_amount` points for at least
+/// `min_lock_secs` earn `multiplier_bps` on later awards.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct StakeTier {
+    pub min_amount: u64,
+    pub min_lock_secs: i64,
+    /// Earn multiplier in basis points (15_000 = 1.5x). Zero disables the tier.
+    pub multiplier_bps: u16,
+}
+
+/// Number of streak bonuses a brand can configure.
+pub const STREAK_BONUS_COUNT: usize = 4;
+
+/// A bonus paid out once when a member's check-in streak reaches `streak_days`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct StreakBonus {
+    /// Streak length that triggers the bonus. Zero disables the entry.
+    pub streak_days: u32,
+    pub bonus: u64,
+}
+
+/// Sum of the bonuses configured for exactly `streak` consecutive days.
+pub fn streak_bonus(bonuses: &[StreakBonus; STREAK_BONUS_COUNT], streak: u32) -> Option<u64> {
+    bonuses
+        .iter()
+        .filter(|b| b.streak_days > 0 && b.streak_days == streak)
+        .try_fold(0u64, |total, b| total.checked_add(b.bonus))
+}
+
+/// Length of the baseline config layout (no header): bool (1) + Pubkey (32) + Pubkey (32).
+/// `MigrateConfig` upgrades such accounts in place.
+pub const CONFIG_V0_LEN: usize = 1 + 32 + 32;
 
 // Configuration state account for the loyalty program.
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
@@ -10,14 +175,36 @@ pub struct ConfigAccount {
     /// Tracks if the account is initialized.
     pub is_initialized: bool,
     /// The public key authorized to award points and change the admin.
+    /// Can be a stablecoin multisig signer PDA, which signs admin instructions through CPI
+    /// when a multisig proposal executes.
     /// ETH Dev Analogy: The 'owner' or 'admin' role address.
     pub admin: Pubkey,
     /// The public key of the SPL Token Mint account representing loyalty points.
     /// This program MUST be the mint_authority for this mint.
     pub loyalty_mint: Pubkey,
+    /// The brand this config belongs to (seed of the config PDA).
+    pub brand_id: u64,
+    /// Bump seed of the config PDA.
+    pub bump: u8,
+    /// Share of the staked amount burned when a member unstakes before the lock ends.
+    pub early_unlock_penalty_bps: u16,
+    /// Stake tiers, ordered from lowest to highest.
+    pub stake_tiers: [StakeTier; STAKE_TIER_COUNT],
+    /// Check-in streak bonuses, typically escalating (e.g. 7 and 30 days).
+    pub streak_bonuses: [StreakBonus; STREAK_BONUS_COUNT],
+    /// Address of a config created before brands existed, as a plain keypair account rather
+    /// than a PDA, recorded by `MigrateConfig`. `Pubkey::default()` for brand configs.
+    pub legacy_address: Pubkey,
     // Add other config if needed, e.g., redemption treasury account
 }
 
+impl ConfigAccount {
+    /// Decodes a config in the baseline layout, with every field it predates set to zero.
+    pub fn unpack_v0(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        upgrade_body(src, Self::LEN - HEADER_LEN)
+    }
+}
+
 impl Sealed for ConfigAccount {}
 impl IsInitialized for ConfigAccount {
     fn is_initialized(&self) -> bool {
@@ -25,17 +212,534 @@ impl IsInitialized for ConfigAccount {
     }
 }
 impl Pack for ConfigAccount {
-    // LEN: bool (1) + Pubkey (32) + Pubkey (32)
-    const LEN: usize = 1 + 32 + 32;
+    // LEN: header (2) + bool (1) + Pubkey (32) + Pubkey (32) + u64 (8) + u8 (1) + u16 (2)
+    //      + STAKE_TIER_COUNT * StakeTier (8 + 8 + 2) + STREAK_BONUS_COUNT * StreakBonus (4 + 8)
+    //      + Pubkey (32)
+    const LEN: usize = HEADER_LEN + 1 + 32 + 32 + 8 + 1 + 2 + STAKE_TIER_COUNT * (8 + 8 + 2)
+        + STREAK_BONUS_COUNT * (4 + 8) + 32;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::Config, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::Config)
+    }
+}
+
+/// Audit record of a single admin clawback.
+/// One record account is written per clawback; a member's history is the set of
+/// records whose `member` field matches their wallet (queryable via `getProgramAccounts`).
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct ClawbackRecord {
+    pub is_initialized: bool,
+    /// The config this clawback was executed under.
+    pub config: Pubkey,
+    /// Wallet that owned the token account the points were burned from.
+    pub member: Pubkey,
+    /// The token account the points were burned from.
+    pub token_account: Pubkey,
+    /// Admin who executed the clawback.
+    pub admin: Pubkey,
+    /// Amount of points burned.
+    pub amount: u64,
+    /// Compliance reason code supplied by the admin.
+    pub reason_code: u16,
+    /// Unix timestamp of the clawback.
+    pub timestamp: i64,
+}
+
+impl Sealed for ClawbackRecord {}
+impl IsInitialized for ClawbackRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for ClawbackRecord {
+    // LEN: header (2) + bool (1) + 4 * Pubkey (32) + u64 (8) + u16 (2) + i64 (8)
+    const LEN: usize = HEADER_LEN + 1 + 32 * 4 + 8 + 2 + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::ClawbackRecord, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::ClawbackRecord)
+    }
+}
+
+/// A merkle distribution of claimable points.
+/// The account data is this header followed by a bitmap with one bit per leaf,
+/// set once that leaf has been claimed.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct Distribution {
+    pub is_initialized: bool,
+    /// The config this distribution mints under.
+    pub config: Pubkey,
+    /// Root of the merkle tree of `(index, claimant, amount)` leaves.
+    pub merkle_root: [u8; 32],
+    /// Maximum amount that can be claimed in total.
+    pub total_amount: u64,
+    /// Amount claimed so far.
+    pub claimed_amount: u64,
+    /// Number of leaves in the tree.
+    pub num_leaves: u64,
+    /// Unix timestamp after which claims are rejected.
+    pub expiry: i64,
+}
+
+impl Distribution {
+    /// Number of bytes needed for the claimed bitmap.
+    pub fn bitmap_len(num_leaves: u64) -> usize {
+        num_leaves.div_ceil(8) as usize
+    }
+
+    /// Total account size for a distribution with `num_leaves` leaves.
+    pub fn account_len(num_leaves: u64) -> usize {
+        Self::LEN + Self::bitmap_len(num_leaves)
+    }
+
+    /// Returns whether `index` is marked as claimed in `bitmap`.
+    pub fn is_claimed(bitmap: &[u8], index: u64) -> bool {
+        bitmap[(index / 8) as usize] & (1 << (index % 8)) != 0
+    }
+
+    /// Marks `index` as claimed in `bitmap`.
+    pub fn set_claimed(bitmap: &mut [u8], index: u64) {
+        bitmap[(index / 8) as usize] |= 1 << (index % 8);
+    }
+}
+
+impl Sealed for Distribution {}
+impl IsInitialized for Distribution {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for Distribution {
+    // LEN: header (2) + bool (1) + Pubkey (32) + root (32) + u64 (8) * 3 + i64 (8)
+    const LEN: usize = HEADER_LEN + 1 + 32 + 32 + 8 * 3 + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::Distribution, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::Distribution)
+    }
+}
+
+/// A negotiated agreement to convert points of one brand into points of another.
+/// Swaps only run while both brands' admins have approved the current terms.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct SwapAgreement {
+    pub is_initialized: bool,
+    /// Config of the brand whose points are burned.
+    pub source_config: Pubkey,
+    /// Config of the brand whose points are minted.
+    pub target_config: Pubkey,
+    /// Target points minted per source point, as `rate_numerator / rate_denominator`.
+    pub rate_numerator: u64,
+    pub rate_denominator: u64,
+    /// Maximum source points that can be swapped per day.
+    pub daily_limit: u64,
+    /// Day bucket (`unix_timestamp / SECONDS_PER_DAY`) that `swapped_today` refers to.
+    pub current_day: i64,
+    /// Source points swapped during `current_day`.
+    pub swapped_today: u64,
+    /// Approval from the source brand's admin.
+    pub source_approved: bool,
+    /// Approval from the target brand's admin.
+    pub target_approved: bool,
+    /// Bump seed of the agreement PDA.
+    pub bump: u8,
+}
+
+impl Sealed for SwapAgreement {}
+impl IsInitialized for SwapAgreement {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for SwapAgreement {
+    // LEN: header (2) + bool (1) + 2 * Pubkey (32) + 3 * u64 (8) + i64 (8) + u64 (8) + 2 * bool (1) + u8 (1)
+    const LEN: usize = HEADER_LEN + 1 + 32 * 2 + 8 * 3 + 8 + 8 + 2 + 1;
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let mut writer = std::io::Cursor::new(dst);
-        self.serialize(&mut writer).unwrap();
+        pack_with_header(self, AccountType::SwapAgreement, dst)
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
-        let mut reader = std::io::Cursor::new(src);
-        ConfigAccount::deserialize(&mut reader)
-            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)
+        unpack_with_header(src, AccountType::SwapAgreement)
     }
-}
\ No newline at end of file
+}
+
+/// On-chain totals for one brand, updated by every instruction that mints or burns points.
+///
+/// Holders can also burn points directly through the token program, which this account
+/// never sees, so for reconciliation the loyalty mint's supply is at most
+/// `total_awarded - total_redeemed - total_clawed_back - total_penalties`; the shortfall is
+/// what holders burned themselves. Points never expire, so there is no expiry total.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct StatsAccount {
+    pub is_initialized: bool,
+    /// The config these statistics belong to.
+    pub config: Pubkey,
+    /// Points minted by any path (awards, distribution claims, incoming swaps),
+    /// plus any supply that existed when the brand was initialized.
+    pub total_awarded: u64,
+    /// Points burned by members (redemptions, outgoing swaps).
+    pub total_redeemed: u64,
+    /// Points burned by admin clawbacks.
+    pub total_clawed_back: u64,
+    /// Points burned as early-unlock penalties on stakes.
+    pub total_penalties: u64,
+    /// Approximate count of non-empty token accounts: incremented when a program mint lands
+    /// in an empty account, decremented when a program burn empties one. Transfers between
+    /// holders are not observed, so this can drift from the true number of holders.
+    pub active_members: u64,
+    /// Bump seed of the stats PDA.
+    pub bump: u8,
+}
+
+impl StatsAccount {
+    /// Records `amount` points minted into a token account that held `balance_before`.
+    pub fn record_mint(&mut self, amount: u64, balance_before: u64) -> Result<(), LoyaltyError> {
+        self.total_awarded = self
+            .total_awarded
+            .checked_add(amount)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        if balance_before == 0 && amount > 0 {
+            self.active_members = self
+                .active_members
+                .checked_add(1)
+                .ok_or(LoyaltyError::NumericalOverflow)?;
+        }
+        Ok(())
+    }
+
+    /// Records `amount` points redeemed by a member from a token account that held `balance_before`.
+    pub fn record_redeem(&mut self, amount: u64, balance_before: u64) -> Result<(), LoyaltyError> {
+        self.total_redeemed = self
+            .total_redeemed
+            .checked_add(amount)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        self.record_burn(amount, balance_before)
+    }
+
+    /// Records `amount` points clawed back from a token account that held `balance_before`.
+    pub fn record_clawback(&mut self, amount: u64, balance_before: u64) -> Result<(), LoyaltyError> {
+        self.total_clawed_back = self
+            .total_clawed_back
+            .checked_add(amount)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        self.record_burn(amount, balance_before)
+    }
+
+    /// Records `amount` points burned from the stake vault as an early-unlock penalty.
+    pub fn record_penalty(&mut self, amount: u64) -> Result<(), LoyaltyError> {
+        self.total_penalties = self
+            .total_penalties
+            .checked_add(amount)
+            .ok_or(LoyaltyError::NumericalOverflow)?;
+        Ok(())
+    }
+
+    /// Updates the active member count after a burn emptied a token account.
+    /// Saturates at zero: the account may have been funded by a transfer the counter never saw.
+    fn record_burn(&mut self, amount: u64, balance_before: u64) -> Result<(), LoyaltyError> {
+        if balance_before > 0 && balance_before == amount {
+            self.active_members = self.active_members.saturating_sub(1);
+        }
+        Ok(())
+    }
+}
+
+impl Sealed for StatsAccount {}
+impl IsInitialized for StatsAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for StatsAccount {
+    // LEN: header (2) + bool (1) + Pubkey (32) + 5 * u64 (8) + u8 (1)
+    const LEN: usize = HEADER_LEN + 1 + 32 + 8 * 5 + 1;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::Stats, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::Stats)
+    }
+}
+
+/// Award totals for one issuer (the signer of `AwardPoints`) within a brand.
+/// Created on the issuer's first award.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct IssuerStatsAccount {
+    pub is_initialized: bool,
+    /// The config these statistics belong to.
+    pub config: Pubkey,
+    /// The issuer these statistics belong to.
+    pub issuer: Pubkey,
+    /// Number of awards issued.
+    pub award_count: u64,
+    /// Points awarded in total.
+    pub total_awarded: u64,
+    /// Bump seed of the issuer stats PDA.
+    pub bump: u8,
+}
+
+impl Sealed for IssuerStatsAccount {}
+impl IsInitialized for IssuerStatsAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for IssuerStatsAccount {
+    // LEN: header (2) + bool (1) + 2 * Pubkey (32) + 2 * u64 (8) + u8 (1)
+    const LEN: usize = HEADER_LEN + 1 + 32 * 2 + 8 * 2 + 1;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::IssuerStats, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::IssuerStats)
+    }
+}
+
+/// Per-member state within a brand, created when the member signs up.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct MemberAccount {
+    pub is_initialized: bool,
+    /// The config this member belongs to.
+    pub config: Pubkey,
+    /// The member's wallet.
+    pub owner: Pubkey,
+    /// Token account the program authority PDA was approved as delegate on at signup.
+    pub token_account: Pubkey,
+    /// Highest voucher nonce redeemed so far.
+    pub last_voucher_nonce: u64,
+    /// Bump seed of the member PDA.
+    pub bump: u8,
+    /// Redeemed-nonce bitmap: bit `i` is set once nonce `last_voucher_nonce - i` was redeemed.
+    pub voucher_nonce_window: u64,
+    /// Day bucket (`unix_timestamp / SECONDS_PER_DAY`) of the last check-in.
+    pub last_check_in_day: i64,
+    /// Check-ins in the current streak. A forgiven grace day keeps the streak alive but is not counted.
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    /// Grace period (`day / DAYS_PER_GRACE_PERIOD`) in which the grace day was last used.
+    pub last_grace_period: i64,
+}
+
+impl MemberAccount {
+    /// Marks voucher `nonce` as redeemed. Vouchers may be redeemed in any order as long as
+    /// the nonce is within `VOUCHER_NONCE_WINDOW` of the highest nonce redeemed so far.
+    pub fn use_voucher_nonce(&mut self, nonce: u64) -> Result<(), LoyaltyError> {
+        if nonce == 0 {
+            return Err(LoyaltyError::VoucherNonceUsed);
+        }
+        if nonce > self.last_voucher_nonce {
+            let shift = nonce - self.last_voucher_nonce;
+            self.voucher_nonce_window = if shift >= VOUCHER_NONCE_WINDOW {
+                0
+            } else {
+                self.voucher_nonce_window << shift
+            };
+            self.voucher_nonce_window |= 1;
+            self.last_voucher_nonce = nonce;
+            return Ok(());
+        }
+        let offset = self.last_voucher_nonce - nonce;
+        if offset >= VOUCHER_NONCE_WINDOW || self.voucher_nonce_window & (1 << offset) != 0 {
+            return Err(LoyaltyError::VoucherNonceUsed);
+        }
+        self.voucher_nonce_window |= 1 << offset;
+        Ok(())
+    }
+
+    /// Records a check-in on `today` and returns the new streak length.
+    /// Checking in the day after the last check-in extends the streak. Missing exactly
+    /// one day is forgiven once per grace period; any longer gap restarts the streak.
+    pub fn check_in(&mut self, today: i64) -> Result<u32, LoyaltyError> {
+        if self.current_streak > 0 && today <= self.last_check_in_day {
+            return Err(LoyaltyError::AlreadyCheckedIn);
+        }
+        let gap = today - self.last_check_in_day;
+        let period = today / DAYS_PER_GRACE_PERIOD;
+        self.current_streak = if self.current_streak > 0 && gap == 1 {
+            self.current_streak.checked_add(1).ok_or(LoyaltyError::NumericalOverflow)?
+        } else if self.current_streak > 0 && gap == 2 && self.last_grace_period != period {
+            self.last_grace_period = period;
+            self.current_streak.checked_add(1).ok_or(LoyaltyError::NumericalOverflow)?
+        } else {
+            1
+        };
+        self.longest_streak = self.longest_streak.max(self.current_streak);
+        self.last_check_in_day = today;
+        Ok(self.current_streak)
+    }
+}
+
+impl Sealed for MemberAccount {}
+impl IsInitialized for MemberAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for MemberAccount {
+    // LEN: header (2) + bool (1) + 3 * Pubkey (32) + u64 (8) + u8 (1) + u64 (8) + i64 (8) + 2 * u32 (4) + i64 (8)
+    const LEN: usize = HEADER_LEN + 1 + 32 * 3 + 8 + 1 + 8 + 8 + 4 * 2 + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::Member, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::Member)
+    }
+}
+
+/// A member's locked points. One position per member and brand; staking again
+/// adds to the position and can only lengthen the lock.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct StakePosition {
+    pub is_initialized: bool,
+    /// The config this position belongs to.
+    pub config: Pubkey,
+    /// The member's wallet.
+    pub owner: Pubkey,
+    /// Points held in the stake vault for this member.
+    pub amount: u64,
+    /// Unix timestamp when the current lock started.
+    pub lock_start: i64,
+    /// Unix timestamp when the points can be unstaked without penalty.
+    pub unlock_at: i64,
+    /// Bump seed of the stake position PDA.
+    pub bump: u8,
+}
+
+impl StakePosition {
+    /// Returns the member's effective tier (1-based, 0 for none) and earn multiplier
+    /// in basis points. Tiers only apply while the points are still locked.
+    pub fn effective_tier(&self, tiers: &[StakeTier; STAKE_TIER_COUNT], now: i64) -> (u8, u64) {
+        let mut effective = (0, BPS_DENOMINATOR);
+        if self.amount == 0 || now >= self.unlock_at {
+            return effective;
+        }
+        let lock_secs = self.unlock_at.saturating_sub(self.lock_start);
+        for (i, tier) in tiers.iter().enumerate() {
+            if tier.multiplier_bps > 0 && self.amount >= tier.min_amount && lock_secs >= tier.min_lock_secs {
+                effective = (i as u8 + 1, tier.multiplier_bps as u64);
+            }
+        }
+        effective
+    }
+}
+
+impl Sealed for StakePosition {}
+impl IsInitialized for StakePosition {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for StakePosition {
+    // LEN: header (2) + bool (1) + 2 * Pubkey (32) + u64 (8) + 2 * i64 (8) + u8 (1)
+    const LEN: usize = HEADER_LEN + 1 + 32 * 2 + 8 + 8 * 2 + 1;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::StakePosition, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::StakePosition)
+    }
+}
+
+/// A store registered to accept point redemptions for a brand, with its settlement ledger.
+/// The brand pays out `pending_settlement` periodically and records it with `Settle`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct MerchantAccount {
+    pub is_initialized: bool,
+    /// The config this merchant belongs to.
+    pub config: Pubkey,
+    /// The merchant wallet that co-signs redemptions.
+    pub merchant: Pubkey,
+    /// Points redeemed at this merchant since the last settlement.
+    pub pending_settlement: u64,
+    /// Points redeemed at this merchant in total.
+    pub total_redeemed: u64,
+    /// Points settled (paid out by the brand) in total.
+    pub total_settled: u64,
+    /// Number of redemptions at this merchant.
+    pub redemption_count: u64,
+    /// Amount covered by the last settlement.
+    pub last_settled_amount: u64,
+    /// Unix timestamp of the last settlement.
+    pub last_settled_at: i64,
+    /// Off-chain payment reference (e.g. hash of the bank transfer ID) of the last settlement.
+    pub last_payment_reference: [u8; 32],
+    /// Bump seed of the merchant PDA.
+    pub bump: u8,
+}
+
+impl Sealed for MerchantAccount {}
+impl IsInitialized for MerchantAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for MerchantAccount {
+    // LEN: header (2) + bool (1) + 2 * Pubkey (32) + 5 * u64 (8) + i64 (8) + [u8; 32] + u8 (1)
+    const LEN: usize = HEADER_LEN + 1 + 32 * 2 + 8 * 5 + 8 + 32 + 1;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::Merchant, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::Merchant)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_baseline_config() {
+        let admin = Pubkey::new_unique();
+        let loyalty_mint = Pubkey::new_unique();
+        // Baseline layout as written before headers: is_initialized, admin, loyalty_mint
+        let mut v0 = vec![1u8];
+        v0.extend_from_slice(admin.as_ref());
+        v0.extend_from_slice(loyalty_mint.as_ref());
+        assert_eq!(v0.len(), CONFIG_V0_LEN);
+
+        let config = ConfigAccount::unpack_v0(&v0).unwrap();
+        let mut migrated = vec![0u8; ConfigAccount::LEN];
+        ConfigAccount::pack(config, &mut migrated).unwrap();
+        assert_eq!(&migrated[..HEADER_LEN], &[CURRENT_VERSION, AccountType::Config as u8]);
+
+        let config = ConfigAccount::unpack(&migrated).unwrap();
+        assert_eq!(config.admin, admin);
+        assert_eq!(config.loyalty_mint, loyalty_mint);
+        assert_eq!(config.brand_id, 0);
+        assert_eq!(config.early_unlock_penalty_bps, 0);
+        assert_eq!(config.stake_tiers, [StakeTier::default(); STAKE_TIER_COUNT]);
+        assert_eq!(config.streak_bonuses, [StreakBonus::default(); STREAK_BONUS_COUNT]);
+        assert_eq!(config.legacy_address, Pubkey::default());
+    }
+
+    #[test]
+    fn rejects_headerless_buffer_as_current_config() {
+        let mut v0 = vec![1u8];
+        v0.extend_from_slice(Pubkey::new_unique().as_ref());
+        v0.extend_from_slice(Pubkey::new_unique().as_ref());
+        v0.resize(ConfigAccount::LEN, 0);
+        assert!(ConfigAccount::unpack(&v0).is_err());
+    }
+}
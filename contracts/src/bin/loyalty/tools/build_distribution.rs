@@ -0,0 +1,91 @@
+// Offline tool that builds a loyalty merkle distribution from a CSV file.
+//
+// Usage: build-distribution <input.csv> <proofs.csv>
+//
+// Input: one `claimant,amount` row per customer (a `claimant,amount` header row is skipped).
+// The row order defines the leaf index, so keep the input file alongside the proofs.
+//
+// Output: prints the merkle root, total amount and leaf count to pass to `CreateDistribution`,
+// and writes `index,claimant,amount,proof` rows where `proof` is the hex sibling hashes
+// joined by `:`. Each row has everything a customer needs to send `ClaimPoints`.
+
+use std::{env, fs, process, str::FromStr};
+
+use loyalty::merkle;
+use solana_program::pubkey::Pubkey;
+
+fn to_hex(bytes: &[u8; 32]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn parse_row(line_number: usize, line: &str) -> Result<(Pubkey, u64), String> {
+    let mut fields = line.split(',').map(str::trim);
+    let (claimant, amount) = match (fields.next(), fields.next(), fields.next()) {
+        (Some(claimant), Some(amount), None) => (claimant, amount),
+        _ => return Err(format!("line {}: expected `claimant,amount`", line_number)),
+    };
+    let claimant = Pubkey::from_str(claimant)
+        .map_err(|e| format!("line {}: invalid claimant {}: {}", line_number, claimant, e))?;
+    let amount = amount
+        .parse::<u64>()
+        .map_err(|e| format!("line {}: invalid amount {}: {}", line_number, amount, e))?;
+    Ok((claimant, amount))
+}
+
+fn run(input_path: &str, output_path: &str) -> Result<(), String> {
+    let input = fs::read_to_string(input_path)
+        .map_err(|e| format!("failed to read {}: {}", input_path, e))?;
+
+    let mut rows = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (i == 0 && line.eq_ignore_ascii_case("claimant,amount")) {
+            continue;
+        }
+        rows.push(parse_row(i + 1, line)?);
+    }
+    if rows.is_empty() {
+        return Err("input contains no rows".to_string());
+    }
+
+    let total_amount = rows
+        .iter()
+        .try_fold(0u64, |total, (_, amount)| total.checked_add(*amount))
+        .ok_or("total amount overflows u64")?;
+
+    let leaves = rows
+        .iter()
+        .enumerate()
+        .map(|(index, (claimant, amount))| merkle::leaf_hash(index as u64, claimant, *amount))
+        .collect();
+    let levels = merkle::build_levels(leaves);
+    let root = levels.last().unwrap()[0];
+
+    let mut output = String::from("index,claimant,amount,proof\n");
+    for (index, (claimant, amount)) in rows.iter().enumerate() {
+        let proof = merkle::proof(&levels, index);
+        debug_assert!(merkle::verify(&proof, &root, levels[0][index]));
+        let proof_hex: Vec<String> = proof.iter().map(to_hex).collect();
+        output.push_str(&format!("{},{},{},{}\n", index, claimant, amount, proof_hex.join(":")));
+    }
+    fs::write(output_path, output)
+        .map_err(|e| format!("failed to write {}: {}", output_path, e))?;
+
+    println!("merkle_root: {}", to_hex(&root));
+    println!("total_amount: {}", total_amount);
+    println!("num_leaves: {}", rows.len());
+    println!("proofs written to {}", output_path);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("Usage: {} <input.csv> <proofs.csv>", args[0]);
+        process::exit(1);
+    }
+    if let Err(e) = run(&args[1], &args[2]) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,76 @@
+use solana_program::{
+    ed25519_program, instruction::Instruction, program_error::ProgramError, pubkey::Pubkey,
+};
+
+// Offline redemption vouchers.
+//
+// A member signs `voucher_message(...)` with their wallet key while offline. The cashier
+// submits a transaction whose instruction right before `RedeemWithVoucher` is an Ed25519
+// program instruction verifying that signature. The runtime aborts the whole transaction
+// if the signature is invalid, so the loyalty program only has to check (via the
+// instructions sysvar) that the verified public key and message are the ones it expects.
+
+/// Domain separator so a voucher signature can't be replayed as any other signed message.
+const VOUCHER_DOMAIN: &[u8] = b"loyalty-voucher";
+
+/// Offset of the first `Ed25519SignatureOffsets` entry (after `num_signatures: u8` and one byte of padding).
+const SIGNATURE_OFFSETS_START: usize = 2;
+const PUBKEY_LEN: usize = 32;
+
+/// Builds the message a member signs to authorize a voucher redemption.
+pub fn voucher_message(config: &Pubkey, amount: u64, nonce: u64, expiry: i64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(VOUCHER_DOMAIN.len() + 32 + 8 * 3);
+    message.extend_from_slice(VOUCHER_DOMAIN);
+    message.extend_from_slice(config.as_ref());
+    message.extend_from_slice(&amount.to_le_bytes());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, ProgramError> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+/// Extracts the `(public key, message)` verified by an Ed25519 program instruction.
+/// Only single-signature instructions that carry their own data are accepted.
+pub fn verified_signature(instruction: &Instruction) -> Result<(Pubkey, Vec<u8>), ProgramError> {
+    if instruction.program_id != ed25519_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let data = &instruction.data;
+    if data.first() != Some(&1) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let offsets = SIGNATURE_OFFSETS_START;
+    let signature_instruction_index = read_u16(data, offsets + 2)?;
+    let public_key_offset = read_u16(data, offsets + 4)? as usize;
+    let public_key_instruction_index = read_u16(data, offsets + 6)?;
+    let message_data_offset = read_u16(data, offsets + 8)? as usize;
+    let message_data_size = read_u16(data, offsets + 10)? as usize;
+    let message_instruction_index = read_u16(data, offsets + 12)?;
+
+    // `u16::MAX` means "this instruction"; anything else would let the signature,
+    // key or message come from a different instruction than the one we inspect.
+    if signature_instruction_index != u16::MAX
+        || public_key_instruction_index != u16::MAX
+        || message_instruction_index != u16::MAX
+    {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let public_key = data
+        .get(public_key_offset..public_key_offset + PUBKEY_LEN)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let message = data
+        .get(message_data_offset..message_data_offset + message_data_size)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    Ok((
+        Pubkey::try_from(public_key).map_err(|_| ProgramError::InvalidInstructionData)?,
+        message.to_vec(),
+    ))
+}
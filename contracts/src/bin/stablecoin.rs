@@ -48,11 +48,23 @@ The following code defines the state, instructions, and processing logic for our
 // spl-token = { version = "4.0.1", features = ["no-entrypoint"] } # SPL Token library
 // borsh = "1.4.0" # For serialization/deserialization
 // thiserror = "1.0.58"
+//
+// [features]
+// no-entrypoint = [] # Builds the library without the program entrypoint (tests, other programs)
+
+// === Offline tools ===
+// The price-publisher bin and its extra dependencies are annotated in stablecoin/Cargo.toml.
 
 // === src/lib.rs ===
+#[path = "../../common/account_header.rs"]
+pub mod account_header;
+#[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
 pub mod error;
+pub mod events;
 pub mod instruction;
+pub mod math;
+pub mod oracle;
 pub mod processor;
 pub mod state;
 
@@ -88,6 +100,238 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
+/// Layout version written into the header of every account this program creates.
+/// Bump it whenever a layout changes. New `ConfigAccount` fields are only ever appended,
+/// so `MigrateConfig` upgrades any older config by zero-extending it.
+/// v0: no header. v1: header. v2: collateral parameters. v3: liquidation parameters.
+/// v4: oracle limits. v5: master minter. v6: blacklister. v7: pause and supply cap.
+/// v8: reserve attestations. v9: peg stability module debt. v10: redemption queue.
+/// v11: timelock. v12: stability fee. v13: savings vault. v14: flash mints.
+/// v15: global settlement.
+pub const CURRENT_VERSION: u8 = 15;
+
+pub use crate::account_header::HEADER_LEN;
+
+/// Discriminator stored in the second header byte, so one account type can never be
+/// unpacked as another.
+/// ETH Dev Analogy: Solidity storage has no equivalent; on Solana any account can be
+/// passed to any instruction, so the program must check what kind of data it holds.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountType {
+    Uninitialized = 0,
+    Config,
+    Position,
+    Price,
+    BadDebt,
+    Publisher,
+    Minter,
+    BlacklistEntry,
+    ReserveAttestation,
+    Psm,
+    RedemptionRequest,
+    TimelockAction,
+    Multisig,
+    MultisigProposal,
+}
+
+fn pack_with_header<T: BorshSerialize>(value: &T, account_type: AccountType, dst: &mut [u8]) {
+    crate::account_header::pack_with_header(value, CURRENT_VERSION, account_type as u8, dst)
+}
+
+fn unpack_with_header<T: BorshDeserialize>(
+    src: &[u8],
+    account_type: AccountType,
+) -> Result<T, solana_program::program_error::ProgramError> {
+    crate::account_header::unpack_with_header(src, CURRENT_VERSION, account_type as u8)
+}
+
+/// Length of the unversioned config layout (no header): bool (1) + Pubkey (32) + Pubkey (32).
+/// `MigrateConfig` upgrades such accounts in place.
+pub const CONFIG_V0_LEN: usize = 1 + 32 + 32;
+
+/// Seed for the program authority PDA (`[b"authority", config]`).
+/// The PDA is the stablecoin's mint authority and the owner of the collateral vault.
+/// ETH Dev Analogy: A PDA is like the contract's own address; only this program can sign for it.
+pub const AUTHORITY_SEED: &[u8] = b"authority";
+
+/// Derives the program authority PDA for a given config account.
+pub fn find_authority_address(config: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY_SEED, config.as_ref()], program_id)
+}
+
+/// Seed for the collateral vault PDA (`[b"vault", config]`), a token account owned by the authority PDA.
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Derives the collateral vault PDA for a config.
+pub fn find_vault_address(config: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, config.as_ref()], program_id)
+}
+
+/// Seed for collateral position PDAs (`[b"position", config, owner]`).
+pub const POSITION_SEED: &[u8] = b"position";
+
+/// Derives the position PDA of a wallet.
+pub fn find_position_address(config: &Pubkey, owner: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POSITION_SEED, config.as_ref(), owner.as_ref()], program_id)
+}
+
+/// Seed for the collateral price PDA (`[b"price", config]`).
+pub const PRICE_SEED: &[u8] = b"price";
+
+/// Derives the collateral price PDA for a config.
+pub fn find_price_address(config: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PRICE_SEED, config.as_ref()], program_id)
+}
+
+/// Seed for whitelisted price publisher PDAs (`[b"publisher", config, publisher]`).
+pub const PUBLISHER_SEED: &[u8] = b"publisher";
+
+/// Derives the whitelist PDA of a price publisher.
+pub fn find_publisher_address(config: &Pubkey, publisher: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PUBLISHER_SEED, config.as_ref(), publisher.as_ref()], program_id)
+}
+
+/// Seed for minter PDAs (`[b"minter", config, minter]`).
+pub const MINTER_SEED: &[u8] = b"minter";
+
+/// Derives the allowance PDA of a minter.
+pub fn find_minter_address(config: &Pubkey, minter: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINTER_SEED, config.as_ref(), minter.as_ref()], program_id)
+}
+
+/// Seed for blacklist entry PDAs (`[b"blacklist", config, holder]`).
+pub const BLACKLIST_SEED: &[u8] = b"blacklist";
+
+/// Derives the blacklist entry PDA of a holder wallet.
+pub fn find_blacklist_address(config: &Pubkey, holder: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BLACKLIST_SEED, config.as_ref(), holder.as_ref()], program_id)
+}
+
+/// Seed for reserve attestation PDAs (`[b"attestation", config, sequence as u64 LE]`).
+pub const ATTESTATION_SEED: &[u8] = b"attestation";
+
+/// Derives the PDA of the `sequence`-th reserve attestation of a config.
+pub fn find_attestation_address(config: &Pubkey, sequence: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ATTESTATION_SEED, config.as_ref(), &sequence.to_le_bytes()], program_id)
+}
+
+/// Seed for peg stability module PDAs (`[b"psm", config, reserve_mint]`).
+pub const PSM_SEED: &[u8] = b"psm";
+
+/// Derives the peg stability module PDA of a reserve token.
+pub fn find_psm_address(config: &Pubkey, reserve_mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PSM_SEED, config.as_ref(), reserve_mint.as_ref()], program_id)
+}
+
+/// Seed for peg stability module reserve vaults (`[b"psm_vault", config, reserve_mint]`),
+/// token accounts owned by the authority PDA.
+pub const PSM_VAULT_SEED: &[u8] = b"psm_vault";
+
+/// Derives the reserve vault PDA of a peg stability module.
+pub fn find_psm_vault_address(config: &Pubkey, reserve_mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PSM_VAULT_SEED, config.as_ref(), reserve_mint.as_ref()], program_id)
+}
+
+/// Seed for redemption request PDAs (`[b"redemption", config, id as u64 LE]`).
+pub const REDEMPTION_SEED: &[u8] = b"redemption";
+
+/// Derives the PDA of the redemption request with the given id.
+pub fn find_redemption_address(config: &Pubkey, id: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REDEMPTION_SEED, config.as_ref(), &id.to_le_bytes()], program_id)
+}
+
+/// Seed for the redemption escrow PDA (`[b"redemption_escrow", config]`), a stablecoin
+/// token account owned by the authority PDA that holds all pending redemptions.
+pub const REDEMPTION_ESCROW_SEED: &[u8] = b"redemption_escrow";
+
+/// Derives the redemption escrow PDA for a config.
+pub fn find_redemption_escrow_address(config: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REDEMPTION_ESCROW_SEED, config.as_ref()], program_id)
+}
+
+/// Seed for timelocked action PDAs (`[b"action", config, id as u64 LE]`).
+pub const ACTION_SEED: &[u8] = b"action";
+
+/// Derives the PDA of the timelocked action with the given id.
+pub fn find_action_address(config: &Pubkey, id: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ACTION_SEED, config.as_ref(), &id.to_le_bytes()], program_id)
+}
+
+/// Largest serialized instruction a timelocked action can hold.
+pub const MAX_ACTION_DATA_LEN: usize = 128;
+
+/// Longest timelock delay the admin can configure (30 days), so a mistake can't lock
+/// the admin out for good.
+pub const MAX_TIMELOCK_DELAY_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// Seed for the surplus PDA (`[b"surplus", config]`): a stablecoin token account owned by
+/// the authority PDA that receives stability fees.
+pub const SURPLUS_SEED: &[u8] = b"surplus";
+
+/// Derives the surplus PDA for a config.
+pub fn find_surplus_address(config: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SURPLUS_SEED, config.as_ref()], program_id)
+}
+
+/// Seed for the savings vault PDA (`[b"savings_vault", config]`): a stablecoin token
+/// account owned by the authority PDA that holds savings deposits and their interest.
+pub const SAVINGS_VAULT_SEED: &[u8] = b"savings_vault";
+
+/// Derives the savings vault PDA for a config.
+pub fn find_savings_vault_address(config: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SAVINGS_VAULT_SEED, config.as_ref()], program_id)
+}
+
+/// Seed for the savings share mint PDA (`[b"savings_shares", config]`), with the
+/// authority PDA as mint authority.
+pub const SAVINGS_SHARES_SEED: &[u8] = b"savings_shares";
+
+/// Derives the savings share mint PDA for a config.
+pub fn find_savings_shares_address(config: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SAVINGS_SHARES_SEED, config.as_ref()], program_id)
+}
+
+/// Seed for the admin multisig PDA (`[b"multisig", config]`).
+pub const MULTISIG_SEED: &[u8] = b"multisig";
+
+/// Derives the PDA of the admin multisig for a config.
+pub fn find_multisig_address(config: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MULTISIG_SEED, config.as_ref()], program_id)
+}
+
+/// Seed for the multisig signer PDA (`[b"multisig_signer", multisig]`). It holds no data,
+/// so it can hold SOL and pay rent; set it as the admin to hand control to the multisig.
+pub const MULTISIG_SIGNER_SEED: &[u8] = b"multisig_signer";
+
+/// Derives the signer PDA that multisig proposals execute as.
+pub fn find_multisig_signer_address(multisig: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MULTISIG_SIGNER_SEED, multisig.as_ref()], program_id)
+}
+
+/// Seed for multisig proposal PDAs (`[b"proposal", multisig, id as u64 LE]`).
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+
+/// Derives the PDA of the multisig proposal with the given id.
+pub fn find_proposal_address(multisig: &Pubkey, id: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROPOSAL_SEED, multisig.as_ref(), &id.to_le_bytes()], program_id)
+}
+
+/// Most signer keys a multisig can have.
+pub const MAX_MULTISIG_SIGNERS: usize = 11;
+/// Most accounts a multisig proposal's instruction can take.
+pub const MAX_PROPOSAL_ACCOUNTS: usize = 16;
+/// Largest instruction data a multisig proposal can hold.
+pub const MAX_PROPOSAL_DATA_LEN: usize = 256;
+
+/// Seed for the protocol bad debt PDA (`[b"bad_debt", config]`).
+pub const BAD_DEBT_SEED: &[u8] = b"bad_debt";
+
+/// Derives the bad debt PDA for a config.
+pub fn find_bad_debt_address(config: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BAD_DEBT_SEED, config.as_ref()], program_id)
+}
+
 // Configuration state account structure
 /// ETH Dev Analogy: Think of this like storage variables in a Solidity contract,
 /// but stored in a separate account, not with the program code.
@@ -103,6 +347,120 @@ pub struct ConfigAccount {
     /// The public key of the SPL Token Mint account this program controls.
     /// This program must be the 'mint_authority' for this Mint account.
     pub mint_account: Pubkey,
+
+    /// SPL Token mint accepted as collateral (default pubkey until `ConfigureCollateral`).
+    pub collateral_mint: Pubkey,
+    /// Decimals of the collateral mint.
+    pub collateral_decimals: u8,
+    /// Decimals of the stablecoin mint.
+    pub stable_decimals: u8,
+    /// Minimum collateral value / debt ratio for minting and withdrawing, in basis points
+    /// (15_000 = 150%).
+    pub collateral_ratio_bps: u16,
+    /// Collateral held in the vault across all positions.
+    pub total_collateral: u64,
+    /// Debt owed across all positions, stability fees included, as of the last accrual.
+    pub total_debt: u64,
+
+    /// Ratio below which a position can be liquidated, in basis points. Zero disables liquidations.
+    pub liquidation_threshold_bps: u16,
+    /// Extra collateral a liquidator receives on top of the repaid value, in basis points.
+    pub liquidation_discount_bps: u16,
+    /// Largest share of a position's debt that one liquidation can repay, in basis points.
+    pub close_factor_bps: u16,
+
+    /// Oldest price (in seconds) collateral instructions will accept.
+    pub max_price_age_secs: i64,
+    /// Widest confidence interval accepted, as a share of the price in basis points.
+    pub max_confidence_bps: u16,
+
+    /// The key allowed to add, remove and set the allowance of minters (`MintTo` callers).
+    /// ETH Dev Analogy: The `masterMinter` role of USDC's FiatToken.
+    pub master_minter: Pubkey,
+
+    /// The key allowed to blacklist and unblacklist holders.
+    pub blacklister: Pubkey,
+
+    /// The key allowed to pause and unpause the program.
+    pub pauser: Pubkey,
+    /// While set, every instruction that moves tokens or collateral is rejected (see `Pause`).
+    /// ETH Dev Analogy: OpenZeppelin's `Pausable` with `whenNotPaused` on every mutating function.
+    pub paused: bool,
+    /// Maximum supply of the stablecoin mint, in base units. Zero means uncapped.
+    pub supply_cap: u64,
+
+    /// The key allowed to post reserve attestations. While set, `MintTo` can't push the
+    /// fiat-backed supply past the latest fresh attested reserves.
+    pub attestor: Pubkey,
+    /// Oldest attestation (by its as-of time, in seconds) `MintTo` still accepts.
+    pub max_attestation_age_secs: i64,
+    /// Number of attestations posted; the latest one has sequence `attestation_count - 1`.
+    pub attestation_count: u64,
+
+    /// Stablecoin outstanding through all peg stability modules, backed 1:1 by their vaults.
+    pub psm_debt: u64,
+
+    /// The key allowed to fulfill and reject redemption requests.
+    pub redemption_operator: Pubkey,
+    /// Seconds after which the holder can cancel a pending redemption request.
+    pub redemption_timeout_secs: i64,
+    /// Number of redemption requests made; also the id of the next one.
+    pub redemption_count: u64,
+
+    /// The key allowed to cancel queued timelocked actions.
+    pub guardian: Pubkey,
+    /// Delay between `QueueAction` and `ExecuteAction`. While non-zero, timelocked
+    /// instructions (admin, minter, supply cap and oracle changes) can only run through
+    /// `ExecuteAction`.
+    pub timelock_delay_secs: i64,
+    /// Number of actions queued; also the id of the next one.
+    pub action_count: u64,
+
+    /// Annual stability fee charged on collateral debt, in basis points.
+    pub stability_fee_bps: u16,
+    /// Cumulative stability fee rate index, scaled by `math::RAY`. Zero until the first
+    /// accrual, which starts it at `RAY`.
+    pub rate_index: u128,
+    /// Unix timestamp of the last accrual.
+    pub rate_updated_at: i64,
+    /// Sum of the positions' normalized debt.
+    pub total_normalized_debt: u64,
+    /// Fees accrued into `total_debt` that repayments haven't minted to the surplus yet,
+    /// the sum of the positions' `position_debt - principal` up to rounding.
+    pub unminted_fees: u64,
+    /// Stability fees minted to the surplus account so far.
+    pub total_fees_minted: u64,
+
+    /// Annual savings rate paid to savings shares out of the surplus, in basis points.
+    pub savings_rate_bps: u16,
+    /// Stablecoin per savings share, scaled by `math::RAY`. Zero until savings are configured.
+    pub savings_share_price: u128,
+    /// Unix timestamp of the last drip.
+    pub savings_updated_at: i64,
+
+    /// Largest flash mint; zero disables flash mints.
+    pub flash_mint_ceiling: u64,
+    /// Fee charged on flash mints, in basis points of the amount.
+    pub flash_fee_bps: u16,
+    /// Stablecoin token account receiving flash mint fees.
+    pub flash_treasury: Pubkey,
+    /// Flash-minted stablecoin the current transaction still has to repay.
+    pub flash_outstanding: u64,
+
+    /// Set by `GlobalSettle`: minting is disabled and collateral is valued at the
+    /// frozen settlement price.
+    pub settled: bool,
+    /// Collateral price frozen at settlement (`settle_price * 10^settle_expo`).
+    pub settle_price: u64,
+    pub settle_expo: i32,
+    /// Unix timestamp of the settlement.
+    pub settled_at: i64,
+    /// Collateral skimmed from settled positions and not yet paid out to holders.
+    pub settle_collateral_pool: u64,
+    /// Collateral pool when the settlement was finalized; fixes the redemption rate.
+    pub settle_pool_at_finalize: u64,
+    /// Stablecoin supply when the settlement was finalized; zero until then.
+    pub settle_supply: u64,
 }
 // Implement Solana's Pack trait for state accounts
 impl Sealed for ConfigAccount {}
@@ -116,334 +474,8766 @@ impl IsInitialized for ConfigAccount {
 // Implement Pack to define how to serialize/deserialize and get the size
 // Note: Borsh handles serialization, Pack integrates it with Solana's account model.
 impl Pack for ConfigAccount {
-    const LEN: usize = 1 + 32 + 32; // bool (1) + Pubkey (32) + Pubkey (32)
+    // header (2) + bool (1) + Pubkey (32) + Pubkey (32)
+    // + Pubkey (32) + 2 * u8 (1) + u16 (2) + 2 * u64 (8) + 3 * u16 (2) + i64 (8) + u16 (2)
+    // + 2 * Pubkey (32) + Pubkey (32) + bool (1) + u64 (8) + Pubkey (32) + i64 (8) + u64 (8) + u64 (8)
+    // + Pubkey (32) + i64 (8) + u64 (8) + Pubkey (32) + i64 (8) + u64 (8)
+    // + u16 (2) + u128 (16) + i64 (8) + u64 * 3 (24) + u16 (2) + u128 (16) + i64 (8)
+    // + u64 (8) + u16 (2) + Pubkey (32) + u64 (8) + bool (1) + u64 (8) + i32 (4) + i64 (8) + u64 * 3 (24)
+    const LEN: usize = HEADER_LEN + CONFIG_V0_LEN + 32 + 2 + 2 + 8 * 2 + 2 * 3 + 8 + 2 + 32 * 2 + 32 + 1 + 8
+        + 32 + 8 + 8 + 8 + 32 + 8 + 8 + 32 + 8 + 8 + 2 + 16 + 8 + 8 * 3 + 2 + 16 + 8 + 8 + 2 + 32 + 8
+        + 1 + 8 + 4 + 8 + 8 * 3;
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let mut writer = std::io::Cursor::new(dst);
-        // Using Borsh for serialization within the Pack trait implementation
-        self.serialize(&mut writer).unwrap();
+        // Using Borsh for serialization within the Pack trait implementation, behind the header
+        pack_with_header(self, AccountType::Config, dst)
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
-        let mut reader = std::io::Cursor::new(src);
-        // Using Borsh for deserialization
-        ConfigAccount::deserialize(&mut reader)
-            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)
+        // Using Borsh for deserialization, after checking the header
+        unpack_with_header(src, AccountType::Config)
     }
 }
-// === src/instruction.rs ===
-use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
 
-/// Defines the different actions (instructions) this program can handle.
-/// ETH Dev Analogy: These are like the public functions you'd define in a Solidity contract.
+/// A user's collateralized debt position.
+/// ETH Dev Analogy: Like a MakerDAO vault (CDP), but stored in its own account.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct Position {
+    pub is_initialized: bool,
+    /// The config this position belongs to.
+    pub config: Pubkey,
+    /// The wallet that owns the position.
+    pub owner: Pubkey,
+    /// Collateral deposited into the vault for this position.
+    pub collateral_amount: u64,
+    /// Debt divided by the rate index when it was taken on; the owner owes
+    /// `math::debt_from_normalized(normalized_debt, rate_index)`, stability fees included.
+    /// Equal to the plain debt for positions opened before the stability fee.
+    pub normalized_debt: u64,
+    /// Bump seed of the position PDA.
+    pub bump: u8,
+    /// Debt minted and not repaid yet, without stability fees. The rest of the position's
+    /// debt is the fees it has accrued, which its repayments mint to the surplus.
+    pub principal: u64,
+}
+
+impl Sealed for Position {}
+impl IsInitialized for Position {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for Position {
+    const LEN: usize = HEADER_LEN + 1 + 32 * 2 + 8 * 2 + 1 + 8; // header + bool + 2 * Pubkey + 2 * u64 + u8 + u64
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::Position, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::Position)
+    }
+}
+
+/// Price feed for the collateral: one whole collateral token is worth `price * 10^expo`
+/// whole stablecoins, give or take `conf * 10^expo`. Every update carries the
+/// publisher's Ed25519 signature over `oracle::price_message(...)`, so the feed's
+/// history can be audited off-chain.
+/// ETH Dev Analogy: Like a Chainlink/Pyth aggregator answer, stored in its own account.
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub enum StablecoinInstruction {
-    /// Initializes the stablecoin configuration account.
-    /// Needs to be called once after deploying the program.
-    ///
-    /// Accounts expected:
-    /// 0. `[writable, signer]` Payer account (pays for account creation rent).
-    /// 1. `[writable]` Config account (the account to be initialized). Needs to be created with `SystemProgram.createAccount` first, typically client-side.
-    /// 2. `[]` SPL Token Mint account address (the mint this program will manage).
-    /// 3. `[]` System program ID.
-    /// 4. `[]` Rent sysvar.
-    Initialize {
-        /// The initial admin address.
-        admin: Pubkey,
-    },
+pub struct PriceAccount {
+    pub is_initialized: bool,
+    /// The config this price belongs to.
+    pub config: Pubkey,
+    pub price: u64,
+    pub expo: i32,
+    /// Confidence interval, in the same units as `price`.
+    pub conf: u64,
+    /// Unix timestamp at which the publisher observed the price.
+    pub publish_time: i64,
+    /// Whitelisted publisher that signed this price.
+    pub publisher: Pubkey,
+    /// Publisher's Ed25519 signature over `oracle::price_message(...)`.
+    pub signature: [u8; 64],
+}
 
-    /// Mints new stablecoins to a specified destination account.
-    /// Only callable by the current admin.
-    ///
-    /// Accounts expected:
-    /// 0. `[signer]` Current Admin account (must match `config_account.admin`).
-    /// 1. `[writable]` Config account (holds admin and mint info).
-    /// 2. `[writable]` SPL Token Mint account (the mint address stored in config).
-    /// 3. `[writable]` Destination SPL Token Account (ATA of the recipient). Must exist.
-    /// 4. `[]` SPL Token Program ID.
-    MintTo {
-        /// Amount of tokens (in smallest unit, like wei) to mint.
-        amount: u64,
-    },
+// `Default` is implemented by hand because std only derives it for arrays up to 32 elements.
+impl Default for PriceAccount {
+    fn default() -> Self {
+        Self {
+            is_initialized: false,
+            config: Pubkey::default(),
+            price: 0,
+            expo: 0,
+            conf: 0,
+            publish_time: 0,
+            publisher: Pubkey::default(),
+            signature: [0; 64],
+        }
+    }
+}
 
-    /// Sets a new admin for the stablecoin.
-    /// Only callable by the current admin.
-    ///
-    /// Accounts expected:
-    /// 0. `[signer]` Current Admin account (must match `config_account.admin`).
-    /// 1. `[writable]` Config account (to update the admin field).
-    SetAdmin {
-        /// The public key of the new admin.
-        new_admin: Pubkey,
-    },
+impl Sealed for PriceAccount {}
+impl IsInitialized for PriceAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
 }
+impl Pack for PriceAccount {
+    // header + bool + Pubkey + u64 + i32 + u64 + i64 + Pubkey + signature (64)
+    const LEN: usize = HEADER_LEN + 1 + 32 + 8 + 4 + 8 + 8 + 32 + 64;
 
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::Price, dst)
+    }
 
-// === src/error.rs ===
-use thiserror::Error;
-use solana_program::program_error::ProgramError;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::Price)
+    }
+}
 
-/// Custom errors for the stablecoin program.
-#[derive(Error, Debug, Copy, Clone, PartialEq)]
-pub enum StablecoinError {
-    #[error("Invalid Instruction")]
-    InvalidInstruction,
-    #[error("Not Rent Exempt")]
-    NotRentExempt,
-    #[error("Account Already Initialized")]
-    AlreadyInitialized,
-    #[error("Admin signature mismatch")]
-    AdminSignatureMismatch,
-    #[error("Mint account mismatch")]
-    MintAccountMismatch,
-    #[error("Account not initialized")]
-    NotInitialized,
-    #[error("Numerical overflow error")]
-    NumericalOverflow,
+/// Marks a key as allowed to publish collateral prices. Closed to remove the publisher.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct PublisherAccount {
+    pub is_initialized: bool,
+    /// The config this publisher is whitelisted for.
+    pub config: Pubkey,
+    /// The publisher's Ed25519 public key.
+    pub publisher: Pubkey,
+    /// Bump seed of the publisher PDA.
+    pub bump: u8,
 }
 
-// Allow conversion from our custom error to the standard Solana ProgramError
-impl From<StablecoinError> for ProgramError {
-    fn from(e: StablecoinError) -> Self {
-        ProgramError::Custom(e as u32)
+impl Sealed for PublisherAccount {}
+impl IsInitialized for PublisherAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
     }
 }
-// === src/processor.rs ===
-use solana_program::{
-    account_info::{next_account_info, AccountInfo},
-    entrypoint::ProgramResult,
-    msg,
-    program::{invoke, invoke_signed}, // For CPI (Cross-Program Invocation)
-    program_error::ProgramError,
-    program_pack::{IsInitialized, Pack},
-    pubkey::Pubkey,
-    sysvar::{rent::Rent, Sysvar},
-};
-use spl_token::instruction as token_instruction; // SPL Token program instructions
-use crate::{
-    error::StablecoinError,
-    instruction::StablecoinInstruction,
-    state::ConfigAccount,
-};
-/// Processes instructions for the stablecoin admin program.
-pub struct Processor;
-impl Processor {
-    pub fn process(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        instruction_data: &[u8],
-    ) -> ProgramResult {
-        // Deserialize the instruction data using Borsh
-        let instruction = StablecoinInstruction::try_from_slice(instruction_data)
-            .map_err(|_| ProgramError::InvalidInstructionData)?;
+impl Pack for PublisherAccount {
+    const LEN: usize = HEADER_LEN + 1 + 32 * 2 + 1; // header + bool + 2 * Pubkey + u8
 
-        // Route to the appropriate handler based on the instruction variant
-        match instruction {
-            StablecoinInstruction::Initialize { admin } => {
-                msg!("Instruction: Initialize");
-                Self::process_initialize(accounts, admin, program_id)
-            }
-            StablecoinInstruction::MintTo { amount } => {
-                msg!("Instruction: MintTo");
-                Self::process_mint_to(accounts, amount, program_id)
-            }
-            StablecoinInstruction::SetAdmin { new_admin } => {
-                msg!("Instruction: SetAdmin");
-                Self::process_set_admin(accounts, new_admin, program_id)
-            }
-        }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::Publisher, dst)
     }
 
-    /// Processes the Initialize instruction.
-    fn process_initialize(
-        accounts: &[AccountInfo],
-        admin: Pubkey,
-        program_id: &Pubkey,
-    ) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
-        // Account 0: Payer (Signer, Writable) - Not directly used here, but needed for tx fee
-        let _payer_account = next_account_info(account_info_iter)?;
-        // Account 1: Config Account (Writable) - The account to initialize
-        let config_account = next_account_info(account_info_iter)?;
-        // Account 2: Mint Account (Readonly) - The SPL Mint this program manages
-        let mint_account_info = next_account_info(account_info_iter)?;
-        // Account 3: System Program (Readonly) - Needed for rent check
-        let _system_program = next_account_info(account_info_iter)?;
-        // Account 4: Rent Sysvar (Readonly) - To check for rent exemption
-        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::Publisher)
+    }
+}
 
-        // Security Check: Ensure the config account is owned by *this* program.
-        // ETH Dev Analogy: This is inherent in Solidity as code+storage are one.
-        // In Solana, you must verify account ownership.
-        if config_account.owner != program_id {
-            msg!("Error: Config account not owned by program");
-            return Err(ProgramError::IncorrectProgramId);
-        }
+/// A key allowed to mint stablecoin through `MintTo`, up to its remaining allowance.
+/// Closed by `RemoveMinter`.
+/// ETH Dev Analogy: An entry in USDC's `minterAllowed` mapping.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct MinterAccount {
+    pub is_initialized: bool,
+    /// The config this minter belongs to.
+    pub config: Pubkey,
+    /// The minter's wallet, which signs `MintTo`.
+    pub minter: Pubkey,
+    /// Stablecoin the minter can still mint. Every mint decrements it.
+    pub allowance: u64,
+    /// Bump seed of the minter PDA.
+    pub bump: u8,
+}
 
-        // Security Check: Ensure the config account is rent-exempt.
-        // ETH Dev Analogy: Rent is Solana's storage cost mechanism, different from gas.
-        if !rent.is_exempt(config_account.lamports(), config_account.data_len()) {
+impl Sealed for MinterAccount {}
+impl IsInitialized for MinterAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for MinterAccount {
+    const LEN: usize = HEADER_LEN + 1 + 32 * 2 + 8 + 1; // header + bool + 2 * Pubkey + u64 + u8
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::Minter, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::Minter)
+    }
+}
+
+/// Marks a holder wallet as blacklisted. While it exists the holder can't receive mints,
+/// and the holder's stablecoin accounts frozen through `Blacklist` stay frozen.
+/// Closed by the `Unblacklist` that thaws the last of them.
+/// ETH Dev Analogy: An entry in USDC's `blacklisted` mapping.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct BlacklistEntry {
+    pub is_initialized: bool,
+    /// The config this entry belongs to.
+    pub config: Pubkey,
+    /// The blacklisted wallet.
+    pub holder: Pubkey,
+    /// Number of the holder's token accounts currently frozen by the program.
+    pub frozen_accounts: u32,
+    /// Unix timestamp at which the holder was blacklisted.
+    pub blacklisted_at: i64,
+    /// Bump seed of the blacklist entry PDA.
+    pub bump: u8,
+}
+
+impl Sealed for BlacklistEntry {}
+impl IsInitialized for BlacklistEntry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for BlacklistEntry {
+    const LEN: usize = HEADER_LEN + 1 + 32 * 2 + 4 + 8 + 1; // header + bool + 2 * Pubkey + u32 + i64 + u8
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::BlacklistEntry, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::BlacklistEntry)
+    }
+}
+
+/// An attestor's statement of the fiat reserves backing the stablecoin. Attestations are
+/// never overwritten: each one gets the next sequence number, so the full history stays
+/// on-chain for auditors.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct ReserveAttestation {
+    pub is_initialized: bool,
+    /// The config this attestation belongs to.
+    pub config: Pubkey,
+    /// Position of this attestation in the config's history, starting at 0.
+    pub sequence: u64,
+    /// Attested reserves, in stablecoin base units.
+    pub reserve_amount: u64,
+    /// Unix timestamp the reserve amount was measured at.
+    pub as_of: i64,
+    /// Hash of the audit report backing the figure (e.g. SHA-256 of the PDF).
+    pub report_hash: [u8; 32],
+    /// The attestor that posted it.
+    pub attestor: Pubkey,
+    /// Unix timestamp at which it was posted.
+    pub posted_at: i64,
+    /// Bump seed of the attestation PDA.
+    pub bump: u8,
+}
+
+impl Sealed for ReserveAttestation {}
+impl IsInitialized for ReserveAttestation {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for ReserveAttestation {
+    // header + bool + Pubkey + 2 * u64 + i64 + [u8; 32] + Pubkey + i64 + u8
+    const LEN: usize = HEADER_LEN + 1 + 32 + 8 * 2 + 8 + 32 + 32 + 8 + 1;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::ReserveAttestation, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::ReserveAttestation)
+    }
+}
+
+/// A peg stability module: swaps between a whitelisted reserve token and the stablecoin at
+/// 1:1 minus a fee. Its existence whitelists the reserve token.
+/// ETH Dev Analogy: MakerDAO's PSM (`tin`/`tout` fees and a `line` debt ceiling).
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct PsmAccount {
+    pub is_initialized: bool,
+    /// The config this module belongs to.
+    pub config: Pubkey,
+    /// The whitelisted reserve token. Must have the stablecoin's decimals.
+    pub reserve_mint: Pubkey,
+    /// Stablecoin token account that receives swap fees.
+    pub treasury: Pubkey,
+    /// Fee on `SwapIn`, in basis points of the swapped amount.
+    pub fee_in_bps: u16,
+    /// Fee on `SwapOut`, in basis points of the swapped amount.
+    pub fee_out_bps: u16,
+    /// Largest reserve balance (and so PSM-backed stablecoin) `SwapIn` may build up.
+    pub swap_in_ceiling: u64,
+    /// Largest net outflow of reserves (`SwapOut` payouts less `SwapIn` deposits since)
+    /// the module allows before the admin raises it.
+    pub swap_out_ceiling: u64,
+    /// Reserve tokens held in the vault.
+    pub total_reserves: u64,
+    /// Reserve tokens paid out by `SwapOut`, less those `SwapIn` has brought back since.
+    /// Never below zero, so deposits made before any payout don't build up headroom.
+    pub net_swapped_out: u64,
+    /// Bump seed of the module PDA.
+    pub bump: u8,
+    /// Bump seed of the reserve vault PDA.
+    pub vault_bump: u8,
+}
+
+impl Sealed for PsmAccount {}
+impl IsInitialized for PsmAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for PsmAccount {
+    // header + bool + 3 * Pubkey + 2 * u16 + 4 * u64 + 2 * u8
+    const LEN: usize = HEADER_LEN + 1 + 32 * 3 + 2 * 2 + 8 * 4 + 2;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::Psm, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::Psm)
+    }
+}
+
+/// Lifecycle of a redemption request.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum RedemptionStatus {
+    /// Tokens are in escrow, waiting for the operator.
+    #[default]
+    Pending,
+    /// The operator paid out off-chain and burned the escrowed tokens.
+    Fulfilled,
+    /// The operator returned the tokens to the holder.
+    Rejected,
+    /// The holder took the tokens back after the request timed out.
+    Cancelled,
+}
+
+/// A holder's request to redeem stablecoin for fiat. Requests are kept after they
+/// resolve, as the on-chain record of each off-ramp.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct RedemptionRequest {
+    pub is_initialized: bool,
+    /// The config this request belongs to.
+    pub config: Pubkey,
+    /// Sequential id of the request.
+    pub id: u64,
+    /// The redeeming wallet.
+    pub holder: Pubkey,
+    /// Stablecoin account the tokens came from, and go back to if the request is
+    /// rejected or cancelled.
+    pub holder_token_account: Pubkey,
+    /// Escrowed stablecoin.
+    pub amount: u64,
+    /// Hash of the off-chain payout instructions (e.g. bank account details).
+    pub payout_ref_hash: [u8; 32],
+    pub status: RedemptionStatus,
+    /// Unix timestamp of the request.
+    pub requested_at: i64,
+    /// Unix timestamp after which the holder can cancel.
+    pub expires_at: i64,
+    /// Unix timestamp at which the request was fulfilled, rejected or cancelled.
+    pub resolved_at: i64,
+    /// Hash of the bank transfer reference recorded on fulfilment.
+    pub bank_ref_hash: [u8; 32],
+    /// Bump seed of the request PDA.
+    pub bump: u8,
+}
+
+impl Sealed for RedemptionRequest {}
+impl IsInitialized for RedemptionRequest {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for RedemptionRequest {
+    // header + bool + Pubkey + u64 + 2 * Pubkey + u64 + [u8; 32] + status (1) + 3 * i64 + [u8; 32] + u8
+    const LEN: usize = HEADER_LEN + 1 + 32 + 8 + 32 * 2 + 8 + 32 + 1 + 8 * 3 + 32 + 1;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::RedemptionRequest, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::RedemptionRequest)
+    }
+}
+
+/// Lifecycle of a timelocked action.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ActionStatus {
+    /// Waiting for its ETA.
+    #[default]
+    Queued,
+    Executed,
+    /// Cancelled by the guardian.
+    Cancelled,
+}
+
+/// A sensitive instruction queued behind the timelock. Kept after it executes or is
+/// cancelled, as the record of the change.
+/// ETH Dev Analogy: A queued transaction in Compound's `Timelock` contract.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct TimelockAction {
+    pub is_initialized: bool,
+    /// The config this action belongs to.
+    pub config: Pubkey,
+    /// Sequential id of the action.
+    pub id: u64,
+    /// The role holder that queued it.
+    pub proposer: Pubkey,
+    /// Borsh-serialized `StablecoinInstruction`, at most `MAX_ACTION_DATA_LEN` bytes.
+    pub instruction_data: Vec<u8>,
+    /// Unix timestamp at which it was queued.
+    pub queued_at: i64,
+    /// Unix timestamp from which it can execute.
+    pub eta: i64,
+    pub status: ActionStatus,
+    /// Bump seed of the action PDA.
+    pub bump: u8,
+}
+
+impl Sealed for TimelockAction {}
+impl IsInitialized for TimelockAction {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for TimelockAction {
+    // header + bool + Pubkey + u64 + Pubkey + Vec<u8> (4 + MAX_ACTION_DATA_LEN) + 2 * i64 + status (1) + u8
+    const LEN: usize = HEADER_LEN + 1 + 32 + 8 + 32 + 4 + MAX_ACTION_DATA_LEN + 8 * 2 + 1 + 1;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::TimelockAction, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::TimelockAction)
+    }
+}
+
+/// An M-of-N set of keys that controls the admin role through its signer PDA.
+/// ETH Dev Analogy: A Gnosis Safe owning the `admin` role.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct MultisigAccount {
+    pub is_initialized: bool,
+    /// The config this multisig was created for.
+    pub config: Pubkey,
+    /// Keys that can propose and approve, at most `MAX_MULTISIG_SIGNERS`.
+    pub signers: Vec<Pubkey>,
+    /// Approvals needed to execute a proposal.
+    pub threshold: u8,
+    /// Bumped whenever the signers change, invalidating open proposals.
+    pub epoch: u32,
+    /// Number of proposals made; also the id of the next one.
+    pub proposal_count: u64,
+    /// Bump seed of the multisig PDA.
+    pub bump: u8,
+    /// Bump seed of the multisig signer PDA.
+    pub signer_bump: u8,
+}
+
+impl Sealed for MultisigAccount {}
+impl IsInitialized for MultisigAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for MultisigAccount {
+    // header + bool + Pubkey + Vec<Pubkey> (4 + 32 * MAX_MULTISIG_SIGNERS) + u8 + u32 + u64 + 2 * u8
+    const LEN: usize = HEADER_LEN + 1 + 32 + 4 + 32 * MAX_MULTISIG_SIGNERS + 1 + 4 + 8 + 2;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::Multisig, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::Multisig)
+    }
+}
+
+/// An account of a multisig proposal's instruction.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProposalAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// Serialized size of a `ProposalAccount`.
+pub const PROPOSAL_ACCOUNT_LEN: usize = 32 + 1 + 1;
+
+/// An instruction proposed to a multisig, executed as its signer PDA once approved.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct MultisigProposal {
+    pub is_initialized: bool,
+    /// The multisig this proposal belongs to.
+    pub multisig: Pubkey,
+    /// Sequential id of the proposal.
+    pub id: u64,
+    /// The signer that proposed it.
+    pub proposer: Pubkey,
+    /// Program the instruction is sent to (this program or another, e.g. the loyalty program).
+    pub program_id: Pubkey,
+    /// The instruction's accounts, at most `MAX_PROPOSAL_ACCOUNTS`.
+    pub accounts: Vec<ProposalAccount>,
+    /// The instruction data, at most `MAX_PROPOSAL_DATA_LEN` bytes.
+    pub data: Vec<u8>,
+    /// Bitmask of approvals, bit `i` for `signers[i]`.
+    pub approvals: u16,
+    /// Multisig epoch the proposal was made in.
+    pub epoch: u32,
+    pub executed: bool,
+    /// Unix timestamp at which it was proposed.
+    pub created_at: i64,
+    /// Bump seed of the proposal PDA.
+    pub bump: u8,
+}
+
+impl Sealed for MultisigProposal {}
+impl IsInitialized for MultisigProposal {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for MultisigProposal {
+    // header + bool + Pubkey + u64 + 2 * Pubkey + Vec<ProposalAccount> + Vec<u8> + u16 + u32 + bool + i64 + u8
+    const LEN: usize = HEADER_LEN + 1 + 32 + 8 + 32 * 2
+        + 4 + PROPOSAL_ACCOUNT_LEN * MAX_PROPOSAL_ACCOUNTS
+        + 4 + MAX_PROPOSAL_DATA_LEN
+        + 2 + 4 + 1 + 8 + 1;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::MultisigProposal, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::MultisigProposal)
+    }
+}
+
+/// Debt left over after liquidations seized all of a position's collateral.
+/// ETH Dev Analogy: Like MakerDAO's `sin` (system debt) that has to be covered by surplus.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct BadDebtAccount {
+    pub is_initialized: bool,
+    /// The config this account belongs to.
+    pub config: Pubkey,
+    /// Uncovered stablecoin debt written off in total.
+    pub total_bad_debt: u64,
+    /// Number of positions that left bad debt behind.
+    pub event_count: u64,
+    /// Unix timestamp of the last write-off.
+    pub last_recorded_at: i64,
+    /// Bump seed of the bad debt PDA.
+    pub bump: u8,
+}
+
+impl Sealed for BadDebtAccount {}
+impl IsInitialized for BadDebtAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for BadDebtAccount {
+    const LEN: usize = HEADER_LEN + 1 + 32 + 8 * 2 + 8 + 1; // header + bool + Pubkey + 2 * u64 + i64 + u8
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_with_header(self, AccountType::BadDebt, dst)
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        unpack_with_header(src, AccountType::BadDebt)
+    }
+}
+
+// === src/math.rs ===
+// Pure collateral math, kept free of account handling so it can be checked in isolation.
+
+/// Denominator for basis point values (10_000 bps = 100%).
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Value of `collateral_amount` (smallest collateral units) in smallest stablecoin units,
+/// given a price of `price * 10^expo` stablecoins per whole collateral token.
+/// Returns `None` on overflow.
+pub fn collateral_value(
+    collateral_amount: u64,
+    price: u64,
+    expo: i32,
+    collateral_decimals: u8,
+    stable_decimals: u8,
+) -> Option<u64> {
+    let scale = expo
+        .checked_add(stable_decimals as i32)?
+        .checked_sub(collateral_decimals as i32)?;
+    let raw = (collateral_amount as u128).checked_mul(price as u128)?;
+    let value = if scale >= 0 {
+        raw.checked_mul(10u128.checked_pow(scale as u32)?)?
+    } else {
+        raw / 10u128.checked_pow(scale.unsigned_abs())?
+    };
+    u64::try_from(value).ok()
+}
+
+/// Smallest-unit collateral amount worth `value` smallest stablecoin units (rounded down).
+/// Inverse of `collateral_value`. Returns `None` on overflow or a zero price.
+pub fn collateral_for_value(
+    value: u64,
+    price: u64,
+    expo: i32,
+    collateral_decimals: u8,
+    stable_decimals: u8,
+) -> Option<u64> {
+    if price == 0 {
+        return None;
+    }
+    let scale = expo
+        .checked_add(stable_decimals as i32)?
+        .checked_sub(collateral_decimals as i32)?;
+    let amount = if scale >= 0 {
+        (value as u128) / (price as u128).checked_mul(10u128.checked_pow(scale as u32)?)?
+    } else {
+        (value as u128).checked_mul(10u128.checked_pow(scale.unsigned_abs())?)? / price as u128
+    };
+    u64::try_from(amount).ok()
+}
+
+/// Applies a basis point share to `amount`, rounding down.
+pub fn apply_bps(amount: u64, bps: u64) -> Option<u64> {
+    let scaled = (amount as u128).checked_mul(bps as u128)? / BPS_DENOMINATOR as u128;
+    u64::try_from(scaled).ok()
+}
+
+/// Fixed-point scale of the stability fee rate index (`RAY` = 1.0).
+pub const RAY: u128 = 1_000_000_000_000_000_000;
+
+/// Seconds in the 365-day year the annual stability fee is quoted over.
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Rate index after `elapsed_secs` at an annual `fee_bps`. Interest is simple between
+/// accruals and compounds at each one. Returns `None` on overflow.
+pub fn accrue_rate_index(index: u128, fee_bps: u16, elapsed_secs: u64) -> Option<u128> {
+    let growth = index.checked_mul(fee_bps as u128)?.checked_mul(elapsed_secs as u128)?
+        / (BPS_DENOMINATOR as u128 * SECONDS_PER_YEAR as u128);
+    index.checked_add(growth)
+}
+
+/// Debt owed on `normalized_debt` at `index`, rounded up so rounding never favours the
+/// borrower. Returns `None` on overflow.
+pub fn debt_from_normalized(normalized_debt: u64, index: u128) -> Option<u64> {
+    let debt = (normalized_debt as u128).checked_mul(index)?.checked_add(RAY - 1)? / RAY;
+    u64::try_from(debt).ok()
+}
+
+/// Normalized debt worth `amount` at `index`. New debt rounds up and repayments round
+/// down, so rounding never favours the borrower. Returns `None` on overflow or a zero index.
+pub fn normalize_debt(amount: u64, index: u128, round_up: bool) -> Option<u64> {
+    if index == 0 {
+        return None;
+    }
+    let scaled = (amount as u128).checked_mul(RAY)?;
+    let normalized = if round_up { scaled.checked_add(index - 1)? / index } else { scaled / index };
+    u64::try_from(normalized).ok()
+}
+
+/// Interest owed to `shares` savings shares for `elapsed_secs` at an annual `rate_bps`,
+/// given a share price of `share_price` (scaled by `RAY`). Returns `None` on overflow.
+pub fn savings_interest(shares: u64, share_price: u128, rate_bps: u16, elapsed_secs: u64) -> Option<u64> {
+    let new_price = accrue_rate_index(share_price, rate_bps, elapsed_secs)?;
+    let interest = (shares as u128).checked_mul(new_price - share_price)? / RAY;
+    u64::try_from(interest).ok()
+}
+
+/// Share price after paying `interest` to `shares` shares, rounded down so the vault always
+/// holds what the shares are worth. Returns `None` on overflow or zero shares.
+pub fn share_price_after_interest(share_price: u128, shares: u64, interest: u64) -> Option<u128> {
+    if shares == 0 {
+        return None;
+    }
+    share_price.checked_add((interest as u128).checked_mul(RAY)? / shares as u128)
+}
+
+/// Shares bought by depositing `amount` at `share_price`, rounded down.
+/// Returns `None` on overflow or a zero price.
+pub fn shares_for_amount(amount: u64, share_price: u128) -> Option<u64> {
+    if share_price == 0 {
+        return None;
+    }
+    u64::try_from((amount as u128).checked_mul(RAY)? / share_price).ok()
+}
+
+/// Stablecoin paid for redeeming `shares` at `share_price`, rounded down.
+/// Returns `None` on overflow.
+pub fn amount_for_shares(shares: u64, share_price: u128) -> Option<u64> {
+    u64::try_from((shares as u128).checked_mul(share_price)? / RAY).ok()
+}
+
+/// Collateral a position hands to the settlement pool to cover `debt` at the settlement
+/// price: enough to be worth the debt (rounded up), or all of it if that falls short.
+/// The owner keeps the rest. Returns `None` on overflow or a zero price.
+pub fn settlement_skim(
+    collateral_amount: u64,
+    debt: u64,
+    price: u64,
+    expo: i32,
+    collateral_decimals: u8,
+    stable_decimals: u8,
+) -> Option<u64> {
+    let mut needed = collateral_for_value(debt, price, expo, collateral_decimals, stable_decimals)?;
+    if collateral_value(needed, price, expo, collateral_decimals, stable_decimals)? < debt {
+        needed = needed.checked_add(1)?;
+    }
+    Some(needed.min(collateral_amount))
+}
+
+/// Collateral paid for redeeming `amount` stablecoin out of a settlement pool of `pool`
+/// collateral shared by `supply` stablecoin, rounded down. Since every redemption uses the
+/// same `pool` and `supply` and rounds down, the payouts for any split of `supply` sum to
+/// at most `pool`. Returns `None` on overflow or a zero supply.
+pub fn settlement_payout(amount: u64, pool: u64, supply: u64) -> Option<u64> {
+    if supply == 0 {
+        return None;
+    }
+    u64::try_from((amount as u128).checked_mul(pool as u128)? / supply as u128).ok()
+}
+
+/// Whether `collateral_value` covers `debt` at `ratio_bps`. A position without debt is always healthy.
+pub fn is_healthy(collateral_value: u64, debt: u64, ratio_bps: u16) -> Option<bool> {
+    let lhs = (collateral_value as u128).checked_mul(BPS_DENOMINATOR as u128)?;
+    let rhs = (debt as u128).checked_mul(ratio_bps as u128)?;
+    Some(lhs >= rhs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 150 stablecoins per collateral token, 9 collateral decimals, 6 stablecoin decimals
+    const PRICE: u64 = 15_000;
+    const EXPO: i32 = -2;
+
+    #[test]
+    fn settlement_skim_covers_debt_rounding_up() {
+        let skim = settlement_skim(2_000_000_000, 100_000_000, PRICE, EXPO, 9, 6).unwrap();
+        assert_eq!(skim, 666_666_667);
+        assert!(collateral_value(skim, PRICE, EXPO, 9, 6).unwrap() >= 100_000_000);
+        assert!(collateral_value(skim - 1, PRICE, EXPO, 9, 6).unwrap() < 100_000_000);
+    }
+
+    #[test]
+    fn settlement_skim_takes_everything_when_short() {
+        assert_eq!(settlement_skim(500_000_000, 100_000_000, PRICE, EXPO, 9, 6), Some(500_000_000));
+        assert_eq!(settlement_skim(500_000_000, 0, PRICE, EXPO, 9, 6), Some(0));
+        assert_eq!(settlement_skim(500_000_000, 100_000_000, 0, EXPO, 9, 6), None);
+    }
+
+    #[test]
+    fn settlement_payout_rounds_down() {
+        assert_eq!(settlement_payout(1, 10, 3), Some(3));
+        assert_eq!(settlement_payout(3, 10, 3), Some(10));
+        assert_eq!(settlement_payout(0, 10, 3), Some(0));
+        assert_eq!(settlement_payout(1, 10, 0), None);
+        // The product is taken in u128, so only a payout above u64 overflows
+        assert_eq!(settlement_payout(u64::MAX, u64::MAX, u64::MAX), Some(u64::MAX));
+        assert_eq!(settlement_payout(u64::MAX, 2, 1), None);
+    }
+
+    #[test]
+    fn settlement_payouts_never_exceed_pool() {
+        let (pool, supply) = (1_000_000_007, 250_000_003);
+        for holders in 1..=7u64 {
+            let share = supply / holders;
+            let mut amounts = vec![share; holders as usize];
+            amounts[0] += supply - share * holders;
+            let paid: u64 = amounts.iter().map(|&amount| settlement_payout(amount, pool, supply).unwrap()).sum();
+            assert!(paid <= pool);
+            assert!(pool - paid < holders);
+        }
+    }
+}
+
+// === src/oracle.rs ===
+use solana_program::{
+    ed25519_program, instruction::Instruction, program_error::ProgramError, pubkey::Pubkey,
+};
+
+// Signed price updates.
+//
+// A whitelisted publisher signs `price_message(...)` off-chain (see the `price-publisher`
+// tool). Whoever submits the update puts an Ed25519 program instruction verifying that
+// signature right before `PostPrice`. The runtime aborts the whole transaction if the
+// signature is invalid, so the program only has to check (via the instructions sysvar)
+// that the verified key is a whitelisted publisher and the message is the posted price.
+
+/// Domain separator so a price signature can't be replayed as any other signed message.
+const PRICE_DOMAIN: &[u8] = b"stablecoin-price";
+
+/// Offset of the first `Ed25519SignatureOffsets` entry (after `num_signatures: u8` and one byte of padding).
+const SIGNATURE_OFFSETS_START: usize = 2;
+/// Size of one `Ed25519SignatureOffsets` entry (seven u16 fields).
+const SIGNATURE_OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+
+/// Builds the message a publisher signs for a price update.
+pub fn price_message(config: &Pubkey, price: u64, expo: i32, conf: u64, publish_time: i64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(PRICE_DOMAIN.len() + 32 + 8 + 4 + 8 + 8);
+    message.extend_from_slice(PRICE_DOMAIN);
+    message.extend_from_slice(config.as_ref());
+    message.extend_from_slice(&price.to_le_bytes());
+    message.extend_from_slice(&expo.to_le_bytes());
+    message.extend_from_slice(&conf.to_le_bytes());
+    message.extend_from_slice(&publish_time.to_le_bytes());
+    message
+}
+
+/// Builds Ed25519 program instruction data verifying one signature, with the key,
+/// signature and message all carried in the instruction itself.
+pub fn ed25519_instruction_data(
+    public_key: &[u8; PUBKEY_LEN],
+    signature: &[u8; SIGNATURE_LEN],
+    message: &[u8],
+) -> Vec<u8> {
+    let public_key_offset = SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN;
+    let signature_offset = public_key_offset + PUBKEY_LEN;
+    let message_data_offset = signature_offset + SIGNATURE_LEN;
+
+    let mut data = Vec::with_capacity(message_data_offset + message.len());
+    data.extend_from_slice(&[1, 0]);
+    for field in [
+        signature_offset as u16,
+        u16::MAX,
+        public_key_offset as u16,
+        u16::MAX,
+        message_data_offset as u16,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(public_key);
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+    data
+}
+
+/// What an Ed25519 program instruction verified.
+pub struct VerifiedSignature {
+    pub public_key: Pubkey,
+    pub signature: [u8; SIGNATURE_LEN],
+    pub message: Vec<u8>,
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, ProgramError> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+/// Extracts the key, signature and message verified by an Ed25519 program instruction.
+/// Only single-signature instructions that carry their own data are accepted.
+pub fn verified_signature(instruction: &Instruction) -> Result<VerifiedSignature, ProgramError> {
+    if instruction.program_id != ed25519_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let data = &instruction.data;
+    if data.first() != Some(&1) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let offsets = SIGNATURE_OFFSETS_START;
+    let signature_offset = read_u16(data, offsets)? as usize;
+    let signature_instruction_index = read_u16(data, offsets + 2)?;
+    let public_key_offset = read_u16(data, offsets + 4)? as usize;
+    let public_key_instruction_index = read_u16(data, offsets + 6)?;
+    let message_data_offset = read_u16(data, offsets + 8)? as usize;
+    let message_data_size = read_u16(data, offsets + 10)? as usize;
+    let message_instruction_index = read_u16(data, offsets + 12)?;
+
+    // `u16::MAX` means "this instruction"; anything else would let the signature,
+    // key or message come from a different instruction than the one we inspect.
+    if signature_instruction_index != u16::MAX
+        || public_key_instruction_index != u16::MAX
+        || message_instruction_index != u16::MAX
+    {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let public_key = data
+        .get(public_key_offset..public_key_offset + PUBKEY_LEN)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let signature = data
+        .get(signature_offset..signature_offset + SIGNATURE_LEN)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let message = data
+        .get(message_data_offset..message_data_offset + message_data_size)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    Ok(VerifiedSignature {
+        public_key: Pubkey::try_from(public_key).map_err(|_| ProgramError::InvalidInstructionData)?,
+        signature: signature.try_into().map_err(|_| ProgramError::InvalidInstructionData)?,
+        message: message.to_vec(),
+    })
+}
+
+// === src/events.rs ===
+use borsh::BorshSerialize;
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+
+// Structured events for off-chain compliance and treasury logs. Each event is Borsh encoded and
+// written with `sol_log_data`, so indexers read it from the transaction's
+// "Program data:" log lines instead of parsing `msg!` text.
+// ETH Dev Analogy: Solidity `emit` of an event; the enum variant plays the role of the topic.
+
+/// Events emitted by the stablecoin program.
+#[derive(BorshSerialize, Debug, Clone)]
+pub enum StablecoinEvent {
+    BlacklisterChanged {
+        config: Pubkey,
+        old_blacklister: Pubkey,
+        new_blacklister: Pubkey,
+    },
+    Blacklisted {
+        config: Pubkey,
+        holder: Pubkey,
+        token_account: Pubkey,
+        blacklister: Pubkey,
+        timestamp: i64,
+    },
+    Unblacklisted {
+        config: Pubkey,
+        holder: Pubkey,
+        token_account: Pubkey,
+        blacklister: Pubkey,
+        timestamp: i64,
+    },
+    RedemptionRequested {
+        config: Pubkey,
+        id: u64,
+        holder: Pubkey,
+        amount: u64,
+        payout_ref_hash: [u8; 32],
+        expires_at: i64,
+    },
+    RedemptionFulfilled {
+        config: Pubkey,
+        id: u64,
+        operator: Pubkey,
+        amount: u64,
+        bank_ref_hash: [u8; 32],
+    },
+    RedemptionRejected {
+        config: Pubkey,
+        id: u64,
+        operator: Pubkey,
+        amount: u64,
+    },
+    RedemptionCancelled {
+        config: Pubkey,
+        id: u64,
+        holder: Pubkey,
+        amount: u64,
+    },
+}
+
+/// Logs an event as Borsh-encoded program data.
+pub fn emit(event: &StablecoinEvent) {
+    // Serializing into a Vec can't fail
+    let data = borsh::to_vec(event).unwrap();
+    sol_log_data(&[&data]);
+}
+
+// === src/instruction.rs ===
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::state::ProposalAccount;
+
+/// Defines the different actions (instructions) this program can handle.
+/// ETH Dev Analogy: These are like the public functions you'd define in a Solidity contract.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub enum StablecoinInstruction {
+    /// Initializes the stablecoin configuration account.
+    /// Needs to be called once after deploying the program.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Payer account (pays for account creation rent).
+    /// 1. `[writable]` Config account (the account to be initialized). Needs to be created with `SystemProgram.createAccount` first, typically client-side.
+    /// 2. `[]` SPL Token Mint account address (the mint this program will manage).
+    /// 3. `[]` System program ID.
+    /// 4. `[]` Rent sysvar.
+    Initialize {
+        /// The initial admin address.
+        admin: Pubkey,
+    },
+
+    /// Mints new stablecoins to a specified destination account.
+    /// Only callable by a minter configured by the master minter, and only up to its
+    /// remaining allowance, which the mint decrements.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Minter account.
+    /// 1. `[writable]` Config account (holds admin and mint info).
+    /// 2. `[writable]` SPL Token Mint account (the mint address stored in config).
+    /// 3. `[writable]` Destination SPL Token Account (ATA of the recipient). Must exist.
+    /// 4. `[]` SPL Token Program ID.
+    /// 5. `[]` Program authority PDA (`[b"authority", config]`), the mint authority.
+    /// 6. `[writable]` Minter PDA (`[b"minter", config, minter]`).
+    /// 7. `[]` Blacklist entry PDA (`[b"blacklist", config, owner]`) of the destination's owner.
+    ///    Minting fails if it exists.
+    /// 8. `[]` Latest reserve attestation PDA (`[b"attestation", config, attestation_count - 1]`).
+    ///    Only checked while an attestor is configured.
+    MintTo {
+        /// Amount of tokens (in smallest unit, like wei) to mint.
+        amount: u64,
+    },
+
+    /// Sets a new admin for the stablecoin.
+    /// Only callable by the current admin. Timelocked.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account (must match `config_account.admin`).
+    /// 1. `[writable]` Config account (to update the admin field).
+    SetAdmin {
+        /// The public key of the new admin.
+        new_admin: Pubkey,
+    },
+
+    /// Upgrades a config account written in an older layout to the current one in place:
+    /// reallocates it, tops up rent from the payer and rewrites it with the current header.
+    /// Does nothing if the config is already current.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Payer for the extra rent.
+    /// 1. `[writable]` Config account.
+    /// 2. `[]` System program ID.
+    MigrateConfig,
+
+    /// Upgrades a position written before the stability fee: reallocates it, tops up rent
+    /// from the payer and sets its principal to its debt, so fees accrued since then stay
+    /// payable by this position.
+    /// Callable by anyone. Does nothing if the position is already current.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Payer for the extra rent.
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Position PDA.
+    /// 3. `[]` System program ID.
+    MigratePosition,
+
+    /// Enables collateralized minting: sets the collateral mint and ratio, and creates the
+    /// collateral vault and price accounts on first use. The collateral mint cannot be
+    /// changed once set. Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Current Admin account (pays rent for the vault and price PDAs).
+    /// 1. `[writable]` Config account.
+    /// 2. `[]` Stablecoin SPL Token Mint account.
+    /// 3. `[]` Collateral SPL Token Mint account.
+    /// 4. `[writable]` Collateral vault PDA (`[b"vault", config]`).
+    /// 5. `[writable]` Price PDA (`[b"price", config]`).
+    /// 6. `[]` Program authority PDA (`[b"authority", config]`), the vault owner.
+    /// 7. `[]` SPL Token Program ID.
+    /// 8. `[]` System program ID.
+    ConfigureCollateral {
+        /// Minimum collateral value / debt ratio, in basis points (15_000 = 150%).
+        collateral_ratio_bps: u16,
+    },
+
+    /// Posts a collateral price signed by a whitelisted publisher. The instruction right
+    /// before this one must be an Ed25519 program instruction verifying the publisher's
+    /// signature over `oracle::price_message(config, price, expo, conf, publish_time)`.
+    /// Anyone can submit the transaction; prices must be newer than the stored one.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` Config account.
+    /// 1. `[writable]` Price PDA.
+    /// 2. `[]` Publisher PDA (`[b"publisher", config, publisher]`) of the signing publisher.
+    /// 3. `[]` Instructions sysvar.
+    PostPrice {
+        /// Price of one whole collateral token in whole stablecoins is `price * 10^expo`.
+        price: u64,
+        expo: i32,
+        /// Confidence interval, in the same units as `price`.
+        conf: u64,
+        /// Unix timestamp at which the publisher observed the price.
+        publish_time: i64,
+    },
+
+    /// Opens an empty collateral position for the signer.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Position owner (pays rent for the position PDA).
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Position PDA (`[b"position", config, owner]`), must not exist yet.
+    /// 3. `[]` System program ID.
+    OpenPosition,
+
+    /// Moves collateral from the owner's token account into the vault.
+    /// Deposits can only improve a position's health.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Position owner (owner of the source token account).
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Position PDA.
+    /// 3. `[writable]` Owner's collateral token account.
+    /// 4. `[writable]` Collateral vault PDA.
+    /// 5. `[]` Collateral SPL Token Mint account.
+    /// 6. `[]` SPL Token Program ID.
+    Deposit {
+        /// Amount of collateral (smallest unit) to deposit.
+        amount: u64,
+    },
+
+    /// Mints stablecoin against the position's collateral. Fails unless the position
+    /// stays at or above the collateral ratio afterwards.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Position owner.
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Position PDA.
+    /// 3. `[writable]` Stablecoin SPL Token Mint account.
+    /// 4. `[writable]` Destination stablecoin token account.
+    /// 5. `[]` Program authority PDA, the mint authority.
+    /// 6. `[]` SPL Token Program ID.
+    /// 7. `[]` Price PDA.
+    /// 8. `[]` Blacklist entry PDA (`[b"blacklist", config, owner]`) of the destination's owner.
+    ///    Minting fails if it exists.
+    MintAgainstCollateral {
+        /// Amount of stablecoin (smallest unit) to mint.
+        amount: u64,
+    },
+
+    /// Burns stablecoin to pay down a position's debt, stability fees included. Anyone can
+    /// repay any position. Accrued fees are minted to the surplus account as repayments
+    /// come in. Refused after `GlobalSettle`, where `SettlePosition` clears the debt instead.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Payer (owner of the source stablecoin token account).
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Position PDA.
+    /// 3. `[writable]` Source stablecoin token account.
+    /// 4. `[writable]` Stablecoin SPL Token Mint account.
+    /// 5. `[]` SPL Token Program ID.
+    /// 6. `[writable]` Surplus PDA.
+    /// 7. `[]` Program authority PDA, the mint authority.
+    Repay {
+        /// Amount of stablecoin (smallest unit) to burn, at most the position's debt.
+        amount: u64,
+    },
+
+    /// Moves collateral from the vault back to the owner. Fails unless the position
+    /// stays at or above the collateral ratio afterwards.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Position owner.
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Position PDA.
+    /// 3. `[writable]` Collateral vault PDA.
+    /// 4. `[writable]` Destination collateral token account.
+    /// 5. `[]` Collateral SPL Token Mint account.
+    /// 6. `[]` Program authority PDA, the vault owner.
+    /// 7. `[]` SPL Token Program ID.
+    /// 8. `[]` Price PDA.
+    Withdraw {
+        /// Amount of collateral (smallest unit) to withdraw.
+        amount: u64,
+    },
+
+    /// Sets the liquidation parameters and creates the bad debt PDA on first use.
+    /// Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Current Admin account (pays rent for the bad debt PDA).
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Bad debt PDA (`[b"bad_debt", config]`).
+    /// 3. `[]` System program ID.
+    ConfigureLiquidation {
+        /// Ratio below which positions can be liquidated (above 100%, at most the collateral ratio).
+        liquidation_threshold_bps: u16,
+        /// Liquidator bonus on seized collateral.
+        liquidation_discount_bps: u16,
+        /// Largest share of a position's debt repaid per liquidation.
+        close_factor_bps: u16,
+    },
+
+    /// Repays part of an unhealthy position's debt and seizes its collateral at a discount.
+    /// Callable by anyone once the position is below the liquidation threshold. The repay
+    /// amount is capped by the close factor. Debt left after all collateral is seized is
+    /// written off to the bad debt account.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Liquidator (owner of the source stablecoin account).
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Position PDA being liquidated.
+    /// 3. `[writable]` Liquidator's stablecoin token account (burned from).
+    /// 4. `[writable]` Stablecoin SPL Token Mint account.
+    /// 5. `[writable]` Collateral vault PDA.
+    /// 6. `[writable]` Liquidator's collateral token account (receives seized collateral).
+    /// 7. `[]` Collateral SPL Token Mint account.
+    /// 8. `[]` Program authority PDA, the vault owner.
+    /// 9. `[]` SPL Token Program ID.
+    /// 10. `[]` Price PDA.
+    /// 11. `[writable]` Bad debt PDA.
+    /// 12. `[writable]` Surplus PDA (receives accrued stability fees).
+    Liquidate {
+        /// The position to liquidate (must match account 2).
+        position: Pubkey,
+        /// Stablecoin the liquidator offers to repay.
+        repay_amount: u64,
+    },
+
+    /// Sets the limits collateral instructions apply to the price feed.
+    /// Only callable by the current admin. Timelocked.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account.
+    /// 1. `[writable]` Config account.
+    ConfigureOracle {
+        /// Oldest price, in seconds, that is still accepted.
+        max_price_age_secs: i64,
+        /// Widest confidence interval accepted, as a share of the price in basis points.
+        max_confidence_bps: u16,
+    },
+
+    /// Whitelists a price publisher. Only callable by the current admin. Timelocked.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Current Admin account (pays rent for the publisher PDA).
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Publisher PDA (`[b"publisher", config, publisher]`), must not exist yet.
+    /// 3. `[]` System program ID.
+    AddPublisher {
+        /// The publisher's Ed25519 public key.
+        publisher: Pubkey,
+    },
+
+    /// Removes a price publisher from the whitelist, closing its PDA.
+    /// Only callable by the current admin. Timelocked.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Current Admin account (receives the PDA's rent).
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Publisher PDA (`[b"publisher", config, publisher]`).
+    RemovePublisher {
+        /// The publisher's Ed25519 public key.
+        publisher: Pubkey,
+    },
+
+    /// Sets the master minter, who manages minters and their allowances.
+    /// Only callable by the current admin. Timelocked.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account.
+    /// 1. `[writable]` Config account.
+    SetMasterMinter {
+        /// The new master minter.
+        master_minter: Pubkey,
+    },
+
+    /// Adds a minter or replaces its remaining allowance. Only callable by the master minter.
+    /// Timelocked.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Master minter (pays rent for a new minter PDA).
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Minter PDA (`[b"minter", config, minter]`), created on first use.
+    /// 3. `[]` System program ID.
+    ConfigureMinter {
+        /// The minter's wallet.
+        minter: Pubkey,
+        /// Stablecoin the minter may mint from now on (not added to the previous allowance).
+        allowance: u64,
+    },
+
+    /// Removes a minter, closing its PDA. Only callable by the master minter. Timelocked.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Master minter (receives the PDA's rent).
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Minter PDA (`[b"minter", config, minter]`).
+    RemoveMinter {
+        /// The minter's wallet.
+        minter: Pubkey,
+    },
+
+    /// Sets the blacklister. Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account.
+    /// 1. `[writable]` Config account.
+    SetBlacklister {
+        /// The new blacklister.
+        blacklister: Pubkey,
+    },
+
+    /// Blacklists a holder and freezes one of its stablecoin accounts. Call once per token
+    /// account of the holder; the first call creates the blacklist entry.
+    /// Only callable by the blacklister. The stablecoin mint's freeze authority must be the
+    /// program authority PDA.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Blacklister (pays rent for a new blacklist entry).
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Blacklist entry PDA (`[b"blacklist", config, holder]`).
+    /// 3. `[writable]` Holder's stablecoin token account to freeze.
+    /// 4. `[]` Stablecoin SPL Token Mint account.
+    /// 5. `[]` Program authority PDA (`[b"authority", config]`), the freeze authority.
+    /// 6. `[]` SPL Token Program ID.
+    /// 7. `[]` System program ID.
+    Blacklist {
+        /// The holder wallet to blacklist.
+        holder: Pubkey,
+    },
+
+    /// Thaws one of a blacklisted holder's stablecoin accounts. Thawing the last frozen
+    /// account closes the blacklist entry. Only callable by the blacklister.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Blacklister (receives the entry's rent when it closes).
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Blacklist entry PDA.
+    /// 3. `[writable]` Holder's frozen stablecoin token account.
+    /// 4. `[]` Stablecoin SPL Token Mint account.
+    /// 5. `[]` Program authority PDA (`[b"authority", config]`), the freeze authority.
+    /// 6. `[]` SPL Token Program ID.
+    Unblacklist,
+
+    /// Sets the pauser. Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account.
+    /// 1. `[writable]` Config account.
+    SetPauser {
+        /// The new pauser.
+        pauser: Pubkey,
+    },
+
+    /// Pauses the program: minting, position changes, liquidations, price updates, swaps,
+    /// redemptions, flash mints and settlement payouts fail until `Unpause`. Admin and
+    /// compliance instructions, and `GlobalSettle`, keep working.
+    /// Only callable by the pauser.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Pauser.
+    /// 1. `[writable]` Config account.
+    Pause,
+
+    /// Lifts a pause. Only callable by the pauser.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Pauser.
+    /// 1. `[writable]` Config account.
+    Unpause,
+
+    /// Sets the maximum supply of the stablecoin mint (zero for uncapped).
+    /// Only callable by the current admin. Timelocked.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account.
+    /// 1. `[writable]` Config account.
+    SetSupplyCap {
+        /// Maximum supply in base units.
+        supply_cap: u64,
+    },
+
+    /// Sets the reserve attestor and how fresh its attestations must be for `MintTo`.
+    /// Setting the default pubkey turns reserve gating off. Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account.
+    /// 1. `[writable]` Config account.
+    ConfigureAttestor {
+        /// The attestor's key.
+        attestor: Pubkey,
+        /// Oldest attestation, in seconds since its as-of time, that `MintTo` accepts.
+        max_attestation_age_secs: i64,
+    },
+
+    /// Records a new reserve attestation. Only callable by the attestor.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Attestor (pays rent for the attestation PDA).
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Attestation PDA (`[b"attestation", config, attestation_count]`).
+    /// 3. `[]` System program ID.
+    /// 4. `[]` Previous attestation PDA (`attestation_count - 1`). Omitted for the first one.
+    PostReserveAttestation {
+        /// Attested reserves, in stablecoin base units.
+        reserve_amount: u64,
+        /// Unix timestamp the reserves were measured at. Must be newer than the previous one.
+        as_of: i64,
+        /// Hash of the audit report.
+        report_hash: [u8; 32],
+    },
+
+    /// Whitelists a reserve token for the peg stability module, or updates its fees,
+    /// ceilings and treasury. Creates the module and its vault on first use.
+    /// Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Current Admin account (pays rent for the module and vault PDAs).
+    /// 1. `[]` Config account.
+    /// 2. `[]` Stablecoin SPL Token Mint account.
+    /// 3. `[]` Reserve SPL Token Mint account (same decimals as the stablecoin).
+    /// 4. `[writable]` PSM PDA (`[b"psm", config, reserve_mint]`).
+    /// 5. `[writable]` PSM vault PDA (`[b"psm_vault", config, reserve_mint]`).
+    /// 6. `[]` Treasury stablecoin token account (receives fees).
+    /// 7. `[]` Program authority PDA (`[b"authority", config]`), the vault owner.
+    /// 8. `[]` SPL Token Program ID.
+    /// 9. `[]` System program ID.
+    ConfigurePsm {
+        /// Fee on `SwapIn`, in basis points.
+        fee_in_bps: u16,
+        /// Fee on `SwapOut`, in basis points.
+        fee_out_bps: u16,
+        /// Largest reserve balance `SwapIn` may build up.
+        swap_in_ceiling: u64,
+        /// Largest net outflow `SwapOut` may pay out.
+        swap_out_ceiling: u64,
+    },
+
+    /// Swaps reserve tokens for stablecoin 1:1: the reserves go to the vault, the signer
+    /// gets `amount - fee` newly minted stablecoin and the treasury gets `fee`.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` User.
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` PSM PDA.
+    /// 3. `[writable]` User's reserve token account (source).
+    /// 4. `[writable]` PSM vault PDA.
+    /// 5. `[writable]` Stablecoin SPL Token Mint account.
+    /// 6. `[writable]` User's stablecoin token account (destination).
+    /// 7. `[writable]` Treasury stablecoin token account.
+    /// 8. `[]` Program authority PDA (`[b"authority", config]`).
+    /// 9. `[]` SPL Token Program ID.
+    /// 10. `[]` Blacklist entry PDA (`[b"blacklist", config, owner]`) of the destination's owner.
+    ///     Minting fails if it exists.
+    SwapIn {
+        /// Reserve tokens to swap in.
+        amount: u64,
+    },
+
+    /// Swaps stablecoin for reserve tokens 1:1: `fee` of the stablecoin goes to the
+    /// treasury, the rest is burned and the signer gets `amount - fee` reserve tokens.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` User.
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` PSM PDA.
+    /// 3. `[writable]` User's stablecoin token account (source).
+    /// 4. `[writable]` Stablecoin SPL Token Mint account.
+    /// 5. `[writable]` Treasury stablecoin token account.
+    /// 6. `[writable]` PSM vault PDA.
+    /// 7. `[writable]` User's reserve token account (destination).
+    /// 8. `[]` Program authority PDA (`[b"authority", config]`).
+    /// 9. `[]` SPL Token Program ID.
+    SwapOut {
+        /// Stablecoin to swap out.
+        amount: u64,
+    },
+
+    /// Sets the redemption operator and request timeout, and creates the redemption
+    /// escrow on first use. Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Current Admin account (pays rent for the escrow PDA).
+    /// 1. `[writable]` Config account.
+    /// 2. `[]` Stablecoin SPL Token Mint account.
+    /// 3. `[writable]` Redemption escrow PDA (`[b"redemption_escrow", config]`).
+    /// 4. `[]` Program authority PDA (`[b"authority", config]`), the escrow owner.
+    /// 5. `[]` SPL Token Program ID.
+    /// 6. `[]` System program ID.
+    ConfigureRedemptions {
+        /// The operator who fulfills and rejects requests.
+        operator: Pubkey,
+        /// Seconds after which a holder can cancel a pending request.
+        timeout_secs: i64,
+    },
+
+    /// Moves stablecoin into the redemption escrow and opens a redemption request.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Holder (pays rent for the request PDA).
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Redemption request PDA (`[b"redemption", config, redemption_count]`).
+    /// 3. `[writable]` Holder's stablecoin token account.
+    /// 4. `[writable]` Redemption escrow PDA.
+    /// 5. `[]` SPL Token Program ID.
+    /// 6. `[]` System program ID.
+    RequestRedemption {
+        /// Stablecoin to redeem.
+        amount: u64,
+        /// Hash of the off-chain payout instructions.
+        payout_ref_hash: [u8; 32],
+    },
+
+    /// Burns a pending request's escrowed tokens after paying out off-chain.
+    /// Only callable by the redemption operator.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Redemption operator.
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Redemption request PDA.
+    /// 3. `[writable]` Redemption escrow PDA.
+    /// 4. `[writable]` Stablecoin SPL Token Mint account.
+    /// 5. `[]` Program authority PDA (`[b"authority", config]`).
+    /// 6. `[]` SPL Token Program ID.
+    FulfillRedemption {
+        /// Hash of the bank transfer reference.
+        bank_ref_hash: [u8; 32],
+    },
+
+    /// Returns a pending request's escrowed tokens to the holder.
+    /// Only callable by the redemption operator.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Redemption operator.
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Redemption request PDA.
+    /// 3. `[writable]` Redemption escrow PDA.
+    /// 4. `[writable]` Holder's stablecoin token account recorded in the request.
+    /// 5. `[]` Program authority PDA (`[b"authority", config]`).
+    /// 6. `[]` SPL Token Program ID.
+    RejectRedemption,
+
+    /// Returns a pending request's escrowed tokens once it has timed out.
+    /// Only callable by the holder.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Holder.
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Redemption request PDA.
+    /// 3. `[writable]` Redemption escrow PDA.
+    /// 4. `[writable]` Holder's stablecoin token account recorded in the request.
+    /// 5. `[]` Program authority PDA (`[b"authority", config]`).
+    /// 6. `[]` SPL Token Program ID.
+    CancelRedemption,
+
+    /// Sets the timelock delay and guardian. Only callable by the current admin. Timelocked.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Current Admin account.
+    /// 1. `[writable]` Config account.
+    ConfigureTimelock {
+        /// Seconds between queueing and executing an action (at most 30 days).
+        delay_secs: i64,
+        /// The key allowed to cancel queued actions.
+        guardian: Pubkey,
+    },
+
+    /// Queues a timelocked instruction to run once the configured delay has passed.
+    /// Only callable by the role holder the instruction requires (the master minter for
+    /// `ConfigureMinter` and `RemoveMinter`, the admin otherwise).
+    ///
+    /// "Timelocked" instructions are the ones for which `is_timelocked` is true. While the
+    /// timelock delay is non-zero they can't be sent directly.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Proposer (pays rent for the action PDA).
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Action PDA (`[b"action", config, action_count]`).
+    /// 3. `[]` System program ID.
+    QueueAction {
+        /// Borsh-serialized `StablecoinInstruction` to run.
+        instruction_data: Vec<u8>,
+    },
+
+    /// Runs a queued action once its ETA has passed. Anyone can execute it; the wrapped
+    /// instruction's role account doesn't need to sign, unless it pays rent.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Executor.
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Action PDA.
+    ///
+    /// The accounts the wrapped instruction expects follow, in its order.
+    ExecuteAction,
+
+    /// Cancels a queued action. Only callable by the guardian.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Guardian.
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Action PDA.
+    CancelAction,
+
+    /// Creates the admin multisig of a config. It takes over the admin role once the admin
+    /// sets its signer PDA (`[b"multisig_signer", multisig]`) as admin with `SetAdmin`; fund
+    /// the signer PDA with SOL for instructions it pays rent for.
+    /// Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Admin Account (pays rent for the multisig PDA).
+    /// 1. `[]` Config account.
+    /// 2. `[writable]` Multisig PDA (`[b"multisig", config]`).
+    /// 3. `[]` System program ID.
+    CreateMultisig {
+        /// Signer keys (1 to 11, no duplicates).
+        signers: Vec<Pubkey>,
+        /// Approvals needed to execute a proposal.
+        threshold: u8,
+    },
+
+    /// Replaces the signers and threshold of a multisig, invalidating open proposals.
+    /// Only callable by the multisig itself, through an executed proposal.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Multisig signer PDA.
+    /// 1. `[writable]` Multisig PDA.
+    ChangeMultisig {
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    },
+
+    /// Proposes an instruction for the multisig to sign, counting as the proposer's approval.
+    /// Only callable by a multisig signer.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Proposer (pays rent for the proposal PDA).
+    /// 1. `[writable]` Multisig PDA.
+    /// 2. `[writable]` Proposal PDA (`[b"proposal", multisig, proposal_count]`).
+    /// 3. `[]` System program ID.
+    Propose {
+        /// Program the instruction is sent to.
+        program_id: Pubkey,
+        /// The instruction's accounts; the multisig signer PDA is marked as a signer.
+        accounts: Vec<ProposalAccount>,
+        /// The instruction data, e.g. a Borsh-serialized `StablecoinInstruction`.
+        data: Vec<u8>,
+    },
+
+    /// Approves a proposal. Only callable by a multisig signer.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Multisig signer.
+    /// 1. `[]` Multisig PDA.
+    /// 2. `[writable]` Proposal PDA.
+    Approve,
+
+    /// Executes a proposal with enough approvals, signing as the multisig signer PDA.
+    /// Callable by anyone, once.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Executor.
+    /// 1. `[]` Multisig PDA.
+    /// 2. `[writable]` Proposal PDA.
+    /// 3. `[]` The proposal's target program.
+    ///
+    /// The proposal's accounts follow, in order.
+    Execute,
+
+    /// Sets the annual stability fee on collateral debt, accruing the old rate up to now
+    /// first. Creates the surplus PDA that fees are minted to on first use.
+    /// Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Admin Account (pays rent for the surplus PDA).
+    /// 1. `[writable]` Config account.
+    /// 2. `[]` Stablecoin SPL Token Mint account.
+    /// 3. `[writable]` Surplus PDA (`[b"surplus", config]`).
+    /// 4. `[]` Program authority PDA, the surplus owner.
+    /// 5. `[]` SPL Token Program ID.
+    /// 6. `[]` System program ID.
+    ConfigureStabilityFee {
+        /// Annual fee in basis points (at most 10_000).
+        stability_fee_bps: u16,
+    },
+
+    /// Sets the annual savings rate, dripping the old rate up to now first. Creates the
+    /// savings vault and share mint on first use. The surplus funds the rate, so run
+    /// `ConfigureStabilityFee` first to create it. Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Admin Account (pays rent for the new PDAs).
+    /// 1. `[writable]` Config account.
+    /// 2. `[]` Stablecoin SPL Token Mint account.
+    /// 3. `[writable]` Surplus PDA (funds the savings rate).
+    /// 4. `[writable]` Savings vault PDA (`[b"savings_vault", config]`).
+    /// 5. `[writable]` Savings share mint PDA (`[b"savings_shares", config]`).
+    /// 6. `[]` Program authority PDA, the vault owner and share mint authority.
+    /// 7. `[]` SPL Token Program ID.
+    /// 8. `[]` System program ID.
+    ConfigureSavings {
+        /// Annual savings rate in basis points (at most 10_000).
+        savings_rate_bps: u16,
+    },
+
+    /// Deposits stablecoin into the savings vault for share tokens at the current share
+    /// price, after a drip.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Depositor (owner of the source stablecoin token account).
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Source stablecoin token account.
+    /// 3. `[writable]` Destination share token account.
+    /// 4. `[writable]` Surplus PDA.
+    /// 5. `[writable]` Savings vault PDA.
+    /// 6. `[writable]` Savings share mint PDA.
+    /// 7. `[]` Program authority PDA.
+    /// 8. `[]` SPL Token Program ID.
+    SavingsDeposit {
+        /// Amount of stablecoin (smallest unit) to deposit.
+        amount: u64,
+    },
+
+    /// Burns share tokens for their stablecoin value at the current share price, after a drip.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Holder (owner of the source share token account).
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Source share token account.
+    /// 3. `[writable]` Destination stablecoin token account.
+    /// 4. `[writable]` Surplus PDA.
+    /// 5. `[writable]` Savings vault PDA.
+    /// 6. `[writable]` Savings share mint PDA.
+    /// 7. `[]` Program authority PDA.
+    /// 8. `[]` SPL Token Program ID.
+    SavingsWithdraw {
+        /// Shares (smallest unit) to redeem.
+        shares: u64,
+    },
+
+    /// Pays the savings interest accrued since the last drip from the surplus into the
+    /// savings vault, raising the share price. Interest the surplus can't cover is skipped.
+    /// Callable by anyone.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` Config account.
+    /// 1. `[writable]` Surplus PDA.
+    /// 2. `[writable]` Savings vault PDA.
+    /// 3. `[]` Savings share mint PDA.
+    /// 4. `[]` Program authority PDA.
+    /// 5. `[]` SPL Token Program ID.
+    Drip,
+
+    /// Sets the flash mint ceiling, fee and treasury. Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Admin Account.
+    /// 1. `[writable]` Config account.
+    /// 2. `[]` Treasury stablecoin token account.
+    ConfigureFlashMint {
+        /// Largest flash mint (zero disables flash mints).
+        ceiling: u64,
+        /// Fee in basis points of the amount.
+        fee_bps: u16,
+    },
+
+    /// Mints stablecoin without collateral. A `FlashRepay` of the same amount against the
+    /// same config must follow later in the transaction, with no other `FlashMint` in
+    /// between; otherwise this instruction fails.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Borrower.
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Stablecoin SPL Token Mint account.
+    /// 3. `[writable]` Destination stablecoin token account.
+    /// 4. `[]` Program authority PDA, the mint authority.
+    /// 5. `[]` SPL Token Program ID.
+    /// 6. `[]` Instructions sysvar.
+    /// 7. `[]` Blacklist entry PDA (`[b"blacklist", config, owner]`) of the destination's owner.
+    ///    Minting fails if it exists.
+    FlashMint {
+        /// Amount of stablecoin (smallest unit) to mint, at most the ceiling.
+        amount: u64,
+    },
+
+    /// Burns a flash mint back and pays its fee to the treasury.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Payer (owner of the source stablecoin token account).
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Stablecoin SPL Token Mint account.
+    /// 3. `[writable]` Source stablecoin token account (amount plus fee).
+    /// 4. `[writable]` Treasury stablecoin token account.
+    /// 5. `[]` SPL Token Program ID.
+    FlashRepay {
+        /// The flash-minted amount (must match the `FlashMint`).
+        amount: u64,
+    },
+
+    /// Winds the stablecoin down: freezes the collateral price at the last price the
+    /// oracle accepted, stops the stability fee and disables minting, collateral price
+    /// updates and liquidations. Only callable by the current admin. Works while paused,
+    /// since winding down is often what follows a pause; the settlement payouts don't.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Admin Account.
+    /// 1. `[writable]` Config account.
+    /// 2. `[]` Price PDA.
+    GlobalSettle,
+
+    /// Moves the collateral covering a position's debt at the settlement price into the
+    /// settlement pool and clears the debt. The owner can then `Withdraw` the rest.
+    /// Callable by anyone after `GlobalSettle`, until the settlement is finalized.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` Config account.
+    /// 1. `[writable]` Position PDA.
+    SettlePosition,
+
+    /// Fixes the redemption rate once every position is settled: the settlement pool
+    /// over the stablecoin supply. Burns the fees held in the surplus PDA first, so only
+    /// holders' stablecoin has a claim on the pool. Only callable by the current admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Admin Account.
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Stablecoin SPL Token Mint account.
+    /// 3. `[writable]` Surplus PDA (`[b"surplus", config]`), empty if never created.
+    /// 4. `[]` Program authority PDA, the surplus owner.
+    /// 5. `[]` SPL Token Program ID.
+    FinalizeSettlement,
+
+    /// Burns stablecoin for its pro rata share of the settlement pool.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Holder (owner of the source stablecoin token account).
+    /// 1. `[writable]` Config account.
+    /// 2. `[writable]` Source stablecoin token account.
+    /// 3. `[writable]` Stablecoin SPL Token Mint account.
+    /// 4. `[writable]` Collateral vault PDA.
+    /// 5. `[writable]` Destination collateral token account.
+    /// 6. `[]` Collateral SPL Token Mint account.
+    /// 7. `[]` Program authority PDA, the vault owner.
+    /// 8. `[]` SPL Token Program ID.
+    RedeemSettled {
+        /// Amount of stablecoin (smallest unit) to redeem.
+        amount: u64,
+    },
+}
+
+impl StablecoinInstruction {
+    /// Whether the instruction changes admin, minter, supply cap, oracle or timelock
+    /// settings and so has to go through `QueueAction` / `ExecuteAction` while a timelock
+    /// delay is set.
+    pub fn is_timelocked(&self) -> bool {
+        matches!(
+            self,
+            StablecoinInstruction::SetAdmin { .. }
+                | StablecoinInstruction::ConfigureOracle { .. }
+                | StablecoinInstruction::AddPublisher { .. }
+                | StablecoinInstruction::RemovePublisher { .. }
+                | StablecoinInstruction::SetMasterMinter { .. }
+                | StablecoinInstruction::ConfigureMinter { .. }
+                | StablecoinInstruction::RemoveMinter { .. }
+                | StablecoinInstruction::SetSupplyCap { .. }
+                | StablecoinInstruction::ConfigureTimelock { .. }
+        )
+    }
+}
+
+
+// === src/error.rs ===
+use thiserror::Error;
+use solana_program::program_error::ProgramError;
+
+/// Custom errors for the stablecoin program.
+#[derive(Error, Debug, Copy, Clone, PartialEq)]
+pub enum StablecoinError {
+    #[error("Invalid Instruction")]
+    InvalidInstruction,
+    #[error("Not Rent Exempt")]
+    NotRentExempt,
+    #[error("Account Already Initialized")]
+    AlreadyInitialized,
+    #[error("Admin signature mismatch")]
+    AdminSignatureMismatch,
+    #[error("Mint account mismatch")]
+    MintAccountMismatch,
+    #[error("Account not initialized")]
+    NotInitialized,
+    #[error("Numerical overflow error")]
+    NumericalOverflow,
+    #[error("Program authority PDA mismatch")]
+    AuthorityMismatch,
+    #[error("Collateral has not been configured")]
+    CollateralNotConfigured,
+    #[error("Collateral mint mismatch")]
+    CollateralMintMismatch,
+    #[error("Invalid collateral ratio")]
+    InvalidCollateralRatio,
+    #[error("Position account mismatch")]
+    PositionMismatch,
+    #[error("Price account mismatch")]
+    PriceAccountMismatch,
+    #[error("Invalid or unset price")]
+    InvalidPrice,
+    #[error("Position would be below the collateral ratio")]
+    PositionUnhealthy,
+    #[error("Insufficient collateral in position")]
+    InsufficientCollateral,
+    #[error("Repay amount exceeds position debt")]
+    RepayExceedsDebt,
+    #[error("Invalid liquidation parameters")]
+    InvalidLiquidationParams,
+    #[error("Position is above the liquidation threshold")]
+    PositionNotLiquidatable,
+    #[error("Price is older than the maximum age")]
+    StalePrice,
+    #[error("Price confidence interval is too wide")]
+    PriceConfidenceTooWide,
+    #[error("Publisher is not whitelisted")]
+    PublisherNotWhitelisted,
+    #[error("Price attestation missing or invalid")]
+    InvalidPriceAttestation,
+    #[error("Signer is not the master minter")]
+    MasterMinterMismatch,
+    #[error("Signer is not a configured minter")]
+    MinterMismatch,
+    #[error("Mint amount exceeds the minter's allowance")]
+    MinterAllowanceExceeded,
+    #[error("Signer is not the blacklister")]
+    BlacklisterMismatch,
+    #[error("Blacklist entry account mismatch")]
+    BlacklistEntryMismatch,
+    #[error("Token account owner is blacklisted")]
+    Blacklisted,
+    #[error("Token account does not belong to the holder or the stablecoin mint")]
+    TokenAccountMismatch,
+    #[error("Signer is not the pauser")]
+    PauserMismatch,
+    #[error("Program is paused")]
+    ProgramPaused,
+    #[error("Mint would exceed the supply cap")]
+    SupplyCapExceeded,
+    #[error("Signer is not the attestor")]
+    AttestorMismatch,
+    #[error("Reserve attestation account mismatch")]
+    AttestationMismatch,
+    #[error("Reserve attestation is missing or too old")]
+    StaleAttestation,
+    #[error("Mint would exceed attested reserves")]
+    ReservesExceeded,
+    #[error("Invalid peg stability module parameters")]
+    InvalidPsmParams,
+    #[error("Peg stability module account mismatch")]
+    PsmMismatch,
+    #[error("Swap would exceed the peg stability module ceiling")]
+    PsmCeilingExceeded,
+    #[error("Redemptions have not been configured")]
+    RedemptionsNotConfigured,
+    #[error("Signer is not the redemption operator")]
+    RedemptionOperatorMismatch,
+    #[error("Redemption request account mismatch")]
+    RedemptionMismatch,
+    #[error("Redemption request is not pending")]
+    RedemptionNotPending,
+    #[error("Redemption request has not timed out yet")]
+    RedemptionNotExpired,
+    #[error("Instruction must be queued through the timelock")]
+    TimelockRequired,
+    #[error("Instruction cannot be timelocked")]
+    ActionNotTimelocked,
+    #[error("Timelocked action account mismatch")]
+    ActionMismatch,
+    #[error("Timelocked action is not queued")]
+    ActionNotQueued,
+    #[error("Timelocked action ETA has not passed")]
+    ActionNotReady,
+    #[error("Signer is not the guardian")]
+    GuardianMismatch,
+    #[error("Invalid timelock delay")]
+    InvalidTimelockDelay,
+    #[error("Invalid multisig signers or threshold")]
+    InvalidMultisigParams,
+    #[error("Multisig account mismatch")]
+    MultisigMismatch,
+    #[error("Signer is not a multisig signer")]
+    NotMultisigSigner,
+    #[error("Multisig proposal account mismatch")]
+    ProposalMismatch,
+    #[error("Multisig proposal is too large")]
+    ProposalTooLarge,
+    #[error("Multisig proposal already approved by this signer")]
+    ProposalAlreadyApproved,
+    #[error("Multisig proposal already executed")]
+    ProposalAlreadyExecuted,
+    #[error("Multisig proposal is from an older signer set")]
+    ProposalStale,
+    #[error("Multisig proposal does not have enough approvals")]
+    NotEnoughApprovals,
+    #[error("Invalid stability fee")]
+    InvalidStabilityFee,
+    #[error("Surplus account mismatch")]
+    SurplusMismatch,
+    #[error("Invalid savings rate")]
+    InvalidSavingsRate,
+    #[error("Savings are not configured")]
+    SavingsNotConfigured,
+    #[error("Savings account mismatch")]
+    SavingsMismatch,
+    #[error("Invalid flash mint parameters")]
+    InvalidFlashMintParams,
+    #[error("Flash mint exceeds the ceiling")]
+    FlashMintCeilingExceeded,
+    #[error("Flash mint has no matching FlashRepay")]
+    FlashRepayMissing,
+    #[error("Flash repay does not match the outstanding flash mint")]
+    FlashRepayMismatch,
+    #[error("Stablecoin has been globally settled")]
+    GloballySettled,
+    #[error("Stablecoin has not been globally settled")]
+    NotSettled,
+    #[error("Settlement is not finalized")]
+    SettlementNotFinalized,
+    #[error("Settlement is already finalized")]
+    SettlementFinalized,
+    #[error("Positions still hold unsettled debt")]
+    UnsettledDebt,
+}
+
+// Allow conversion from our custom error to the standard Solana ProgramError
+impl From<StablecoinError> for ProgramError {
+    fn from(e: StablecoinError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+// === src/processor.rs ===
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed}, // For CPI (Cross-Program Invocation)
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{
+        clock::Clock,
+        instructions::{load_current_index_checked, load_instruction_at_checked},
+        rent::Rent,
+        Sysvar,
+    },
+};
+use spl_token::{
+    instruction as token_instruction, // SPL Token program instructions
+    state::{Account as TokenAccount, Mint},
+};
+use crate::{
+    account_header::{grow_account, upgrade_body},
+    error::StablecoinError,
+    events::{emit, StablecoinEvent},
+    instruction::StablecoinInstruction,
+    math::{
+        accrue_rate_index, amount_for_shares, apply_bps, collateral_for_value, collateral_value,
+        debt_from_normalized, is_healthy, normalize_debt, savings_interest, settlement_payout,
+        settlement_skim, share_price_after_interest, shares_for_amount, BPS_DENOMINATOR, RAY,
+    },
+    oracle,
+    state::{
+        find_action_address, find_attestation_address, find_authority_address, find_bad_debt_address,
+        find_blacklist_address, find_minter_address, find_multisig_address, find_multisig_signer_address,
+        find_position_address, find_price_address, find_proposal_address, find_psm_address,
+        find_psm_vault_address, find_publisher_address, find_redemption_address,
+        find_redemption_escrow_address, find_savings_shares_address, find_savings_vault_address,
+        find_surplus_address, find_vault_address, AccountType, ActionStatus, BadDebtAccount,
+        BlacklistEntry, ConfigAccount, MinterAccount, MultisigAccount, MultisigProposal, Position,
+        PriceAccount, ProposalAccount, PsmAccount, PublisherAccount, RedemptionRequest, RedemptionStatus,
+        ReserveAttestation, TimelockAction, ACTION_SEED, ATTESTATION_SEED, AUTHORITY_SEED,
+        MAX_ACTION_DATA_LEN, MAX_MULTISIG_SIGNERS, MAX_PROPOSAL_ACCOUNTS, MAX_PROPOSAL_DATA_LEN,
+        MAX_TIMELOCK_DELAY_SECS, BAD_DEBT_SEED, BLACKLIST_SEED, CONFIG_V0_LEN, CURRENT_VERSION,
+        HEADER_LEN, MINTER_SEED, MULTISIG_SEED, MULTISIG_SIGNER_SEED, POSITION_SEED, PRICE_SEED,
+        PROPOSAL_SEED, PSM_SEED, PSM_VAULT_SEED, PUBLISHER_SEED, REDEMPTION_ESCROW_SEED,
+        REDEMPTION_SEED, SAVINGS_SHARES_SEED, SAVINGS_VAULT_SEED, SURPLUS_SEED, VAULT_SEED,
+    },
+};
+/// How a timelocked instruction was authorized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AuthorizedBy {
+    /// Sent directly, signed by the role holder. Only allowed while the timelock delay is zero.
+    Signer,
+    /// Run by `ExecuteAction` after the role holder queued it and the delay passed.
+    Timelock,
+}
+
+/// Processes instructions for the stablecoin admin program.
+pub struct Processor;
+impl Processor {
+    pub fn process(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        // Deserialize the instruction data using Borsh
+        let instruction = StablecoinInstruction::try_from_slice(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        // Route to the appropriate handler based on the instruction variant
+        match instruction {
+            StablecoinInstruction::Initialize { admin } => {
+                msg!("Instruction: Initialize");
+                Self::process_initialize(accounts, admin, program_id)
+            }
+            StablecoinInstruction::MintTo { amount } => {
+                msg!("Instruction: MintTo");
+                Self::process_mint_to(accounts, amount, program_id)
+            }
+            StablecoinInstruction::MigrateConfig => {
+                msg!("Instruction: MigrateConfig");
+                Self::process_migrate_config(accounts, program_id)
+            }
+            StablecoinInstruction::MigratePosition => {
+                msg!("Instruction: MigratePosition");
+                Self::process_migrate_position(accounts, program_id)
+            }
+            StablecoinInstruction::ConfigureCollateral { collateral_ratio_bps } => {
+                msg!("Instruction: ConfigureCollateral");
+                Self::process_configure_collateral(accounts, collateral_ratio_bps, program_id)
+            }
+            StablecoinInstruction::PostPrice { price, expo, conf, publish_time } => {
+                msg!("Instruction: PostPrice");
+                Self::process_post_price(accounts, price, expo, conf, publish_time, program_id)
+            }
+            StablecoinInstruction::OpenPosition => {
+                msg!("Instruction: OpenPosition");
+                Self::process_open_position(accounts, program_id)
+            }
+            StablecoinInstruction::Deposit { amount } => {
+                msg!("Instruction: Deposit");
+                Self::process_deposit(accounts, amount, program_id)
+            }
+            StablecoinInstruction::MintAgainstCollateral { amount } => {
+                msg!("Instruction: MintAgainstCollateral");
+                Self::process_mint_against_collateral(accounts, amount, program_id)
+            }
+            StablecoinInstruction::Repay { amount } => {
+                msg!("Instruction: Repay");
+                Self::process_repay(accounts, amount, program_id)
+            }
+            StablecoinInstruction::Withdraw { amount } => {
+                msg!("Instruction: Withdraw");
+                Self::process_withdraw(accounts, amount, program_id)
+            }
+            StablecoinInstruction::ConfigureLiquidation {
+                liquidation_threshold_bps,
+                liquidation_discount_bps,
+                close_factor_bps,
+            } => {
+                msg!("Instruction: ConfigureLiquidation");
+                Self::process_configure_liquidation(
+                    accounts,
+                    liquidation_threshold_bps,
+                    liquidation_discount_bps,
+                    close_factor_bps,
+                    program_id,
+                )
+            }
+            StablecoinInstruction::Liquidate { position, repay_amount } => {
+                msg!("Instruction: Liquidate");
+                Self::process_liquidate(accounts, position, repay_amount, program_id)
+            }
+            StablecoinInstruction::SetBlacklister { blacklister } => {
+                msg!("Instruction: SetBlacklister");
+                Self::process_set_blacklister(accounts, blacklister, program_id)
+            }
+            StablecoinInstruction::Blacklist { holder } => {
+                msg!("Instruction: Blacklist");
+                Self::process_blacklist(accounts, holder, program_id)
+            }
+            StablecoinInstruction::Unblacklist => {
+                msg!("Instruction: Unblacklist");
+                Self::process_unblacklist(accounts, program_id)
+            }
+            StablecoinInstruction::SetPauser { pauser } => {
+                msg!("Instruction: SetPauser");
+                Self::process_set_pauser(accounts, pauser, program_id)
+            }
+            StablecoinInstruction::Pause => {
+                msg!("Instruction: Pause");
+                Self::process_set_paused(accounts, true, program_id)
+            }
+            StablecoinInstruction::Unpause => {
+                msg!("Instruction: Unpause");
+                Self::process_set_paused(accounts, false, program_id)
+            }
+            StablecoinInstruction::ConfigureAttestor { attestor, max_attestation_age_secs } => {
+                msg!("Instruction: ConfigureAttestor");
+                Self::process_configure_attestor(accounts, attestor, max_attestation_age_secs, program_id)
+            }
+            StablecoinInstruction::PostReserveAttestation { reserve_amount, as_of, report_hash } => {
+                msg!("Instruction: PostReserveAttestation");
+                Self::process_post_reserve_attestation(accounts, reserve_amount, as_of, report_hash, program_id)
+            }
+            StablecoinInstruction::ConfigurePsm {
+                fee_in_bps,
+                fee_out_bps,
+                swap_in_ceiling,
+                swap_out_ceiling,
+            } => {
+                msg!("Instruction: ConfigurePsm");
+                Self::process_configure_psm(
+                    accounts,
+                    fee_in_bps,
+                    fee_out_bps,
+                    swap_in_ceiling,
+                    swap_out_ceiling,
+                    program_id,
+                )
+            }
+            StablecoinInstruction::SwapIn { amount } => {
+                msg!("Instruction: SwapIn");
+                Self::process_swap_in(accounts, amount, program_id)
+            }
+            StablecoinInstruction::SwapOut { amount } => {
+                msg!("Instruction: SwapOut");
+                Self::process_swap_out(accounts, amount, program_id)
+            }
+            StablecoinInstruction::ConfigureRedemptions { operator, timeout_secs } => {
+                msg!("Instruction: ConfigureRedemptions");
+                Self::process_configure_redemptions(accounts, operator, timeout_secs, program_id)
+            }
+            StablecoinInstruction::RequestRedemption { amount, payout_ref_hash } => {
+                msg!("Instruction: RequestRedemption");
+                Self::process_request_redemption(accounts, amount, payout_ref_hash, program_id)
+            }
+            StablecoinInstruction::FulfillRedemption { bank_ref_hash } => {
+                msg!("Instruction: FulfillRedemption");
+                Self::process_fulfill_redemption(accounts, bank_ref_hash, program_id)
+            }
+            StablecoinInstruction::RejectRedemption => {
+                msg!("Instruction: RejectRedemption");
+                Self::process_return_redemption(accounts, RedemptionStatus::Rejected, program_id)
+            }
+            StablecoinInstruction::CancelRedemption => {
+                msg!("Instruction: CancelRedemption");
+                Self::process_return_redemption(accounts, RedemptionStatus::Cancelled, program_id)
+            }
+            StablecoinInstruction::QueueAction { instruction_data } => {
+                msg!("Instruction: QueueAction");
+                Self::process_queue_action(accounts, instruction_data, program_id)
+            }
+            StablecoinInstruction::ExecuteAction => {
+                msg!("Instruction: ExecuteAction");
+                Self::process_execute_action(accounts, program_id)
+            }
+            StablecoinInstruction::CancelAction => {
+                msg!("Instruction: CancelAction");
+                Self::process_cancel_action(accounts, program_id)
+            }
+            StablecoinInstruction::CreateMultisig { signers, threshold } => {
+                msg!("Instruction: CreateMultisig");
+                Self::process_create_multisig(accounts, signers, threshold, program_id)
+            }
+            StablecoinInstruction::ChangeMultisig { signers, threshold } => {
+                msg!("Instruction: ChangeMultisig");
+                Self::process_change_multisig(accounts, signers, threshold, program_id)
+            }
+            StablecoinInstruction::Propose { program_id: target_program_id, accounts: proposal_accounts, data } => {
+                msg!("Instruction: Propose");
+                Self::process_propose(accounts, target_program_id, proposal_accounts, data, program_id)
+            }
+            StablecoinInstruction::Approve => {
+                msg!("Instruction: Approve");
+                Self::process_approve(accounts, program_id)
+            }
+            StablecoinInstruction::Execute => {
+                msg!("Instruction: Execute");
+                Self::process_execute(accounts, program_id)
+            }
+            StablecoinInstruction::ConfigureStabilityFee { stability_fee_bps } => {
+                msg!("Instruction: ConfigureStabilityFee");
+                Self::process_configure_stability_fee(accounts, stability_fee_bps, program_id)
+            }
+            StablecoinInstruction::ConfigureSavings { savings_rate_bps } => {
+                msg!("Instruction: ConfigureSavings");
+                Self::process_configure_savings(accounts, savings_rate_bps, program_id)
+            }
+            StablecoinInstruction::SavingsDeposit { amount } => {
+                msg!("Instruction: SavingsDeposit");
+                Self::process_savings_deposit(accounts, amount, program_id)
+            }
+            StablecoinInstruction::SavingsWithdraw { shares } => {
+                msg!("Instruction: SavingsWithdraw");
+                Self::process_savings_withdraw(accounts, shares, program_id)
+            }
+            StablecoinInstruction::Drip => {
+                msg!("Instruction: Drip");
+                Self::process_drip(accounts, program_id)
+            }
+            StablecoinInstruction::ConfigureFlashMint { ceiling, fee_bps } => {
+                msg!("Instruction: ConfigureFlashMint");
+                Self::process_configure_flash_mint(accounts, ceiling, fee_bps, program_id)
+            }
+            StablecoinInstruction::FlashMint { amount } => {
+                msg!("Instruction: FlashMint");
+                Self::process_flash_mint(accounts, amount, program_id)
+            }
+            StablecoinInstruction::FlashRepay { amount } => {
+                msg!("Instruction: FlashRepay");
+                Self::process_flash_repay(accounts, amount, program_id)
+            }
+            StablecoinInstruction::GlobalSettle => {
+                msg!("Instruction: GlobalSettle");
+                Self::process_global_settle(accounts, program_id)
+            }
+            StablecoinInstruction::SettlePosition => {
+                msg!("Instruction: SettlePosition");
+                Self::process_settle_position(accounts, program_id)
+            }
+            StablecoinInstruction::FinalizeSettlement => {
+                msg!("Instruction: FinalizeSettlement");
+                Self::process_finalize_settlement(accounts, program_id)
+            }
+            StablecoinInstruction::RedeemSettled { amount } => {
+                msg!("Instruction: RedeemSettled");
+                Self::process_redeem_settled(accounts, amount, program_id)
+            }
+            // Sent directly by the role holder; refused while a timelock delay is set
+            instruction => Self::process_timelocked(accounts, instruction, AuthorizedBy::Signer, program_id),
+        }
+    }
+
+    /// Routes the timelocked instructions, either sent directly (`AuthorizedBy::Signer`) or
+    /// run by `ExecuteAction` (`AuthorizedBy::Timelock`).
+    fn process_timelocked(
+        accounts: &[AccountInfo],
+        instruction: StablecoinInstruction,
+        authorized_by: AuthorizedBy,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        match instruction {
+            StablecoinInstruction::SetAdmin { new_admin } => {
+                msg!("Instruction: SetAdmin");
+                Self::process_set_admin(accounts, new_admin, authorized_by, program_id)
+            }
+            StablecoinInstruction::ConfigureOracle { max_price_age_secs, max_confidence_bps } => {
+                msg!("Instruction: ConfigureOracle");
+                Self::process_configure_oracle(
+                    accounts,
+                    max_price_age_secs,
+                    max_confidence_bps,
+                    authorized_by,
+                    program_id,
+                )
+            }
+            StablecoinInstruction::AddPublisher { publisher } => {
+                msg!("Instruction: AddPublisher");
+                Self::process_add_publisher(accounts, publisher, authorized_by, program_id)
+            }
+            StablecoinInstruction::RemovePublisher { publisher } => {
+                msg!("Instruction: RemovePublisher");
+                Self::process_remove_publisher(accounts, publisher, authorized_by, program_id)
+            }
+            StablecoinInstruction::SetMasterMinter { master_minter } => {
+                msg!("Instruction: SetMasterMinter");
+                Self::process_set_master_minter(accounts, master_minter, authorized_by, program_id)
+            }
+            StablecoinInstruction::ConfigureMinter { minter, allowance } => {
+                msg!("Instruction: ConfigureMinter");
+                Self::process_configure_minter(accounts, minter, allowance, authorized_by, program_id)
+            }
+            StablecoinInstruction::RemoveMinter { minter } => {
+                msg!("Instruction: RemoveMinter");
+                Self::process_remove_minter(accounts, minter, authorized_by, program_id)
+            }
+            StablecoinInstruction::SetSupplyCap { supply_cap } => {
+                msg!("Instruction: SetSupplyCap");
+                Self::process_set_supply_cap(accounts, supply_cap, authorized_by, program_id)
+            }
+            StablecoinInstruction::ConfigureTimelock { delay_secs, guardian } => {
+                msg!("Instruction: ConfigureTimelock");
+                Self::process_configure_timelock(accounts, delay_secs, guardian, authorized_by, program_id)
+            }
+            _ => {
+                msg!("Error: Instruction cannot be timelocked");
+                Err(StablecoinError::ActionNotTimelocked.into())
+            }
+        }
+    }
+
+    /// Processes the Initialize instruction.
+    fn process_initialize(
+        accounts: &[AccountInfo],
+        admin: Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Payer (Signer, Writable) - Not directly used here, but needed for tx fee
+        let _payer_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable) - The account to initialize
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Mint Account (Readonly) - The SPL Mint this program manages
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 3: System Program (Readonly) - Needed for rent check
+        let _system_program = next_account_info(account_info_iter)?;
+        // Account 4: Rent Sysvar (Readonly) - To check for rent exemption
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+
+        // Security Check: Ensure the config account is owned by *this* program.
+        // ETH Dev Analogy: This is inherent in Solidity as code+storage are one.
+        // In Solana, you must verify account ownership.
+        if config_account.owner != program_id {
+            msg!("Error: Config account not owned by program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Security Check: Ensure the config account is rent-exempt.
+        // ETH Dev Analogy: Rent is Solana's storage cost mechanism, different from gas.
+        if !rent.is_exempt(config_account.lamports(), config_account.data_len()) {
             msg!("Error: Config account not rent exempt");
             return Err(StablecoinError::NotRentExempt.into());
         }
 
-        // Deserialize config account data to check if already initialized
-        let mut config_data = ConfigAccount::unpack_unchecked(&config_account.data.borrow())?;
-        if config_data.is_initialized() {
-            msg!("Error: Config account already initialized");
-            return Err(StablecoinError::AlreadyInitialized.into());
+        // Deserialize config account data to check if already initialized
+        let mut config_data = ConfigAccount::unpack_unchecked(&config_account.data.borrow())?;
+        if config_data.is_initialized() {
+            msg!("Error: Config account already initialized");
+            return Err(StablecoinError::AlreadyInitialized.into());
+        }
+
+        // Initialize the state
+        config_data.is_initialized = true;
+        config_data.admin = admin;
+        config_data.mint_account = *mint_account_info.key;
+
+        // Serialize the updated state back into the account
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Config account initialized. Admin: {}, Mint: {}", admin, mint_account_info.key);
+        Ok(())
+    }
+
+    /// Processes the MintTo instruction.
+    fn process_mint_to(
+        accounts: &[AccountInfo],
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Minter Account (Signer) - Must sign the transaction
+        let minter_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable) - Read admin & mint info
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Mint Account (Writable) - The SPL Mint to mint from
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 3: Destination Token Account (Writable) - Recipient's ATA
+        let destination_account = next_account_info(account_info_iter)?;
+        // Account 4: SPL Token Program ID (Readonly) - Program to invoke via CPI
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 5: Program Authority PDA (Readonly) - The mint authority
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 6: Minter PDA (Writable) - Holds the minter's remaining allowance
+        let minter_pda_account = next_account_info(account_info_iter)?;
+        // Account 7: Blacklist Entry PDA (Readonly) - Of the destination's owner
+        let blacklist_account = next_account_info(account_info_iter)?;
+        // Account 8: Latest Reserve Attestation PDA (Readonly)
+        let attestation_account = next_account_info(account_info_iter)?;
+
+        // Basic validation
+        if !minter_account.is_signer {
+            msg!("Error: Minter signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if config_account.owner != program_id {
+             msg!("Error: Config account not owned by program");
+             return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Deserialize config state
+        let config_data = ConfigAccount::unpack(&config_account.data.borrow())?;
+        if !config_data.is_initialized() {
+             msg!("Error: Config account not initialized");
+             return Err(StablecoinError::NotInitialized.into());
+        }
+
+        // Security Check: Verify the signer is a minter of this config with enough allowance left
+        // ETH Dev Analogy: Similar to an `onlyMinter` modifier plus `minterAllowed[msg.sender]`.
+        let mut minter_data = Self::load_minter(minter_pda_account, config_account, program_id)?;
+        if minter_data.minter != *minter_account.key {
+            msg!("Error: Signer is not the minter of this minter account");
+            return Err(StablecoinError::MinterMismatch.into());
+        }
+        if amount > minter_data.allowance {
+            msg!("Error: Mint amount {} exceeds remaining allowance {}", amount, minter_data.allowance);
+            return Err(StablecoinError::MinterAllowanceExceeded.into());
+        }
+
+        // Security Check: Verify the passed mint account matches the one in config
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+
+        Self::check_token_program(token_program_info)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_not_settled(&config_data)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        Self::check_supply_cap(&config_data, mint_account_info, amount)?;
+        Self::check_reserves(&config_data, config_account, mint_account_info, attestation_account, amount, program_id)?;
+
+        // Compliance Check: Refuse to mint to a blacklisted owner
+        let destination_data = TokenAccount::unpack(&destination_account.data.borrow())?;
+        Self::check_not_blacklisted(blacklist_account, config_account, &destination_data.owner, program_id)?;
+
+        // Perform the minting via Cross-Program Invocation (CPI)
+        // ETH Dev Analogy: This is like calling `IERC20(tokenAddress).mint(...)`
+        msg!("Invoking SPL Token program to mint {} tokens", amount);
+        Self::mint_stablecoin(
+            token_program_info,
+            mint_account_info,
+            destination_account,
+            authority_account,
+            config_account,
+            authority_bump,
+            amount,
+        )?;
+
+        minter_data.allowance -= amount;
+        MinterAccount::pack(minter_data.clone(), &mut minter_pda_account.data.borrow_mut())?;
+
+        msg!("Mint successful. Remaining allowance: {}", minter_data.allowance);
+        Ok(())
+    }
+
+     /// Processes the SetAdmin instruction.
+    fn process_set_admin(
+        accounts: &[AccountInfo],
+        new_admin: Pubkey,
+        authorized_by: AuthorizedBy,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Current Admin (Signer)
+        let current_admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+
+        // Security checks
+         if config_account.owner != program_id {
+             msg!("Error: Config account not owned by program");
+             return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Unpack config state
+        let mut config_data = ConfigAccount::unpack(&config_account.data.borrow())?;
+        if !config_data.is_initialized() {
+             msg!("Error: Config account not initialized");
+             return Err(StablecoinError::NotInitialized.into());
+        }
+
+        // Verify signer is the current admin (or the timelock is running a queued change)
+        Self::check_admin_action(&config_data, current_admin_account, authorized_by)?;
+
+        // Update the admin
+        config_data.admin = new_admin;
+
+        // Pack the updated state back into the account
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Admin updated successfully to: {}", new_admin);
+        Ok(())
+    }
+
+    /// Processes the MigrateConfig instruction.
+    fn process_migrate_config(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Payer (Signer, Writable) - Pays for the extra rent
+        let payer_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable) - The account to migrate
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: System Program (Readonly) - Needed for the rent transfer
+        let system_program = next_account_info(account_info_iter)?;
+
+        if !payer_account.is_signer {
+            msg!("Error: Payer signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if config_account.owner != program_id {
+            msg!("Error: Config account not owned by program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Each layout has a distinct length, so the length tells us which one we have.
+        // ETH Dev Analogy: Like an upgradeable proxy's storage migration, except the
+        // account itself has to grow to fit the new layout.
+        let config_data: ConfigAccount = {
+            let data = config_account.data.borrow();
+            match data.len() {
+                ConfigAccount::LEN => {
+                    msg!("Config already at layout version {}", data[0]);
+                    return Ok(());
+                }
+                // Unversioned layout: same leading fields, no header
+                CONFIG_V0_LEN => upgrade_body(&data[..], ConfigAccount::LEN - HEADER_LEN)?,
+                // Older versioned layout: fields are only ever appended, so zero-extend
+                len if len > HEADER_LEN
+                    && len < ConfigAccount::LEN
+                    && data[0] < CURRENT_VERSION
+                    && data[1] == AccountType::Config as u8 =>
+                {
+                    upgrade_body(&data[HEADER_LEN..], ConfigAccount::LEN - HEADER_LEN)?
+                }
+                _ => {
+                    msg!("Error: Unknown config layout ({} bytes)", data.len());
+                    return Err(ProgramError::InvalidAccountData);
+                }
+            }
+        };
+        if !config_data.is_initialized() {
+            msg!("Error: Config account not initialized");
+            return Err(StablecoinError::NotInitialized.into());
+        }
+
+        // Top up rent for the larger account, then grow it
+        grow_account(payer_account, config_account, system_program, ConfigAccount::LEN)?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Config migrated to layout version {}", CURRENT_VERSION);
+        Ok(())
+    }
+
+    /// Processes the MigratePosition instruction.
+    fn process_migrate_position(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Payer (Signer, Writable) - Pays for the extra rent
+        let payer_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Position PDA (Writable) - The account to migrate
+        let position_account = next_account_info(account_info_iter)?;
+        // Account 3: System Program (Readonly) - Needed for the rent transfer
+        let system_program = next_account_info(account_info_iter)?;
+
+        if !payer_account.is_signer {
+            msg!("Error: Payer signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if position_account.owner != program_id {
+            msg!("Error: Position account not owned by program");
+            return Err(StablecoinError::PositionMismatch.into());
+        }
+
+        let mut position: Position = {
+            let data = position_account.data.borrow();
+            match data.len() {
+                Position::LEN => {
+                    msg!("Position already at layout version {}", data[0]);
+                    return Ok(());
+                }
+                // Layout before the principal was appended
+                len if len == Position::LEN - 8
+                    && data[0] < CURRENT_VERSION
+                    && data[1] == AccountType::Position as u8 =>
+                {
+                    upgrade_body(&data[HEADER_LEN..], Position::LEN - HEADER_LEN)?
+                }
+                _ => {
+                    msg!("Error: Unknown position layout ({} bytes)", data.len());
+                    return Err(ProgramError::InvalidAccountData);
+                }
+            }
+        };
+        if !position.is_initialized() || position.config != *config_account.key {
+            msg!("Error: Position belongs to a different config");
+            return Err(StablecoinError::PositionMismatch.into());
+        }
+        // Normalized debt is the plain debt at the index's starting value `RAY`; anything
+        // above it is fees accrued on this position.
+        position.principal = position.normalized_debt;
+
+        grow_account(payer_account, position_account, system_program, Position::LEN)?;
+        Position::pack(position, &mut position_account.data.borrow_mut())?;
+
+        msg!("Position migrated to layout version {}", CURRENT_VERSION);
+        Ok(())
+    }
+
+    // --- Shared helpers for the collateral instructions ---
+
+    /// Loads an initialized config account owned by this program.
+    fn load_config(config_account: &AccountInfo, program_id: &Pubkey) -> Result<ConfigAccount, ProgramError> {
+        if config_account.owner != program_id {
+            msg!("Error: Config account not owned by program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let config_data = ConfigAccount::unpack_unchecked(&config_account.data.borrow())?;
+        if !config_data.is_initialized() {
+            msg!("Error: Config account not initialized");
+            return Err(StablecoinError::NotInitialized.into());
+        }
+        Ok(config_data)
+    }
+
+    /// Checks the signer is the configured admin.
+    fn check_admin(config_data: &ConfigAccount, admin_account: &AccountInfo) -> ProgramResult {
+        if !admin_account.is_signer {
+            msg!("Error: Admin signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if config_data.admin != *admin_account.key {
+            msg!("Error: Signer is not the configured admin");
+            return Err(StablecoinError::AdminSignatureMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Checks a timelocked instruction may run: sent directly, the role holder must sign
+    /// and no timelock delay may be set; run by `ExecuteAction`, the queueing already
+    /// checked the role holder.
+    fn check_authorized(
+        config_data: &ConfigAccount,
+        role_account: &AccountInfo,
+        authorized_by: AuthorizedBy,
+    ) -> ProgramResult {
+        if authorized_by == AuthorizedBy::Timelock {
+            return Ok(());
+        }
+        if !role_account.is_signer {
+            msg!("Error: Signature of {} missing", role_account.key);
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if config_data.timelock_delay_secs > 0 {
+            msg!("Error: Timelock delay is set; queue this instruction with QueueAction");
+            return Err(StablecoinError::TimelockRequired.into());
+        }
+        Ok(())
+    }
+
+    /// Checks the admin for a timelocked instruction.
+    fn check_admin_action(
+        config_data: &ConfigAccount,
+        admin_account: &AccountInfo,
+        authorized_by: AuthorizedBy,
+    ) -> ProgramResult {
+        Self::check_authorized(config_data, admin_account, authorized_by)?;
+        if config_data.admin != *admin_account.key {
+            msg!("Error: Account is not the configured admin");
+            return Err(StablecoinError::AdminSignatureMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Checks the master minter for a timelocked instruction.
+    fn check_master_minter(
+        config_data: &ConfigAccount,
+        master_minter_account: &AccountInfo,
+        authorized_by: AuthorizedBy,
+    ) -> ProgramResult {
+        Self::check_authorized(config_data, master_minter_account, authorized_by)?;
+        if config_data.master_minter == Pubkey::default()
+            || config_data.master_minter != *master_minter_account.key
+        {
+            msg!("Error: Account is not the master minter");
+            return Err(StablecoinError::MasterMinterMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Loads a minter account and checks it belongs to the given config.
+    fn load_minter(
+        minter_pda_account: &AccountInfo,
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<MinterAccount, ProgramError> {
+        if minter_pda_account.owner != program_id {
+            msg!("Error: Minter account not owned by program");
+            return Err(StablecoinError::MinterMismatch.into());
+        }
+        let minter_data = MinterAccount::unpack(&minter_pda_account.data.borrow())?;
+        if minter_data.config != *config_account.key {
+            msg!("Error: Minter belongs to a different config");
+            return Err(StablecoinError::MinterMismatch.into());
+        }
+        Ok(minter_data)
+    }
+
+    /// Checks the signer is the configured blacklister.
+    fn check_blacklister(config_data: &ConfigAccount, blacklister_account: &AccountInfo) -> ProgramResult {
+        if !blacklister_account.is_signer {
+            msg!("Error: Blacklister signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if config_data.blacklister == Pubkey::default() || config_data.blacklister != *blacklister_account.key {
+            msg!("Error: Signer is not the blacklister");
+            return Err(StablecoinError::BlacklisterMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Fails if `owner` has a blacklist entry. `blacklist_account` must be the owner's
+    /// blacklist PDA, so a caller can't dodge the check by passing some other account.
+    fn check_not_blacklisted(
+        blacklist_account: &AccountInfo,
+        config_account: &AccountInfo,
+        owner: &Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let (blacklist_key, _) = find_blacklist_address(config_account.key, owner, program_id);
+        if blacklist_key != *blacklist_account.key {
+            msg!("Error: Blacklist account is not the PDA for owner {}", owner);
+            return Err(StablecoinError::BlacklistEntryMismatch.into());
+        }
+        if blacklist_account.owner == program_id && !blacklist_account.data_is_empty() {
+            msg!("Error: Owner {} is blacklisted", owner);
+            return Err(StablecoinError::Blacklisted.into());
+        }
+        Ok(())
+    }
+
+    /// Checks a stablecoin token account belongs to `holder` and returns it.
+    fn load_holder_token_account(
+        token_account: &AccountInfo,
+        config_data: &ConfigAccount,
+        holder: &Pubkey,
+    ) -> Result<TokenAccount, ProgramError> {
+        if *token_account.owner != spl_token::id() {
+            msg!("Error: Token account not owned by SPL Token");
+            return Err(StablecoinError::TokenAccountMismatch.into());
+        }
+        let token_data = TokenAccount::unpack(&token_account.data.borrow())?;
+        if token_data.owner != *holder || token_data.mint != config_data.mint_account {
+            msg!("Error: Token account is not a stablecoin account of {}", holder);
+            return Err(StablecoinError::TokenAccountMismatch.into());
+        }
+        Ok(token_data)
+    }
+
+    /// Fails while the program is paused.
+    fn check_not_paused(config_data: &ConfigAccount) -> ProgramResult {
+        if config_data.paused {
+            msg!("Error: Program is paused");
+            return Err(StablecoinError::ProgramPaused.into());
+        }
+        Ok(())
+    }
+
+    /// Fails if minting `amount` would push the stablecoin supply past the configured cap.
+    fn check_supply_cap(config_data: &ConfigAccount, mint_account_info: &AccountInfo, amount: u64) -> ProgramResult {
+        if config_data.supply_cap == 0 {
+            return Ok(());
+        }
+        let supply = Mint::unpack(&mint_account_info.data.borrow())?.supply;
+        let new_supply = supply.checked_add(amount).ok_or(StablecoinError::NumericalOverflow)?;
+        if new_supply > config_data.supply_cap {
+            msg!("Error: Supply {} + {} exceeds cap {}", supply, amount, config_data.supply_cap);
+            return Err(StablecoinError::SupplyCapExceeded.into());
+        }
+        Ok(())
+    }
+
+    /// While an attestor is configured, fails unless the latest attestation is fresh and
+    /// covers the fiat-backed supply after minting `amount`. Stablecoin minted against
+    /// collateral (`total_debt` less fees not minted yet), as stability fees
+    /// (`total_fees_minted`) or through a peg stability module (`psm_debt`) is backed by
+    /// on-chain vaults, not fiat reserves, so it is left out, as are flash mints burned
+    /// later in the transaction (`flash_outstanding`).
+    fn check_reserves(
+        config_data: &ConfigAccount,
+        config_account: &AccountInfo,
+        mint_account_info: &AccountInfo,
+        attestation_account: &AccountInfo,
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        if config_data.attestor == Pubkey::default() {
+            return Ok(());
+        }
+        if config_data.attestation_count == 0 {
+            msg!("Error: No reserve attestation posted yet");
+            return Err(StablecoinError::StaleAttestation.into());
+        }
+        let (attestation_key, _) =
+            find_attestation_address(config_account.key, config_data.attestation_count - 1, program_id);
+        if attestation_key != *attestation_account.key || attestation_account.owner != program_id {
+            msg!("Error: Attestation account is not the latest attestation PDA");
+            return Err(StablecoinError::AttestationMismatch.into());
+        }
+        let attestation = ReserveAttestation::unpack(&attestation_account.data.borrow())?;
+
+        let age = Clock::get()?.unix_timestamp.saturating_sub(attestation.as_of);
+        if age > config_data.max_attestation_age_secs {
+            msg!("Error: Attestation is {}s old, max age is {}s", age, config_data.max_attestation_age_secs);
+            return Err(StablecoinError::StaleAttestation.into());
+        }
+
+        let supply = Mint::unpack(&mint_account_info.data.borrow())?.supply;
+        let fiat_backed_supply = supply
+            .saturating_sub(config_data.total_debt.saturating_sub(config_data.unminted_fees))
+            .saturating_sub(config_data.total_fees_minted)
+            .saturating_sub(config_data.psm_debt)
+            .saturating_sub(config_data.flash_outstanding)
+            .checked_add(amount)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        if fiat_backed_supply > attestation.reserve_amount {
+            msg!(
+                "Error: Fiat-backed supply {} would exceed attested reserves {}",
+                fiat_backed_supply,
+                attestation.reserve_amount
+            );
+            return Err(StablecoinError::ReservesExceeded.into());
+        }
+        Ok(())
+    }
+
+    /// Loads a peg stability module and checks it belongs to the given config.
+    fn load_psm(
+        psm_account: &AccountInfo,
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<PsmAccount, ProgramError> {
+        if psm_account.owner != program_id {
+            msg!("Error: PSM account not owned by program");
+            return Err(StablecoinError::PsmMismatch.into());
+        }
+        let psm_data = PsmAccount::unpack(&psm_account.data.borrow())?;
+        if psm_data.config != *config_account.key {
+            msg!("Error: PSM belongs to a different config");
+            return Err(StablecoinError::PsmMismatch.into());
+        }
+        Ok(psm_data)
+    }
+
+    /// Checks the vault and treasury accounts passed to a swap are the module's own.
+    fn check_psm_accounts(
+        psm_data: &PsmAccount,
+        config_account: &AccountInfo,
+        vault_account: &AccountInfo,
+        treasury_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let (vault_key, _) = find_psm_vault_address(config_account.key, &psm_data.reserve_mint, program_id);
+        if vault_key != *vault_account.key {
+            msg!("Error: Vault account is not the PSM vault PDA");
+            return Err(StablecoinError::PsmMismatch.into());
+        }
+        if psm_data.treasury != *treasury_account.key {
+            msg!("Error: Treasury account does not match the PSM treasury");
+            return Err(StablecoinError::PsmMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Loads a redemption request of the given config and checks it is still pending.
+    fn load_pending_redemption(
+        request_account: &AccountInfo,
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<RedemptionRequest, ProgramError> {
+        if request_account.owner != program_id {
+            msg!("Error: Redemption request not owned by program");
+            return Err(StablecoinError::RedemptionMismatch.into());
+        }
+        let request = RedemptionRequest::unpack(&request_account.data.borrow())?;
+        if request.config != *config_account.key {
+            msg!("Error: Redemption request belongs to a different config");
+            return Err(StablecoinError::RedemptionMismatch.into());
+        }
+        if request.status != RedemptionStatus::Pending {
+            msg!("Error: Redemption request {} is {:?}", request.id, request.status);
+            return Err(StablecoinError::RedemptionNotPending.into());
+        }
+        Ok(request)
+    }
+
+    /// Checks the redemption escrow PDA.
+    fn check_redemption_escrow(
+        escrow_account: &AccountInfo,
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let (escrow_key, _) = find_redemption_escrow_address(config_account.key, program_id);
+        if escrow_key != *escrow_account.key {
+            msg!("Error: Escrow account is not the redemption escrow PDA");
+            return Err(StablecoinError::RedemptionMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Checks the signer is the configured redemption operator.
+    fn check_redemption_operator(config_data: &ConfigAccount, operator_account: &AccountInfo) -> ProgramResult {
+        if !operator_account.is_signer {
+            msg!("Error: Operator signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if config_data.redemption_operator == Pubkey::default()
+            || config_data.redemption_operator != *operator_account.key
+        {
+            msg!("Error: Signer is not the redemption operator");
+            return Err(StablecoinError::RedemptionOperatorMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Checks the program authority PDA and returns its bump.
+    fn check_authority(
+        authority_account: &AccountInfo,
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<u8, ProgramError> {
+        let (authority_key, authority_bump) = find_authority_address(config_account.key, program_id);
+        if authority_key != *authority_account.key {
+            msg!("Error: Authority account is not the program authority PDA");
+            return Err(StablecoinError::AuthorityMismatch.into());
+        }
+        Ok(authority_bump)
+    }
+
+    /// Checks the token program is SPL Token, so CPIs can't be redirected to a fake program.
+    fn check_token_program(token_program_info: &AccountInfo) -> ProgramResult {
+        if *token_program_info.key != spl_token::id() {
+            msg!("Error: Token program is not SPL Token");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Ok(())
+    }
+
+    /// Loads a position and checks it belongs to the given config.
+    fn load_position(
+        position_account: &AccountInfo,
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<Position, ProgramError> {
+        if position_account.owner != program_id {
+            msg!("Error: Position account not owned by program");
+            return Err(StablecoinError::PositionMismatch.into());
+        }
+        let position = Position::unpack(&position_account.data.borrow())?;
+        if position.config != *config_account.key {
+            msg!("Error: Position belongs to a different config");
+            return Err(StablecoinError::PositionMismatch.into());
+        }
+        Ok(position)
+    }
+
+    /// Loads the config's price account and checks the price is set, fresh and precise
+    /// enough under the config's oracle limits.
+    fn load_price(
+        price_account: &AccountInfo,
+        config_account: &AccountInfo,
+        config_data: &ConfigAccount,
+        program_id: &Pubkey,
+    ) -> Result<PriceAccount, ProgramError> {
+        let (price_key, _) = find_price_address(config_account.key, program_id);
+        if price_key != *price_account.key || price_account.owner != program_id {
+            msg!("Error: Price account is not the price PDA for this config");
+            return Err(StablecoinError::PriceAccountMismatch.into());
+        }
+        let price_data = PriceAccount::unpack(&price_account.data.borrow())?;
+        if price_data.price == 0 {
+            msg!("Error: Collateral price has not been set");
+            return Err(StablecoinError::InvalidPrice.into());
+        }
+
+        // A zero max age means the oracle limits were never configured: accept nothing
+        let age = Clock::get()?.unix_timestamp.saturating_sub(price_data.publish_time);
+        if config_data.max_price_age_secs <= 0 || age > config_data.max_price_age_secs {
+            msg!("Error: Price is {}s old, max age is {}s", age, config_data.max_price_age_secs);
+            return Err(StablecoinError::StalePrice.into());
+        }
+        let conf_scaled = (price_data.conf as u128) * BPS_DENOMINATOR as u128;
+        let max_conf_scaled = (price_data.price as u128) * config_data.max_confidence_bps as u128;
+        if conf_scaled > max_conf_scaled {
+            msg!("Error: Price confidence {} too wide for price {}", price_data.conf, price_data.price);
+            return Err(StablecoinError::PriceConfidenceTooWide.into());
+        }
+        Ok(price_data)
+    }
+
+    /// Fails unless the position is at or above the collateral ratio at the given price.
+    fn check_health(config_data: &ConfigAccount, position: &Position, price_data: &PriceAccount) -> ProgramResult {
+        let value = collateral_value(
+            position.collateral_amount,
+            price_data.price,
+            price_data.expo,
+            config_data.collateral_decimals,
+            config_data.stable_decimals,
+        )
+        .ok_or(StablecoinError::NumericalOverflow)?;
+        let debt = Self::position_debt(config_data, position)?;
+        let healthy = is_healthy(value, debt, config_data.collateral_ratio_bps)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        if !healthy {
+            msg!("Error: Collateral value {} does not cover debt {}", value, debt);
+            return Err(StablecoinError::PositionUnhealthy.into());
+        }
+        Ok(())
+    }
+
+    /// Brings the stability fee rate index up to now, adding the fees accrued on all
+    /// positions since the last accrual to `total_debt`. The first accrual after the
+    /// upgrade starts the index at `RAY`, where normalized debt equals the old plain debt.
+    /// No-op once settled.
+    fn accrue_stability_fee(config_data: &mut ConfigAccount) -> ProgramResult {
+        // The rate index stops at settlement
+        if config_data.settled {
+            return Ok(());
+        }
+        let now = Clock::get()?.unix_timestamp;
+        if config_data.rate_index == 0 {
+            config_data.rate_index = RAY;
+            config_data.total_normalized_debt = config_data.total_debt;
+            config_data.rate_updated_at = now;
+            return Ok(());
+        }
+        let elapsed = now.saturating_sub(config_data.rate_updated_at);
+        if elapsed <= 0 {
+            return Ok(());
+        }
+        let old_debt = debt_from_normalized(config_data.total_normalized_debt, config_data.rate_index)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        let new_index = accrue_rate_index(config_data.rate_index, config_data.stability_fee_bps, elapsed as u64)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        let new_debt = debt_from_normalized(config_data.total_normalized_debt, new_index)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        let fees = new_debt.saturating_sub(old_debt);
+
+        config_data.total_debt = config_data.total_debt.checked_add(fees).ok_or(StablecoinError::NumericalOverflow)?;
+        config_data.unminted_fees = config_data
+            .unminted_fees
+            .checked_add(fees)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        config_data.rate_index = new_index;
+        config_data.rate_updated_at = now;
+        Ok(())
+    }
+
+    /// Debt the position owes at the config's current rate index, stability fees included.
+    fn position_debt(config_data: &ConfigAccount, position: &Position) -> Result<u64, ProgramError> {
+        let index = if config_data.rate_index == 0 { RAY } else { config_data.rate_index };
+        debt_from_normalized(position.normalized_debt, index).ok_or_else(|| StablecoinError::NumericalOverflow.into())
+    }
+
+    /// Stability fees a position has accrued and not repaid yet.
+    fn position_fees(config_data: &ConfigAccount, position: &Position) -> Result<u64, ProgramError> {
+        Ok(Self::position_debt(config_data, position)?.saturating_sub(position.principal))
+    }
+
+    /// Takes `amount` of debt off a position, rounding so it never ends up owing less than
+    /// it should, and returns the stability fees the repayment covers. A repayment pays
+    /// the position's own accrued fees first, then its principal.
+    fn apply_repayment(config_data: &mut ConfigAccount, position: &mut Position, amount: u64) -> Result<u64, ProgramError> {
+        let debt = Self::position_debt(config_data, position)?;
+        // Capped by the global accrual, which per-position rounding can trail slightly
+        let fees = amount
+            .min(debt.saturating_sub(position.principal))
+            .min(config_data.unminted_fees);
+        let normalized = if amount >= debt {
+            position.normalized_debt
+        } else {
+            normalize_debt(amount, config_data.rate_index, false).ok_or(StablecoinError::NumericalOverflow)?
+        };
+        position.normalized_debt -= normalized;
+        config_data.total_normalized_debt = config_data.total_normalized_debt.saturating_sub(normalized);
+        // Per-position debt rounds up, so the total can trail the sum of repayments slightly
+        config_data.total_debt = config_data.total_debt.saturating_sub(amount);
+        position.principal = if amount >= debt { 0 } else { position.principal.saturating_sub(amount - fees) };
+
+        config_data.unminted_fees -= fees;
+        config_data.total_fees_minted = config_data
+            .total_fees_minted
+            .checked_add(fees)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        Ok(fees)
+    }
+
+    /// Checks the surplus account is the config's surplus PDA.
+    fn check_surplus(surplus_account: &AccountInfo, config_account: &AccountInfo, program_id: &Pubkey) -> ProgramResult {
+        let (surplus_key, _) = find_surplus_address(config_account.key, program_id);
+        if surplus_key != *surplus_account.key {
+            msg!("Error: Surplus account is not the surplus PDA");
+            return Err(StablecoinError::SurplusMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Mints stablecoin with the program authority PDA as mint authority.
+    fn mint_stablecoin<'a>(
+        token_program_info: &AccountInfo<'a>,
+        mint_account_info: &AccountInfo<'a>,
+        destination_account: &AccountInfo<'a>,
+        authority_account: &AccountInfo<'a>,
+        config_account: &AccountInfo<'a>,
+        authority_bump: u8,
+        amount: u64,
+    ) -> ProgramResult {
+        // The mint authority must be set to the program authority PDA (`[b"authority", config]`)
+        // when the mint is created, so the program signs the CPI with the PDA seeds.
+        let mint_instruction = token_instruction::mint_to(
+            token_program_info.key,
+            mint_account_info.key,
+            destination_account.key,
+            authority_account.key,
+            &[],
+            amount,
+        )?;
+        invoke_signed(
+            &mint_instruction,
+            &[
+                mint_account_info.clone(),
+                destination_account.clone(),
+                authority_account.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )
+    }
+
+    /// Creates a PDA account of `space` bytes owned by `owner`, paid for by `payer`.
+    fn create_pda_account<'a>(
+        payer: &AccountInfo<'a>,
+        pda_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        space: usize,
+        signer_seeds: &[&[u8]],
+        owner: &Pubkey,
+    ) -> ProgramResult {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                pda_account.key,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                owner,
+            ),
+            &[payer.clone(), pda_account.clone(), system_program.clone()],
+            &[signer_seeds],
+        )
+    }
+
+    /// Processes the ConfigureCollateral instruction.
+    fn process_configure_collateral(
+        accounts: &[AccountInfo],
+        collateral_ratio_bps: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer, Writable) - Pays for the vault and price PDAs
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Stablecoin Mint (Readonly)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 3: Collateral Mint (Readonly)
+        let collateral_mint_info = next_account_info(account_info_iter)?;
+        // Account 4: Collateral Vault PDA (Writable)
+        let vault_account = next_account_info(account_info_iter)?;
+        // Account 5: Price PDA (Writable)
+        let price_account = next_account_info(account_info_iter)?;
+        // Account 6: Program Authority PDA (Readonly) - Vault owner
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 7: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 8: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin(&config_data, admin_account)?;
+        Self::check_token_program(token_program_info)?;
+        Self::check_authority(authority_account, config_account, program_id)?;
+
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        // A ratio at or below 100% would let positions mint more than their collateral is worth
+        if (collateral_ratio_bps as u64) <= BPS_DENOMINATOR {
+            msg!("Error: Collateral ratio must be above 100%");
+            return Err(StablecoinError::InvalidCollateralRatio.into());
+        }
+        if config_data.collateral_mint != Pubkey::default()
+            && config_data.collateral_mint != *collateral_mint_info.key
+        {
+            msg!("Error: Collateral mint cannot be changed once set");
+            return Err(StablecoinError::CollateralMintMismatch.into());
+        }
+
+        // Create the collateral vault on first use
+        let (vault_key, vault_bump) = find_vault_address(config_account.key, program_id);
+        if vault_key != *vault_account.key {
+            msg!("Error: Vault account is not the vault PDA");
+            return Err(StablecoinError::CollateralMintMismatch.into());
+        }
+        if vault_account.data_is_empty() {
+            Self::create_pda_account(
+                admin_account,
+                vault_account,
+                system_program,
+                TokenAccount::LEN,
+                &[VAULT_SEED, config_account.key.as_ref(), &[vault_bump]],
+                &spl_token::id(),
+            )?;
+            invoke(
+                &token_instruction::initialize_account3(
+                    token_program_info.key,
+                    vault_account.key,
+                    collateral_mint_info.key,
+                    authority_account.key,
+                )?,
+                &[vault_account.clone(), collateral_mint_info.clone()],
+            )?;
+        }
+
+        // Create the price account on first use; the price stays unset (zero) until the first PostPrice
+        let (price_key, price_bump) = find_price_address(config_account.key, program_id);
+        if price_key != *price_account.key {
+            msg!("Error: Price account is not the price PDA");
+            return Err(StablecoinError::PriceAccountMismatch.into());
+        }
+        if price_account.data_is_empty() {
+            Self::create_pda_account(
+                admin_account,
+                price_account,
+                system_program,
+                PriceAccount::LEN,
+                &[PRICE_SEED, config_account.key.as_ref(), &[price_bump]],
+                program_id,
+            )?;
+            let price_data = PriceAccount {
+                is_initialized: true,
+                config: *config_account.key,
+                ..PriceAccount::default()
+            };
+            PriceAccount::pack(price_data, &mut price_account.data.borrow_mut())?;
+        }
+
+        config_data.collateral_mint = *collateral_mint_info.key;
+        config_data.collateral_decimals = Mint::unpack(&collateral_mint_info.data.borrow())?.decimals;
+        config_data.stable_decimals = Mint::unpack(&mint_account_info.data.borrow())?.decimals;
+        config_data.collateral_ratio_bps = collateral_ratio_bps;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!(
+            "Collateral configured. Mint: {}, ratio: {} bps",
+            collateral_mint_info.key,
+            collateral_ratio_bps
+        );
+        Ok(())
+    }
+
+    /// Processes the PostPrice instruction.
+    fn process_post_price(
+        accounts: &[AccountInfo],
+        price: u64,
+        expo: i32,
+        conf: u64,
+        publish_time: i64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 1: Price PDA (Writable)
+        let price_account = next_account_info(account_info_iter)?;
+        // Account 2: Publisher PDA (Readonly) - Whitelist entry of the signing publisher
+        let publisher_account = next_account_info(account_info_iter)?;
+        // Account 3: Instructions Sysvar (Readonly) - To find the Ed25519 verification
+        let instructions_sysvar = next_account_info(account_info_iter)?;
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_not_settled(&config_data)?;
+        let (price_key, _) = find_price_address(config_account.key, program_id);
+        if price_key != *price_account.key || price_account.owner != program_id {
+            msg!("Error: Price account is not the price PDA for this config");
+            return Err(StablecoinError::PriceAccountMismatch.into());
+        }
+
+        // The publisher must be whitelisted for this config
+        if publisher_account.owner != program_id {
+            msg!("Error: Publisher is not whitelisted");
+            return Err(StablecoinError::PublisherNotWhitelisted.into());
+        }
+        let publisher_data = PublisherAccount::unpack(&publisher_account.data.borrow())?;
+        if publisher_data.config != *config_account.key {
+            msg!("Error: Publisher is whitelisted for a different config");
+            return Err(StablecoinError::PublisherNotWhitelisted.into());
+        }
+
+        // The previous instruction must verify the publisher's signature over this price
+        let current_index = load_current_index_checked(instructions_sysvar)?;
+        if current_index == 0 {
+            msg!("Error: Missing Ed25519 verification instruction");
+            return Err(StablecoinError::InvalidPriceAttestation.into());
+        }
+        let verify_instruction = load_instruction_at_checked(current_index as usize - 1, instructions_sysvar)?;
+        let verified = oracle::verified_signature(&verify_instruction)
+            .map_err(|_| StablecoinError::InvalidPriceAttestation)?;
+        let expected_message = oracle::price_message(config_account.key, price, expo, conf, publish_time);
+        if verified.public_key != publisher_data.publisher || verified.message != expected_message {
+            msg!("Error: Ed25519 instruction does not attest this price from this publisher");
+            return Err(StablecoinError::InvalidPriceAttestation.into());
+        }
+
+        let mut price_data = PriceAccount::unpack(&price_account.data.borrow())?;
+        if price == 0 {
+            msg!("Error: Price must be positive");
+            return Err(StablecoinError::InvalidPrice.into());
+        }
+        // Prices only move forward in time and can't come from the future
+        if publish_time <= price_data.publish_time || publish_time > Clock::get()?.unix_timestamp {
+            msg!("Error: Publish time {} is not newer than {} or lies in the future", publish_time, price_data.publish_time);
+            return Err(StablecoinError::StalePrice.into());
+        }
+
+        price_data.price = price;
+        price_data.expo = expo;
+        price_data.conf = conf;
+        price_data.publish_time = publish_time;
+        price_data.publisher = publisher_data.publisher;
+        price_data.signature = verified.signature;
+        PriceAccount::pack(price_data, &mut price_account.data.borrow_mut())?;
+
+        msg!("Collateral price posted: {} ± {} * 10^{} at {}", price, conf, expo, publish_time);
+        Ok(())
+    }
+
+    /// Processes the OpenPosition instruction.
+    fn process_open_position(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Owner (Signer, Writable) - Pays rent for the position
+        let owner_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Position PDA (Writable)
+        let position_account = next_account_info(account_info_iter)?;
+        // Account 3: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        if !owner_account.is_signer {
+            msg!("Error: Owner signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        if config_data.collateral_mint == Pubkey::default() {
+            msg!("Error: Collateral has not been configured");
+            return Err(StablecoinError::CollateralNotConfigured.into());
+        }
+
+        let (position_key, position_bump) =
+            find_position_address(config_account.key, owner_account.key, program_id);
+        if position_key != *position_account.key {
+            msg!("Error: Position account is not the position PDA for this owner");
+            return Err(StablecoinError::PositionMismatch.into());
+        }
+        if !position_account.data_is_empty() {
+            msg!("Error: Position already exists");
+            return Err(StablecoinError::AlreadyInitialized.into());
+        }
+
+        Self::create_pda_account(
+            owner_account,
+            position_account,
+            system_program,
+            Position::LEN,
+            &[
+                POSITION_SEED,
+                config_account.key.as_ref(),
+                owner_account.key.as_ref(),
+                &[position_bump],
+            ],
+            program_id,
+        )?;
+        let position = Position {
+            is_initialized: true,
+            config: *config_account.key,
+            owner: *owner_account.key,
+            bump: position_bump,
+            ..Position::default()
+        };
+        Position::pack(position, &mut position_account.data.borrow_mut())?;
+
+        msg!("Position opened for {}", owner_account.key);
+        Ok(())
+    }
+
+    /// Processes the Deposit instruction.
+    fn process_deposit(accounts: &[AccountInfo], amount: u64, program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Owner (Signer) - Owner of the source collateral account
+        let owner_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Position PDA (Writable)
+        let position_account = next_account_info(account_info_iter)?;
+        // Account 3: Source Collateral Token Account (Writable)
+        let source_account = next_account_info(account_info_iter)?;
+        // Account 4: Collateral Vault PDA (Writable)
+        let vault_account = next_account_info(account_info_iter)?;
+        // Account 5: Collateral Mint (Readonly)
+        let collateral_mint_info = next_account_info(account_info_iter)?;
+        // Account 6: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if !owner_account.is_signer {
+            msg!("Error: Owner signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        Self::check_collateral_accounts(&config_data, config_account, vault_account, collateral_mint_info, program_id)?;
+
+        let mut position = Self::load_position(position_account, config_account, program_id)?;
+        if position.owner != *owner_account.key {
+            msg!("Error: Signer does not own the position");
+            return Err(StablecoinError::PositionMismatch.into());
+        }
+
+        position.collateral_amount = position
+            .collateral_amount
+            .checked_add(amount)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        config_data.total_collateral = config_data
+            .total_collateral
+            .checked_add(amount)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+
+        invoke(
+            &token_instruction::transfer_checked(
+                token_program_info.key,
+                source_account.key,
+                collateral_mint_info.key,
+                vault_account.key,
+                owner_account.key,
+                &[],
+                amount,
+                config_data.collateral_decimals,
+            )?,
+            &[
+                source_account.clone(),
+                collateral_mint_info.clone(),
+                vault_account.clone(),
+                owner_account.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+
+        let collateral_amount = position.collateral_amount;
+        Position::pack(position, &mut position_account.data.borrow_mut())?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Deposited {} collateral. Position collateral: {}", amount, collateral_amount);
+        Ok(())
+    }
+
+    /// Checks the collateral is configured and the vault and collateral mint are the configured ones.
+    fn check_collateral_accounts(
+        config_data: &ConfigAccount,
+        config_account: &AccountInfo,
+        vault_account: &AccountInfo,
+        collateral_mint_info: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        if config_data.collateral_mint == Pubkey::default() {
+            msg!("Error: Collateral has not been configured");
+            return Err(StablecoinError::CollateralNotConfigured.into());
+        }
+        if config_data.collateral_mint != *collateral_mint_info.key {
+            msg!("Error: Collateral mint does not match configured collateral");
+            return Err(StablecoinError::CollateralMintMismatch.into());
+        }
+        let (vault_key, _) = find_vault_address(config_account.key, program_id);
+        if vault_key != *vault_account.key {
+            msg!("Error: Vault account is not the vault PDA");
+            return Err(StablecoinError::CollateralMintMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Processes the MintAgainstCollateral instruction.
+    fn process_mint_against_collateral(
+        accounts: &[AccountInfo],
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Owner (Signer)
+        let owner_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Position PDA (Writable)
+        let position_account = next_account_info(account_info_iter)?;
+        // Account 3: Stablecoin Mint (Writable)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 4: Destination Stablecoin Token Account (Writable)
+        let destination_account = next_account_info(account_info_iter)?;
+        // Account 5: Program Authority PDA (Readonly) - Mint authority
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 6: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 7: Price PDA (Readonly)
+        let price_account = next_account_info(account_info_iter)?;
+        // Account 8: Blacklist PDA of the destination's owner (Readonly)
+        let blacklist_account = next_account_info(account_info_iter)?;
+
+        if !owner_account.is_signer {
+            msg!("Error: Owner signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_not_settled(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+
+        let mut position = Self::load_position(position_account, config_account, program_id)?;
+        if position.owner != *owner_account.key {
+            msg!("Error: Signer does not own the position");
+            return Err(StablecoinError::PositionMismatch.into());
+        }
+
+        Self::accrue_stability_fee(&mut config_data)?;
+        let normalized = normalize_debt(amount, config_data.rate_index, true).ok_or(StablecoinError::NumericalOverflow)?;
+        position.normalized_debt = position
+            .normalized_debt
+            .checked_add(normalized)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        position.principal = position.principal.checked_add(amount).ok_or(StablecoinError::NumericalOverflow)?;
+        config_data.total_normalized_debt = config_data
+            .total_normalized_debt
+            .checked_add(normalized)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        config_data.total_debt = config_data
+            .total_debt
+            .checked_add(amount)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+
+        // Health check on the position as it will be after minting
+        let price_data = Self::load_price(price_account, config_account, &config_data, program_id)?;
+        Self::check_health(&config_data, &position, &price_data)?;
+
+        Self::check_supply_cap(&config_data, mint_account_info, amount)?;
+        // Compliance Check: Refuse to mint to a blacklisted owner
+        let destination_data = TokenAccount::unpack(&destination_account.data.borrow())?;
+        Self::check_not_blacklisted(blacklist_account, config_account, &destination_data.owner, program_id)?;
+
+        let debt = Self::position_debt(&config_data, &position)?;
+        Position::pack(position, &mut position_account.data.borrow_mut())?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        Self::mint_stablecoin(
+            token_program_info,
+            mint_account_info,
+            destination_account,
+            authority_account,
+            config_account,
+            authority_bump,
+            amount,
+        )?;
+
+        msg!("Minted {} against collateral. Position debt: {}", amount, debt);
+        Ok(())
+    }
+
+    /// Processes the Repay instruction.
+    fn process_repay(accounts: &[AccountInfo], amount: u64, program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Payer (Signer) - Owner of the source stablecoin account
+        let payer_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Position PDA (Writable)
+        let position_account = next_account_info(account_info_iter)?;
+        // Account 3: Source Stablecoin Token Account (Writable)
+        let source_account = next_account_info(account_info_iter)?;
+        // Account 4: Stablecoin Mint (Writable)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 5: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 6: Surplus PDA (Writable) - Receives the fees covered
+        let surplus_account = next_account_info(account_info_iter)?;
+        // Account 7: Program Authority PDA (Readonly) - Mint authority
+        let authority_account = next_account_info(account_info_iter)?;
+
+        if !payer_account.is_signer {
+            msg!("Error: Payer signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_not_settled(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        Self::check_surplus(surplus_account, config_account, program_id)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+
+        Self::accrue_stability_fee(&mut config_data)?;
+        let mut position = Self::load_position(position_account, config_account, program_id)?;
+        let position_debt = Self::position_debt(&config_data, &position)?;
+        if amount > position_debt {
+            msg!("Error: Repay amount {} exceeds debt {}", amount, position_debt);
+            return Err(StablecoinError::RepayExceedsDebt.into());
+        }
+        let fees = Self::apply_repayment(&mut config_data, &mut position, amount)?;
+
+        invoke(
+            &token_instruction::burn(
+                token_program_info.key,
+                source_account.key,
+                mint_account_info.key,
+                payer_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                source_account.clone(),
+                mint_account_info.clone(),
+                payer_account.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+
+        if fees > 0 {
+            Self::mint_stablecoin(
+                token_program_info,
+                mint_account_info,
+                surplus_account,
+                authority_account,
+                config_account,
+                authority_bump,
+                fees,
+            )?;
+        }
+
+        let debt = Self::position_debt(&config_data, &position)?;
+        Position::pack(position, &mut position_account.data.borrow_mut())?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Repaid {} ({} to surplus). Position debt: {}", amount, fees, debt);
+        Ok(())
+    }
+
+    /// Processes the Withdraw instruction.
+    fn process_withdraw(accounts: &[AccountInfo], amount: u64, program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Owner (Signer)
+        let owner_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Position PDA (Writable)
+        let position_account = next_account_info(account_info_iter)?;
+        // Account 3: Collateral Vault PDA (Writable)
+        let vault_account = next_account_info(account_info_iter)?;
+        // Account 4: Destination Collateral Token Account (Writable)
+        let destination_account = next_account_info(account_info_iter)?;
+        // Account 5: Collateral Mint (Readonly)
+        let collateral_mint_info = next_account_info(account_info_iter)?;
+        // Account 6: Program Authority PDA (Readonly) - Vault owner
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 7: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 8: Price PDA (Readonly)
+        let price_account = next_account_info(account_info_iter)?;
+
+        if !owner_account.is_signer {
+            msg!("Error: Owner signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        Self::check_collateral_accounts(&config_data, config_account, vault_account, collateral_mint_info, program_id)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+
+        let mut position = Self::load_position(position_account, config_account, program_id)?;
+        if position.owner != *owner_account.key {
+            msg!("Error: Signer does not own the position");
+            return Err(StablecoinError::PositionMismatch.into());
+        }
+        if amount > position.collateral_amount {
+            msg!("Error: Withdraw amount {} exceeds collateral {}", amount, position.collateral_amount);
+            return Err(StablecoinError::InsufficientCollateral.into());
+        }
+        position.collateral_amount -= amount;
+        config_data.total_collateral = config_data
+            .total_collateral
+            .checked_sub(amount)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+
+        // Health check on the position as it will be after withdrawing
+        if position.normalized_debt > 0 {
+            Self::accrue_stability_fee(&mut config_data)?;
+            let price_data = Self::load_price(price_account, config_account, &config_data, program_id)?;
+            Self::check_health(&config_data, &position, &price_data)?;
+        }
+
+        invoke_signed(
+            &token_instruction::transfer_checked(
+                token_program_info.key,
+                vault_account.key,
+                collateral_mint_info.key,
+                destination_account.key,
+                authority_account.key,
+                &[],
+                amount,
+                config_data.collateral_decimals,
+            )?,
+            &[
+                vault_account.clone(),
+                collateral_mint_info.clone(),
+                destination_account.clone(),
+                authority_account.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+
+        let collateral_amount = position.collateral_amount;
+        Position::pack(position, &mut position_account.data.borrow_mut())?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Withdrew {} collateral. Position collateral: {}", amount, collateral_amount);
+        Ok(())
+    }
+
+    /// Processes the ConfigureLiquidation instruction.
+    fn process_configure_liquidation(
+        accounts: &[AccountInfo],
+        liquidation_threshold_bps: u16,
+        liquidation_discount_bps: u16,
+        close_factor_bps: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer, Writable) - Pays for the bad debt PDA
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Bad Debt PDA (Writable)
+        let bad_debt_account = next_account_info(account_info_iter)?;
+        // Account 3: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin(&config_data, admin_account)?;
+
+        // The threshold sits between 100% and the minting ratio, so a freshly minted
+        // position is never immediately liquidatable and liquidations happen while
+        // the collateral still covers the debt.
+        if (liquidation_threshold_bps as u64) <= BPS_DENOMINATOR
+            || liquidation_threshold_bps > config_data.collateral_ratio_bps
+            || liquidation_discount_bps as u64 >= BPS_DENOMINATOR
+            || close_factor_bps == 0
+            || close_factor_bps as u64 > BPS_DENOMINATOR
+        {
+            msg!("Error: Invalid liquidation parameters");
+            return Err(StablecoinError::InvalidLiquidationParams.into());
+        }
+
+        let (bad_debt_key, bad_debt_bump) = find_bad_debt_address(config_account.key, program_id);
+        if bad_debt_key != *bad_debt_account.key {
+            msg!("Error: Bad debt account is not the bad debt PDA");
+            return Err(StablecoinError::InvalidLiquidationParams.into());
+        }
+        if bad_debt_account.data_is_empty() {
+            Self::create_pda_account(
+                admin_account,
+                bad_debt_account,
+                system_program,
+                BadDebtAccount::LEN,
+                &[BAD_DEBT_SEED, config_account.key.as_ref(), &[bad_debt_bump]],
+                program_id,
+            )?;
+            let bad_debt_data = BadDebtAccount {
+                is_initialized: true,
+                config: *config_account.key,
+                bump: bad_debt_bump,
+                ..BadDebtAccount::default()
+            };
+            BadDebtAccount::pack(bad_debt_data, &mut bad_debt_account.data.borrow_mut())?;
+        }
+
+        config_data.liquidation_threshold_bps = liquidation_threshold_bps;
+        config_data.liquidation_discount_bps = liquidation_discount_bps;
+        config_data.close_factor_bps = close_factor_bps;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!(
+            "Liquidation configured. Threshold: {} bps, discount: {} bps, close factor: {} bps",
+            liquidation_threshold_bps,
+            liquidation_discount_bps,
+            close_factor_bps
+        );
+        Ok(())
+    }
+
+    /// Processes the Liquidate instruction.
+    fn process_liquidate(
+        accounts: &[AccountInfo],
+        position_key: Pubkey,
+        repay_amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Liquidator (Signer) - Owner of the source stablecoin account
+        let liquidator_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Position PDA (Writable)
+        let position_account = next_account_info(account_info_iter)?;
+        // Account 3: Liquidator Stablecoin Token Account (Writable)
+        let source_account = next_account_info(account_info_iter)?;
+        // Account 4: Stablecoin Mint (Writable)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 5: Collateral Vault PDA (Writable)
+        let vault_account = next_account_info(account_info_iter)?;
+        // Account 6: Liquidator Collateral Token Account (Writable)
+        let destination_account = next_account_info(account_info_iter)?;
+        // Account 7: Collateral Mint (Readonly)
+        let collateral_mint_info = next_account_info(account_info_iter)?;
+        // Account 8: Program Authority PDA (Readonly) - Vault owner
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 9: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 10: Price PDA (Readonly)
+        let price_account = next_account_info(account_info_iter)?;
+        // Account 11: Bad Debt PDA (Writable)
+        let bad_debt_account = next_account_info(account_info_iter)?;
+        // Account 12: Surplus PDA (Writable) - Receives the fees covered
+        let surplus_account = next_account_info(account_info_iter)?;
+
+        if !liquidator_account.is_signer {
+            msg!("Error: Liquidator signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_not_settled(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        Self::check_collateral_accounts(&config_data, config_account, vault_account, collateral_mint_info, program_id)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        if config_data.liquidation_threshold_bps == 0 {
+            msg!("Error: Liquidations are not configured");
+            return Err(StablecoinError::InvalidLiquidationParams.into());
+        }
+        if position_key != *position_account.key {
+            msg!("Error: Position account does not match the instruction");
+            return Err(StablecoinError::PositionMismatch.into());
+        }
+        let (bad_debt_key, _) = find_bad_debt_address(config_account.key, program_id);
+        if bad_debt_key != *bad_debt_account.key || bad_debt_account.owner != program_id {
+            msg!("Error: Bad debt account is not the bad debt PDA");
+            return Err(StablecoinError::InvalidLiquidationParams.into());
+        }
+        Self::check_surplus(surplus_account, config_account, program_id)?;
+
+        Self::accrue_stability_fee(&mut config_data)?;
+        let mut position = Self::load_position(position_account, config_account, program_id)?;
+        let debt = Self::position_debt(&config_data, &position)?;
+        let price_data = Self::load_price(price_account, config_account, &config_data, program_id)?;
+        let value = collateral_value(
+            position.collateral_amount,
+            price_data.price,
+            price_data.expo,
+            config_data.collateral_decimals,
+            config_data.stable_decimals,
+        )
+        .ok_or(StablecoinError::NumericalOverflow)?;
+        let healthy = is_healthy(value, debt, config_data.liquidation_threshold_bps)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        if healthy {
+            msg!("Error: Position is above the liquidation threshold");
+            return Err(StablecoinError::PositionNotLiquidatable.into());
+        }
+
+        // --- Size the liquidation ---
+        let max_repay = apply_bps(debt, config_data.close_factor_bps as u64)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        let mut repay = repay_amount.min(max_repay);
+        let bonus_bps = BPS_DENOMINATOR + config_data.liquidation_discount_bps as u64;
+        let repay_collateral = collateral_for_value(
+            repay,
+            price_data.price,
+            price_data.expo,
+            config_data.collateral_decimals,
+            config_data.stable_decimals,
+        )
+        .ok_or(StablecoinError::NumericalOverflow)?;
+        let mut seize = apply_bps(repay_collateral, bonus_bps).ok_or(StablecoinError::NumericalOverflow)?;
+        if seize >= position.collateral_amount {
+            // Not enough collateral for the full bonus: seize everything and only
+            // charge the liquidator what it is worth at the discount.
+            seize = position.collateral_amount;
+            let seized_value = collateral_value(
+                seize,
+                price_data.price,
+                price_data.expo,
+                config_data.collateral_decimals,
+                config_data.stable_decimals,
+            )
+            .ok_or(StablecoinError::NumericalOverflow)?;
+            let discounted = (seized_value as u128 * BPS_DENOMINATOR as u128) / bonus_bps as u128;
+            repay = repay.min(discounted as u64);
+        }
+        if repay == 0 || seize == 0 {
+            msg!("Error: Liquidation too small");
+            return Err(StablecoinError::PositionNotLiquidatable.into());
+        }
+
+        let fees = Self::apply_repayment(&mut config_data, &mut position, repay)?;
+        position.collateral_amount -= seize;
+        config_data.total_collateral = config_data
+            .total_collateral
+            .checked_sub(seize)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        let mut written_off = 0;
+        if position.collateral_amount == 0 && position.normalized_debt > 0 {
+            // Nothing left to back the remaining debt: write it off as bad debt
+            written_off = Self::position_debt(&config_data, &position)?;
+            // Fees on written-off debt will never be paid
+            let unpaid_fees = Self::position_fees(&config_data, &position)?;
+            config_data.unminted_fees = config_data.unminted_fees.saturating_sub(unpaid_fees);
+            config_data.total_normalized_debt =
+                config_data.total_normalized_debt.saturating_sub(position.normalized_debt);
+            position.normalized_debt = 0;
+            position.principal = 0;
+            let mut bad_debt_data = BadDebtAccount::unpack(&bad_debt_account.data.borrow())?;
+            bad_debt_data.total_bad_debt = bad_debt_data
+                .total_bad_debt
+                .checked_add(written_off)
+                .ok_or(StablecoinError::NumericalOverflow)?;
+            bad_debt_data.event_count = bad_debt_data
+                .event_count
+                .checked_add(1)
+                .ok_or(StablecoinError::NumericalOverflow)?;
+            bad_debt_data.last_recorded_at = Clock::get()?.unix_timestamp;
+            BadDebtAccount::pack(bad_debt_data, &mut bad_debt_account.data.borrow_mut())?;
+        }
+        config_data.total_debt = config_data.total_debt.saturating_sub(written_off);
+        config_data.unminted_fees = config_data.unminted_fees.min(config_data.total_debt);
+
+        Position::pack(position, &mut position_account.data.borrow_mut())?;
+        ConfigAccount::pack(config_data.clone(), &mut config_account.data.borrow_mut())?;
+
+        // --- Burn the liquidator's stablecoin and hand over the collateral ---
+        invoke(
+            &token_instruction::burn(
+                token_program_info.key,
+                source_account.key,
+                mint_account_info.key,
+                liquidator_account.key,
+                &[],
+                repay,
+            )?,
+            &[
+                source_account.clone(),
+                mint_account_info.clone(),
+                liquidator_account.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        invoke_signed(
+            &token_instruction::transfer_checked(
+                token_program_info.key,
+                vault_account.key,
+                collateral_mint_info.key,
+                destination_account.key,
+                authority_account.key,
+                &[],
+                seize,
+                config_data.collateral_decimals,
+            )?,
+            &[
+                vault_account.clone(),
+                collateral_mint_info.clone(),
+                destination_account.clone(),
+                authority_account.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+        if fees > 0 {
+            Self::mint_stablecoin(
+                token_program_info,
+                mint_account_info,
+                surplus_account,
+                authority_account,
+                config_account,
+                authority_bump,
+                fees,
+            )?;
+        }
+
+        msg!(
+            "Liquidated {}: repaid {}, seized {} collateral, bad debt {}",
+            position_key,
+            repay,
+            seize,
+            written_off
+        );
+        Ok(())
+    }
+
+    /// Processes the ConfigureOracle instruction.
+    fn process_configure_oracle(
+        accounts: &[AccountInfo],
+        max_price_age_secs: i64,
+        max_confidence_bps: u16,
+        authorized_by: AuthorizedBy,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer)
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin_action(&config_data, admin_account, authorized_by)?;
+
+        if max_price_age_secs <= 0 || max_confidence_bps as u64 > BPS_DENOMINATOR {
+            msg!("Error: Invalid oracle limits");
+            return Err(StablecoinError::InvalidPrice.into());
+        }
+
+        config_data.max_price_age_secs = max_price_age_secs;
+        config_data.max_confidence_bps = max_confidence_bps;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Oracle limits set. Max age: {}s, max confidence: {} bps", max_price_age_secs, max_confidence_bps);
+        Ok(())
+    }
+
+    /// Processes the AddPublisher instruction.
+    fn process_add_publisher(
+        accounts: &[AccountInfo],
+        publisher: Pubkey,
+        authorized_by: AuthorizedBy,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer, Writable) - Pays rent for the publisher PDA
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Publisher PDA (Writable)
+        let publisher_account = next_account_info(account_info_iter)?;
+        // Account 3: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin_action(&config_data, admin_account, authorized_by)?;
+
+        let (publisher_key, publisher_bump) = find_publisher_address(config_account.key, &publisher, program_id);
+        if publisher_key != *publisher_account.key {
+            msg!("Error: Publisher account is not the PDA for this publisher");
+            return Err(StablecoinError::PublisherNotWhitelisted.into());
+        }
+        if !publisher_account.data_is_empty() {
+            msg!("Error: Publisher already whitelisted");
+            return Err(StablecoinError::AlreadyInitialized.into());
+        }
+
+        Self::create_pda_account(
+            admin_account,
+            publisher_account,
+            system_program,
+            PublisherAccount::LEN,
+            &[PUBLISHER_SEED, config_account.key.as_ref(), publisher.as_ref(), &[publisher_bump]],
+            program_id,
+        )?;
+        let publisher_data = PublisherAccount {
+            is_initialized: true,
+            config: *config_account.key,
+            publisher,
+            bump: publisher_bump,
+        };
+        PublisherAccount::pack(publisher_data, &mut publisher_account.data.borrow_mut())?;
+
+        msg!("Price publisher {} whitelisted", publisher);
+        Ok(())
+    }
+
+    /// Processes the RemovePublisher instruction.
+    fn process_remove_publisher(
+        accounts: &[AccountInfo],
+        publisher: Pubkey,
+        authorized_by: AuthorizedBy,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer, Writable) - Receives the PDA's rent
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Publisher PDA (Writable)
+        let publisher_account = next_account_info(account_info_iter)?;
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin_action(&config_data, admin_account, authorized_by)?;
+
+        let (publisher_key, _) = find_publisher_address(config_account.key, &publisher, program_id);
+        if publisher_key != *publisher_account.key {
+            msg!("Error: Publisher account is not the PDA for this publisher");
+            return Err(StablecoinError::PublisherNotWhitelisted.into());
+        }
+        if publisher_account.owner != program_id {
+            msg!("Error: Publisher is not whitelisted");
+            return Err(StablecoinError::PublisherNotWhitelisted.into());
+        }
+        let publisher_data = PublisherAccount::unpack(&publisher_account.data.borrow())?;
+        if publisher_data.config != *config_account.key || publisher_data.publisher != publisher {
+            msg!("Error: Publisher account does not hold this publisher");
+            return Err(StablecoinError::PublisherNotWhitelisted.into());
+        }
+
+        Self::close_account(publisher_account, admin_account)?;
+
+        msg!("Price publisher {} removed", publisher);
+        Ok(())
+    }
+
+    /// Processes the SetMasterMinter instruction.
+    fn process_set_master_minter(
+        accounts: &[AccountInfo],
+        master_minter: Pubkey,
+        authorized_by: AuthorizedBy,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer)
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin_action(&config_data, admin_account, authorized_by)?;
+
+        config_data.master_minter = master_minter;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Master minter set to: {}", master_minter);
+        Ok(())
+    }
+
+    /// Processes the ConfigureMinter instruction.
+    fn process_configure_minter(
+        accounts: &[AccountInfo],
+        minter: Pubkey,
+        allowance: u64,
+        authorized_by: AuthorizedBy,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Master Minter Account (Signer, Writable) - Pays rent for a new minter PDA
+        let master_minter_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Minter PDA (Writable)
+        let minter_pda_account = next_account_info(account_info_iter)?;
+        // Account 3: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        Self::check_master_minter(&config_data, master_minter_account, authorized_by)?;
+
+        let (minter_key, minter_bump) = find_minter_address(config_account.key, &minter, program_id);
+        if minter_key != *minter_pda_account.key {
+            msg!("Error: Minter account is not the PDA for this minter");
+            return Err(StablecoinError::MinterMismatch.into());
+        }
+
+        // Create the minter account on first use; later calls replace the allowance
+        if minter_pda_account.data_is_empty() {
+            Self::create_pda_account(
+                master_minter_account,
+                minter_pda_account,
+                system_program,
+                MinterAccount::LEN,
+                &[MINTER_SEED, config_account.key.as_ref(), minter.as_ref(), &[minter_bump]],
+                program_id,
+            )?;
+        } else {
+            Self::load_minter(minter_pda_account, config_account, program_id)?;
+        }
+
+        let minter_data = MinterAccount {
+            is_initialized: true,
+            config: *config_account.key,
+            minter,
+            allowance,
+            bump: minter_bump,
+        };
+        MinterAccount::pack(minter_data, &mut minter_pda_account.data.borrow_mut())?;
+
+        msg!("Minter {} configured with allowance {}", minter, allowance);
+        Ok(())
+    }
+
+    /// Processes the RemoveMinter instruction.
+    fn process_remove_minter(
+        accounts: &[AccountInfo],
+        minter: Pubkey,
+        authorized_by: AuthorizedBy,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Master Minter Account (Signer, Writable) - Receives the PDA's rent
+        let master_minter_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Minter PDA (Writable)
+        let minter_pda_account = next_account_info(account_info_iter)?;
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        Self::check_master_minter(&config_data, master_minter_account, authorized_by)?;
+
+        let (minter_key, _) = find_minter_address(config_account.key, &minter, program_id);
+        if minter_key != *minter_pda_account.key {
+            msg!("Error: Minter account is not the PDA for this minter");
+            return Err(StablecoinError::MinterMismatch.into());
+        }
+        let minter_data = Self::load_minter(minter_pda_account, config_account, program_id)?;
+        if minter_data.minter != minter {
+            msg!("Error: Minter account does not hold this minter");
+            return Err(StablecoinError::MinterMismatch.into());
+        }
+        Self::close_account(minter_pda_account, master_minter_account)?;
+
+        msg!("Minter {} removed", minter);
+        Ok(())
+    }
+
+    /// Processes the SetBlacklister instruction.
+    fn process_set_blacklister(
+        accounts: &[AccountInfo],
+        blacklister: Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer)
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin(&config_data, admin_account)?;
+
+        let old_blacklister = config_data.blacklister;
+        config_data.blacklister = blacklister;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        emit(&StablecoinEvent::BlacklisterChanged {
+            config: *config_account.key,
+            old_blacklister,
+            new_blacklister: blacklister,
+        });
+        msg!("Blacklister set to: {}", blacklister);
+        Ok(())
+    }
+
+    /// Processes the Blacklist instruction.
+    fn process_blacklist(accounts: &[AccountInfo], holder: Pubkey, program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Blacklister Account (Signer, Writable) - Pays rent for a new entry
+        let blacklister_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Blacklist Entry PDA (Writable)
+        let blacklist_account = next_account_info(account_info_iter)?;
+        // Account 3: Holder Token Account (Writable) - The account to freeze
+        let token_account = next_account_info(account_info_iter)?;
+        // Account 4: Mint Account (Readonly)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 5: Program Authority PDA (Readonly) - The freeze authority
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 6: SPL Token Program (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 7: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        Self::check_blacklister(&config_data, blacklister_account)?;
+        Self::check_token_program(token_program_info)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        Self::load_holder_token_account(token_account, &config_data, &holder)?;
+
+        let (blacklist_key, blacklist_bump) = find_blacklist_address(config_account.key, &holder, program_id);
+        if blacklist_key != *blacklist_account.key {
+            msg!("Error: Blacklist account is not the PDA for this holder");
+            return Err(StablecoinError::BlacklistEntryMismatch.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut entry = if blacklist_account.data_is_empty() {
+            Self::create_pda_account(
+                blacklister_account,
+                blacklist_account,
+                system_program,
+                BlacklistEntry::LEN,
+                &[BLACKLIST_SEED, config_account.key.as_ref(), holder.as_ref(), &[blacklist_bump]],
+                program_id,
+            )?;
+            BlacklistEntry {
+                is_initialized: true,
+                config: *config_account.key,
+                holder,
+                frozen_accounts: 0,
+                blacklisted_at: now,
+                bump: blacklist_bump,
+            }
+        } else {
+            BlacklistEntry::unpack(&blacklist_account.data.borrow())?
+        };
+
+        // Freeze the holder's account; SPL Token rejects accounts that are already frozen
+        invoke_signed(
+            &token_instruction::freeze_account(
+                token_program_info.key,
+                token_account.key,
+                mint_account_info.key,
+                authority_account.key,
+                &[],
+            )?,
+            &[token_account.clone(), mint_account_info.clone(), authority_account.clone()],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+
+        entry.frozen_accounts = entry
+            .frozen_accounts
+            .checked_add(1)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        BlacklistEntry::pack(entry, &mut blacklist_account.data.borrow_mut())?;
+
+        emit(&StablecoinEvent::Blacklisted {
+            config: *config_account.key,
+            holder,
+            token_account: *token_account.key,
+            blacklister: *blacklister_account.key,
+            timestamp: now,
+        });
+        msg!("Holder {} blacklisted, token account {} frozen", holder, token_account.key);
+        Ok(())
+    }
+
+    /// Processes the Unblacklist instruction.
+    fn process_unblacklist(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Blacklister Account (Signer, Writable) - Receives the entry's rent
+        let blacklister_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Blacklist Entry PDA (Writable)
+        let blacklist_account = next_account_info(account_info_iter)?;
+        // Account 3: Holder Token Account (Writable) - The account to thaw
+        let token_account = next_account_info(account_info_iter)?;
+        // Account 4: Mint Account (Readonly)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 5: Program Authority PDA (Readonly) - The freeze authority
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 6: SPL Token Program (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        Self::check_blacklister(&config_data, blacklister_account)?;
+        Self::check_token_program(token_program_info)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+
+        if blacklist_account.owner != program_id {
+            msg!("Error: Blacklist entry not owned by program");
+            return Err(StablecoinError::BlacklistEntryMismatch.into());
+        }
+        let mut entry = BlacklistEntry::unpack(&blacklist_account.data.borrow())?;
+        if entry.config != *config_account.key {
+            msg!("Error: Blacklist entry belongs to a different config");
+            return Err(StablecoinError::BlacklistEntryMismatch.into());
+        }
+        let holder = entry.holder;
+        Self::load_holder_token_account(token_account, &config_data, &holder)?;
+
+        // Thaw the holder's account; SPL Token rejects accounts that aren't frozen
+        invoke_signed(
+            &token_instruction::thaw_account(
+                token_program_info.key,
+                token_account.key,
+                mint_account_info.key,
+                authority_account.key,
+                &[],
+            )?,
+            &[token_account.clone(), mint_account_info.clone(), authority_account.clone()],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+
+        entry.frozen_accounts = entry.frozen_accounts.saturating_sub(1);
+        if entry.frozen_accounts == 0 {
+            // Last frozen account thawed: the holder is no longer blacklisted
+            Self::close_account(blacklist_account, blacklister_account)?;
+        } else {
+            BlacklistEntry::pack(entry, &mut blacklist_account.data.borrow_mut())?;
+        }
+
+        emit(&StablecoinEvent::Unblacklisted {
+            config: *config_account.key,
+            holder,
+            token_account: *token_account.key,
+            blacklister: *blacklister_account.key,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Token account {} of {} thawed", token_account.key, holder);
+        Ok(())
+    }
+
+    /// Processes the SetPauser instruction.
+    fn process_set_pauser(accounts: &[AccountInfo], pauser: Pubkey, program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer)
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin(&config_data, admin_account)?;
+
+        config_data.pauser = pauser;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Pauser set to: {}", pauser);
+        Ok(())
+    }
+
+    /// Processes the Pause and Unpause instructions.
+    fn process_set_paused(accounts: &[AccountInfo], paused: bool, program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Pauser Account (Signer)
+        let pauser_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        if !pauser_account.is_signer {
+            msg!("Error: Pauser signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if config_data.pauser == Pubkey::default() || config_data.pauser != *pauser_account.key {
+            msg!("Error: Signer is not the pauser");
+            return Err(StablecoinError::PauserMismatch.into());
+        }
+
+        config_data.paused = paused;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Program {}", if paused { "paused" } else { "unpaused" });
+        Ok(())
+    }
+
+    /// Processes the SetSupplyCap instruction.
+    fn process_set_supply_cap(
+        accounts: &[AccountInfo],
+        supply_cap: u64,
+        authorized_by: AuthorizedBy,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer)
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin_action(&config_data, admin_account, authorized_by)?;
+
+        config_data.supply_cap = supply_cap;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Supply cap set to: {}", supply_cap);
+        Ok(())
+    }
+
+    /// Processes the ConfigureAttestor instruction.
+    fn process_configure_attestor(
+        accounts: &[AccountInfo],
+        attestor: Pubkey,
+        max_attestation_age_secs: i64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer)
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin(&config_data, admin_account)?;
+
+        if attestor != Pubkey::default() && max_attestation_age_secs <= 0 {
+            msg!("Error: Max attestation age must be positive");
+            return Err(StablecoinError::StaleAttestation.into());
+        }
+
+        config_data.attestor = attestor;
+        config_data.max_attestation_age_secs = max_attestation_age_secs;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Attestor set to: {}, max age: {}s", attestor, max_attestation_age_secs);
+        Ok(())
+    }
+
+    /// Processes the PostReserveAttestation instruction.
+    fn process_post_reserve_attestation(
+        accounts: &[AccountInfo],
+        reserve_amount: u64,
+        as_of: i64,
+        report_hash: [u8; 32],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Attestor Account (Signer, Writable) - Pays rent for the attestation
+        let attestor_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable) - Attestation count
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Attestation PDA (Writable)
+        let attestation_account = next_account_info(account_info_iter)?;
+        // Account 3: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        if !attestor_account.is_signer {
+            msg!("Error: Attestor signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if config_data.attestor == Pubkey::default() || config_data.attestor != *attestor_account.key {
+            msg!("Error: Signer is not the attestor");
+            return Err(StablecoinError::AttestorMismatch.into());
+        }
+
+        let sequence = config_data.attestation_count;
+        let (attestation_key, attestation_bump) = find_attestation_address(config_account.key, sequence, program_id);
+        if attestation_key != *attestation_account.key {
+            msg!("Error: Attestation account is not the PDA for sequence {}", sequence);
+            return Err(StablecoinError::AttestationMismatch.into());
+        }
+
+        // Attestations only move forward in time and can't be dated in the future
+        let now = Clock::get()?.unix_timestamp;
+        if as_of > now {
+            msg!("Error: Attestation as-of time {} lies in the future", as_of);
+            return Err(StablecoinError::StaleAttestation.into());
+        }
+        if sequence > 0 {
+            // Account 4: Previous Attestation PDA (Readonly)
+            let previous_account = next_account_info(account_info_iter)?;
+            let (previous_key, _) = find_attestation_address(config_account.key, sequence - 1, program_id);
+            if previous_key != *previous_account.key || previous_account.owner != program_id {
+                msg!("Error: Account 4 is not the previous attestation (sequence {})", sequence - 1);
+                return Err(StablecoinError::AttestationMismatch.into());
+            }
+            let previous = ReserveAttestation::unpack(&previous_account.data.borrow())?;
+            if as_of <= previous.as_of {
+                msg!("Error: As-of time {} is not newer than {}", as_of, previous.as_of);
+                return Err(StablecoinError::StaleAttestation.into());
+            }
+        }
+
+        Self::create_pda_account(
+            attestor_account,
+            attestation_account,
+            system_program,
+            ReserveAttestation::LEN,
+            &[ATTESTATION_SEED, config_account.key.as_ref(), &sequence.to_le_bytes(), &[attestation_bump]],
+            program_id,
+        )?;
+        let attestation = ReserveAttestation {
+            is_initialized: true,
+            config: *config_account.key,
+            sequence,
+            reserve_amount,
+            as_of,
+            report_hash,
+            attestor: *attestor_account.key,
+            posted_at: now,
+            bump: attestation_bump,
+        };
+        ReserveAttestation::pack(attestation, &mut attestation_account.data.borrow_mut())?;
+
+        config_data.attestation_count = sequence.checked_add(1).ok_or(StablecoinError::NumericalOverflow)?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Reserve attestation {} posted: {} as of {}", sequence, reserve_amount, as_of);
+        Ok(())
+    }
+
+    /// Processes the ConfigurePsm instruction.
+    fn process_configure_psm(
+        accounts: &[AccountInfo],
+        fee_in_bps: u16,
+        fee_out_bps: u16,
+        swap_in_ceiling: u64,
+        swap_out_ceiling: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer, Writable) - Pays for the module and vault PDAs
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Stablecoin Mint (Readonly)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 3: Reserve Mint (Readonly)
+        let reserve_mint_info = next_account_info(account_info_iter)?;
+        // Account 4: PSM PDA (Writable)
+        let psm_account = next_account_info(account_info_iter)?;
+        // Account 5: PSM Vault PDA (Writable)
+        let vault_account = next_account_info(account_info_iter)?;
+        // Account 6: Treasury Stablecoin Token Account (Readonly)
+        let treasury_account = next_account_info(account_info_iter)?;
+        // Account 7: Program Authority PDA (Readonly) - Vault owner
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 8: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 9: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin(&config_data, admin_account)?;
+        Self::check_token_program(token_program_info)?;
+        Self::check_authority(authority_account, config_account, program_id)?;
+
+        if fee_in_bps as u64 > BPS_DENOMINATOR || fee_out_bps as u64 > BPS_DENOMINATOR {
+            msg!("Error: PSM fees must be at most 100%");
+            return Err(StablecoinError::InvalidPsmParams.into());
+        }
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        // Swaps are 1:1 in base units, which is only 1:1 in value with equal decimals
+        let stable_decimals = Mint::unpack(&mint_account_info.data.borrow())?.decimals;
+        let reserve_decimals = Mint::unpack(&reserve_mint_info.data.borrow())?.decimals;
+        if reserve_decimals != stable_decimals || *reserve_mint_info.key == config_data.mint_account {
+            msg!("Error: Reserve mint must be another token with {} decimals", stable_decimals);
+            return Err(StablecoinError::InvalidPsmParams.into());
+        }
+        let treasury_data = TokenAccount::unpack(&treasury_account.data.borrow())?;
+        if treasury_data.mint != config_data.mint_account {
+            msg!("Error: Treasury is not a stablecoin token account");
+            return Err(StablecoinError::TokenAccountMismatch.into());
+        }
+
+        let (psm_key, psm_bump) = find_psm_address(config_account.key, reserve_mint_info.key, program_id);
+        let (vault_key, vault_bump) = find_psm_vault_address(config_account.key, reserve_mint_info.key, program_id);
+        if psm_key != *psm_account.key || vault_key != *vault_account.key {
+            msg!("Error: PSM or vault account is not the PDA for this reserve mint");
+            return Err(StablecoinError::PsmMismatch.into());
+        }
+
+        // Create the module and its vault on first use
+        let mut psm_data = if psm_account.data_is_empty() {
+            Self::create_pda_account(
+                admin_account,
+                psm_account,
+                system_program,
+                PsmAccount::LEN,
+                &[PSM_SEED, config_account.key.as_ref(), reserve_mint_info.key.as_ref(), &[psm_bump]],
+                program_id,
+            )?;
+            Self::create_pda_account(
+                admin_account,
+                vault_account,
+                system_program,
+                TokenAccount::LEN,
+                &[PSM_VAULT_SEED, config_account.key.as_ref(), reserve_mint_info.key.as_ref(), &[vault_bump]],
+                &spl_token::id(),
+            )?;
+            invoke(
+                &token_instruction::initialize_account3(
+                    token_program_info.key,
+                    vault_account.key,
+                    reserve_mint_info.key,
+                    authority_account.key,
+                )?,
+                &[vault_account.clone(), reserve_mint_info.clone()],
+            )?;
+            PsmAccount {
+                is_initialized: true,
+                config: *config_account.key,
+                reserve_mint: *reserve_mint_info.key,
+                bump: psm_bump,
+                vault_bump,
+                ..PsmAccount::default()
+            }
+        } else {
+            Self::load_psm(psm_account, config_account, program_id)?
+        };
+
+        psm_data.treasury = *treasury_account.key;
+        psm_data.fee_in_bps = fee_in_bps;
+        psm_data.fee_out_bps = fee_out_bps;
+        psm_data.swap_in_ceiling = swap_in_ceiling;
+        psm_data.swap_out_ceiling = swap_out_ceiling;
+        PsmAccount::pack(psm_data, &mut psm_account.data.borrow_mut())?;
+
+        msg!(
+            "PSM for {} configured. Fees: {}/{} bps, ceilings: {}/{}",
+            reserve_mint_info.key,
+            fee_in_bps,
+            fee_out_bps,
+            swap_in_ceiling,
+            swap_out_ceiling
+        );
+        Ok(())
+    }
+
+    /// Processes the SwapIn instruction.
+    fn process_swap_in(accounts: &[AccountInfo], amount: u64, program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: User (Signer) - Owner of the source reserve account
+        let user_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: PSM PDA (Writable)
+        let psm_account = next_account_info(account_info_iter)?;
+        // Account 3: User Reserve Token Account (Writable) - Source
+        let source_account = next_account_info(account_info_iter)?;
+        // Account 4: PSM Vault PDA (Writable)
+        let vault_account = next_account_info(account_info_iter)?;
+        // Account 5: Stablecoin Mint (Writable)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 6: User Stablecoin Token Account (Writable) - Destination
+        let destination_account = next_account_info(account_info_iter)?;
+        // Account 7: Treasury Stablecoin Token Account (Writable)
+        let treasury_account = next_account_info(account_info_iter)?;
+        // Account 8: Program Authority PDA (Readonly) - The mint authority
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 9: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 10: Blacklist PDA of the destination's owner (Readonly)
+        let blacklist_account = next_account_info(account_info_iter)?;
+
+        if !user_account.is_signer {
+            msg!("Error: User signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_not_settled(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        let mut psm_data = Self::load_psm(psm_account, config_account, program_id)?;
+        Self::check_psm_accounts(&psm_data, config_account, vault_account, treasury_account, program_id)?;
+
+        let new_reserves = psm_data
+            .total_reserves
+            .checked_add(amount)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        if new_reserves > psm_data.swap_in_ceiling {
+            msg!("Error: Reserves {} would exceed the swap-in ceiling {}", new_reserves, psm_data.swap_in_ceiling);
+            return Err(StablecoinError::PsmCeilingExceeded.into());
+        }
+        Self::check_supply_cap(&config_data, mint_account_info, amount)?;
+        // Compliance Check: Refuse to mint to a blacklisted owner
+        let destination_data = TokenAccount::unpack(&destination_account.data.borrow())?;
+        Self::check_not_blacklisted(blacklist_account, config_account, &destination_data.owner, program_id)?;
+        let fee = apply_bps(amount, psm_data.fee_in_bps as u64).ok_or(StablecoinError::NumericalOverflow)?;
+
+        // Reserves in; the vault balance backs every stablecoin this swap mints
+        invoke(
+            &token_instruction::transfer(
+                token_program_info.key,
+                source_account.key,
+                vault_account.key,
+                user_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                source_account.clone(),
+                vault_account.clone(),
+                user_account.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        Self::mint_stablecoin(
+            token_program_info,
+            mint_account_info,
+            destination_account,
+            authority_account,
+            config_account,
+            authority_bump,
+            amount - fee,
+        )?;
+        if fee > 0 {
+            Self::mint_stablecoin(
+                token_program_info,
+                mint_account_info,
+                treasury_account,
+                authority_account,
+                config_account,
+                authority_bump,
+                fee,
+            )?;
+        }
+
+        psm_data.total_reserves = new_reserves;
+        psm_data.net_swapped_out = psm_data.net_swapped_out.saturating_sub(amount);
+        config_data.psm_debt = config_data
+            .psm_debt
+            .checked_add(amount)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        PsmAccount::pack(psm_data, &mut psm_account.data.borrow_mut())?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Swapped in {} reserve tokens. Minted {}, fee {}", amount, amount - fee, fee);
+        Ok(())
+    }
+
+    /// Processes the SwapOut instruction.
+    fn process_swap_out(accounts: &[AccountInfo], amount: u64, program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: User (Signer) - Owner of the source stablecoin account
+        let user_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: PSM PDA (Writable)
+        let psm_account = next_account_info(account_info_iter)?;
+        // Account 3: User Stablecoin Token Account (Writable) - Source
+        let source_account = next_account_info(account_info_iter)?;
+        // Account 4: Stablecoin Mint (Writable)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 5: Treasury Stablecoin Token Account (Writable)
+        let treasury_account = next_account_info(account_info_iter)?;
+        // Account 6: PSM Vault PDA (Writable)
+        let vault_account = next_account_info(account_info_iter)?;
+        // Account 7: User Reserve Token Account (Writable) - Destination
+        let destination_account = next_account_info(account_info_iter)?;
+        // Account 8: Program Authority PDA (Readonly) - The vault owner
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 9: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if !user_account.is_signer {
+            msg!("Error: User signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        let mut psm_data = Self::load_psm(psm_account, config_account, program_id)?;
+        Self::check_psm_accounts(&psm_data, config_account, vault_account, treasury_account, program_id)?;
+
+        let fee = apply_bps(amount, psm_data.fee_out_bps as u64).ok_or(StablecoinError::NumericalOverflow)?;
+        let payout = amount - fee;
+        let new_swapped_out = psm_data
+            .net_swapped_out
+            .checked_add(payout)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        if new_swapped_out > psm_data.swap_out_ceiling {
+            msg!("Error: Net outflow {} would exceed the swap-out ceiling {}", new_swapped_out, psm_data.swap_out_ceiling);
+            return Err(StablecoinError::PsmCeilingExceeded.into());
+        }
+        if payout > psm_data.total_reserves {
+            msg!("Error: Payout {} exceeds PSM reserves {}", payout, psm_data.total_reserves);
+            return Err(StablecoinError::PsmCeilingExceeded.into());
+        }
+
+        // The fee stays in circulation with the treasury; the rest is burned against the payout
+        if fee > 0 {
+            invoke(
+                &token_instruction::transfer(
+                    token_program_info.key,
+                    source_account.key,
+                    treasury_account.key,
+                    user_account.key,
+                    &[],
+                    fee,
+                )?,
+                &[
+                    source_account.clone(),
+                    treasury_account.clone(),
+                    user_account.clone(),
+                    token_program_info.clone(),
+                ],
+            )?;
+        }
+        invoke(
+            &token_instruction::burn(
+                token_program_info.key,
+                source_account.key,
+                mint_account_info.key,
+                user_account.key,
+                &[],
+                payout,
+            )?,
+            &[
+                source_account.clone(),
+                mint_account_info.clone(),
+                user_account.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        invoke_signed(
+            &token_instruction::transfer(
+                token_program_info.key,
+                vault_account.key,
+                destination_account.key,
+                authority_account.key,
+                &[],
+                payout,
+            )?,
+            &[
+                vault_account.clone(),
+                destination_account.clone(),
+                authority_account.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+
+        psm_data.total_reserves -= payout;
+        psm_data.net_swapped_out = new_swapped_out;
+        config_data.psm_debt = config_data.psm_debt.saturating_sub(payout);
+        PsmAccount::pack(psm_data, &mut psm_account.data.borrow_mut())?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Swapped out {} stablecoin. Paid {} reserve tokens, fee {}", amount, payout, fee);
+        Ok(())
+    }
+
+    /// Processes the ConfigureRedemptions instruction.
+    fn process_configure_redemptions(
+        accounts: &[AccountInfo],
+        operator: Pubkey,
+        timeout_secs: i64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer, Writable) - Pays for the escrow PDA
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Stablecoin Mint (Readonly)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 3: Redemption Escrow PDA (Writable)
+        let escrow_account = next_account_info(account_info_iter)?;
+        // Account 4: Program Authority PDA (Readonly) - Escrow owner
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 5: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 6: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin(&config_data, admin_account)?;
+        Self::check_token_program(token_program_info)?;
+        Self::check_authority(authority_account, config_account, program_id)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        if timeout_secs <= 0 {
+            msg!("Error: Redemption timeout must be positive");
+            return Err(StablecoinError::RedemptionsNotConfigured.into());
+        }
+
+        // Create the escrow on first use
+        let (escrow_key, escrow_bump) = find_redemption_escrow_address(config_account.key, program_id);
+        if escrow_key != *escrow_account.key {
+            msg!("Error: Escrow account is not the redemption escrow PDA");
+            return Err(StablecoinError::RedemptionMismatch.into());
+        }
+        if escrow_account.data_is_empty() {
+            Self::create_pda_account(
+                admin_account,
+                escrow_account,
+                system_program,
+                TokenAccount::LEN,
+                &[REDEMPTION_ESCROW_SEED, config_account.key.as_ref(), &[escrow_bump]],
+                &spl_token::id(),
+            )?;
+            invoke(
+                &token_instruction::initialize_account3(
+                    token_program_info.key,
+                    escrow_account.key,
+                    mint_account_info.key,
+                    authority_account.key,
+                )?,
+                &[escrow_account.clone(), mint_account_info.clone()],
+            )?;
+        }
+
+        config_data.redemption_operator = operator;
+        config_data.redemption_timeout_secs = timeout_secs;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Redemptions configured. Operator: {}, timeout: {}s", operator, timeout_secs);
+        Ok(())
+    }
+
+    /// Processes the RequestRedemption instruction.
+    fn process_request_redemption(
+        accounts: &[AccountInfo],
+        amount: u64,
+        payout_ref_hash: [u8; 32],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Holder (Signer, Writable) - Pays for the request PDA
+        let holder_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable) - Request counter
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Redemption Request PDA (Writable)
+        let request_account = next_account_info(account_info_iter)?;
+        // Account 3: Holder Stablecoin Token Account (Writable) - Source
+        let source_account = next_account_info(account_info_iter)?;
+        // Account 4: Redemption Escrow PDA (Writable)
+        let escrow_account = next_account_info(account_info_iter)?;
+        // Account 5: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 6: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        if !holder_account.is_signer {
+            msg!("Error: Holder signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        if config_data.redemption_operator == Pubkey::default() {
+            msg!("Error: Redemptions have not been configured");
+            return Err(StablecoinError::RedemptionsNotConfigured.into());
+        }
+        Self::check_redemption_escrow(escrow_account, config_account, program_id)?;
+        if amount == 0 {
+            msg!("Error: Redemption amount must be positive");
+            return Err(StablecoinError::InvalidInstruction.into());
+        }
+
+        let id = config_data.redemption_count;
+        let (request_key, request_bump) = find_redemption_address(config_account.key, id, program_id);
+        if request_key != *request_account.key {
+            msg!("Error: Request account is not the PDA for redemption {}", id);
+            return Err(StablecoinError::RedemptionMismatch.into());
+        }
+
+        // Escrow the tokens; SPL Token checks the holder owns the source account
+        invoke(
+            &token_instruction::transfer(
+                token_program_info.key,
+                source_account.key,
+                escrow_account.key,
+                holder_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                source_account.clone(),
+                escrow_account.clone(),
+                holder_account.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+
+        Self::create_pda_account(
+            holder_account,
+            request_account,
+            system_program,
+            RedemptionRequest::LEN,
+            &[REDEMPTION_SEED, config_account.key.as_ref(), &id.to_le_bytes(), &[request_bump]],
+            program_id,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        let expires_at = now
+            .checked_add(config_data.redemption_timeout_secs)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        let request = RedemptionRequest {
+            is_initialized: true,
+            config: *config_account.key,
+            id,
+            holder: *holder_account.key,
+            holder_token_account: *source_account.key,
+            amount,
+            payout_ref_hash,
+            status: RedemptionStatus::Pending,
+            requested_at: now,
+            expires_at,
+            resolved_at: 0,
+            bank_ref_hash: [0; 32],
+            bump: request_bump,
+        };
+        RedemptionRequest::pack(request, &mut request_account.data.borrow_mut())?;
+
+        config_data.redemption_count = id.checked_add(1).ok_or(StablecoinError::NumericalOverflow)?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        emit(&StablecoinEvent::RedemptionRequested {
+            config: *config_account.key,
+            id,
+            holder: *holder_account.key,
+            amount,
+            payout_ref_hash,
+            expires_at,
+        });
+        msg!("Redemption {} requested for {}", id, amount);
+        Ok(())
+    }
+
+    /// Processes the FulfillRedemption instruction.
+    fn process_fulfill_redemption(
+        accounts: &[AccountInfo],
+        bank_ref_hash: [u8; 32],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Redemption Operator (Signer)
+        let operator_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Redemption Request PDA (Writable)
+        let request_account = next_account_info(account_info_iter)?;
+        // Account 3: Redemption Escrow PDA (Writable)
+        let escrow_account = next_account_info(account_info_iter)?;
+        // Account 4: Stablecoin Mint (Writable)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 5: Program Authority PDA (Readonly) - The escrow owner
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 6: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_redemption_operator(&config_data, operator_account)?;
+        Self::check_token_program(token_program_info)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        Self::check_redemption_escrow(escrow_account, config_account, program_id)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        let mut request = Self::load_pending_redemption(request_account, config_account, program_id)?;
+
+        invoke_signed(
+            &token_instruction::burn(
+                token_program_info.key,
+                escrow_account.key,
+                mint_account_info.key,
+                authority_account.key,
+                &[],
+                request.amount,
+            )?,
+            &[
+                escrow_account.clone(),
+                mint_account_info.clone(),
+                authority_account.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+
+        request.status = RedemptionStatus::Fulfilled;
+        request.resolved_at = Clock::get()?.unix_timestamp;
+        request.bank_ref_hash = bank_ref_hash;
+        let (id, amount) = (request.id, request.amount);
+        RedemptionRequest::pack(request, &mut request_account.data.borrow_mut())?;
+
+        emit(&StablecoinEvent::RedemptionFulfilled {
+            config: *config_account.key,
+            id,
+            operator: *operator_account.key,
+            amount,
+            bank_ref_hash,
+        });
+        msg!("Redemption {} fulfilled, {} burned", id, amount);
+        Ok(())
+    }
+
+    /// Processes the RejectRedemption (operator) and CancelRedemption (holder, after the
+    /// timeout) instructions, which both return the escrowed tokens to the holder.
+    fn process_return_redemption(
+        accounts: &[AccountInfo],
+        outcome: RedemptionStatus,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Operator (Reject) or Holder (Cancel) (Signer)
+        let signer_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Redemption Request PDA (Writable)
+        let request_account = next_account_info(account_info_iter)?;
+        // Account 3: Redemption Escrow PDA (Writable)
+        let escrow_account = next_account_info(account_info_iter)?;
+        // Account 4: Holder Stablecoin Token Account (Writable) - Destination
+        let destination_account = next_account_info(account_info_iter)?;
+        // Account 5: Program Authority PDA (Readonly) - The escrow owner
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 6: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        Self::check_redemption_escrow(escrow_account, config_account, program_id)?;
+        let mut request = Self::load_pending_redemption(request_account, config_account, program_id)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        if outcome == RedemptionStatus::Rejected {
+            Self::check_redemption_operator(&config_data, signer_account)?;
+        } else {
+            if !signer_account.is_signer || *signer_account.key != request.holder {
+                msg!("Error: Only the holder can cancel a redemption request");
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if now < request.expires_at {
+                msg!("Error: Redemption {} can be cancelled from {}", request.id, request.expires_at);
+                return Err(StablecoinError::RedemptionNotExpired.into());
+            }
+        }
+        if *destination_account.key != request.holder_token_account {
+            msg!("Error: Destination is not the token account the tokens came from");
+            return Err(StablecoinError::RedemptionMismatch.into());
+        }
+
+        invoke_signed(
+            &token_instruction::transfer(
+                token_program_info.key,
+                escrow_account.key,
+                destination_account.key,
+                authority_account.key,
+                &[],
+                request.amount,
+            )?,
+            &[
+                escrow_account.clone(),
+                destination_account.clone(),
+                authority_account.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+
+        request.status = outcome;
+        request.resolved_at = now;
+        let (id, holder, amount) = (request.id, request.holder, request.amount);
+        RedemptionRequest::pack(request, &mut request_account.data.borrow_mut())?;
+
+        let event = if outcome == RedemptionStatus::Rejected {
+            StablecoinEvent::RedemptionRejected {
+                config: *config_account.key,
+                id,
+                operator: *signer_account.key,
+                amount,
+            }
+        } else {
+            StablecoinEvent::RedemptionCancelled {
+                config: *config_account.key,
+                id,
+                holder,
+                amount,
+            }
+        };
+        emit(&event);
+        msg!("Redemption {} {:?}, {} returned", id, outcome, amount);
+        Ok(())
+    }
+
+    /// Processes the ConfigureTimelock instruction.
+    fn process_configure_timelock(
+        accounts: &[AccountInfo],
+        delay_secs: i64,
+        guardian: Pubkey,
+        authorized_by: AuthorizedBy,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer)
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin_action(&config_data, admin_account, authorized_by)?;
+
+        if !(0..=MAX_TIMELOCK_DELAY_SECS).contains(&delay_secs) {
+            msg!("Error: Timelock delay must be between 0 and {}s", MAX_TIMELOCK_DELAY_SECS);
+            return Err(StablecoinError::InvalidTimelockDelay.into());
+        }
+
+        config_data.timelock_delay_secs = delay_secs;
+        config_data.guardian = guardian;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Timelock delay set to {}s, guardian: {}", delay_secs, guardian);
+        Ok(())
+    }
+
+    /// Processes the QueueAction instruction.
+    fn process_queue_action(
+        accounts: &[AccountInfo],
+        instruction_data: Vec<u8>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Proposer (Signer, Writable) - Pays for the action PDA
+        let proposer_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable) - Action counter
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Action PDA (Writable)
+        let action_account = next_account_info(account_info_iter)?;
+        // Account 3: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        if !proposer_account.is_signer {
+            msg!("Error: Proposer signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+
+        if instruction_data.len() > MAX_ACTION_DATA_LEN {
+            msg!("Error: Instruction data longer than {} bytes", MAX_ACTION_DATA_LEN);
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let instruction = StablecoinInstruction::try_from_slice(&instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        if !instruction.is_timelocked() {
+            msg!("Error: Instruction cannot be timelocked");
+            return Err(StablecoinError::ActionNotTimelocked.into());
+        }
+        // Only the role holder that could send the instruction directly can queue it
+        let role = match instruction {
+            StablecoinInstruction::ConfigureMinter { .. } | StablecoinInstruction::RemoveMinter { .. } => {
+                config_data.master_minter
+            }
+            _ => config_data.admin,
+        };
+        if role == Pubkey::default() || role != *proposer_account.key {
+            msg!("Error: Signer does not hold the role this instruction requires");
+            return Err(StablecoinError::AdminSignatureMismatch.into());
+        }
+
+        let id = config_data.action_count;
+        let (action_key, action_bump) = find_action_address(config_account.key, id, program_id);
+        if action_key != *action_account.key {
+            msg!("Error: Action account is not the PDA for action {}", id);
+            return Err(StablecoinError::ActionMismatch.into());
+        }
+        Self::create_pda_account(
+            proposer_account,
+            action_account,
+            system_program,
+            TimelockAction::LEN,
+            &[ACTION_SEED, config_account.key.as_ref(), &id.to_le_bytes(), &[action_bump]],
+            program_id,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let eta = now
+            .checked_add(config_data.timelock_delay_secs)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        let action = TimelockAction {
+            is_initialized: true,
+            config: *config_account.key,
+            id,
+            proposer: *proposer_account.key,
+            instruction_data,
+            queued_at: now,
+            eta,
+            status: ActionStatus::Queued,
+            bump: action_bump,
+        };
+        TimelockAction::pack(action, &mut action_account.data.borrow_mut())?;
+
+        config_data.action_count = id.checked_add(1).ok_or(StablecoinError::NumericalOverflow)?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Action {} queued: {:?}, ETA {}", id, instruction, eta);
+        Ok(())
+    }
+
+    /// Loads a timelocked action of the given config and checks it is still queued.
+    fn load_queued_action(
+        action_account: &AccountInfo,
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<TimelockAction, ProgramError> {
+        if action_account.owner != program_id {
+            msg!("Error: Action account not owned by program");
+            return Err(StablecoinError::ActionMismatch.into());
+        }
+        let action = TimelockAction::unpack(&action_account.data.borrow())?;
+        if action.config != *config_account.key {
+            msg!("Error: Action belongs to a different config");
+            return Err(StablecoinError::ActionMismatch.into());
+        }
+        if action.status != ActionStatus::Queued {
+            msg!("Error: Action {} is {:?}", action.id, action.status);
+            return Err(StablecoinError::ActionNotQueued.into());
+        }
+        Ok(action)
+    }
+
+    /// Processes the ExecuteAction instruction.
+    fn process_execute_action(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Executor (Signer)
+        let executor_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Action PDA (Writable)
+        let action_account = next_account_info(account_info_iter)?;
+        // Accounts 3..: The wrapped instruction's accounts
+        let action_accounts = account_info_iter.as_slice();
+
+        if !executor_account.is_signer {
+            msg!("Error: Executor signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Self::load_config(config_account, program_id)?;
+        let mut action = Self::load_queued_action(action_account, config_account, program_id)?;
+        if Clock::get()?.unix_timestamp < action.eta {
+            msg!("Error: Action {} can execute from {}", action.id, action.eta);
+            return Err(StablecoinError::ActionNotReady.into());
+        }
+        // Every timelocked instruction takes the config as its second account; it must be
+        // the config the action was queued for
+        if action_accounts.get(1).map(|account| account.key) != Some(config_account.key) {
+            msg!("Error: Wrapped instruction must use the action's config account");
+            return Err(StablecoinError::ActionMismatch.into());
+        }
+        let instruction = StablecoinInstruction::try_from_slice(&action.instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        // Mark it executed first so the action can't run twice
+        action.status = ActionStatus::Executed;
+        let id = action.id;
+        TimelockAction::pack(action, &mut action_account.data.borrow_mut())?;
+
+        msg!("Executing action {}", id);
+        Self::process_timelocked(action_accounts, instruction, AuthorizedBy::Timelock, program_id)
+    }
+
+    /// Processes the CancelAction instruction.
+    fn process_cancel_action(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Guardian (Signer)
+        let guardian_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Action PDA (Writable)
+        let action_account = next_account_info(account_info_iter)?;
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        if !guardian_account.is_signer {
+            msg!("Error: Guardian signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if config_data.guardian == Pubkey::default() || config_data.guardian != *guardian_account.key {
+            msg!("Error: Signer is not the guardian");
+            return Err(StablecoinError::GuardianMismatch.into());
+        }
+        let mut action = Self::load_queued_action(action_account, config_account, program_id)?;
+
+        action.status = ActionStatus::Cancelled;
+        let id = action.id;
+        TimelockAction::pack(action, &mut action_account.data.borrow_mut())?;
+
+        msg!("Action {} cancelled by the guardian", id);
+        Ok(())
+    }
+
+    /// Checks a multisig signer set: 1 to 11 distinct keys and a threshold between 1 and
+    /// their count.
+    fn check_multisig_params(signers: &[Pubkey], threshold: u8) -> ProgramResult {
+        let has_duplicates = signers.iter().enumerate().any(|(i, signer)| signers[..i].contains(signer));
+        if signers.is_empty()
+            || signers.len() > MAX_MULTISIG_SIGNERS
+            || has_duplicates
+            || threshold == 0
+            || threshold as usize > signers.len()
+        {
+            msg!("Error: Need 1 to {} distinct signers and a threshold up to their count", MAX_MULTISIG_SIGNERS);
+            return Err(StablecoinError::InvalidMultisigParams.into());
+        }
+        Ok(())
+    }
+
+    /// Loads a multisig account owned by the program.
+    fn load_multisig(multisig_account: &AccountInfo, program_id: &Pubkey) -> Result<MultisigAccount, ProgramError> {
+        if multisig_account.owner != program_id {
+            msg!("Error: Multisig account not owned by program");
+            return Err(StablecoinError::MultisigMismatch.into());
+        }
+        MultisigAccount::unpack(&multisig_account.data.borrow())
+    }
+
+    /// Returns the index of a signing multisig signer.
+    fn multisig_signer_index(multisig: &MultisigAccount, signer_account: &AccountInfo) -> Result<usize, ProgramError> {
+        if !signer_account.is_signer {
+            msg!("Error: Multisig signer signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        multisig.signers.iter().position(|signer| signer == signer_account.key).ok_or_else(|| {
+            msg!("Error: {} is not a multisig signer", signer_account.key);
+            StablecoinError::NotMultisigSigner.into()
+        })
+    }
+
+    /// Loads an open proposal of the given multisig, made under its current signer set.
+    fn load_open_proposal(
+        proposal_account: &AccountInfo,
+        multisig_account: &AccountInfo,
+        multisig: &MultisigAccount,
+        program_id: &Pubkey,
+    ) -> Result<MultisigProposal, ProgramError> {
+        if proposal_account.owner != program_id {
+            msg!("Error: Proposal account not owned by program");
+            return Err(StablecoinError::ProposalMismatch.into());
+        }
+        let proposal = MultisigProposal::unpack(&proposal_account.data.borrow())?;
+        if proposal.multisig != *multisig_account.key {
+            msg!("Error: Proposal belongs to a different multisig");
+            return Err(StablecoinError::ProposalMismatch.into());
+        }
+        if proposal.executed {
+            msg!("Error: Proposal {} already executed", proposal.id);
+            return Err(StablecoinError::ProposalAlreadyExecuted.into());
+        }
+        if proposal.epoch != multisig.epoch {
+            msg!("Error: Proposal {} predates the current signer set", proposal.id);
+            return Err(StablecoinError::ProposalStale.into());
+        }
+        Ok(proposal)
+    }
+
+    /// Processes the CreateMultisig instruction.
+    fn process_create_multisig(
+        accounts: &[AccountInfo],
+        signers: Vec<Pubkey>,
+        threshold: u8,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer, Writable) - Pays for the multisig PDA
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Readonly)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Multisig PDA (Writable)
+        let multisig_account = next_account_info(account_info_iter)?;
+        // Account 3: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        let config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin(&config_data, admin_account)?;
+        Self::check_multisig_params(&signers, threshold)?;
+
+        let (multisig_key, multisig_bump) = find_multisig_address(config_account.key, program_id);
+        if multisig_key != *multisig_account.key {
+            msg!("Error: Multisig account is not the PDA for this config");
+            return Err(StablecoinError::MultisigMismatch.into());
+        }
+        Self::create_pda_account(
+            admin_account,
+            multisig_account,
+            system_program,
+            MultisigAccount::LEN,
+            &[MULTISIG_SEED, config_account.key.as_ref(), &[multisig_bump]],
+            program_id,
+        )?;
+        let (signer_key, signer_bump) = find_multisig_signer_address(&multisig_key, program_id);
+
+        let multisig = MultisigAccount {
+            is_initialized: true,
+            config: *config_account.key,
+            signers,
+            threshold,
+            epoch: 0,
+            proposal_count: 0,
+            bump: multisig_bump,
+            signer_bump,
+        };
+        msg!("Multisig created: {} of {} signers", threshold, multisig.signers.len());
+        MultisigAccount::pack(multisig, &mut multisig_account.data.borrow_mut())?;
+
+        msg!("Set {} as admin to hand control to the multisig", signer_key);
+        Ok(())
+    }
+
+    /// Processes the ChangeMultisig instruction.
+    fn process_change_multisig(
+        accounts: &[AccountInfo],
+        signers: Vec<Pubkey>,
+        threshold: u8,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Multisig Signer PDA (Signer)
+        let multisig_signer = next_account_info(account_info_iter)?;
+        // Account 1: Multisig PDA (Writable)
+        let multisig_account = next_account_info(account_info_iter)?;
+
+        let mut multisig = Self::load_multisig(multisig_account, program_id)?;
+        let signer_key = Pubkey::create_program_address(
+            &[MULTISIG_SIGNER_SEED, multisig_account.key.as_ref(), &[multisig.signer_bump]],
+            program_id,
+        )?;
+        if !multisig_signer.is_signer || signer_key != *multisig_signer.key {
+            msg!("Error: Only the multisig itself can change its signers");
+            return Err(StablecoinError::MultisigMismatch.into());
+        }
+        Self::check_multisig_params(&signers, threshold)?;
+
+        multisig.signers = signers;
+        multisig.threshold = threshold;
+        multisig.epoch = multisig.epoch.checked_add(1).ok_or(StablecoinError::NumericalOverflow)?;
+        msg!("Multisig changed to {} of {} signers", threshold, multisig.signers.len());
+        MultisigAccount::pack(multisig, &mut multisig_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes the Propose instruction.
+    fn process_propose(
+        accounts: &[AccountInfo],
+        target_program_id: Pubkey,
+        proposal_accounts: Vec<ProposalAccount>,
+        data: Vec<u8>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Proposer (Signer, Writable) - Pays for the proposal PDA
+        let proposer_account = next_account_info(account_info_iter)?;
+        // Account 1: Multisig PDA (Writable) - Proposal counter
+        let multisig_account = next_account_info(account_info_iter)?;
+        // Account 2: Proposal PDA (Writable)
+        let proposal_account = next_account_info(account_info_iter)?;
+        // Account 3: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        let mut multisig = Self::load_multisig(multisig_account, program_id)?;
+        let signer_index = Self::multisig_signer_index(&multisig, proposer_account)?;
+        if proposal_accounts.len() > MAX_PROPOSAL_ACCOUNTS || data.len() > MAX_PROPOSAL_DATA_LEN {
+            msg!(
+                "Error: Proposals hold at most {} accounts and {} bytes of data",
+                MAX_PROPOSAL_ACCOUNTS,
+                MAX_PROPOSAL_DATA_LEN
+            );
+            return Err(StablecoinError::ProposalTooLarge.into());
+        }
+
+        let id = multisig.proposal_count;
+        let (proposal_key, proposal_bump) = find_proposal_address(multisig_account.key, id, program_id);
+        if proposal_key != *proposal_account.key {
+            msg!("Error: Proposal account is not the PDA for proposal {}", id);
+            return Err(StablecoinError::ProposalMismatch.into());
+        }
+        Self::create_pda_account(
+            proposer_account,
+            proposal_account,
+            system_program,
+            MultisigProposal::LEN,
+            &[PROPOSAL_SEED, multisig_account.key.as_ref(), &id.to_le_bytes(), &[proposal_bump]],
+            program_id,
+        )?;
+
+        let proposal = MultisigProposal {
+            is_initialized: true,
+            multisig: *multisig_account.key,
+            id,
+            proposer: *proposer_account.key,
+            program_id: target_program_id,
+            accounts: proposal_accounts,
+            data,
+            approvals: 1 << signer_index,
+            epoch: multisig.epoch,
+            executed: false,
+            created_at: Clock::get()?.unix_timestamp,
+            bump: proposal_bump,
+        };
+        MultisigProposal::pack(proposal, &mut proposal_account.data.borrow_mut())?;
+
+        multisig.proposal_count = id.checked_add(1).ok_or(StablecoinError::NumericalOverflow)?;
+        MultisigAccount::pack(multisig, &mut multisig_account.data.borrow_mut())?;
+
+        msg!("Proposal {} created for program {}", id, target_program_id);
+        Ok(())
+    }
+
+    /// Processes the Approve instruction.
+    fn process_approve(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Multisig Signer (Signer)
+        let signer_account = next_account_info(account_info_iter)?;
+        // Account 1: Multisig PDA (Readonly)
+        let multisig_account = next_account_info(account_info_iter)?;
+        // Account 2: Proposal PDA (Writable)
+        let proposal_account = next_account_info(account_info_iter)?;
+
+        let multisig = Self::load_multisig(multisig_account, program_id)?;
+        let signer_index = Self::multisig_signer_index(&multisig, signer_account)?;
+        let mut proposal = Self::load_open_proposal(proposal_account, multisig_account, &multisig, program_id)?;
+        if proposal.approvals & (1 << signer_index) != 0 {
+            msg!("Error: {} already approved proposal {}", signer_account.key, proposal.id);
+            return Err(StablecoinError::ProposalAlreadyApproved.into());
+        }
+
+        proposal.approvals |= 1 << signer_index;
+        msg!(
+            "Proposal {} approved by {} ({} of {})",
+            proposal.id,
+            signer_account.key,
+            proposal.approvals.count_ones(),
+            multisig.threshold
+        );
+        MultisigProposal::pack(proposal, &mut proposal_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes the Execute instruction.
+    fn process_execute(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Executor (Signer)
+        let executor_account = next_account_info(account_info_iter)?;
+        // Account 1: Multisig PDA (Readonly)
+        let multisig_account = next_account_info(account_info_iter)?;
+        // Account 2: Proposal PDA (Writable)
+        let proposal_account = next_account_info(account_info_iter)?;
+        // Account 3: Target Program (Readonly)
+        let target_program = next_account_info(account_info_iter)?;
+        // Accounts 4..: The proposal's accounts
+        let instruction_accounts = account_info_iter.as_slice();
+
+        if !executor_account.is_signer {
+            msg!("Error: Executor signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let multisig = Self::load_multisig(multisig_account, program_id)?;
+        let mut proposal = Self::load_open_proposal(proposal_account, multisig_account, &multisig, program_id)?;
+        if proposal.approvals.count_ones() < multisig.threshold as u32 {
+            msg!(
+                "Error: Proposal {} has {} of {} approvals",
+                proposal.id,
+                proposal.approvals.count_ones(),
+                multisig.threshold
+            );
+            return Err(StablecoinError::NotEnoughApprovals.into());
+        }
+        // The approvals cover these exact accounts, so the executor can't swap any
+        if *target_program.key != proposal.program_id
+            || instruction_accounts.len() != proposal.accounts.len()
+            || instruction_accounts
+                .iter()
+                .zip(&proposal.accounts)
+                .any(|(account, expected)| account.key != &expected.pubkey)
+        {
+            msg!("Error: Accounts do not match proposal {}", proposal.id);
+            return Err(StablecoinError::ProposalMismatch.into());
+        }
+
+        let instruction = Instruction {
+            program_id: proposal.program_id,
+            accounts: proposal
+                .accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: proposal.data.clone(),
+        };
+
+        // Mark it executed first so the proposal can't run twice
+        proposal.executed = true;
+        let id = proposal.id;
+        MultisigProposal::pack(proposal, &mut proposal_account.data.borrow_mut())?;
+
+        msg!("Executing proposal {}", id);
+        let mut account_infos = instruction_accounts.to_vec();
+        account_infos.push(target_program.clone());
+        invoke_signed(
+            &instruction,
+            &account_infos,
+            &[&[MULTISIG_SIGNER_SEED, multisig_account.key.as_ref(), &[multisig.signer_bump]]],
+        )
+    }
+
+    /// Processes the ConfigureStabilityFee instruction.
+    fn process_configure_stability_fee(
+        accounts: &[AccountInfo],
+        stability_fee_bps: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer, Writable) - Pays for the surplus PDA
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Stablecoin Mint (Readonly)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 3: Surplus PDA (Writable)
+        let surplus_account = next_account_info(account_info_iter)?;
+        // Account 4: Program Authority PDA (Readonly) - Surplus owner
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 5: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 6: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin(&config_data, admin_account)?;
+        Self::check_token_program(token_program_info)?;
+        Self::check_authority(authority_account, config_account, program_id)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        if stability_fee_bps as u64 > BPS_DENOMINATOR {
+            msg!("Error: Stability fee must be at most {} bps", BPS_DENOMINATOR);
+            return Err(StablecoinError::InvalidStabilityFee.into());
+        }
+
+        // Create the surplus on first use
+        let (surplus_key, surplus_bump) = find_surplus_address(config_account.key, program_id);
+        if surplus_key != *surplus_account.key {
+            msg!("Error: Surplus account is not the surplus PDA");
+            return Err(StablecoinError::SurplusMismatch.into());
+        }
+        if surplus_account.data_is_empty() {
+            Self::create_pda_account(
+                admin_account,
+                surplus_account,
+                system_program,
+                TokenAccount::LEN,
+                &[SURPLUS_SEED, config_account.key.as_ref(), &[surplus_bump]],
+                &spl_token::id(),
+            )?;
+            invoke(
+                &token_instruction::initialize_account3(
+                    token_program_info.key,
+                    surplus_account.key,
+                    mint_account_info.key,
+                    authority_account.key,
+                )?,
+                &[surplus_account.clone(), mint_account_info.clone()],
+            )?;
         }
 
-        // Initialize the state
-        config_data.is_initialized = true;
-        config_data.admin = admin;
-        config_data.mint_account = *mint_account_info.key;
+        // Fees up to now accrue at the old rate
+        Self::accrue_stability_fee(&mut config_data)?;
+        config_data.stability_fee_bps = stability_fee_bps;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Stability fee set to {} bps per year", stability_fee_bps);
+        Ok(())
+    }
+
+    /// Processes the ConfigureSavings instruction.
+    fn process_configure_savings(
+        accounts: &[AccountInfo],
+        savings_rate_bps: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer, Writable) - Pays for the savings PDAs
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Stablecoin Mint (Readonly)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 3: Surplus PDA (Writable)
+        let surplus_account = next_account_info(account_info_iter)?;
+        // Account 4: Savings Vault PDA (Writable)
+        let savings_vault_account = next_account_info(account_info_iter)?;
+        // Account 5: Savings Share Mint PDA (Writable)
+        let share_mint_account = next_account_info(account_info_iter)?;
+        // Account 6: Program Authority PDA (Readonly) - Vault owner and share mint authority
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 7: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 8: System Program (Readonly)
+        let system_program = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin(&config_data, admin_account)?;
+        Self::check_token_program(token_program_info)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        Self::check_surplus(surplus_account, config_account, program_id)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        if savings_rate_bps as u64 > BPS_DENOMINATOR {
+            msg!("Error: Savings rate must be at most {} bps", BPS_DENOMINATOR);
+            return Err(StablecoinError::InvalidSavingsRate.into());
+        }
+
+        // Create the vault and share mint on first use
+        let (vault_key, vault_bump) = find_savings_vault_address(config_account.key, program_id);
+        let (share_mint_key, share_mint_bump) = find_savings_shares_address(config_account.key, program_id);
+        if vault_key != *savings_vault_account.key || share_mint_key != *share_mint_account.key {
+            msg!("Error: Savings accounts are not the savings PDAs");
+            return Err(StablecoinError::SavingsMismatch.into());
+        }
+        if savings_vault_account.data_is_empty() {
+            Self::create_pda_account(
+                admin_account,
+                savings_vault_account,
+                system_program,
+                TokenAccount::LEN,
+                &[SAVINGS_VAULT_SEED, config_account.key.as_ref(), &[vault_bump]],
+                &spl_token::id(),
+            )?;
+            invoke(
+                &token_instruction::initialize_account3(
+                    token_program_info.key,
+                    savings_vault_account.key,
+                    mint_account_info.key,
+                    authority_account.key,
+                )?,
+                &[savings_vault_account.clone(), mint_account_info.clone()],
+            )?;
+        }
+        if share_mint_account.data_is_empty() {
+            Self::create_pda_account(
+                admin_account,
+                share_mint_account,
+                system_program,
+                Mint::LEN,
+                &[SAVINGS_SHARES_SEED, config_account.key.as_ref(), &[share_mint_bump]],
+                &spl_token::id(),
+            )?;
+            invoke(
+                &token_instruction::initialize_mint2(
+                    token_program_info.key,
+                    share_mint_account.key,
+                    authority_account.key,
+                    None,
+                    config_data.stable_decimals,
+                )?,
+                std::slice::from_ref(share_mint_account),
+            )?;
+        }
+
+        // Interest up to now accrues at the old rate
+        if config_data.savings_share_price == 0 {
+            config_data.savings_share_price = RAY;
+            config_data.savings_updated_at = Clock::get()?.unix_timestamp;
+        } else {
+            Self::drip(
+                &mut config_data,
+                config_account,
+                surplus_account,
+                savings_vault_account,
+                share_mint_account,
+                authority_account,
+                token_program_info,
+                authority_bump,
+            )?;
+        }
+        config_data.savings_rate_bps = savings_rate_bps;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Savings rate set to {} bps per year", savings_rate_bps);
+        Ok(())
+    }
+
+    /// Checks savings are configured and the accounts are the config's surplus and
+    /// savings PDAs.
+    fn check_savings_accounts(
+        config_data: &ConfigAccount,
+        config_account: &AccountInfo,
+        surplus_account: &AccountInfo,
+        savings_vault_account: &AccountInfo,
+        share_mint_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        if config_data.savings_share_price == 0 {
+            msg!("Error: Savings are not configured");
+            return Err(StablecoinError::SavingsNotConfigured.into());
+        }
+        Self::check_surplus(surplus_account, config_account, program_id)?;
+        let (vault_key, _) = find_savings_vault_address(config_account.key, program_id);
+        let (share_mint_key, _) = find_savings_shares_address(config_account.key, program_id);
+        if vault_key != *savings_vault_account.key || share_mint_key != *share_mint_account.key {
+            msg!("Error: Savings accounts are not the savings PDAs");
+            return Err(StablecoinError::SavingsMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Pays the savings interest accrued since the last drip from the surplus into the
+    /// savings vault and raises the share price to match. Interest beyond the surplus
+    /// balance is skipped, so the vault always covers the shares at the new price.
+    #[allow(clippy::too_many_arguments)]
+    fn drip<'a>(
+        config_data: &mut ConfigAccount,
+        config_account: &AccountInfo<'a>,
+        surplus_account: &AccountInfo<'a>,
+        savings_vault_account: &AccountInfo<'a>,
+        share_mint_account: &AccountInfo<'a>,
+        authority_account: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        authority_bump: u8,
+    ) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let elapsed = now.saturating_sub(config_data.savings_updated_at);
+        if elapsed <= 0 {
+            return Ok(());
+        }
+        config_data.savings_updated_at = now;
+
+        let shares = Mint::unpack(&share_mint_account.data.borrow())?.supply;
+        if shares == 0 || config_data.savings_rate_bps == 0 {
+            return Ok(());
+        }
+        let owed = savings_interest(shares, config_data.savings_share_price, config_data.savings_rate_bps, elapsed as u64)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        let surplus = TokenAccount::unpack(&surplus_account.data.borrow())?.amount;
+        let interest = owed.min(surplus);
+        if interest < owed {
+            msg!("Surplus {} covers {} of {} savings interest", surplus, interest, owed);
+        }
+        if interest == 0 {
+            return Ok(());
+        }
+        config_data.savings_share_price = share_price_after_interest(config_data.savings_share_price, shares, interest)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+
+        invoke_signed(
+            &token_instruction::transfer(
+                token_program_info.key,
+                surplus_account.key,
+                savings_vault_account.key,
+                authority_account.key,
+                &[],
+                interest,
+            )?,
+            &[
+                surplus_account.clone(),
+                savings_vault_account.clone(),
+                authority_account.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+        msg!("Dripped {} to savings. Share price: {}", interest, config_data.savings_share_price);
+        Ok(())
+    }
+
+    /// Processes the SavingsDeposit instruction.
+    fn process_savings_deposit(accounts: &[AccountInfo], amount: u64, program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Depositor (Signer) - Owner of the source stablecoin account
+        let depositor_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Source Stablecoin Token Account (Writable)
+        let source_account = next_account_info(account_info_iter)?;
+        // Account 3: Destination Share Token Account (Writable)
+        let destination_account = next_account_info(account_info_iter)?;
+        // Account 4: Surplus PDA (Writable)
+        let surplus_account = next_account_info(account_info_iter)?;
+        // Account 5: Savings Vault PDA (Writable)
+        let savings_vault_account = next_account_info(account_info_iter)?;
+        // Account 6: Savings Share Mint PDA (Writable)
+        let share_mint_account = next_account_info(account_info_iter)?;
+        // Account 7: Program Authority PDA (Readonly)
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 8: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if !depositor_account.is_signer {
+            msg!("Error: Depositor signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        Self::check_savings_accounts(
+            &config_data,
+            config_account,
+            surplus_account,
+            savings_vault_account,
+            share_mint_account,
+            program_id,
+        )?;
+
+        Self::drip(
+            &mut config_data,
+            config_account,
+            surplus_account,
+            savings_vault_account,
+            share_mint_account,
+            authority_account,
+            token_program_info,
+            authority_bump,
+        )?;
+        let shares = shares_for_amount(amount, config_data.savings_share_price).ok_or(StablecoinError::NumericalOverflow)?;
+        if shares == 0 {
+            msg!("Error: Deposit too small to buy a share");
+            return Err(StablecoinError::InvalidInstruction.into());
+        }
+
+        // SPL Token checks the depositor owns the source account
+        invoke(
+            &token_instruction::transfer(
+                token_program_info.key,
+                source_account.key,
+                savings_vault_account.key,
+                depositor_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                source_account.clone(),
+                savings_vault_account.clone(),
+                depositor_account.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        invoke_signed(
+            &token_instruction::mint_to(
+                token_program_info.key,
+                share_mint_account.key,
+                destination_account.key,
+                authority_account.key,
+                &[],
+                shares,
+            )?,
+            &[
+                share_mint_account.clone(),
+                destination_account.clone(),
+                authority_account.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Deposited {} into savings for {} shares", amount, shares);
+        Ok(())
+    }
+
+    /// Processes the SavingsWithdraw instruction.
+    fn process_savings_withdraw(accounts: &[AccountInfo], shares: u64, program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Holder (Signer) - Owner of the source share account
+        let holder_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Source Share Token Account (Writable)
+        let source_account = next_account_info(account_info_iter)?;
+        // Account 3: Destination Stablecoin Token Account (Writable)
+        let destination_account = next_account_info(account_info_iter)?;
+        // Account 4: Surplus PDA (Writable)
+        let surplus_account = next_account_info(account_info_iter)?;
+        // Account 5: Savings Vault PDA (Writable)
+        let savings_vault_account = next_account_info(account_info_iter)?;
+        // Account 6: Savings Share Mint PDA (Writable)
+        let share_mint_account = next_account_info(account_info_iter)?;
+        // Account 7: Program Authority PDA (Readonly)
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 8: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if !holder_account.is_signer {
+            msg!("Error: Holder signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        Self::check_savings_accounts(
+            &config_data,
+            config_account,
+            surplus_account,
+            savings_vault_account,
+            share_mint_account,
+            program_id,
+        )?;
+
+        // Drip first so the shares redeem at the price including interest up to now
+        Self::drip(
+            &mut config_data,
+            config_account,
+            surplus_account,
+            savings_vault_account,
+            share_mint_account,
+            authority_account,
+            token_program_info,
+            authority_bump,
+        )?;
+        let amount = amount_for_shares(shares, config_data.savings_share_price).ok_or(StablecoinError::NumericalOverflow)?;
+
+        // SPL Token checks the holder owns the source account
+        invoke(
+            &token_instruction::burn(
+                token_program_info.key,
+                source_account.key,
+                share_mint_account.key,
+                holder_account.key,
+                &[],
+                shares,
+            )?,
+            &[
+                source_account.clone(),
+                share_mint_account.clone(),
+                holder_account.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        invoke_signed(
+            &token_instruction::transfer(
+                token_program_info.key,
+                savings_vault_account.key,
+                destination_account.key,
+                authority_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                savings_vault_account.clone(),
+                destination_account.clone(),
+                authority_account.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+        )?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Redeemed {} savings shares for {}", shares, amount);
+        Ok(())
+    }
+
+    /// Processes the Drip instruction.
+    fn process_drip(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 1: Surplus PDA (Writable)
+        let surplus_account = next_account_info(account_info_iter)?;
+        // Account 2: Savings Vault PDA (Writable)
+        let savings_vault_account = next_account_info(account_info_iter)?;
+        // Account 3: Savings Share Mint PDA (Readonly)
+        let share_mint_account = next_account_info(account_info_iter)?;
+        // Account 4: Program Authority PDA (Readonly)
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 5: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        Self::check_savings_accounts(
+            &config_data,
+            config_account,
+            surplus_account,
+            savings_vault_account,
+            share_mint_account,
+            program_id,
+        )?;
+
+        Self::drip(
+            &mut config_data,
+            config_account,
+            surplus_account,
+            savings_vault_account,
+            share_mint_account,
+            authority_account,
+            token_program_info,
+            authority_bump,
+        )?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes the ConfigureFlashMint instruction.
+    fn process_configure_flash_mint(
+        accounts: &[AccountInfo],
+        ceiling: u64,
+        fee_bps: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer)
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Treasury Stablecoin Token Account (Readonly)
+        let treasury_account = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin(&config_data, admin_account)?;
+        if fee_bps as u64 > BPS_DENOMINATOR {
+            msg!("Error: Flash mint fee must be at most {} bps", BPS_DENOMINATOR);
+            return Err(StablecoinError::InvalidFlashMintParams.into());
+        }
+        if treasury_account.owner != &spl_token::id() {
+            msg!("Error: Treasury is not an SPL Token account");
+            return Err(StablecoinError::InvalidFlashMintParams.into());
+        }
+        let treasury = TokenAccount::unpack(&treasury_account.data.borrow())?;
+        if treasury.mint != config_data.mint_account {
+            msg!("Error: Treasury does not hold the stablecoin");
+            return Err(StablecoinError::InvalidFlashMintParams.into());
+        }
+
+        config_data.flash_mint_ceiling = ceiling;
+        config_data.flash_fee_bps = fee_bps;
+        config_data.flash_treasury = *treasury_account.key;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!("Flash mints configured. Ceiling: {}, fee: {} bps, treasury: {}", ceiling, fee_bps, treasury_account.key);
+        Ok(())
+    }
+
+    /// Fails unless a `FlashRepay { amount }` against this config follows the current
+    /// instruction, with no `FlashMint` in between. Transactions are atomic, so the
+    /// repayment either runs and burns the amount or the mint is rolled back with it.
+    fn check_flash_repay_follows(
+        instructions_sysvar: &AccountInfo,
+        config_account: &AccountInfo,
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let current_index = load_current_index_checked(instructions_sysvar)? as usize;
+        let mut index = current_index + 1;
+        while let Ok(instruction) = load_instruction_at_checked(index, instructions_sysvar) {
+            index += 1;
+            if instruction.program_id != *program_id {
+                continue;
+            }
+            match StablecoinInstruction::try_from_slice(&instruction.data) {
+                Ok(StablecoinInstruction::FlashRepay { amount: repay_amount }) => {
+                    if repay_amount != amount
+                        || instruction.accounts.get(1).map(|meta| meta.pubkey) != Some(*config_account.key)
+                    {
+                        msg!("Error: Next FlashRepay does not repay {} against this config", amount);
+                        return Err(StablecoinError::FlashRepayMismatch.into());
+                    }
+                    return Ok(());
+                }
+                Ok(StablecoinInstruction::FlashMint { .. }) => {
+                    msg!("Error: Flash mints can't be nested");
+                    return Err(StablecoinError::FlashRepayMissing.into());
+                }
+                _ => {}
+            }
+        }
+        msg!("Error: No FlashRepay of {} later in the transaction", amount);
+        Err(StablecoinError::FlashRepayMissing.into())
+    }
+
+    /// Processes the FlashMint instruction.
+    fn process_flash_mint(accounts: &[AccountInfo], amount: u64, program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Borrower (Signer)
+        let borrower_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Stablecoin Mint (Writable)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 3: Destination Stablecoin Token Account (Writable)
+        let destination_account = next_account_info(account_info_iter)?;
+        // Account 4: Program Authority PDA (Readonly) - Mint authority
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 5: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+        // Account 6: Instructions Sysvar (Readonly)
+        let instructions_sysvar = next_account_info(account_info_iter)?;
+        // Account 7: Blacklist PDA of the destination's owner (Readonly)
+        let blacklist_account = next_account_info(account_info_iter)?;
+
+        if !borrower_account.is_signer {
+            msg!("Error: Borrower signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_not_settled(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        if amount == 0 || amount > config_data.flash_mint_ceiling {
+            msg!("Error: Flash mint {} exceeds ceiling {}", amount, config_data.flash_mint_ceiling);
+            return Err(StablecoinError::FlashMintCeilingExceeded.into());
+        }
+        if config_data.flash_outstanding != 0 {
+            msg!("Error: Flash mints can't be nested");
+            return Err(StablecoinError::FlashRepayMissing.into());
+        }
+        Self::check_flash_repay_follows(instructions_sysvar, config_account, amount, program_id)?;
+        Self::check_supply_cap(&config_data, mint_account_info, amount)?;
+        // Compliance Check: Refuse to mint to a blacklisted owner
+        let destination_data = TokenAccount::unpack(&destination_account.data.borrow())?;
+        Self::check_not_blacklisted(blacklist_account, config_account, &destination_data.owner, program_id)?;
+
+        config_data.flash_outstanding = amount;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        Self::mint_stablecoin(
+            token_program_info,
+            mint_account_info,
+            destination_account,
+            authority_account,
+            config_account,
+            authority_bump,
+            amount,
+        )?;
+
+        msg!("Flash minted {}", amount);
+        Ok(())
+    }
+
+    /// Processes the FlashRepay instruction.
+    fn process_flash_repay(accounts: &[AccountInfo], amount: u64, program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Payer (Signer) - Owner of the source stablecoin account
+        let payer_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Stablecoin Mint (Writable)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 3: Source Stablecoin Token Account (Writable)
+        let source_account = next_account_info(account_info_iter)?;
+        // Account 4: Treasury Stablecoin Token Account (Writable)
+        let treasury_account = next_account_info(account_info_iter)?;
+        // Account 5: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if !payer_account.is_signer {
+            msg!("Error: Payer signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        if config_data.flash_treasury != *treasury_account.key {
+            msg!("Error: Treasury does not match the configured flash mint treasury");
+            return Err(StablecoinError::InvalidFlashMintParams.into());
+        }
+        if amount == 0 || config_data.flash_outstanding != amount {
+            msg!("Error: Repay {} does not match outstanding flash mint {}", amount, config_data.flash_outstanding);
+            return Err(StablecoinError::FlashRepayMismatch.into());
+        }
+        let fee = apply_bps(amount, config_data.flash_fee_bps as u64).ok_or(StablecoinError::NumericalOverflow)?;
+
+        config_data.flash_outstanding = 0;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        // SPL Token checks the payer owns the source account
+        invoke(
+            &token_instruction::burn(
+                token_program_info.key,
+                source_account.key,
+                mint_account_info.key,
+                payer_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                source_account.clone(),
+                mint_account_info.clone(),
+                payer_account.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        if fee > 0 {
+            invoke(
+                &token_instruction::transfer(
+                    token_program_info.key,
+                    source_account.key,
+                    treasury_account.key,
+                    payer_account.key,
+                    &[],
+                    fee,
+                )?,
+                &[
+                    source_account.clone(),
+                    treasury_account.clone(),
+                    payer_account.clone(),
+                    token_program_info.clone(),
+                ],
+            )?;
+        }
+
+        msg!("Flash mint of {} repaid, fee {}", amount, fee);
+        Ok(())
+    }
+
+    /// Fails once the stablecoin has been globally settled.
+    fn check_not_settled(config_data: &ConfigAccount) -> ProgramResult {
+        if config_data.settled {
+            msg!("Error: Stablecoin has been globally settled");
+            return Err(StablecoinError::GloballySettled.into());
+        }
+        Ok(())
+    }
+
+    /// Processes the GlobalSettle instruction.
+    fn process_global_settle(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer)
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Price PDA (Readonly)
+        let price_account = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_admin(&config_data, admin_account)?;
+        Self::check_not_settled(&config_data)?;
+
+        // The last price PostPrice accepted, however old: the oracle may be the reason
+        // for settling, so its age and confidence limits don't apply
+        let (price_key, _) = find_price_address(config_account.key, program_id);
+        if price_key != *price_account.key || price_account.owner != program_id {
+            msg!("Error: Price account is not the price PDA for this config");
+            return Err(StablecoinError::PriceAccountMismatch.into());
+        }
+        let price_data = PriceAccount::unpack(&price_account.data.borrow())?;
+        if price_data.price == 0 {
+            msg!("Error: Collateral price has not been set");
+            return Err(StablecoinError::InvalidPrice.into());
+        }
+
+        // Charge the stability fee up to now, then freeze everything
+        Self::accrue_stability_fee(&mut config_data)?;
+        config_data.settled = true;
+        config_data.settle_price = price_data.price;
+        config_data.settle_expo = price_data.expo;
+        config_data.settled_at = Clock::get()?.unix_timestamp;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!(
+            "Globally settled at price {} x 10^{} (published at {})",
+            price_data.price,
+            price_data.expo,
+            price_data.publish_time
+        );
+        Ok(())
+    }
+
+    /// Processes the SettlePosition instruction.
+    fn process_settle_position(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 1: Position PDA (Writable)
+        let position_account = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        if !config_data.settled {
+            msg!("Error: Stablecoin has not been globally settled");
+            return Err(StablecoinError::NotSettled.into());
+        }
+        if config_data.settle_supply != 0 {
+            msg!("Error: Settlement is already finalized");
+            return Err(StablecoinError::SettlementFinalized.into());
+        }
+        let mut position = Self::load_position(position_account, config_account, program_id)?;
+        if position.normalized_debt == 0 {
+            msg!("Position has no debt to settle");
+            return Ok(());
+        }
+
+        let debt = Self::position_debt(&config_data, &position)?;
+        // Settled fees are paid in collateral, never minted
+        let settled_fees = Self::position_fees(&config_data, &position)?;
+        let skim = settlement_skim(
+            position.collateral_amount,
+            debt,
+            config_data.settle_price,
+            config_data.settle_expo,
+            config_data.collateral_decimals,
+            config_data.stable_decimals,
+        )
+        .ok_or(StablecoinError::NumericalOverflow)?;
+
+        position.collateral_amount -= skim;
+        config_data.total_collateral = config_data.total_collateral.saturating_sub(skim);
+        config_data.settle_collateral_pool = config_data
+            .settle_collateral_pool
+            .checked_add(skim)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        config_data.total_normalized_debt = config_data.total_normalized_debt.saturating_sub(position.normalized_debt);
+        config_data.total_debt = config_data.total_debt.saturating_sub(debt);
+        config_data.unminted_fees = config_data.unminted_fees.saturating_sub(settled_fees).min(config_data.total_debt);
+        position.normalized_debt = 0;
+        position.principal = 0;
+
+        let collateral_amount = position.collateral_amount;
+        Position::pack(position, &mut position_account.data.borrow_mut())?;
+        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+
+        msg!(
+            "Settled debt {} with {} collateral. Position collateral left: {}",
+            debt,
+            skim,
+            collateral_amount
+        );
+        Ok(())
+    }
+
+    /// Processes the FinalizeSettlement instruction.
+    fn process_finalize_settlement(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        // Account 0: Admin Account (Signer)
+        let admin_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
+        let config_account = next_account_info(account_info_iter)?;
+        // Account 2: Stablecoin Mint (Writable)
+        let mint_account_info = next_account_info(account_info_iter)?;
+        // Account 3: Surplus PDA (Writable) - Its fees are burned
+        let surplus_account = next_account_info(account_info_iter)?;
+        // Account 4: Program Authority PDA (Readonly) - Surplus owner
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 5: SPL Token Program ID (Readonly)
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_admin(&config_data, admin_account)?;
+        if !config_data.settled {
+            msg!("Error: Stablecoin has not been globally settled");
+            return Err(StablecoinError::NotSettled.into());
+        }
+        if config_data.settle_supply != 0 {
+            msg!("Error: Settlement is already finalized");
+            return Err(StablecoinError::SettlementFinalized.into());
+        }
+        if config_data.mint_account != *mint_account_info.key {
+            msg!("Error: Mint account does not match configured mint");
+            return Err(StablecoinError::MintAccountMismatch.into());
+        }
+        if config_data.total_normalized_debt != 0 {
+            msg!("Error: Positions still owe {} normalized debt; settle them first", config_data.total_normalized_debt);
+            return Err(StablecoinError::UnsettledDebt.into());
+        }
+
+        // Fees in the surplus are the program's own stablecoin, not a holder's claim on the
+        // pool; burn them so `Drip` can't hand them to savers after the rate is fixed.
+        // The surplus PDA only exists once a stability fee has been configured.
+        Self::check_surplus(surplus_account, config_account, program_id)?;
+        if !surplus_account.data_is_empty() {
+            Self::check_token_program(token_program_info)?;
+            let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
+            let surplus = TokenAccount::unpack(&surplus_account.data.borrow())?.amount;
+            if surplus > 0 {
+                invoke_signed(
+                    &token_instruction::burn(
+                        token_program_info.key,
+                        surplus_account.key,
+                        mint_account_info.key,
+                        authority_account.key,
+                        &[],
+                        surplus,
+                    )?,
+                    &[
+                        surplus_account.clone(),
+                        mint_account_info.clone(),
+                        authority_account.clone(),
+                        token_program_info.clone(),
+                    ],
+                    &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+                )?;
+                msg!("Burned {} stablecoin of surplus fees", surplus);
+            }
+        }
+
+        let supply = Mint::unpack(&mint_account_info.data.borrow())?.supply;
+        if supply == 0 {
+            msg!("Error: No stablecoin left to redeem");
+            return Err(StablecoinError::NotSettled.into());
+        }
 
-        // Serialize the updated state back into the account
+        let pool = config_data.settle_collateral_pool;
+        config_data.settle_supply = supply;
+        config_data.settle_pool_at_finalize = pool;
         ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
 
-        msg!("Config account initialized. Admin: {}, Mint: {}", admin, mint_account_info.key);
+        msg!("Settlement finalized: {} collateral for {} stablecoin", pool, supply);
         Ok(())
     }
 
-    /// Processes the MintTo instruction.
-    fn process_mint_to(
-        accounts: &[AccountInfo],
-        amount: u64,
-        program_id: &Pubkey,
-    ) -> ProgramResult {
+    /// Processes the RedeemSettled instruction.
+    fn process_redeem_settled(accounts: &[AccountInfo], amount: u64, program_id: &Pubkey) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
-        // Account 0: Admin Account (Signer) - Must sign the transaction
-        let admin_account = next_account_info(account_info_iter)?;
-        // Account 1: Config Account (Writable) - Read admin & mint info
+        // Account 0: Holder (Signer) - Owner of the source stablecoin account
+        let holder_account = next_account_info(account_info_iter)?;
+        // Account 1: Config Account (Writable)
         let config_account = next_account_info(account_info_iter)?;
-        // Account 2: Mint Account (Writable) - The SPL Mint to mint from
+        // Account 2: Source Stablecoin Token Account (Writable)
+        let source_account = next_account_info(account_info_iter)?;
+        // Account 3: Stablecoin Mint (Writable)
         let mint_account_info = next_account_info(account_info_iter)?;
-        // Account 3: Destination Token Account (Writable) - Recipient's ATA
+        // Account 4: Collateral Vault PDA (Writable)
+        let vault_account = next_account_info(account_info_iter)?;
+        // Account 5: Destination Collateral Token Account (Writable)
         let destination_account = next_account_info(account_info_iter)?;
-        // Account 4: SPL Token Program ID (Readonly) - Program to invoke via CPI
+        // Account 6: Collateral Mint (Readonly)
+        let collateral_mint_info = next_account_info(account_info_iter)?;
+        // Account 7: Program Authority PDA (Readonly) - Vault owner
+        let authority_account = next_account_info(account_info_iter)?;
+        // Account 8: SPL Token Program ID (Readonly)
         let token_program_info = next_account_info(account_info_iter)?;
 
-        // Basic validation
-        if !admin_account.is_signer {
-            msg!("Error: Admin signature missing");
+        if !holder_account.is_signer {
+            msg!("Error: Holder signature missing");
             return Err(ProgramError::MissingRequiredSignature);
         }
-        if config_account.owner != program_id {
-             msg!("Error: Config account not owned by program");
-             return Err(ProgramError::IncorrectProgramId);
-        }
-
-        // Deserialize config state
-        let config_data = ConfigAccount::unpack(&config_account.data.borrow())?;
-        if !config_data.is_initialized() {
-             msg!("Error: Config account not initialized");
-             return Err(StablecoinError::NotInitialized.into());
-        }
-
-        // Security Check: Verify the signing account is the admin stored in config
-        // ETH Dev Analogy: Similar to an `onlyOwner` or `onlyMinter` modifier.
-        if config_data.admin != *admin_account.key {
-            msg!("Error: Signer is not the configured admin");
-            return Err(StablecoinError::AdminSignatureMismatch.into());
-        }
-
-        // Security Check: Verify the passed mint account matches the one in config
+        let mut config_data = Self::load_config(config_account, program_id)?;
+        Self::check_not_paused(&config_data)?;
+        Self::check_token_program(token_program_info)?;
+        Self::check_collateral_accounts(&config_data, config_account, vault_account, collateral_mint_info, program_id)?;
+        let authority_bump = Self::check_authority(authority_account, config_account, program_id)?;
         if config_data.mint_account != *mint_account_info.key {
             msg!("Error: Mint account does not match configured mint");
             return Err(StablecoinError::MintAccountMismatch.into());
         }
+        if config_data.settle_supply == 0 {
+            msg!("Error: Settlement is not finalized");
+            return Err(StablecoinError::SettlementNotFinalized.into());
+        }
 
-        // Perform the minting via Cross-Program Invocation (CPI)
-        // ETH Dev Analogy: This is like calling `IERC20(tokenAddress).mint(...)`
-        msg!("Invoking SPL Token program to mint {} tokens", amount);
-        let mint_instruction = token_instruction::mint_to(
-            token_program_info.key, // SPL Token program ID
-            mint_account_info.key,  // The Mint account to mint from
-            destination_account.key,// The destination Token Account (ATA)
-            program_id,             // Mint Authority: THIS program's ID
-            &[program_id],          // Signers: THIS program is the authority
-            amount,
-        )?;
-
-        // We need to provide the accounts required by the *SPL Token program's* mint_to instruction.
-        // Note: The 'authority' account for spl-token's mint_to is this program's derived address (PDA),
-        // but since *this program itself* is the authority, we can use its program_id and invoke_signed.
-        // However, a simpler model (used here) is if this program's *Config Account* is the authority.
-        // Let's assume the Config Account's address was used as mint authority when creating the mint.
-        // If the *program* is the authority, you'd need a PDA derived from the program_id.
-        // **Correction**: The authority signing should be the one set *on the mint account*.
-        // If this program is the authority, it needs to sign via PDA.
-        // If the *admin* account was set as mint authority (less secure, not typical), admin signs.
-        // Let's assume this program *itself* is the mint authority. We need a PDA seed.
-        // **Simplification for Example**: Let's assume the *admin account* was directly set
-        // as the mint authority on the SPL Token Mint (less common, but simpler for demo).
-        // If program was authority, you'd use invoke_signed with PDA seeds.
-
-        // **Revised Assumption**: Assume the `admin_account` *is* the mint authority
-        // set on the `mint_account_info`. This simplifies the CPI call.
-        // A more robust design uses a Program Derived Address (PDA) owned by this
-        // program as the mint authority.
+        let payout = settlement_payout(amount, config_data.settle_pool_at_finalize, config_data.settle_supply)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        // Payouts round down, so the pool always covers them
+        config_data.settle_collateral_pool = config_data
+            .settle_collateral_pool
+            .checked_sub(payout)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        ConfigAccount::pack(config_data.clone(), &mut config_account.data.borrow_mut())?;
 
+        // SPL Token checks the holder owns the source account
         invoke(
-            &mint_instruction,
+            &token_instruction::burn(
+                token_program_info.key,
+                source_account.key,
+                mint_account_info.key,
+                holder_account.key,
+                &[],
+                amount,
+            )?,
             &[
-                mint_account_info.clone(),      // Mint account (source)
-                destination_account.clone(),    // Destination ATA
-                admin_account.clone(),          // Mint authority (signer) - AS PER REVISED ASSUMPTION
-                token_program_info.clone(),     // SPL Token program ID
+                source_account.clone(),
+                mint_account_info.clone(),
+                holder_account.clone(),
+                token_program_info.clone(),
             ],
         )?;
+        if payout > 0 {
+            invoke_signed(
+                &token_instruction::transfer_checked(
+                    token_program_info.key,
+                    vault_account.key,
+                    collateral_mint_info.key,
+                    destination_account.key,
+                    authority_account.key,
+                    &[],
+                    payout,
+                    config_data.collateral_decimals,
+                )?,
+                &[
+                    vault_account.clone(),
+                    collateral_mint_info.clone(),
+                    destination_account.clone(),
+                    authority_account.clone(),
+                    token_program_info.clone(),
+                ],
+                &[&[AUTHORITY_SEED, config_account.key.as_ref(), &[authority_bump]]],
+            )?;
+        }
 
+        msg!("Redeemed {} stablecoin for {} collateral", amount, payout);
+        Ok(())
+    }
 
-        msg!("Mint successful.");
+    /// Closes a program-owned account, sending its lamports to `destination`.
+    fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+        let lamports = account.lamports();
+        **destination.try_borrow_mut_lamports()? = destination
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(StablecoinError::NumericalOverflow)?;
+        **account.try_borrow_mut_lamports()? = 0;
+        account.data.borrow_mut().fill(0);
         Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::{
+        entrypoint::{MAX_PERMITTED_DATA_INCREASE, SUCCESS},
+        instruction::Instruction,
+        program_option::COption,
+        program_stubs,
+        program_utils::limited_deserialize,
+        system_instruction::SystemInstruction,
+        system_program,
+        sysvar::instructions::{
+            construct_instructions_data, store_current_index, BorrowedAccountMeta, BorrowedInstruction,
+        },
+    };
+    use crate::math::SECONDS_PER_YEAR;
+    use spl_token::state::AccountState;
+    use std::cell::Cell;
+
+    const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+    thread_local! {
+        static NOW: Cell<i64> = const { Cell::new(1_700_000_000) };
+    }
 
-     /// Processes the SetAdmin instruction.
-    fn process_set_admin(
-        accounts: &[AccountInfo],
-        new_admin: Pubkey,
-        program_id: &Pubkey,
-    ) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
-        // Account 0: Current Admin (Signer)
-        let current_admin_account = next_account_info(account_info_iter)?;
-        // Account 1: Config Account (Writable)
-        let config_account = next_account_info(account_info_iter)?;
+    /// Runs SPL Token and System CPIs in-process, signing for the PDAs named by the signer seeds.
+    struct TokenStubs;
 
-        // Security checks
-        if !current_admin_account.is_signer {
-            msg!("Error: Current admin signature missing");
+    impl program_stubs::SyscallStubs for TokenStubs {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock { unix_timestamp: NOW.with(Cell::get), ..Clock::default() };
+            unsafe { *(var_addr as *mut Clock) = clock };
+            SUCCESS
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            let signers: Vec<Pubkey> = signers_seeds
+                .iter()
+                .map(|seeds| Pubkey::create_program_address(seeds, &PROGRAM_ID).unwrap())
+                .collect();
+            let accounts: Vec<AccountInfo> = instruction
+                .accounts
+                .iter()
+                .map(|meta| {
+                    let info = account_infos.iter().find(|info| *info.key == meta.pubkey).unwrap().clone();
+                    AccountInfo { is_signer: info.is_signer || signers.contains(info.key), ..info }
+                })
+                .collect();
+            if instruction.program_id == spl_token::id() {
+                spl_token::processor::Processor::process(&instruction.program_id, &accounts, &instruction.data)
+            } else if instruction.program_id == system_program::id() {
+                process_system_instruction(&accounts, &instruction.data)
+            } else if instruction.program_id == PROGRAM_ID {
+                Processor::process(&PROGRAM_ID, &accounts, &instruction.data)
+            } else {
+                Err(ProgramError::IncorrectProgramId)
+            }
+        }
+    }
+
+    /// The System program instructions the stablecoin program uses: creating PDAs.
+    fn process_system_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+        let SystemInstruction::CreateAccount { lamports, space, owner } = limited_deserialize(data, 1024).unwrap() else {
+            unimplemented!("system instruction");
+        };
+        let (from, to) = (&accounts[0], &accounts[1]);
+        if !from.is_signer || !to.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
-         if config_account.owner != program_id {
-             msg!("Error: Config account not owned by program");
-             return Err(ProgramError::IncorrectProgramId);
+        **from.lamports.borrow_mut() -= lamports;
+        **to.lamports.borrow_mut() += lamports;
+        *to.data.borrow_mut() = Box::leak(vec![0; space as usize].into_boxed_slice());
+        to.assign(&owner);
+        Ok(())
+    }
+
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
+
+    impl TestAccount {
+        fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+            Self { key, owner, lamports: 1_000_000_000, data }
         }
 
-        // Unpack config state
-        let mut config_data = ConfigAccount::unpack(&config_account.data.borrow())?;
-        if !config_data.is_initialized() {
-             msg!("Error: Config account not initialized");
-             return Err(StablecoinError::NotInitialized.into());
+        fn packed<T: Pack>(key: Pubkey, owner: Pubkey, value: T) -> Self {
+            let mut data = vec![0; T::LEN];
+            T::pack(value, &mut data).unwrap();
+            Self::new(key, owner, data)
         }
 
-        // Verify signer is the current admin
-        if config_data.admin != *current_admin_account.key {
-            msg!("Error: Signer is not the current admin");
-            return Err(StablecoinError::AdminSignatureMismatch.into());
+        fn wallet() -> Self {
+            Self::new(Pubkey::new_unique(), Pubkey::default(), vec![])
         }
 
-        // Update the admin
-        config_data.admin = new_admin;
+        fn mint(key: Pubkey, mint_authority: Pubkey, supply: u64, decimals: u8) -> Self {
+            let mint = Mint {
+                mint_authority: COption::Some(mint_authority),
+                supply,
+                decimals,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            };
+            Self::packed(key, spl_token::id(), mint)
+        }
 
-        // Pack the updated state back into the account
-        ConfigAccount::pack(config_data, &mut config_account.data.borrow_mut())?;
+        fn token(key: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> Self {
+            let account = TokenAccount { mint, owner, amount, state: AccountState::Initialized, ..TokenAccount::default() };
+            Self::packed(key, spl_token::id(), account)
+        }
 
-        msg!("Admin updated successfully to: {}", new_admin);
-        Ok(())
+        fn info(&mut self, is_signer: bool) -> AccountInfo<'_> {
+            AccountInfo::new(&self.key, is_signer, true, &mut self.lamports, &mut self.data, &self.owner, false, 0)
+        }
+    }
+
+    fn run(accounts: &[&AccountInfo], instruction: StablecoinInstruction) -> ProgramResult {
+        let accounts: Vec<AccountInfo> = accounts.iter().map(|&info| info.clone()).collect();
+        Processor::process(&PROGRAM_ID, &accounts, &borsh::to_vec(&instruction).unwrap())
+    }
+
+    fn token_amount(info: &AccountInfo) -> u64 {
+        TokenAccount::unpack(&info.data.borrow()).unwrap().amount
+    }
+
+    fn supply(info: &AccountInfo) -> u64 {
+        Mint::unpack(&info.data.borrow()).unwrap().supply
+    }
+
+    fn config_of(info: &AccountInfo) -> ConfigAccount {
+        ConfigAccount::unpack(&info.data.borrow()).unwrap()
+    }
+
+    fn position_of(info: &AccountInfo) -> Position {
+        Position::unpack(&info.data.borrow()).unwrap()
+    }
+
+    // 150 stablecoins per collateral token, 9 collateral decimals, 6 stablecoin decimals
+    const PRICE: u64 = 15_000;
+    const EXPO: i32 = -2;
+    const RATIO_BPS: u16 = 15_000;
+
+    /// A config with collateral configured and priced, and an owner with an open position.
+    struct Market {
+        admin: TestAccount,
+        config: TestAccount,
+        mint: TestAccount,
+        collateral_mint: TestAccount,
+        vault: TestAccount,
+        price: TestAccount,
+        authority: TestAccount,
+        token_program: TestAccount,
+        system: TestAccount,
+        owner: TestAccount,
+        position: TestAccount,
+        owner_collateral: TestAccount,
+        owner_stable: TestAccount,
+        bad_debt: TestAccount,
+        blacklist: TestAccount,
+        surplus: TestAccount,
+    }
+
+    impl Market {
+        /// `collateral` sits in the owner's position against `debt`, which the owner holds;
+        /// the owner has as much collateral again in their wallet.
+        fn new(collateral: u64, debt: u64) -> Self {
+            program_stubs::set_syscall_stubs(Box::new(TokenStubs));
+            let admin = TestAccount::wallet();
+            let owner = TestAccount::wallet();
+            let config_key = Pubkey::new_unique();
+            let mint_key = Pubkey::new_unique();
+            let collateral_mint_key = Pubkey::new_unique();
+            let (authority_key, _) = find_authority_address(&config_key, &PROGRAM_ID);
+
+            let mut config_data = ConfigAccount::unpack_unchecked(&[0; ConfigAccount::LEN]).unwrap();
+            config_data.is_initialized = true;
+            config_data.admin = admin.key;
+            config_data.mint_account = mint_key;
+            config_data.collateral_mint = collateral_mint_key;
+            config_data.collateral_decimals = 9;
+            config_data.stable_decimals = 6;
+            config_data.collateral_ratio_bps = RATIO_BPS;
+            config_data.total_collateral = collateral;
+            config_data.total_debt = debt;
+            config_data.max_price_age_secs = 60;
+            config_data.max_confidence_bps = 100;
+
+            let (position_key, position_bump) = find_position_address(&config_key, &owner.key, &PROGRAM_ID);
+            let position = Position {
+                is_initialized: true,
+                config: config_key,
+                owner: owner.key,
+                collateral_amount: collateral,
+                normalized_debt: debt,
+                principal: debt,
+                bump: position_bump,
+            };
+            let price = PriceAccount {
+                is_initialized: true,
+                config: config_key,
+                price: PRICE,
+                expo: EXPO,
+                publish_time: NOW.with(Cell::get),
+                ..PriceAccount::default()
+            };
+            Self {
+                config: TestAccount::packed(config_key, PROGRAM_ID, config_data),
+                mint: TestAccount::mint(mint_key, authority_key, debt, 6),
+                collateral_mint: TestAccount::mint(collateral_mint_key, Pubkey::new_unique(), 2 * collateral, 9),
+                vault: TestAccount::token(
+                    find_vault_address(&config_key, &PROGRAM_ID).0,
+                    collateral_mint_key,
+                    authority_key,
+                    collateral,
+                ),
+                price: TestAccount::packed(find_price_address(&config_key, &PROGRAM_ID).0, PROGRAM_ID, price),
+                authority: TestAccount::new(authority_key, Pubkey::default(), vec![]),
+                token_program: TestAccount::new(spl_token::id(), Pubkey::default(), vec![]),
+                system: TestAccount::new(system_program::id(), Pubkey::default(), vec![]),
+                position: TestAccount::packed(position_key, PROGRAM_ID, position),
+                owner_collateral: TestAccount::token(Pubkey::new_unique(), collateral_mint_key, owner.key, collateral),
+                owner_stable: TestAccount::token(Pubkey::new_unique(), mint_key, owner.key, debt),
+                bad_debt: TestAccount::new(find_bad_debt_address(&config_key, &PROGRAM_ID).0, system_program::id(), vec![]),
+                blacklist: TestAccount::new(
+                    find_blacklist_address(&config_key, &owner.key, &PROGRAM_ID).0,
+                    system_program::id(),
+                    vec![],
+                ),
+                surplus: TestAccount::token(find_surplus_address(&config_key, &PROGRAM_ID).0, mint_key, authority_key, 0),
+                admin,
+                owner,
+            }
+        }
+    }
+
+    /// A minter PDA of `config` for `minter`, with `allowance` left to mint.
+    fn minter_account(config: &Pubkey, minter: &Pubkey, allowance: u64) -> TestAccount {
+        let (key, bump) = find_minter_address(config, minter, &PROGRAM_ID);
+        let minter_data = MinterAccount { is_initialized: true, config: *config, minter: *minter, allowance, bump };
+        TestAccount::packed(key, PROGRAM_ID, minter_data)
+    }
+
+    #[test]
+    fn mint_to_mints_through_the_authority_pda_within_the_allowance() {
+        let mut market = Market::new(0, 0);
+        let mut minter = TestAccount::wallet();
+        let mut outsider = TestAccount::wallet();
+        let mut fake_token_program = TestAccount::wallet();
+        let mut wrong_authority = TestAccount::wallet();
+        let mut no_attestation = TestAccount::wallet();
+        let mut minter_pda = minter_account(&market.config.key, &minter.key, 150);
+        let config = market.config.info(false);
+        let mint = market.mint.info(false);
+        let destination = market.owner_stable.info(false);
+        let minter_pda = minter_pda.info(false);
+        let blacklist = market.blacklist.info(false);
+        let no_attestation = no_attestation.info(false);
+        let signer = minter.info(true);
+        let minters = [signer.clone(), outsider.info(true), AccountInfo { is_signer: false, ..signer }];
+        let token_programs = [market.token_program.info(false), fake_token_program.info(false)];
+        let authorities = [market.authority.info(false), wrong_authority.info(false)];
+        let mint_to = |minter: usize, token_program: usize, authority: usize, amount: u64| {
+            run(
+                &[
+                    &minters[minter],
+                    &config,
+                    &mint,
+                    &destination,
+                    &token_programs[token_program],
+                    &authorities[authority],
+                    &minter_pda,
+                    &blacklist,
+                    &no_attestation,
+                ],
+                StablecoinInstruction::MintTo { amount },
+            )
+        };
+
+        assert_eq!(mint_to(1, 0, 0, 100), Err(StablecoinError::MinterMismatch.into()));
+        assert_eq!(mint_to(2, 0, 0, 100), Err(ProgramError::MissingRequiredSignature));
+        assert_eq!(mint_to(0, 1, 0, 100), Err(ProgramError::IncorrectProgramId));
+        assert_eq!(mint_to(0, 0, 1, 100), Err(StablecoinError::AuthorityMismatch.into()));
+        assert_eq!(mint_to(0, 0, 0, 151), Err(StablecoinError::MinterAllowanceExceeded.into()));
+        assert_eq!(token_amount(&destination), 0);
+
+        mint_to(0, 0, 0, 100).unwrap();
+        assert_eq!(token_amount(&destination), 100);
+        assert_eq!(supply(&mint), 100);
+        assert_eq!(MinterAccount::unpack(&minter_pda.data.borrow()).unwrap().allowance, 50);
+        assert_eq!(mint_to(0, 0, 0, 51), Err(StablecoinError::MinterAllowanceExceeded.into()));
+    }
+
+    #[test]
+    fn master_minter_configures_and_removes_minters() {
+        let mut market = Market::new(0, 0);
+        let mut master = TestAccount::wallet();
+        let minter = Pubkey::new_unique();
+        let (minter_pda_key, _) = find_minter_address(&market.config.key, &minter, &PROGRAM_ID);
+        let mut minter_pda = TestAccount::new(minter_pda_key, system_program::id(), vec![]);
+        let mut other_pda = TestAccount::new(
+            find_minter_address(&market.config.key, &Pubkey::new_unique(), &PROGRAM_ID).0,
+            system_program::id(),
+            vec![],
+        );
+        let config = market.config.info(false);
+        let system = market.system.info(false);
+        let master = master.info(true);
+        // 0: master minter, 1: admin, 2: master minter without its signature
+        let signers = [master.clone(), market.admin.info(true), AccountInfo { is_signer: false, ..master }];
+        let pdas = [minter_pda.info(false), other_pda.info(false)];
+        let configure = |signer: usize, pda: usize, allowance: u64| {
+            run(
+                &[&signers[signer], &config, &pdas[pda], &system],
+                StablecoinInstruction::ConfigureMinter { minter, allowance },
+            )
+        };
+        let remove = |signer: usize, minter: Pubkey| {
+            run(&[&signers[signer], &config, &pdas[0]], StablecoinInstruction::RemoveMinter { minter })
+        };
+        let allowance = || MinterAccount::unpack(&pdas[0].data.borrow()).map(|minter_data| minter_data.allowance);
+
+        // Nobody can configure minters until the admin names a master minter
+        assert_eq!(configure(0, 0, 100), Err(StablecoinError::MasterMinterMismatch.into()));
+        let set_master = StablecoinInstruction::SetMasterMinter { master_minter: *signers[0].key };
+        assert_eq!(run(&[&signers[0], &config], set_master.clone()), Err(StablecoinError::AdminSignatureMismatch.into()));
+        run(&[&signers[1], &config], set_master).unwrap();
+        assert_eq!(config_of(&config).master_minter, *signers[0].key);
+
+        assert_eq!(configure(1, 0, 100), Err(StablecoinError::MasterMinterMismatch.into()));
+        assert_eq!(configure(2, 0, 100), Err(ProgramError::MissingRequiredSignature));
+        assert_eq!(configure(0, 1, 100), Err(StablecoinError::MinterMismatch.into()));
+
+        configure(0, 0, 100).unwrap();
+        assert_eq!(MinterAccount::unpack(&pdas[0].data.borrow()).unwrap().minter, minter);
+        assert_eq!(allowance(), Ok(100));
+        // A second call replaces the allowance rather than adding to it
+        configure(0, 0, 40).unwrap();
+        assert_eq!(allowance(), Ok(40));
+
+        assert_eq!(remove(1, minter), Err(StablecoinError::MasterMinterMismatch.into()));
+        // The instruction names the minter it closes, which has to match the PDA
+        assert_eq!(remove(0, Pubkey::new_unique()), Err(StablecoinError::MinterMismatch.into()));
+        let master_lamports = signers[0].lamports();
+        let rent = pdas[0].lamports();
+        remove(0, minter).unwrap();
+        assert_eq!(signers[0].lamports(), master_lamports + rent);
+        assert_eq!(pdas[0].lamports(), 0);
+        assert!(allowance().is_err());
+    }
+
+    #[test]
+    fn blacklist_freezes_holder_accounts_and_blocks_mints_to_them() {
+        let mut market = Market::new(1_000_000_000, 0);
+        let mut mint_data = Mint::unpack(&market.mint.data).unwrap();
+        mint_data.freeze_authority = COption::Some(market.authority.key);
+        Mint::pack(mint_data, &mut market.mint.data).unwrap();
+        let mut blacklister = TestAccount::wallet();
+        let mut second_stable = TestAccount::token(Pubkey::new_unique(), market.mint.key, market.owner.key, 0);
+        let mut fake_token_program = TestAccount::wallet();
+        let mut wrong_entry = TestAccount::wallet();
+        let holder = market.owner.key;
+        let owner = market.owner.info(true);
+        let config = market.config.info(false);
+        let position = market.position.info(false);
+        let mint = market.mint.info(false);
+        let authority = market.authority.info(false);
+        let price = market.price.info(false);
+        let system = market.system.info(false);
+        // 0: blacklister, 1: admin
+        let signers = [blacklister.info(true), market.admin.info(true)];
+        let entries = [market.blacklist.info(false), wrong_entry.info(false)];
+        let token_accounts = [market.owner_stable.info(false), second_stable.info(false)];
+        let token_programs = [market.token_program.info(false), fake_token_program.info(false)];
+        let blacklist = |signer: usize, entry: usize, token_account: usize, token_program: usize| {
+            run(
+                &[
+                    &signers[signer],
+                    &config,
+                    &entries[entry],
+                    &token_accounts[token_account],
+                    &mint,
+                    &authority,
+                    &token_programs[token_program],
+                    &system,
+                ],
+                StablecoinInstruction::Blacklist { holder },
+            )
+        };
+        let unblacklist = |signer: usize, token_account: usize| {
+            run(
+                &[&signers[signer], &config, &entries[0], &token_accounts[token_account], &mint, &authority, &token_programs[0]],
+                StablecoinInstruction::Unblacklist,
+            )
+        };
+        let mint_against = |entry: usize| {
+            run(
+                &[&owner, &config, &position, &mint, &token_accounts[0], &authority, &token_programs[0], &price, &entries[entry]],
+                StablecoinInstruction::MintAgainstCollateral { amount: 1 },
+            )
+        };
+        let state = |token_account: usize| TokenAccount::unpack(&token_accounts[token_account].data.borrow()).unwrap().state;
+        let frozen_accounts = || BlacklistEntry::unpack(&entries[0].data.borrow()).unwrap().frozen_accounts;
+
+        let set_blacklister = StablecoinInstruction::SetBlacklister { blacklister: *signers[0].key };
+        assert_eq!(run(&[&signers[0], &config], set_blacklister.clone()), Err(StablecoinError::AdminSignatureMismatch.into()));
+        run(&[&signers[1], &config], set_blacklister).unwrap();
+        assert_eq!(config_of(&config).blacklister, *signers[0].key);
+
+        assert_eq!(blacklist(1, 0, 0, 0), Err(StablecoinError::BlacklisterMismatch.into()));
+        assert_eq!(blacklist(0, 1, 0, 0), Err(StablecoinError::BlacklistEntryMismatch.into()));
+        assert_eq!(blacklist(0, 0, 0, 1), Err(ProgramError::IncorrectProgramId));
+        assert_eq!(state(0), AccountState::Initialized);
+
+        blacklist(0, 0, 0, 0).unwrap();
+        blacklist(0, 0, 1, 0).unwrap();
+        assert_eq!((state(0), state(1)), (AccountState::Frozen, AccountState::Frozen));
+        assert_eq!(frozen_accounts(), 2);
+
+        // Mints to the holder fail, and passing some other account instead of its entry doesn't help
+        assert_eq!(mint_against(0), Err(StablecoinError::Blacklisted.into()));
+        assert_eq!(mint_against(1), Err(StablecoinError::BlacklistEntryMismatch.into()));
+        assert_eq!(supply(&mint), 0);
+
+        assert_eq!(unblacklist(1, 0), Err(StablecoinError::BlacklisterMismatch.into()));
+        unblacklist(0, 0).unwrap();
+        assert_eq!(state(0), AccountState::Initialized);
+        assert_eq!(frozen_accounts(), 1);
+        // Thawing the last frozen account closes the entry and refunds its rent
+        let blacklister_lamports = signers[0].lamports();
+        let rent = entries[0].lamports();
+        unblacklist(0, 1).unwrap();
+        assert_eq!(state(1), AccountState::Initialized);
+        assert_eq!(signers[0].lamports(), blacklister_lamports + rent);
+        assert_eq!(entries[0].lamports(), 0);
+    }
+
+    #[test]
+    fn pauser_halts_minting_and_positions_until_unpaused() {
+        let mut market = Market::new(1_000_000_000, 0);
+        let mut pauser = TestAccount::wallet();
+        let owner = market.owner.info(true);
+        let config = market.config.info(false);
+        let position = market.position.info(false);
+        let owner_collateral = market.owner_collateral.info(false);
+        let vault = market.vault.info(false);
+        let collateral_mint = market.collateral_mint.info(false);
+        let mint = market.mint.info(false);
+        let owner_stable = market.owner_stable.info(false);
+        let authority = market.authority.info(false);
+        let token_program = market.token_program.info(false);
+        let price = market.price.info(false);
+        let blacklist = market.blacklist.info(false);
+        // 0: pauser, 1: admin
+        let signers = [pauser.info(true), market.admin.info(true)];
+        let mint_against = || {
+            run(
+                &[&owner, &config, &position, &mint, &owner_stable, &authority, &token_program, &price, &blacklist],
+                StablecoinInstruction::MintAgainstCollateral { amount: 1 },
+            )
+        };
+        let deposit = || {
+            run(
+                &[&owner, &config, &position, &owner_collateral, &vault, &collateral_mint, &token_program],
+                StablecoinInstruction::Deposit { amount: 1 },
+            )
+        };
+
+        let set_pauser = StablecoinInstruction::SetPauser { pauser: *signers[0].key };
+        assert_eq!(run(&[&signers[0], &config], set_pauser.clone()), Err(StablecoinError::AdminSignatureMismatch.into()));
+        run(&[&signers[1], &config], set_pauser).unwrap();
+
+        assert_eq!(run(&[&signers[1], &config], StablecoinInstruction::Pause), Err(StablecoinError::PauserMismatch.into()));
+        run(&[&signers[0], &config], StablecoinInstruction::Pause).unwrap();
+        assert!(config_of(&config).paused);
+        assert_eq!(mint_against(), Err(StablecoinError::ProgramPaused.into()));
+        assert_eq!(deposit(), Err(StablecoinError::ProgramPaused.into()));
+        // Admin instructions keep working while paused
+        run(&[&signers[1], &config], StablecoinInstruction::SetSupplyCap { supply_cap: 1_000 }).unwrap();
+
+        assert_eq!(run(&[&signers[1], &config], StablecoinInstruction::Unpause), Err(StablecoinError::PauserMismatch.into()));
+        run(&[&signers[0], &config], StablecoinInstruction::Unpause).unwrap();
+        mint_against().unwrap();
+        deposit().unwrap();
+        assert_eq!(supply(&mint), 1);
+    }
+
+    #[test]
+    fn supply_cap_limits_every_mint() {
+        let mut market = Market::new(1_000_000_000, 0);
+        let mut outsider = TestAccount::wallet();
+        let owner = market.owner.info(true);
+        let config = market.config.info(false);
+        let position = market.position.info(false);
+        let mint = market.mint.info(false);
+        let owner_stable = market.owner_stable.info(false);
+        let authority = market.authority.info(false);
+        let token_program = market.token_program.info(false);
+        let price = market.price.info(false);
+        let blacklist = market.blacklist.info(false);
+        // 0: admin, 1: outsider
+        let signers = [market.admin.info(true), outsider.info(true)];
+        let mint_against = |amount| {
+            run(
+                &[&owner, &config, &position, &mint, &owner_stable, &authority, &token_program, &price, &blacklist],
+                StablecoinInstruction::MintAgainstCollateral { amount },
+            )
+        };
+        let set_cap = |signer: usize, supply_cap| {
+            run(&[&signers[signer], &config], StablecoinInstruction::SetSupplyCap { supply_cap })
+        };
+
+        assert_eq!(set_cap(1, 100), Err(StablecoinError::AdminSignatureMismatch.into()));
+        set_cap(0, 100).unwrap();
+        assert_eq!(mint_against(101), Err(StablecoinError::SupplyCapExceeded.into()));
+        mint_against(60).unwrap();
+        assert_eq!(mint_against(41), Err(StablecoinError::SupplyCapExceeded.into()));
+        mint_against(40).unwrap();
+        assert_eq!(supply(&mint), 100);
+
+        // Zero lifts the cap
+        set_cap(0, 0).unwrap();
+        mint_against(1_000).unwrap();
+        assert_eq!(supply(&mint), 1_100);
+    }
+
+    #[test]
+    fn fresh_reserve_attestations_gate_fiat_backed_mints() {
+        let mut market = Market::new(0, 0);
+        let mut attestor = TestAccount::wallet();
+        let mut minter = TestAccount::wallet();
+        let mut minter_pda = minter_account(&market.config.key, &minter.key, 10_000);
+        let mut attestations: Vec<TestAccount> = (0..2)
+            .map(|sequence| {
+                let key = find_attestation_address(&market.config.key, sequence, &PROGRAM_ID).0;
+                TestAccount::new(key, system_program::id(), vec![])
+            })
+            .collect();
+        let config = market.config.info(false);
+        let mint = market.mint.info(false);
+        let destination = market.owner_stable.info(false);
+        let token_program = market.token_program.info(false);
+        let authority = market.authority.info(false);
+        let blacklist = market.blacklist.info(false);
+        let system = market.system.info(false);
+        let minter = minter.info(true);
+        let minter_pda = minter_pda.info(false);
+        // 0: attestor, 1: admin
+        let signers = [attestor.info(true), market.admin.info(true)];
+        let attestations: Vec<AccountInfo> = attestations.iter_mut().map(|attestation| attestation.info(false)).collect();
+        let mint_to = |attestation: usize, amount: u64| {
+            run(
+                &[
+                    &minter,
+                    &config,
+                    &mint,
+                    &destination,
+                    &token_program,
+                    &authority,
+                    &minter_pda,
+                    &blacklist,
+                    &attestations[attestation],
+                ],
+                StablecoinInstruction::MintTo { amount },
+            )
+        };
+        let post = |signer: usize, sequence: usize, reserve_amount: u64, as_of: i64| {
+            let mut accounts = vec![&signers[signer], &config, &attestations[sequence], &system];
+            if sequence > 0 {
+                accounts.push(&attestations[sequence - 1]);
+            }
+            run(
+                &accounts,
+                StablecoinInstruction::PostReserveAttestation { reserve_amount, as_of, report_hash: [1; 32] },
+            )
+        };
+        let now = NOW.with(Cell::get);
+
+        let configure = |signer: usize, max_attestation_age_secs| {
+            run(
+                &[&signers[signer], &config],
+                StablecoinInstruction::ConfigureAttestor { attestor: *signers[0].key, max_attestation_age_secs },
+            )
+        };
+        assert_eq!(configure(0, 3_600), Err(StablecoinError::AdminSignatureMismatch.into()));
+        assert_eq!(configure(1, 0), Err(StablecoinError::StaleAttestation.into()));
+        configure(1, 3_600).unwrap();
+
+        // Nothing is minted until reserves are attested
+        assert_eq!(mint_to(0, 100), Err(StablecoinError::StaleAttestation.into()));
+        assert_eq!(post(1, 0, 500, now - 10), Err(StablecoinError::AttestorMismatch.into()));
+        assert_eq!(post(0, 1, 500, now - 10), Err(StablecoinError::AttestationMismatch.into()));
+        assert_eq!(post(0, 0, 500, now + 1), Err(StablecoinError::StaleAttestation.into()));
+        post(0, 0, 500, now - 10).unwrap();
+        assert_eq!(config_of(&config).attestation_count, 1);
+
+        assert_eq!(mint_to(1, 100), Err(StablecoinError::AttestationMismatch.into()));
+        assert_eq!(mint_to(0, 501), Err(StablecoinError::ReservesExceeded.into()));
+        mint_to(0, 300).unwrap();
+
+        // Each attestation must be newer than the last, and only the latest one counts
+        assert_eq!(post(0, 1, 1_000, now - 10), Err(StablecoinError::StaleAttestation.into()));
+        post(0, 1, 1_000, now).unwrap();
+        assert_eq!(mint_to(0, 100), Err(StablecoinError::AttestationMismatch.into()));
+        assert_eq!(mint_to(1, 701), Err(StablecoinError::ReservesExceeded.into()));
+        mint_to(1, 700).unwrap();
+        assert_eq!(supply(&mint), 1_000);
+
+        NOW.with(|now| now.set(now.get() + 3_601));
+        assert_eq!(mint_to(1, 0), Err(StablecoinError::StaleAttestation.into()));
+    }
+
+    #[test]
+    fn deposit_and_mint_up_to_the_collateral_ratio() {
+        let mut market = Market::new(0, 0);
+        market.owner_collateral = TestAccount::token(
+            market.owner_collateral.key,
+            market.collateral_mint.key,
+            market.owner.key,
+            2_000_000_000,
+        );
+        market.position = TestAccount::new(market.position.key, system_program::id(), vec![]);
+        let owner = market.owner.info(true);
+        let config = market.config.info(false);
+        let position = market.position.info(false);
+        let owner_collateral = market.owner_collateral.info(false);
+        let vault = market.vault.info(false);
+        let collateral_mint = market.collateral_mint.info(false);
+        let mint = market.mint.info(false);
+        let owner_stable = market.owner_stable.info(false);
+        let authority = market.authority.info(false);
+        let token_program = market.token_program.info(false);
+        let price = market.price.info(false);
+        let blacklist = market.blacklist.info(false);
+
+        run(&[&owner, &config, &position, &market.system.info(false)], StablecoinInstruction::OpenPosition).unwrap();
+        assert_eq!(position_of(&position).owner, *owner.key);
+        run(
+            &[&owner, &config, &position, &owner_collateral, &vault, &collateral_mint, &token_program],
+            StablecoinInstruction::Deposit { amount: 2_000_000_000 },
+        )
+        .unwrap();
+        assert_eq!(token_amount(&vault), 2_000_000_000);
+        assert_eq!(config_of(&config).total_collateral, 2_000_000_000);
+
+        // 2 tokens at 150 are worth 300 stablecoins, which back at most 200 at 150%
+        let mint_against = |amount| {
+            run(
+                &[&owner, &config, &position, &mint, &owner_stable, &authority, &token_program, &price, &blacklist],
+                StablecoinInstruction::MintAgainstCollateral { amount },
+            )
+        };
+        assert_eq!(mint_against(200_000_001), Err(StablecoinError::PositionUnhealthy.into()));
+        mint_against(200_000_000).unwrap();
+        assert_eq!(token_amount(&owner_stable), 200_000_000);
+        assert_eq!(position_of(&position).normalized_debt, 200_000_000);
+        assert_eq!(config_of(&config).total_debt, 200_000_000);
+    }
+
+    #[test]
+    fn withdraw_and_repay_keep_the_position_healthy() {
+        let mut market = Market::new(2_000_000_000, 200_000_000);
+        let owner = market.owner.info(true);
+        let config = market.config.info(false);
+        let position = market.position.info(false);
+        let vault = market.vault.info(false);
+        let owner_collateral = market.owner_collateral.info(false);
+        let collateral_mint = market.collateral_mint.info(false);
+        let authority = market.authority.info(false);
+        let token_program = market.token_program.info(false);
+        let price = market.price.info(false);
+        let owner_stable = market.owner_stable.info(false);
+        let mint = market.mint.info(false);
+        let surplus = market.surplus.info(false);
+        let withdraw = |amount| {
+            run(
+                &[&owner, &config, &position, &vault, &owner_collateral, &collateral_mint, &authority, &token_program, &price],
+                StablecoinInstruction::Withdraw { amount },
+            )
+        };
+        let repay = |amount| {
+            run(
+                &[&owner, &config, &position, &owner_stable, &mint, &token_program, &surplus, &authority],
+                StablecoinInstruction::Repay { amount },
+            )
+        };
+
+        assert_eq!(withdraw(1), Err(StablecoinError::PositionUnhealthy.into()));
+        assert_eq!(repay(200_000_001), Err(StablecoinError::RepayExceedsDebt.into()));
+
+        repay(100_000_000).unwrap();
+        assert_eq!(supply(&mint), 100_000_000);
+        withdraw(1_000_000_000).unwrap();
+        assert_eq!(token_amount(&owner_collateral), 3_000_000_000);
+        let position_data = position_of(&position);
+        assert_eq!((position_data.collateral_amount, position_data.normalized_debt), (1_000_000_000, 100_000_000));
+        let config_data = config_of(&config);
+        assert_eq!((config_data.total_collateral, config_data.total_debt), (1_000_000_000, 100_000_000));
+        assert_eq!(withdraw(1_000_000_001), Err(StablecoinError::InsufficientCollateral.into()));
+    }
+
+    #[test]
+    fn collateral_instructions_reject_other_owners_and_token_programs() {
+        let mut market = Market::new(2_000_000_000, 0);
+        let mut outsider = TestAccount::wallet();
+        let mut fake_token_program = TestAccount::wallet();
+        let config = market.config.info(false);
+        let position = market.position.info(false);
+        let vault = market.vault.info(false);
+        let owner_collateral = market.owner_collateral.info(false);
+        let collateral_mint = market.collateral_mint.info(false);
+        let authority = market.authority.info(false);
+        let price = market.price.info(false);
+        let blacklist = market.blacklist.info(false);
+        let mint = market.mint.info(false);
+        let owner_stable = market.owner_stable.info(false);
+        let owners = [market.owner.info(true), outsider.info(true)];
+        let token_programs = [market.token_program.info(false), fake_token_program.info(false)];
+        let withdraw = |owner: usize, token_program: usize| {
+            run(
+                &[
+                    &owners[owner],
+                    &config,
+                    &position,
+                    &vault,
+                    &owner_collateral,
+                    &collateral_mint,
+                    &authority,
+                    &token_programs[token_program],
+                    &price,
+                ],
+                StablecoinInstruction::Withdraw { amount: 1 },
+            )
+        };
+        let mint_against = |owner: usize, token_program: usize| {
+            run(
+                &[&owners[owner], &config, &position, &mint, &owner_stable, &authority, &token_programs[token_program], &price, &blacklist],
+                StablecoinInstruction::MintAgainstCollateral { amount: 1 },
+            )
+        };
+
+        assert_eq!(withdraw(1, 0), Err(StablecoinError::PositionMismatch.into()));
+        assert_eq!(withdraw(0, 1), Err(ProgramError::IncorrectProgramId));
+        assert_eq!(mint_against(1, 0), Err(StablecoinError::PositionMismatch.into()));
+        assert_eq!(mint_against(0, 1), Err(ProgramError::IncorrectProgramId));
+        assert_eq!(token_amount(&vault), 2_000_000_000);
+        assert_eq!(supply(&mint), 0);
+    }
+
+    /// Account data laid out like the runtime's input buffer, with its length in the 8 bytes
+    /// before it and room to grow, so `AccountInfo::realloc` can extend it in place.
+    fn reallocatable(data: &[u8]) -> &'static mut [u8] {
+        let words = (8 + data.len() + MAX_PERMITTED_DATA_INCREASE).div_ceil(8);
+        let buffer = Box::leak(vec![0u64; words].into_boxed_slice());
+        let bytes = unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, words * 8) };
+        bytes[..8].copy_from_slice(&(data.len() as u64).to_le_bytes());
+        bytes[8..8 + data.len()].copy_from_slice(data);
+        &mut bytes[8..8 + data.len()]
+    }
+
+    #[test]
+    fn migrate_config_upgrades_unversioned_and_older_layouts() {
+        let mut market = Market::new(0, 0);
+        let admin_key = market.admin.key;
+        let mint_key = market.mint.key;
+        // Unversioned: is_initialized, admin, mint. v1: the same behind a header.
+        let mut v0 = vec![1u8];
+        v0.extend_from_slice(admin_key.as_ref());
+        v0.extend_from_slice(mint_key.as_ref());
+        let v1 = [&[1, AccountType::Config as u8][..], &v0].concat();
+        let mut configs = [v0, v1].map(|layout| (TestAccount::new(Pubkey::new_unique(), PROGRAM_ID, vec![]), layout));
+        let payer = market.owner.info(true);
+        let system = market.system.info(false);
+
+        for (config, layout) in configs.iter_mut() {
+            let config = config.info(false);
+            *config.data.borrow_mut() = reallocatable(layout);
+            // An older layout cannot be read until it is migrated
+            assert!(ConfigAccount::unpack(&config.data.borrow()).is_err());
+
+            run(&[&payer, &config, &system], StablecoinInstruction::MigrateConfig).unwrap();
+            assert_eq!(config.data_len(), ConfigAccount::LEN);
+            let config_data = config_of(&config);
+            assert_eq!((config_data.admin, config_data.mint_account), (admin_key, mint_key));
+            assert_eq!(config_data.collateral_mint, Pubkey::default());
+            // Migrating a current config is a no-op
+            run(&[&payer, &config, &system], StablecoinInstruction::MigrateConfig).unwrap();
+        }
+    }
+
+    /// Stores a fresh collateral price, as a publisher's `PostPrice` would.
+    fn set_price(price: &AccountInfo, value: u64) {
+        let mut price_data = PriceAccount::unpack(&price.data.borrow()).unwrap();
+        price_data.price = value;
+        price_data.publish_time = NOW.with(Cell::get);
+        PriceAccount::pack(price_data, &mut price.data.borrow_mut()).unwrap();
+    }
+
+    /// Sets liquidations up at a 125% threshold, 5% discount and 50% close factor, then
+    /// moves the collateral price to `price`.
+    fn configure_liquidation(market: &mut Market, price: u64) {
+        let admin = market.admin.info(true);
+        let config = market.config.info(false);
+        let bad_debt = market.bad_debt.info(false);
+        run(
+            &[&admin, &config, &bad_debt, &market.system.info(false)],
+            StablecoinInstruction::ConfigureLiquidation {
+                liquidation_threshold_bps: 12_500,
+                liquidation_discount_bps: 500,
+                close_factor_bps: 5_000,
+            },
+        )
+        .unwrap();
+        set_price(&market.price.info(false), price);
+        // The created account's data only lives in the `AccountInfo`, so keep it
+        let bad_debt_data = bad_debt.data.borrow().to_vec();
+        market.bad_debt.data = bad_debt_data;
+    }
+
+    /// A liquidator holding `stable` stablecoin and no collateral.
+    fn liquidator(market: &Market, stable: u64) -> (TestAccount, TestAccount, TestAccount) {
+        let wallet = TestAccount::wallet();
+        let stable_tokens = TestAccount::token(Pubkey::new_unique(), market.mint.key, wallet.key, stable);
+        let collateral_tokens = TestAccount::token(Pubkey::new_unique(), market.collateral_mint.key, wallet.key, 0);
+        (wallet, stable_tokens, collateral_tokens)
+    }
+
+    #[test]
+    fn configure_liquidation_checks_admin_and_parameters() {
+        let mut market = Market::new(0, 0);
+        let mut outsider = TestAccount::wallet();
+        let config = market.config.info(false);
+        let bad_debt = market.bad_debt.info(false);
+        let system = market.system.info(false);
+        let admins = [market.admin.info(true), outsider.info(true)];
+        let configure = |admin: usize, liquidation_threshold_bps| {
+            run(
+                &[&admins[admin], &config, &bad_debt, &system],
+                StablecoinInstruction::ConfigureLiquidation {
+                    liquidation_threshold_bps,
+                    liquidation_discount_bps: 500,
+                    close_factor_bps: 5_000,
+                },
+            )
+        };
+
+        assert_eq!(configure(1, 12_500), Err(StablecoinError::AdminSignatureMismatch.into()));
+        // The threshold has to sit above 100% and at or below the 150% minting ratio
+        assert_eq!(configure(0, 10_000), Err(StablecoinError::InvalidLiquidationParams.into()));
+        assert_eq!(configure(0, 15_001), Err(StablecoinError::InvalidLiquidationParams.into()));
+        assert!(bad_debt.data_is_empty());
+
+        configure(0, 12_500).unwrap();
+        assert_eq!(config_of(&config).liquidation_threshold_bps, 12_500);
+        let bad_debt_data = BadDebtAccount::unpack(&bad_debt.data.borrow()).unwrap();
+        assert_eq!((bad_debt_data.config, bad_debt_data.total_bad_debt), (*config.key, 0));
+    }
+
+    #[test]
+    fn liquidation_repays_up_to_the_close_factor_at_a_discount() {
+        // 1 token backing 100 stablecoins: 150% at 150, 120% once the price drops to 120
+        let mut market = Market::new(1_000_000_000, 100_000_000);
+        let (mut wallet, mut stable_tokens, mut collateral_tokens) = liquidator(&market, 100_000_000);
+        configure_liquidation(&mut market, 15_000);
+        let mut fake_token_program = TestAccount::wallet();
+        let liquidator = wallet.info(true);
+        let config = market.config.info(false);
+        let position = market.position.info(false);
+        let stable_tokens = stable_tokens.info(false);
+        let mint = market.mint.info(false);
+        let vault = market.vault.info(false);
+        let collateral_tokens = collateral_tokens.info(false);
+        let collateral_mint = market.collateral_mint.info(false);
+        let authority = market.authority.info(false);
+        let price = market.price.info(false);
+        let bad_debt = market.bad_debt.info(false);
+        let surplus = market.surplus.info(false);
+        let token_programs = [market.token_program.info(false), fake_token_program.info(false)];
+        let liquidate = |token_program: usize| {
+            run(
+                &[
+                    &liquidator,
+                    &config,
+                    &position,
+                    &stable_tokens,
+                    &mint,
+                    &vault,
+                    &collateral_tokens,
+                    &collateral_mint,
+                    &authority,
+                    &token_programs[token_program],
+                    &price,
+                    &bad_debt,
+                    &surplus,
+                ],
+                StablecoinInstruction::Liquidate { position: *position.key, repay_amount: 100_000_000 },
+            )
+        };
+
+        assert_eq!(liquidate(0), Err(StablecoinError::PositionNotLiquidatable.into()));
+        set_price(&price, 12_000);
+        assert_eq!(liquidate(1), Err(ProgramError::IncorrectProgramId));
+
+        liquidate(0).unwrap();
+        // Half the debt is repaid, for its worth in collateral plus the 5% discount
+        let seized = 437_499_999;
+        assert_eq!(token_amount(&stable_tokens), 50_000_000);
+        assert_eq!(token_amount(&collateral_tokens), seized);
+        assert_eq!(token_amount(&vault), 1_000_000_000 - seized);
+        let position_data = position_of(&position);
+        assert_eq!((position_data.collateral_amount, position_data.normalized_debt), (1_000_000_000 - seized, 50_000_000));
+        let config_data = config_of(&config);
+        assert_eq!((config_data.total_collateral, config_data.total_debt), (1_000_000_000 - seized, 50_000_000));
+        assert_eq!(BadDebtAccount::unpack(&bad_debt.data.borrow()).unwrap().total_bad_debt, 0);
+    }
+
+    #[test]
+    fn liquidation_writes_off_debt_left_without_collateral() {
+        // At 50 per token the collateral is worth half the debt
+        let mut market = Market::new(1_000_000_000, 100_000_000);
+        let (mut wallet, mut stable_tokens, mut collateral_tokens) = liquidator(&market, 100_000_000);
+        configure_liquidation(&mut market, 5_000);
+        let position_key = market.position.key;
+        let config = market.config.info(false);
+        let position = market.position.info(false);
+        let stable_tokens = stable_tokens.info(false);
+        let collateral_tokens = collateral_tokens.info(false);
+        let bad_debt = market.bad_debt.info(false);
+
+        run(
+            &[
+                &wallet.info(true),
+                &config,
+                &position,
+                &stable_tokens,
+                &market.mint.info(false),
+                &market.vault.info(false),
+                &collateral_tokens,
+                &market.collateral_mint.info(false),
+                &market.authority.info(false),
+                &market.token_program.info(false),
+                &market.price.info(false),
+                &bad_debt,
+                &market.surplus.info(false),
+            ],
+            StablecoinInstruction::Liquidate { position: position_key, repay_amount: 100_000_000 },
+        )
+        .unwrap();
+
+        // All collateral is seized, and the liquidator only pays its discounted worth
+        let repaid = 47_619_047;
+        assert_eq!(token_amount(&collateral_tokens), 1_000_000_000);
+        assert_eq!(token_amount(&stable_tokens), 100_000_000 - repaid);
+        let position_data = position_of(&position);
+        assert_eq!((position_data.collateral_amount, position_data.normalized_debt), (0, 0));
+        assert_eq!(config_of(&config).total_debt, 0);
+        let bad_debt_data = BadDebtAccount::unpack(&bad_debt.data.borrow()).unwrap();
+        assert_eq!((bad_debt_data.total_bad_debt, bad_debt_data.event_count), (100_000_000 - repaid, 1));
+        assert_eq!(bad_debt_data.last_recorded_at, NOW.with(Cell::get));
+    }
+
+    /// An instructions sysvar in which the current instruction follows an Ed25519 program
+    /// instruction verifying `signer`'s signature over `message`. The runtime would have
+    /// checked the signature itself, so any bytes do.
+    fn ed25519_verified(signer: &Pubkey, message: &[u8]) -> TestAccount {
+        let data = oracle::ed25519_instruction_data(&signer.to_bytes(), &[9; 64], message);
+        let instructions = [
+            BorrowedInstruction { program_id: &solana_program::ed25519_program::id(), accounts: vec![], data: &data },
+            BorrowedInstruction { program_id: &PROGRAM_ID, accounts: vec![], data: &[] },
+        ];
+        let mut sysvar_data = construct_instructions_data(&instructions);
+        store_current_index(&mut sysvar_data, 1);
+        TestAccount::new(solana_program::sysvar::instructions::id(), solana_program::sysvar::id(), sysvar_data)
+    }
+
+    #[test]
+    fn post_price_accepts_newer_prices_signed_by_whitelisted_publishers() {
+        let mut market = Market::new(0, 0);
+        let publisher_key = Pubkey::new_unique();
+        let (publisher_pda_key, _) = find_publisher_address(&market.config.key, &publisher_key, &PROGRAM_ID);
+        let mut publisher_pda = TestAccount::new(publisher_pda_key, system_program::id(), vec![]);
+        let config_key = market.config.key;
+        let now = NOW.with(Cell::get);
+        let mut sysvars = [
+            ed25519_verified(&publisher_key, &oracle::price_message(&config_key, 14_000, EXPO, 10, now)),
+            // Signed by the publisher, but for another price than the one posted
+            ed25519_verified(&publisher_key, &oracle::price_message(&config_key, 99_000, EXPO, 10, now)),
+            ed25519_verified(&publisher_key, &oracle::price_message(&config_key, 14_000, EXPO, 10, now + 1)),
+        ];
+        let admin = market.admin.info(true);
+        let config = market.config.info(false);
+        let price = market.price.info(false);
+        let publisher_pda = publisher_pda.info(false);
+        let sysvars: Vec<AccountInfo> = sysvars.iter_mut().map(|sysvar| sysvar.info(false)).collect();
+        // The fixture's price was published now, so move it back to accept a new one
+        let mut price_data = PriceAccount::unpack(&price.data.borrow()).unwrap();
+        price_data.publish_time = now - 10;
+        PriceAccount::pack(price_data, &mut price.data.borrow_mut()).unwrap();
+        let post = |sysvar: usize, publish_time| {
+            run(
+                &[&config, &price, &publisher_pda, &sysvars[sysvar]],
+                StablecoinInstruction::PostPrice { price: 14_000, expo: EXPO, conf: 10, publish_time },
+            )
+        };
+
+        assert_eq!(post(0, now), Err(StablecoinError::PublisherNotWhitelisted.into()));
+        run(
+            &[&admin, &config, &publisher_pda, &market.system.info(false)],
+            StablecoinInstruction::AddPublisher { publisher: publisher_key },
+        )
+        .unwrap();
+        assert_eq!(post(1, now), Err(StablecoinError::InvalidPriceAttestation.into()));
+        // Prices can't come from the future
+        assert_eq!(post(2, now + 1), Err(StablecoinError::StalePrice.into()));
+
+        post(0, now).unwrap();
+        let price_data = PriceAccount::unpack(&price.data.borrow()).unwrap();
+        assert_eq!((price_data.price, price_data.conf, price_data.publish_time), (14_000, 10, now));
+        assert_eq!((price_data.publisher, price_data.signature), (publisher_key, [9; 64]));
+        // Nor can they be replayed
+        assert_eq!(post(0, now), Err(StablecoinError::StalePrice.into()));
+
+        // A removed publisher's PDA is wiped, so it no longer reads as a whitelist entry
+        let remove = |publisher| run(&[&admin, &config, &publisher_pda], StablecoinInstruction::RemovePublisher { publisher });
+        assert_eq!(remove(Pubkey::new_unique()), Err(StablecoinError::PublisherNotWhitelisted.into()));
+        remove(publisher_key).unwrap();
+        assert_eq!(publisher_pda.lamports(), 0);
+        NOW.with(|clock| clock.set(now + 1));
+        assert_eq!(post(2, now + 1), Err(ProgramError::UninitializedAccount));
+    }
+
+    #[test]
+    fn collateral_instructions_reject_stale_and_uncertain_prices() {
+        let mut market = Market::new(2_000_000_000, 0);
+        let owner = market.owner.info(true);
+        let config = market.config.info(false);
+        let position = market.position.info(false);
+        let mint = market.mint.info(false);
+        let owner_stable = market.owner_stable.info(false);
+        let authority = market.authority.info(false);
+        let token_program = market.token_program.info(false);
+        let price = market.price.info(false);
+        let blacklist = market.blacklist.info(false);
+        let mint_against = || {
+            run(
+                &[&owner, &config, &position, &mint, &owner_stable, &authority, &token_program, &price, &blacklist],
+                StablecoinInstruction::MintAgainstCollateral { amount: 1 },
+            )
+        };
+
+        // The confidence may be at most 1% of the price
+        let mut price_data = PriceAccount::unpack(&price.data.borrow()).unwrap();
+        price_data.conf = PRICE / 100 + 1;
+        PriceAccount::pack(price_data, &mut price.data.borrow_mut()).unwrap();
+        assert_eq!(mint_against(), Err(StablecoinError::PriceConfidenceTooWide.into()));
+
+        set_price(&price, PRICE);
+        NOW.with(|now| now.set(now.get() + 61));
+        assert_eq!(mint_against(), Err(StablecoinError::StalePrice.into()));
+        assert_eq!(supply(&mint), 0);
+    }
+
+    #[test]
+    fn configure_oracle_checks_admin_and_limits() {
+        let mut market = Market::new(0, 0);
+        let mut outsider = TestAccount::wallet();
+        let config = market.config.info(false);
+        let admins = [market.admin.info(true), outsider.info(true)];
+        let configure = |admin: usize, max_price_age_secs, max_confidence_bps| {
+            run(
+                &[&admins[admin], &config],
+                StablecoinInstruction::ConfigureOracle { max_price_age_secs, max_confidence_bps },
+            )
+        };
+
+        assert_eq!(configure(1, 30, 50), Err(StablecoinError::AdminSignatureMismatch.into()));
+        assert_eq!(configure(0, 0, 50), Err(StablecoinError::InvalidPrice.into()));
+        assert_eq!(configure(0, 30, 10_001), Err(StablecoinError::InvalidPrice.into()));
+        configure(0, 30, 50).unwrap();
+        let config_data = config_of(&config);
+        assert_eq!((config_data.max_price_age_secs, config_data.max_confidence_bps), (30, 50));
+    }
+
+    #[test]
+    fn psm_swaps_reserves_one_to_one_minus_fees_within_its_ceilings() {
+        let mut market = Market::new(0, 0);
+        let reserve_mint_key = Pubkey::new_unique();
+        let mut reserve_mint = TestAccount::mint(reserve_mint_key, Pubkey::new_unique(), 1_000_000, 6);
+        let mut psm = TestAccount::new(
+            find_psm_address(&market.config.key, &reserve_mint_key, &PROGRAM_ID).0,
+            system_program::id(),
+            vec![],
+        );
+        let mut psm_vault = TestAccount::new(
+            find_psm_vault_address(&market.config.key, &reserve_mint_key, &PROGRAM_ID).0,
+            system_program::id(),
+            vec![],
+        );
+        let mut treasury = TestAccount::token(Pubkey::new_unique(), market.mint.key, Pubkey::new_unique(), 0);
+        let mut owner_reserve = TestAccount::token(Pubkey::new_unique(), reserve_mint_key, market.owner.key, 1_000_000);
+        let mut outsider = TestAccount::wallet();
+        let mut fake_token_program = TestAccount::wallet();
+        let owner = market.owner.info(true);
+        let config = market.config.info(false);
+        let mint = market.mint.info(false);
+        let owner_stable = market.owner_stable.info(false);
+        let authority = market.authority.info(false);
+        let blacklist = market.blacklist.info(false);
+        let system = market.system.info(false);
+        let psm = psm.info(false);
+        let psm_vault = psm_vault.info(false);
+        let treasury = treasury.info(false);
+        let owner_reserve = owner_reserve.info(false);
+        // 0: admin, 1: outsider
+        let signers = [market.admin.info(true), outsider.info(true)];
+        // 0: the reserve mint, 1: a mint with other decimals than the stablecoin
+        let reserve_mints = [reserve_mint.info(false), market.collateral_mint.info(false)];
+        let token_programs = [market.token_program.info(false), fake_token_program.info(false)];
+        let configure = |signer: usize, reserve_mint: usize, token_program: usize, fee_in_bps: u16| {
+            run(
+                &[
+                    &signers[signer],
+                    &config,
+                    &mint,
+                    &reserve_mints[reserve_mint],
+                    &psm,
+                    &psm_vault,
+                    &treasury,
+                    &authority,
+                    &token_programs[token_program],
+                    &system,
+                ],
+                StablecoinInstruction::ConfigurePsm {
+                    fee_in_bps,
+                    fee_out_bps: 20,
+                    swap_in_ceiling: 1_000_000,
+                    swap_out_ceiling: 60_000,
+                },
+            )
+        };
+        let swap_in = |token_program: usize, amount: u64| {
+            run(
+                &[
+                    &owner,
+                    &config,
+                    &psm,
+                    &owner_reserve,
+                    &psm_vault,
+                    &mint,
+                    &owner_stable,
+                    &treasury,
+                    &authority,
+                    &token_programs[token_program],
+                    &blacklist,
+                ],
+                StablecoinInstruction::SwapIn { amount },
+            )
+        };
+        let swap_out = |token_program: usize, amount: u64| {
+            run(
+                &[
+                    &owner,
+                    &config,
+                    &psm,
+                    &owner_stable,
+                    &mint,
+                    &treasury,
+                    &psm_vault,
+                    &owner_reserve,
+                    &authority,
+                    &token_programs[token_program],
+                ],
+                StablecoinInstruction::SwapOut { amount },
+            )
+        };
+
+        assert_eq!(configure(1, 0, 0, 10), Err(StablecoinError::AdminSignatureMismatch.into()));
+        assert_eq!(configure(0, 0, 1, 10), Err(ProgramError::IncorrectProgramId));
+        assert_eq!(configure(0, 0, 0, 10_001), Err(StablecoinError::InvalidPsmParams.into()));
+        assert_eq!(configure(0, 1, 0, 10), Err(StablecoinError::InvalidPsmParams.into()));
+        configure(0, 0, 0, 10).unwrap();
+        assert_eq!(TokenAccount::unpack(&psm_vault.data.borrow()).unwrap().owner, *authority.key);
+
+        // 10 bps of the reserves swapped in go to the treasury as stablecoin
+        assert_eq!(swap_in(1, 100_000), Err(ProgramError::IncorrectProgramId));
+        assert_eq!(swap_in(0, 1_000_001), Err(StablecoinError::PsmCeilingExceeded.into()));
+        swap_in(0, 100_000).unwrap();
+        assert_eq!(token_amount(&psm_vault), 100_000);
+        assert_eq!((token_amount(&owner_stable), token_amount(&treasury)), (99_900, 100));
+        assert_eq!(config_of(&config).psm_debt, 100_000);
+
+        // 20 bps of the stablecoin swapped out go to the treasury, the rest is burned
+        assert_eq!(swap_out(1, 50_000), Err(ProgramError::IncorrectProgramId));
+        swap_out(0, 50_000).unwrap();
+        assert_eq!((token_amount(&owner_stable), token_amount(&treasury)), (49_900, 200));
+        assert_eq!(token_amount(&owner_reserve), 900_000 + 49_900);
+        assert_eq!(token_amount(&psm_vault), 50_100);
+        assert_eq!(supply(&mint), 100_000 - 49_900);
+
+        // The net outflow is capped at 60_000
+        assert_eq!(swap_out(0, 20_000), Err(StablecoinError::PsmCeilingExceeded.into()));
+        swap_out(0, 10_000).unwrap();
+        assert_eq!(token_amount(&psm_vault), 50_100 - 9_980);
+    }
+
+    #[test]
+    fn redemptions_escrow_tokens_until_fulfilled_rejected_or_cancelled() {
+        let mut market = Market::new(0, 1_000);
+        let mut operator = TestAccount::wallet();
+        let mut fake_token_program = TestAccount::wallet();
+        let mut escrow = TestAccount::new(
+            find_redemption_escrow_address(&market.config.key, &PROGRAM_ID).0,
+            system_program::id(),
+            vec![],
+        );
+        let mut requests: Vec<TestAccount> = (0..3)
+            .map(|id| {
+                let key = find_redemption_address(&market.config.key, id, &PROGRAM_ID).0;
+                TestAccount::new(key, system_program::id(), vec![])
+            })
+            .collect();
+        let config = market.config.info(false);
+        let mint = market.mint.info(false);
+        let owner_stable = market.owner_stable.info(false);
+        let authority = market.authority.info(false);
+        let system = market.system.info(false);
+        let escrow = escrow.info(false);
+        // 0: operator, 1: admin, 2: holder
+        let signers = [operator.info(true), market.admin.info(true), market.owner.info(true)];
+        let token_programs = [market.token_program.info(false), fake_token_program.info(false)];
+        let requests: Vec<AccountInfo> = requests.iter_mut().map(|request| request.info(false)).collect();
+        let configure = |signer: usize, timeout_secs: i64| {
+            run(
+                &[&signers[signer], &config, &mint, &escrow, &authority, &token_programs[0], &system],
+                StablecoinInstruction::ConfigureRedemptions { operator: *signers[0].key, timeout_secs },
+            )
+        };
+        let request = |id: usize, token_program: usize, amount: u64| {
+            run(
+                &[&signers[2], &config, &requests[id], &owner_stable, &escrow, &token_programs[token_program], &system],
+                StablecoinInstruction::RequestRedemption { amount, payout_ref_hash: [2; 32] },
+            )
+        };
+        let fulfill = |signer: usize, id: usize| {
+            run(
+                &[&signers[signer], &config, &requests[id], &escrow, &mint, &authority, &token_programs[0]],
+                StablecoinInstruction::FulfillRedemption { bank_ref_hash: [3; 32] },
+            )
+        };
+        let give_back = |signer: usize, id: usize, instruction: StablecoinInstruction| {
+            run(&[&signers[signer], &config, &requests[id], &escrow, &owner_stable, &authority, &token_programs[0]], instruction)
+        };
+        let (reject, cancel) = (StablecoinInstruction::RejectRedemption, StablecoinInstruction::CancelRedemption);
+        let status = |id: usize| RedemptionRequest::unpack(&requests[id].data.borrow()).unwrap().status;
+
+        assert_eq!(request(0, 0, 300), Err(StablecoinError::RedemptionsNotConfigured.into()));
+        assert_eq!(configure(0, 3_600), Err(StablecoinError::AdminSignatureMismatch.into()));
+        assert_eq!(configure(1, 0), Err(StablecoinError::RedemptionsNotConfigured.into()));
+        configure(1, 3_600).unwrap();
+
+        assert_eq!(request(1, 0, 300), Err(StablecoinError::RedemptionMismatch.into()));
+        assert_eq!(request(0, 1, 300), Err(ProgramError::IncorrectProgramId));
+        request(0, 0, 300).unwrap();
+        request(1, 0, 200).unwrap();
+        request(2, 0, 100).unwrap();
+        assert_eq!((token_amount(&owner_stable), token_amount(&escrow)), (400, 600));
+        assert_eq!(config_of(&config).redemption_count, 3);
+
+        // The operator burns fulfilled requests and returns rejected ones
+        assert_eq!(fulfill(2, 0), Err(StablecoinError::RedemptionOperatorMismatch.into()));
+        fulfill(0, 0).unwrap();
+        assert_eq!(status(0), RedemptionStatus::Fulfilled);
+        assert_eq!((token_amount(&escrow), supply(&mint)), (300, 700));
+        assert_eq!(fulfill(0, 0), Err(StablecoinError::RedemptionNotPending.into()));
+        assert_eq!(give_back(2, 1, reject.clone()), Err(StablecoinError::RedemptionOperatorMismatch.into()));
+        give_back(0, 1, reject).unwrap();
+        assert_eq!(status(1), RedemptionStatus::Rejected);
+        assert_eq!(token_amount(&owner_stable), 600);
+
+        // Only the holder can cancel, and only once the request has timed out
+        assert_eq!(give_back(2, 2, cancel.clone()), Err(StablecoinError::RedemptionNotExpired.into()));
+        NOW.with(|now| now.set(now.get() + 3_600));
+        assert_eq!(give_back(0, 2, cancel.clone()), Err(ProgramError::MissingRequiredSignature));
+        give_back(2, 2, cancel).unwrap();
+        assert_eq!(status(2), RedemptionStatus::Cancelled);
+        assert_eq!((token_amount(&owner_stable), token_amount(&escrow)), (700, 0));
+    }
+
+    #[test]
+    fn timelocked_instructions_run_only_after_the_delay_unless_cancelled() {
+        let mut market = Market::new(0, 0);
+        let mut guardian = TestAccount::wallet();
+        let mut executor = TestAccount::wallet();
+        let mut other_config = TestAccount::wallet();
+        let mut actions: Vec<TestAccount> = (0..3)
+            .map(|id| {
+                let key = find_action_address(&market.config.key, id, &PROGRAM_ID).0;
+                TestAccount::new(key, system_program::id(), vec![])
+            })
+            .collect();
+        let new_admin = Pubkey::new_unique();
+        let config = market.config.info(false);
+        let system = market.system.info(false);
+        let executor = executor.info(true);
+        let admin = market.admin.info(true);
+        let unsigned_admin = AccountInfo { is_signer: false, ..admin.clone() };
+        // 0: admin, 1: guardian
+        let signers = [admin, guardian.info(true)];
+        let actions: Vec<AccountInfo> = actions.iter_mut().map(|action| action.info(false)).collect();
+        // The config the wrapped instruction is given: 0: the action's, 1: another one
+        let wrapped_configs = [config.clone(), other_config.info(false)];
+        let queue = |signer: usize, id: usize, instruction: StablecoinInstruction| {
+            run(
+                &[&signers[signer], &config, &actions[id], &system],
+                StablecoinInstruction::QueueAction { instruction_data: borsh::to_vec(&instruction).unwrap() },
+            )
+        };
+        // The admin doesn't sign the wrapped instruction; the queueing authorized it
+        let execute = |id: usize, wrapped_config: usize| {
+            run(
+                &[&executor, &config, &actions[id], &unsigned_admin, &wrapped_configs[wrapped_config]],
+                StablecoinInstruction::ExecuteAction,
+            )
+        };
+        let cancel = |signer: usize, id: usize| {
+            run(&[&signers[signer], &config, &actions[id]], StablecoinInstruction::CancelAction)
+        };
+        let status = |id: usize| TimelockAction::unpack(&actions[id].data.borrow()).unwrap().status;
+        let configure = |delay_secs| StablecoinInstruction::ConfigureTimelock { delay_secs, guardian: *signers[1].key };
+
+        assert_eq!(run(&[&signers[1], &config], configure(86_400)), Err(StablecoinError::AdminSignatureMismatch.into()));
+        assert_eq!(
+            run(&[&signers[0], &config], configure(MAX_TIMELOCK_DELAY_SECS + 1)),
+            Err(StablecoinError::InvalidTimelockDelay.into())
+        );
+        run(&[&signers[0], &config], configure(86_400)).unwrap();
+
+        // Timelocked instructions can no longer be sent directly
+        let set_cap = StablecoinInstruction::SetSupplyCap { supply_cap: 500 };
+        assert_eq!(run(&[&signers[0], &config], set_cap.clone()), Err(StablecoinError::TimelockRequired.into()));
+        assert_eq!(queue(0, 0, StablecoinInstruction::Pause), Err(StablecoinError::ActionNotTimelocked.into()));
+        assert_eq!(queue(1, 0, set_cap.clone()), Err(StablecoinError::AdminSignatureMismatch.into()));
+        assert_eq!(queue(0, 1, set_cap.clone()), Err(StablecoinError::ActionMismatch.into()));
+        queue(0, 0, set_cap).unwrap();
+        queue(0, 1, StablecoinInstruction::SetAdmin { new_admin }).unwrap();
+        assert_eq!(config_of(&config).action_count, 2);
+
+        assert_eq!(execute(0, 0), Err(StablecoinError::ActionNotReady.into()));
+        NOW.with(|now| now.set(now.get() + 86_400));
+        assert_eq!(execute(0, 1), Err(StablecoinError::ActionMismatch.into()));
+        execute(0, 0).unwrap();
+        assert_eq!(status(0), ActionStatus::Executed);
+        assert_eq!(config_of(&config).supply_cap, 500);
+        assert_eq!(execute(0, 0), Err(StablecoinError::ActionNotQueued.into()));
+
+        // The guardian can stop a queued admin change before it runs
+        assert_eq!(cancel(0, 1), Err(StablecoinError::GuardianMismatch.into()));
+        cancel(1, 1).unwrap();
+        assert_eq!(status(1), ActionStatus::Cancelled);
+        assert_eq!(execute(1, 0), Err(StablecoinError::ActionNotQueued.into()));
+        assert_eq!(config_of(&config).admin, *signers[0].key);
+    }
+
+    #[test]
+    fn multisig_executes_approved_proposals_as_its_signer_pda() {
+        let mut market = Market::new(0, 0);
+        let mut wallets: Vec<TestAccount> = (0..4).map(|_| TestAccount::wallet()).collect();
+        let multisig_key = find_multisig_address(&market.config.key, &PROGRAM_ID).0;
+        let signer_key = find_multisig_signer_address(&multisig_key, &PROGRAM_ID).0;
+        let mut multisig = TestAccount::new(multisig_key, system_program::id(), vec![]);
+        let mut multisig_signer = TestAccount::new(signer_key, system_program::id(), vec![]);
+        let mut proposals: Vec<TestAccount> = (0..3)
+            .map(|id| TestAccount::new(find_proposal_address(&multisig_key, id, &PROGRAM_ID).0, system_program::id(), vec![]))
+            .collect();
+        let mut program = TestAccount::new(PROGRAM_ID, Pubkey::default(), vec![]);
+        let mut other_config = TestAccount::wallet();
+        let config = market.config.info(false);
+        let system = market.system.info(false);
+        // 0..3: multisig signers, 3: an outsider, 4: the admin
+        let mut signers: Vec<AccountInfo> = wallets.iter_mut().map(|wallet| wallet.info(true)).collect();
+        signers.push(market.admin.info(true));
+        let multisig = multisig.info(false);
+        let proposals: Vec<AccountInfo> = proposals.iter_mut().map(|proposal| proposal.info(false)).collect();
+        let program = program.info(false);
+        // The accounts a proposal can name: 0: the signer PDA, 1: the config, 2: another config, 3: the multisig
+        let targets = [multisig_signer.info(false), config.clone(), other_config.info(false), multisig.clone()];
+        let keys: Vec<Pubkey> = signers[..3].iter().map(|signer| *signer.key).collect();
+        let create = |creator: usize, signers_keys: Vec<Pubkey>, threshold| {
+            run(
+                &[&signers[creator], &config, &multisig, &system],
+                StablecoinInstruction::CreateMultisig { signers: signers_keys, threshold },
+            )
+        };
+        let propose = |signer: usize, id: usize, accounts: &[usize], instruction: StablecoinInstruction| {
+            let accounts = accounts
+                .iter()
+                .map(|&i| ProposalAccount { pubkey: *targets[i].key, is_signer: i == 0, is_writable: true })
+                .collect();
+            let data = borsh::to_vec(&instruction).unwrap();
+            run(
+                &[&signers[signer], &multisig, &proposals[id], &system],
+                StablecoinInstruction::Propose { program_id: PROGRAM_ID, accounts, data },
+            )
+        };
+        let approve = |signer: usize, id: usize| {
+            run(&[&signers[signer], &multisig, &proposals[id]], StablecoinInstruction::Approve)
+        };
+        // The signer PDA doesn't sign the outer instruction; the program signs for it
+        let execute = |id: usize, accounts: &[usize]| {
+            let mut all = vec![&signers[3], &multisig, &proposals[id], &program];
+            all.extend(accounts.iter().map(|&i| &targets[i]));
+            run(&all, StablecoinInstruction::Execute)
+        };
+        let multisig_of = || MultisigAccount::unpack(&multisig.data.borrow()).unwrap();
+
+        assert_eq!(create(0, keys.clone(), 2), Err(StablecoinError::AdminSignatureMismatch.into()));
+        assert_eq!(create(4, keys.clone(), 0), Err(StablecoinError::InvalidMultisigParams.into()));
+        assert_eq!(create(4, keys.clone(), 4), Err(StablecoinError::InvalidMultisigParams.into()));
+        assert_eq!(
+            create(4, vec![keys[0], keys[1], keys[0]], 2),
+            Err(StablecoinError::InvalidMultisigParams.into())
+        );
+        create(4, keys.clone(), 2).unwrap();
+        run(&[&signers[4], &config], StablecoinInstruction::SetAdmin { new_admin: signer_key }).unwrap();
+
+        // Only signers propose, and proposing counts as the first approval
+        let set_cap = StablecoinInstruction::SetSupplyCap { supply_cap: 700 };
+        let cap_accounts = [0, 1];
+        assert_eq!(propose(3, 0, &cap_accounts, set_cap.clone()), Err(StablecoinError::NotMultisigSigner.into()));
+        assert_eq!(propose(0, 1, &cap_accounts, set_cap.clone()), Err(StablecoinError::ProposalMismatch.into()));
+        propose(0, 0, &cap_accounts, set_cap).unwrap();
+        assert_eq!(execute(0, &[0, 1]), Err(StablecoinError::NotEnoughApprovals.into()));
+        assert_eq!(approve(0, 0), Err(StablecoinError::ProposalAlreadyApproved.into()));
+        assert_eq!(approve(3, 0), Err(StablecoinError::NotMultisigSigner.into()));
+        approve(1, 0).unwrap();
+
+        // The approvals cover the proposed accounts only
+        assert_eq!(execute(0, &[0, 2]), Err(StablecoinError::ProposalMismatch.into()));
+        assert_eq!(execute(0, &[0]), Err(StablecoinError::ProposalMismatch.into()));
+        execute(0, &[0, 1]).unwrap();
+        assert_eq!(config_of(&config).supply_cap, 700);
+        assert_eq!(execute(0, &[0, 1]), Err(StablecoinError::ProposalAlreadyExecuted.into()));
+
+        // Changing the signers goes through a proposal too and invalidates open ones
+        let change = StablecoinInstruction::ChangeMultisig { signers: keys[..2].to_vec(), threshold: 1 };
+        assert_eq!(
+            run(&[&signers[0], &multisig], change.clone()),
+            Err(StablecoinError::MultisigMismatch.into())
+        );
+        propose(1, 1, &[0, 3], change).unwrap();
+        propose(2, 2, &cap_accounts, StablecoinInstruction::SetSupplyCap { supply_cap: 0 }).unwrap();
+        approve(2, 1).unwrap();
+        execute(1, &[0, 3]).unwrap();
+        assert_eq!(multisig_of().signers, keys[..2]);
+        assert_eq!(multisig_of().epoch, 1);
+        assert_eq!(approve(0, 2), Err(StablecoinError::ProposalStale.into()));
+        assert_eq!(execute(2, &[0, 1]), Err(StablecoinError::ProposalStale.into()));
+        assert_eq!(config_of(&config).supply_cap, 700);
+    }
+
+    #[test]
+    fn stability_fees_accrue_on_position_debt_and_repayments_mint_them_to_the_surplus() {
+        let mut market = Market::new(2_000_000_000, 100_000_000);
+        // Enough to repay the debt with a year of fees on top
+        market.owner_stable = TestAccount::token(market.owner_stable.key, market.mint.key, market.owner.key, 200_000_000);
+        market.mint = TestAccount::mint(market.mint.key, market.authority.key, 200_000_000, 6);
+        let mut outsider = TestAccount::wallet();
+        let owner = market.owner.info(true);
+        let config = market.config.info(false);
+        let position = market.position.info(false);
+        let owner_stable = market.owner_stable.info(false);
+        let mint = market.mint.info(false);
+        let surplus = market.surplus.info(false);
+        let authority = market.authority.info(false);
+        let token_program = market.token_program.info(false);
+        let system = market.system.info(false);
+        let admins = [market.admin.info(true), outsider.info(true)];
+        let configure = |admin: usize, stability_fee_bps| {
+            run(
+                &[&admins[admin], &config, &mint, &surplus, &authority, &token_program, &system],
+                StablecoinInstruction::ConfigureStabilityFee { stability_fee_bps },
+            )
+        };
+        let repay = |amount| {
+            run(
+                &[&owner, &config, &position, &owner_stable, &mint, &token_program, &surplus, &authority],
+                StablecoinInstruction::Repay { amount },
+            )
+        };
+
+        assert_eq!(configure(1, 1_000), Err(StablecoinError::AdminSignatureMismatch.into()));
+        assert_eq!(configure(0, 10_001), Err(StablecoinError::InvalidStabilityFee.into()));
+        configure(0, 1_000).unwrap();
+        assert_eq!(config_of(&config).rate_index, RAY);
+
+        // A year at 10% adds 10 to the 100 owed
+        NOW.with(|now| now.set(now.get() + SECONDS_PER_YEAR as i64));
+        assert_eq!(repay(110_000_001), Err(StablecoinError::RepayExceedsDebt.into()));
+        // The position's own fees are paid first, then its principal
+        repay(20_000_000).unwrap();
+        assert_eq!(token_amount(&surplus), 10_000_000);
+        assert_eq!(position_of(&position).principal, 90_000_000);
+        let config_data = config_of(&config);
+        assert_eq!((config_data.unminted_fees, config_data.total_fees_minted), (0, 10_000_000));
+
+        let debt = debt_from_normalized(position_of(&position).normalized_debt, config_data.rate_index).unwrap();
+        repay(debt).unwrap();
+        let position_data = position_of(&position);
+        assert_eq!((position_data.normalized_debt, position_data.principal), (0, 0));
+        assert_eq!(token_amount(&surplus), 10_000_000);
+        assert_eq!(supply(&mint), 200_000_000 - 20_000_000 - debt + 10_000_000);
+    }
+
+    #[test]
+    fn migrate_position_sets_the_principal_of_older_positions() {
+        let mut market = Market::new(1_000_000_000, 100_000_000);
+        let payer = market.owner.info(true);
+        let payers = [AccountInfo { is_signer: false, ..payer.clone() }, payer];
+        let config = market.config.info(false);
+        let system = market.system.info(false);
+        let position = market.position.info(false);
+        // The layout before the principal, written by an older program version
+        let mut old_layout = position.data.borrow()[..Position::LEN - 8].to_vec();
+        old_layout[0] = CURRENT_VERSION - 1;
+        *position.data.borrow_mut() = reallocatable(&old_layout);
+        assert!(Position::unpack(&position.data.borrow()).is_err());
+
+        let migrate = |signed: bool| {
+            run(&[&payers[signed as usize], &config, &position, &system], StablecoinInstruction::MigratePosition)
+        };
+        assert_eq!(migrate(false), Err(ProgramError::MissingRequiredSignature));
+        migrate(true).unwrap();
+        assert_eq!(position.data_len(), Position::LEN);
+        let position_data = position_of(&position);
+        assert_eq!((position_data.normalized_debt, position_data.principal), (100_000_000, 100_000_000));
+        // Migrating a current position is a no-op
+        migrate(true).unwrap();
+    }
+
+    #[test]
+    fn savings_shares_grow_with_interest_dripped_from_the_surplus() {
+        let mut market = Market::new(0, 100_000_000);
+        market.surplus = TestAccount::token(market.surplus.key, market.mint.key, market.authority.key, 50_000_000);
+        let config_key = market.config.key;
+        let share_mint_key = find_savings_shares_address(&config_key, &PROGRAM_ID).0;
+        let mut vault = TestAccount::new(find_savings_vault_address(&config_key, &PROGRAM_ID).0, system_program::id(), vec![]);
+        let mut share_mint = TestAccount::new(share_mint_key, system_program::id(), vec![]);
+        let mut owner_shares = TestAccount::token(Pubkey::new_unique(), share_mint_key, market.owner.key, 0);
+        let mut outsider = TestAccount::wallet();
+        let mut fake_vault = TestAccount::token(Pubkey::new_unique(), market.mint.key, market.authority.key, 0);
+        let mut fake_token_program = TestAccount::wallet();
+        let owner = market.owner.info(true);
+        let config = market.config.info(false);
+        let mint = market.mint.info(false);
+        let surplus = market.surplus.info(false);
+        let authority = market.authority.info(false);
+        let system = market.system.info(false);
+        let owner_stable = market.owner_stable.info(false);
+        let owner_shares = owner_shares.info(false);
+        let share_mint = share_mint.info(false);
+        let admins = [market.admin.info(true), outsider.info(true)];
+        let vaults = [vault.info(false), fake_vault.info(false)];
+        let token_programs = [market.token_program.info(false), fake_token_program.info(false)];
+        let configure = |admin: usize, savings_rate_bps| {
+            run(
+                &[&admins[admin], &config, &mint, &surplus, &vaults[0], &share_mint, &authority, &token_programs[0], &system],
+                StablecoinInstruction::ConfigureSavings { savings_rate_bps },
+            )
+        };
+        let deposit = |amount| {
+            run(
+                &[&owner, &config, &owner_stable, &owner_shares, &surplus, &vaults[0], &share_mint, &authority, &token_programs[0]],
+                StablecoinInstruction::SavingsDeposit { amount },
+            )
+        };
+        let withdraw = |shares| {
+            run(
+                &[&owner, &config, &owner_shares, &owner_stable, &surplus, &vaults[0], &share_mint, &authority, &token_programs[0]],
+                StablecoinInstruction::SavingsWithdraw { shares },
+            )
+        };
+        let drip = |vault: usize, token_program: usize| {
+            run(
+                &[&config, &surplus, &vaults[vault], &share_mint, &authority, &token_programs[token_program]],
+                StablecoinInstruction::Drip,
+            )
+        };
+        let years = |count: i64| NOW.with(|now| now.set(now.get() + count * SECONDS_PER_YEAR as i64));
+
+        assert_eq!(deposit(100_000_000), Err(StablecoinError::SavingsNotConfigured.into()));
+        assert_eq!(configure(1, 1_000), Err(StablecoinError::AdminSignatureMismatch.into()));
+        assert_eq!(configure(0, 10_001), Err(StablecoinError::InvalidSavingsRate.into()));
+        configure(0, 1_000).unwrap();
+        assert_eq!(config_of(&config).savings_share_price, RAY);
+
+        deposit(100_000_000).unwrap();
+        assert_eq!((token_amount(&owner_shares), token_amount(&vaults[0])), (100_000_000, 100_000_000));
+
+        // A year at 10%, paid by the surplus
+        years(1);
+        assert_eq!(drip(1, 0), Err(StablecoinError::SavingsMismatch.into()));
+        assert_eq!(drip(0, 1), Err(ProgramError::IncorrectProgramId));
+        drip(0, 0).unwrap();
+        assert_eq!((token_amount(&surplus), token_amount(&vaults[0])), (40_000_000, 110_000_000));
+        withdraw(50_000_000).unwrap();
+        assert_eq!(token_amount(&owner_stable), 55_000_000);
+
+        // Ten more years owe 55 on the remaining shares; the surplus only covers 40
+        years(10);
+        withdraw(50_000_000).unwrap();
+        assert_eq!(token_amount(&surplus), 0);
+        assert_eq!((token_amount(&owner_stable), token_amount(&vaults[0])), (150_000_000, 0));
+        assert_eq!(supply(&share_mint), 0);
+    }
+
+    /// An instructions sysvar in which the current instruction is the first of
+    /// `instructions`, each sent to the program with `[payer, config]` as its leading accounts.
+    fn program_instructions(instructions: &[(&Pubkey, StablecoinInstruction)]) -> TestAccount {
+        let payer = Pubkey::new_unique();
+        let data: Vec<Vec<u8>> = instructions.iter().map(|(_, instruction)| borsh::to_vec(instruction).unwrap()).collect();
+        let borrowed: Vec<BorrowedInstruction> = instructions
+            .iter()
+            .zip(&data)
+            .map(|((config, _), data)| BorrowedInstruction {
+                program_id: &PROGRAM_ID,
+                accounts: [&payer, *config]
+                    .into_iter()
+                    .map(|pubkey| BorrowedAccountMeta { pubkey, is_signer: false, is_writable: true })
+                    .collect(),
+                data,
+            })
+            .collect();
+        let mut sysvar_data = construct_instructions_data(&borrowed);
+        store_current_index(&mut sysvar_data, 0);
+        TestAccount::new(solana_program::sysvar::instructions::id(), solana_program::sysvar::id(), sysvar_data)
+    }
+
+    #[test]
+    fn flash_mints_need_a_matching_repay_later_in_the_transaction() {
+        let mut market = Market::new(0, 1_000_000);
+        let config_key = market.config.key;
+        let other_config = Pubkey::new_unique();
+        let flash_mint = |amount| StablecoinInstruction::FlashMint { amount };
+        let flash_repay = |amount| StablecoinInstruction::FlashRepay { amount };
+        let mut sysvars = [
+            program_instructions(&[(&config_key, flash_mint(100_000_000)), (&config_key, flash_repay(100_000_000))]),
+            program_instructions(&[(&config_key, flash_mint(100_000_000))]),
+            program_instructions(&[(&config_key, flash_mint(100_000_000)), (&config_key, flash_repay(50_000_000))]),
+            program_instructions(&[(&config_key, flash_mint(100_000_000)), (&other_config, flash_repay(100_000_000))]),
+            program_instructions(&[
+                (&config_key, flash_mint(100_000_000)),
+                (&config_key, flash_mint(100_000_000)),
+                (&config_key, flash_repay(100_000_000)),
+            ]),
+        ];
+        let mut treasury = TestAccount::token(Pubkey::new_unique(), market.mint.key, Pubkey::new_unique(), 0);
+        let mut outsider = TestAccount::wallet();
+        let mut blacklisted = TestAccount::new(market.blacklist.key, PROGRAM_ID, vec![1]);
+        let owner = market.owner.info(true);
+        let config = market.config.info(false);
+        let mint = market.mint.info(false);
+        let owner_stable = market.owner_stable.info(false);
+        let authority = market.authority.info(false);
+        let token_program = market.token_program.info(false);
+        let sysvars: Vec<AccountInfo> = sysvars.iter_mut().map(|sysvar| sysvar.info(false)).collect();
+        let admins = [market.admin.info(true), outsider.info(true)];
+        // 0: the configured treasury, 1: an account holding another token
+        let treasuries = [treasury.info(false), market.owner_collateral.info(false)];
+        let blacklists = [market.blacklist.info(false), blacklisted.info(false)];
+        let configure = |admin: usize, fee_bps, treasury: usize| {
+            run(
+                &[&admins[admin], &config, &treasuries[treasury]],
+                StablecoinInstruction::ConfigureFlashMint { ceiling: 100_000_000, fee_bps },
+            )
+        };
+        let mint_with = |amount, sysvar: usize, blacklist: usize| {
+            run(
+                &[&owner, &config, &mint, &owner_stable, &authority, &token_program, &sysvars[sysvar], &blacklists[blacklist]],
+                flash_mint(amount),
+            )
+        };
+        let repay_to = |amount, treasury: usize| {
+            run(&[&owner, &config, &mint, &owner_stable, &treasuries[treasury], &token_program], flash_repay(amount))
+        };
+
+        assert_eq!(configure(1, 10, 0), Err(StablecoinError::AdminSignatureMismatch.into()));
+        assert_eq!(configure(0, 10_001, 0), Err(StablecoinError::InvalidFlashMintParams.into()));
+        assert_eq!(configure(0, 10, 1), Err(StablecoinError::InvalidFlashMintParams.into()));
+        configure(0, 10, 0).unwrap();
+
+        assert_eq!(mint_with(100_000_001, 0, 0), Err(StablecoinError::FlashMintCeilingExceeded.into()));
+        assert_eq!(mint_with(100_000_000, 1, 0), Err(StablecoinError::FlashRepayMissing.into()));
+        assert_eq!(mint_with(100_000_000, 2, 0), Err(StablecoinError::FlashRepayMismatch.into()));
+        assert_eq!(mint_with(100_000_000, 3, 0), Err(StablecoinError::FlashRepayMismatch.into()));
+        assert_eq!(mint_with(100_000_000, 4, 0), Err(StablecoinError::FlashRepayMissing.into()));
+        assert_eq!(mint_with(100_000_000, 0, 1), Err(StablecoinError::Blacklisted.into()));
+        assert_eq!(repay_to(100_000_000, 0), Err(StablecoinError::FlashRepayMismatch.into()));
+
+        mint_with(100_000_000, 0, 0).unwrap();
+        assert_eq!((token_amount(&owner_stable), config_of(&config).flash_outstanding), (101_000_000, 100_000_000));
+        assert_eq!(mint_with(100_000_000, 0, 0), Err(StablecoinError::FlashRepayMissing.into()));
+        assert_eq!(repay_to(100_000_000, 1), Err(StablecoinError::InvalidFlashMintParams.into()));
+
+        // The amount is burned back and the 0.1% fee goes to the treasury
+        repay_to(100_000_000, 0).unwrap();
+        assert_eq!((token_amount(&owner_stable), token_amount(&treasuries[0])), (900_000, 100_000));
+        assert_eq!((supply(&mint), config_of(&config).flash_outstanding), (1_000_000, 0));
+    }
+
+    #[test]
+    fn settlement_pays_holders_at_most_the_pool() {
+        let holders = [83_333_333, 100_000_000, 66_666_667];
+        let surplus_fees = 7;
+        let now = NOW.with(Cell::get);
+        let mut market = Market::new(2_000_000_000, 100_000_001);
+        let config_key = market.config.key;
+        let (mint_key, collateral_mint_key, authority_key) = (market.mint.key, market.collateral_mint.key, market.authority.key);
+        // A quarter of a year of 5% stability fee is due when the stablecoin is settled
+        let mut config_data = ConfigAccount::unpack(&market.config.data).unwrap();
+        config_data.total_collateral = 3_000_000_000;
+        config_data.total_debt = 250_000_000;
+        config_data.total_normalized_debt = 250_000_000;
+        config_data.stability_fee_bps = 500;
+        config_data.rate_index = RAY;
+        config_data.rate_updated_at = now - (SECONDS_PER_YEAR / 4) as i64;
+        config_data.total_fees_minted = surplus_fees;
+        ConfigAccount::pack(config_data, &mut market.config.data).unwrap();
+        // Stale prices are still accepted for settlement
+        let mut price_data = PriceAccount::unpack(&market.price.data).unwrap();
+        price_data.publish_time = now - 1_000_000;
+        PriceAccount::pack(price_data, &mut market.price.data).unwrap();
+        market.mint = TestAccount::mint(mint_key, authority_key, holders.iter().sum::<u64>() + surplus_fees, 6);
+        market.vault = TestAccount::token(market.vault.key, collateral_mint_key, authority_key, 3_000_000_000);
+        market.surplus = TestAccount::token(market.surplus.key, mint_key, authority_key, surplus_fees);
+        // Besides the well collateralized fixture, one whose debt outgrows its collateral with the fee
+        let owner_b = Pubkey::new_unique();
+        let (position_b_key, position_b_bump) = find_position_address(&config_key, &owner_b, &PROGRAM_ID);
+        let position_b = Position {
+            is_initialized: true,
+            config: config_key,
+            owner: owner_b,
+            collateral_amount: 1_000_000_000,
+            normalized_debt: 149_999_999,
+            principal: 149_999_999,
+            bump: position_b_bump,
+        };
+        let mut position_b = TestAccount::packed(position_b_key, PROGRAM_ID, position_b);
+        let mut wallets: Vec<(TestAccount, TestAccount, TestAccount)> = holders
+            .iter()
+            .map(|&amount| {
+                let holder = TestAccount::wallet();
+                let stable = TestAccount::token(Pubkey::new_unique(), mint_key, holder.key, amount);
+                let collateral = TestAccount::token(Pubkey::new_unique(), collateral_mint_key, holder.key, 0);
+                (holder, stable, collateral)
+            })
+            .collect();
+        let mut outsider = TestAccount::wallet();
+        let config = market.config.info(false);
+        let price = market.price.info(false);
+        let position_a = market.position.info(false);
+        let position_b = position_b.info(false);
+        let mint = market.mint.info(false);
+        let collateral_mint = market.collateral_mint.info(false);
+        let vault = market.vault.info(false);
+        let surplus = market.surplus.info(false);
+        let authority = market.authority.info(false);
+        let token_program = market.token_program.info(false);
+        let admins = [market.admin.info(true), outsider.info(true)];
+        let set_paused = |paused| {
+            let mut config_data = config_of(&config);
+            config_data.paused = paused;
+            ConfigAccount::pack(config_data, &mut config.data.borrow_mut()).unwrap();
+        };
+        let finalize = || {
+            run(
+                &[&admins[0], &config, &mint, &surplus, &authority, &token_program],
+                StablecoinInstruction::FinalizeSettlement,
+            )
+        };
+
+        assert_eq!(
+            run(&[&admins[1], &config, &price], StablecoinInstruction::GlobalSettle),
+            Err(StablecoinError::AdminSignatureMismatch.into())
+        );
+        // Settling works while paused; paying out doesn't
+        set_paused(true);
+        run(&[&admins[0], &config, &price], StablecoinInstruction::GlobalSettle).unwrap();
+        assert_eq!(
+            run(&[&config, &position_a], StablecoinInstruction::SettlePosition),
+            Err(StablecoinError::ProgramPaused.into())
+        );
+        set_paused(false);
+        assert_eq!(
+            run(&[&admins[0], &config, &price], StablecoinInstruction::GlobalSettle),
+            Err(StablecoinError::GloballySettled.into())
+        );
+
+        run(&[&config, &position_a], StablecoinInstruction::SettlePosition).unwrap();
+        assert_eq!(finalize(), Err(StablecoinError::UnsettledDebt.into()));
+        run(&[&config, &position_b], StablecoinInstruction::SettlePosition).unwrap();
+        finalize().unwrap();
+        assert_eq!(finalize(), Err(StablecoinError::SettlementFinalized.into()));
+
+        let config_data = config_of(&config);
+        let pool = config_data.settle_pool_at_finalize;
+        // The surplus fees were burned and have no claim on the pool
+        assert_eq!(token_amount(&surplus), 0);
+        assert_eq!(config_data.settle_supply, holders.iter().sum::<u64>());
+        assert_eq!(config_data.unminted_fees, 0);
+
+        let mut paid = 0;
+        for (holder, source, destination) in wallets.iter_mut() {
+            let source = source.info(false);
+            let destination = destination.info(false);
+            let amount = token_amount(&source);
+            run(
+                &[
+                    &holder.info(true),
+                    &config,
+                    &source,
+                    &mint,
+                    &vault,
+                    &destination,
+                    &collateral_mint,
+                    &authority,
+                    &token_program,
+                ],
+                StablecoinInstruction::RedeemSettled { amount },
+            )
+            .unwrap();
+            assert_eq!(token_amount(&source), 0);
+            paid += token_amount(&destination);
+        }
+
+        // Payouts round down: they never exceed the pool, and the dust stays in the vault
+        let remaining_a = position_of(&position_a).collateral_amount;
+        let remaining_b = position_of(&position_b).collateral_amount;
+        let dust = pool - paid;
+        assert!(paid <= pool);
+        assert!(dust < holders.len() as u64);
+        assert_eq!(remaining_b, 0);
+        assert_eq!(config_of(&config).settle_collateral_pool, dust);
+        assert_eq!(token_amount(&vault), remaining_a + remaining_b + dust);
+        assert_eq!(supply(&mint), 0);
     }
 }
 ```
@@ -454,8 +9244,8 @@ impl Processor {
 deploy it to the Solana network. This gives you a `program_id`.
 2.  **Create SPL Token Mint:** Use the SPL Token CLI or client-side JS to create a new token mint 
 (e.g., `spl-token create-token`). Crucially, 
-set the **mint authority** to the `program_id` you just deployed 
-(or a PDA derived from it for better security, though the example assumes the admin for simplicity in CPI).
+set the **mint authority** to the program authority PDA, `find_authority_address(config, program_id)`
+(`[b"authority", config]`), so the program can sign mint CPIs itself.
 3.  **Create Config Account:** Client-side (e.g., JavaScript), 
 create a new empty account using `SystemProgram.createAccount`, 
 allocating `ConfigAccount::LEN` space and assigning ownership to your deployed `program_id`. 
@@ -465,9 +9255,25 @@ passing in the required accounts (payer, the newly created config account, the S
 and the desired initial `admin` public key.
 5.  **Mint Tokens:**
     * Ensure the recipient has an Associated Token Account (ATA) for your stablecoin mint (create it client-side if needed using the ATA program).
-    * The authorized `admin` user signs a transaction calling the `MintTo` instruction, providing the config account, mint account, destination ATA, and the amount. The program verifies the signer is the admin and uses CPI to call the SPL Token program's `mint_to` function.
+    * The admin names a master minter with `SetMasterMinter`, who grants minters (e.g. banking partners) an allowance with `ConfigureMinter` and revokes them with `RemoveMinter`.
+    * A minter signs a transaction calling the `MintTo` instruction, providing the config account, mint account, destination ATA, its minter PDA, the blacklist PDA of the destination's owner, and the amount. The program verifies the signer is a minter with enough allowance left, decrements it, and uses CPI to call the SPL Token program's `mint_to` function.
 6.  **Transfer/Burn:** Users interact directly with the SPL Token program (via wallets like Phantom or client-side JS) to transfer or burn their tokens held in their ATAs. Your custom program isn't involved in standard transfers/burns.
 7.  **Set New Admin:** The current `admin` signs a transaction calling the `SetAdmin` instruction to transfer control.
+8.  **Collateralized Minting:** The admin calls `ConfigureCollateral` (creates the vault and price PDAs)
+and `ConfigureOracle`, whitelists publishers with `AddPublisher`, and prices arrive through
+`PostPrice` (signed locally with the `price-publisher` tool). Users then `OpenPosition`, `Deposit` collateral, `MintAgainstCollateral` up to the
+collateral ratio, and later `Repay` and `Withdraw`.
+9.  **Compliance:** Create the mint with the authority PDA as freeze authority too. The admin names a blacklister with `SetBlacklister`, who freezes a sanctioned holder's accounts with `Blacklist` and thaws them with `Unblacklist`. Both emit Borsh-encoded `StablecoinEvent`s for the compliance log. Every instruction that mints (`MintTo`, `MintAgainstCollateral`, `SwapIn`, `FlashMint`) takes the blacklist PDA of the destination's owner and refuses to mint to a blacklisted owner.
+10. **Risk Controls:** The admin caps supply with `SetSupplyCap` (zero for uncapped) and names a pauser with `SetPauser`, who can halt minting, positions, liquidations and price updates with `Pause` until `Unpause`.
+11. **Proof of Reserves:** The admin sets an attestor with `ConfigureAttestor`, who posts each audit with `PostReserveAttestation`. While an attestor is set, `MintTo` fails once the fiat-backed supply would exceed the latest fresh attestation.
+12. **Peg Stability Module:** The admin whitelists a reserve stablecoin with `ConfigurePsm` (fees, per-direction ceilings, treasury). Anyone can then `SwapIn` reserves for newly minted stablecoin or `SwapOut` the other way at 1:1 minus the fee.
+13. **Redemptions:** The admin sets an operator and timeout with `ConfigureRedemptions`. Holders escrow tokens with `RequestRedemption`; the operator pays out off-chain and calls `FulfillRedemption` (burns the escrow, records the bank reference hash) or `RejectRedemption` (returns it). Once a request times out, the holder can `CancelRedemption`.
+14. **Timelock:** The admin sets a delay and guardian with `ConfigureTimelock`. From then on admin, minter, supply cap, oracle and timelock changes are queued with `QueueAction`, run by anyone with `ExecuteAction` after the delay, and can be stopped by the guardian with `CancelAction`.
+15. **Multisig:** The admin creates a multisig with `CreateMultisig` and sets its signer PDA as admin (of the stablecoin config, and of loyalty configs too). Signers then `Propose` and `Approve` admin instructions, and once the threshold is met anyone can `Execute` them, signed by the signer PDA. With a timelock set, the proposal is a `QueueAction`.
+16. **Stability Fee:** The admin sets an annual fee with `ConfigureStabilityFee`. Position debt grows with a global rate index, so `Repay` has to cover the fees accrued; the fee part of repayments and liquidations (each position's own accrued fees, paid before its principal) is minted to the surplus account. Positions opened before this upgrade are brought to the current layout with `MigratePosition`.
+17. **Savings:** The admin sets a savings rate with `ConfigureSavings`. Holders `SavingsDeposit` stablecoin for share tokens and `SavingsWithdraw` them later for more; anyone can `Drip`, which pays the accrued interest from the surplus into the savings vault.
+18. **Flash Mints:** The admin sets a ceiling, fee and treasury with `ConfigureFlashMint`. Anyone can then `FlashMint` up to the ceiling without collateral, as long as a `FlashRepay` later in the same transaction burns it back and pays the fee to the treasury.
+19. **Global Settlement:** If the oracle breaks, the admin calls `GlobalSettle`, freezing the collateral price at its last accepted value and disabling minting. Anyone then `SettlePosition`s each position (its debt's worth of collateral goes to the settlement pool) and owners `Withdraw` the rest. Once no debt is left the admin calls `FinalizeSettlement`, which burns the surplus fees, and holders `RedeemSettled` stablecoin for their pro rata share of the pool.
 
 This structure separates concerns: the robust, audited SPL Token program handles core token mechanics, while your custom program focuses solely on the specific administrative logic (mint contro
 
@@ -0,0 +1,111 @@
+// Offline tool that signs collateral prices for the stablecoin's `PostPrice` instruction.
+//
+// Usage: price-publisher <keypair.json> <config_pubkey> <prices.csv> <signed.csv>
+//
+// Input: a Solana CLI keypair file (JSON array of 64 bytes) for a whitelisted publisher, and
+// one `price,expo,conf,publish_time` row per update (a header row with those names is skipped).
+//
+// Output: prints the publisher's public key, and writes
+// `price,expo,conf,publish_time,signature,ed25519_ix_data` rows with the signature and the
+// Ed25519 program instruction data hex encoded. A relayer submits each row as an Ed25519
+// program instruction with that data followed by `PostPrice` with the same values, so the
+// private key never has to leave this machine.
+
+use std::{env, fs, process, str::FromStr};
+
+use ed25519_dalek::{Signer, SigningKey};
+use solana_program::pubkey::Pubkey;
+use stablecoin::oracle;
+
+const HEADER: &str = "price,expo,conf,publish_time";
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn load_keypair(path: &str) -> Result<SigningKey, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+    let bytes: Vec<u8> =
+        serde_json::from_str(&contents).map_err(|e| format!("invalid keypair file {}: {}", path, e))?;
+    // Solana keypair files hold the 32-byte secret key followed by the 32-byte public key
+    let keypair: [u8; 64] = bytes
+        .try_into()
+        .map_err(|_| format!("keypair file {} must contain 64 bytes", path))?;
+    SigningKey::from_keypair_bytes(&keypair).map_err(|e| format!("invalid keypair in {}: {}", path, e))
+}
+
+fn parse_row(line_number: usize, line: &str) -> Result<(u64, i32, u64, i64), String> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [price, expo, conf, publish_time] = fields[..] else {
+        return Err(format!("line {}: expected `{}`", line_number, HEADER));
+    };
+    let price = price
+        .parse::<u64>()
+        .map_err(|e| format!("line {}: invalid price {}: {}", line_number, price, e))?;
+    let expo = expo
+        .parse::<i32>()
+        .map_err(|e| format!("line {}: invalid expo {}: {}", line_number, expo, e))?;
+    let conf = conf
+        .parse::<u64>()
+        .map_err(|e| format!("line {}: invalid conf {}: {}", line_number, conf, e))?;
+    let publish_time = publish_time
+        .parse::<i64>()
+        .map_err(|e| format!("line {}: invalid publish_time {}: {}", line_number, publish_time, e))?;
+    if price == 0 {
+        return Err(format!("line {}: price must be positive", line_number));
+    }
+    Ok((price, expo, conf, publish_time))
+}
+
+fn run(keypair_path: &str, config: &str, input_path: &str, output_path: &str) -> Result<(), String> {
+    let signing_key = load_keypair(keypair_path)?;
+    let public_key = signing_key.verifying_key().to_bytes();
+    let config = Pubkey::from_str(config).map_err(|e| format!("invalid config {}: {}", config, e))?;
+    let input = fs::read_to_string(input_path)
+        .map_err(|e| format!("failed to read {}: {}", input_path, e))?;
+
+    let mut output = format!("{},signature,ed25519_ix_data\n", HEADER);
+    let mut count = 0;
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (i == 0 && line.eq_ignore_ascii_case(HEADER)) {
+            continue;
+        }
+        let (price, expo, conf, publish_time) = parse_row(i + 1, line)?;
+        let message = oracle::price_message(&config, price, expo, conf, publish_time);
+        let signature = signing_key.sign(&message).to_bytes();
+        let ix_data = oracle::ed25519_instruction_data(&public_key, &signature, &message);
+        output.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            price,
+            expo,
+            conf,
+            publish_time,
+            to_hex(&signature),
+            to_hex(&ix_data)
+        ));
+        count += 1;
+    }
+    if count == 0 {
+        return Err("input contains no rows".to_string());
+    }
+    fs::write(output_path, output)
+        .map_err(|e| format!("failed to write {}: {}", output_path, e))?;
+
+    println!("publisher: {}", Pubkey::new_from_array(public_key));
+    println!("signed_prices: {}", count);
+    println!("signed prices written to {}", output_path);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 5 {
+        eprintln!("Usage: {} <keypair.json> <config_pubkey> <prices.csv> <signed.csv>", args[0]);
+        process::exit(1);
+    }
+    if let Err(e) = run(&args[1], &args[2], &args[3], &args[4]) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}